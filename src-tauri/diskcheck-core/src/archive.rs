@@ -0,0 +1,117 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{node::FsNodeKind, tree::ScanTree};
+
+/// One entry listed inside an archive file.
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    // Path inside the archive, `/`-separated.
+    pub path: String,
+    pub is_dir: bool,
+    // Bytes the entry takes up inside the archive.
+    pub compressed: u64,
+    // Bytes once extracted.
+    pub uncompressed: u64,
+}
+
+/// Lists archive files, so a scan can show their contents as children of the file.
+pub trait ArchiveLister {
+    /// The entries of the `len`-byte file at `path`, or `None` when it is not an archive
+    /// this lister reads (or reading it failed).
+    fn list(&self, path: &Path, len: u64) -> Option<Vec<ArchiveEntry>>;
+}
+
+struct Folder {
+    id: u32,
+    parent: String,
+    depth: usize,
+    children: Vec<u32>,
+    size: u64,
+    uncompressed: u64,
+}
+
+/// The folder at `path` inside the archive, created along with its parents if needed.
+fn folder<'a>(
+    tree: &mut ScanTree,
+    folders: &'a mut HashMap<String, Folder>,
+    path: &str,
+) -> &'a mut Folder {
+    if !folders.contains_key(path) {
+        let (parent, name) = path.rsplit_once('/').unwrap_or(("", path));
+        let parent_folder = folder(tree, folders, parent);
+        let (parent_id, depth) = (parent_folder.id, parent_folder.depth + 1);
+        let id = tree.push(parent_id, name, FsNodeKind::Directory, 0);
+        parent_folder.children.push(id);
+        folders.insert(
+            path.to_string(),
+            Folder {
+                id,
+                parent: parent.to_string(),
+                depth,
+                children: vec![],
+                size: 0,
+                uncompressed: 0,
+            },
+        );
+    }
+    folders.get_mut(path).expect("folder was just inserted")
+}
+
+/// Adds `entries` below the archive file `archive`, which must be the last node pushed.
+/// Folders inside the archive become directories; every node's size is what it takes up
+/// inside the archive, and the uncompressed sizes are kept alongside.
+pub(crate) fn push_entries(tree: &mut ScanTree, archive: u32, entries: Vec<ArchiveEntry>) {
+    let mut folders = HashMap::from([(
+        String::new(),
+        Folder {
+            id: archive,
+            parent: String::new(),
+            depth: 0,
+            children: vec![],
+            size: 0,
+            uncompressed: 0,
+        },
+    )]);
+    for entry in entries {
+        // Tars often list `./dir/file`; zips written on Windows may use backslashes.
+        let path = entry
+            .path
+            .split(['/', '\\'])
+            .filter(|segment| !matches!(*segment, "" | "." | ".."))
+            .collect::<Vec<_>>()
+            .join("/");
+        let path = path.as_str();
+        if path.is_empty() {
+            continue;
+        }
+        if entry.is_dir {
+            folder(tree, &mut folders, path);
+            continue;
+        }
+        let (parent, name) = path.rsplit_once('/').unwrap_or(("", path));
+        let parent = folder(tree, &mut folders, parent);
+        let id = tree.push(parent.id, name, FsNodeKind::File, entry.compressed);
+        tree.set_uncompressed(id, entry.uncompressed);
+        parent.children.push(id);
+        parent.size = parent.size.saturating_add(entry.compressed);
+        parent.uncompressed = parent.uncompressed.saturating_add(entry.uncompressed);
+    }
+
+    // Deepest folders first, so every folder has its totals before its parent needs them.
+    let mut paths: Vec<String> = folders.keys().cloned().collect();
+    paths.sort_by_key(|path| std::cmp::Reverse(folders[path].depth));
+    for path in paths {
+        let mut folder = folders.remove(&path).expect("listed above");
+        tree.link_children(folder.id, &mut folder.children);
+        tree.set_uncompressed(folder.id, folder.uncompressed);
+        if path.is_empty() {
+            // The archive keeps its size on disk.
+            continue;
+        }
+        tree.set_size(folder.id, folder.size);
+        if let Some(parent) = folders.get_mut(&folder.parent) {
+            parent.size = parent.size.saturating_add(folder.size);
+            parent.uncompressed = parent.uncompressed.saturating_add(folder.uncompressed);
+        }
+    }
+}
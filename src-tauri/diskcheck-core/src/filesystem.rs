@@ -0,0 +1,586 @@
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::node::FsNodeKind;
+
+/// What the scanner needs to know about an entry, without following symlinks.
+#[derive(Debug, Clone, Copy)]
+pub struct EntryMetadata {
+    pub kind: FsNodeKind,
+    pub len: u64,
+    // Only when the listing or stat provided it for free; directory entries listed on
+    // Unix carry none.
+    pub modified: Option<SystemTime>,
+}
+
+fn kind_of(file_type: fs::FileType) -> FsNodeKind {
+    if file_type.is_symlink() {
+        FsNodeKind::Symlink
+    } else if file_type.is_file() {
+        FsNodeKind::File
+    } else if file_type.is_dir() {
+        FsNodeKind::Directory
+    } else {
+        FsNodeKind::Other
+    }
+}
+
+impl From<&fs::Metadata> for EntryMetadata {
+    fn from(meta: &fs::Metadata) -> Self {
+        Self {
+            kind: kind_of(meta.file_type()),
+            len: meta.len(),
+            modified: meta.modified().ok(),
+        }
+    }
+}
+
+/// A listed directory entry together with its metadata.
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    pub path: PathBuf,
+    pub metadata: EntryMetadata,
+}
+
+/// The error of a listed entry whose metadata could not be read, keeping its path so the
+/// scanner can stat it again.
+#[derive(Debug)]
+pub(crate) struct EntryError {
+    path: PathBuf,
+    source: io::Error,
+}
+
+impl EntryError {
+    // Windows listings carry all metadata, so no entry fails on its own there.
+    #[cfg_attr(windows, allow(dead_code))]
+    pub(crate) fn wrap(path: PathBuf, source: io::Error) -> io::Error {
+        io::Error::new(source.kind(), EntryError { path, source })
+    }
+
+    /// The entry `err` is about, if it came from [`EntryError::wrap`].
+    pub(crate) fn path_of(err: &io::Error) -> Option<&Path> {
+        err.get_ref()?
+            .downcast_ref::<EntryError>()
+            .map(|e| e.path.as_path())
+    }
+}
+
+impl fmt::Display for EntryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.to_string_lossy(), self.source)
+    }
+}
+
+impl std::error::Error for EntryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// The error of a listing that made no progress for too long, e.g. on a dead network
+/// mount. Not worth retrying: the thread stuck on it never comes back.
+#[derive(Debug)]
+pub(crate) struct NotResponding {
+    path: PathBuf,
+    timeout: Duration,
+}
+
+impl NotResponding {
+    fn error(path: &Path, timeout: Duration) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::TimedOut,
+            NotResponding {
+                path: path.to_path_buf(),
+                timeout,
+            },
+        )
+    }
+
+    pub(crate) fn is(err: &io::Error) -> bool {
+        err.get_ref().is_some_and(|e| e.is::<NotResponding>())
+    }
+}
+
+impl fmt::Display for NotResponding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: not responding after {} s",
+            self.path.to_string_lossy(),
+            self.timeout.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for NotResponding {}
+
+/// A directory's entries, in no particular order. An entry whose metadata cannot be read
+/// is an error naming its path.
+pub type DirEntries<'a> = Box<dyn Iterator<Item = io::Result<DirEntry>> + 'a>;
+
+/// The filesystem operations the scanner uses, so scans can run against something
+/// other than the real disk.
+pub trait FileSystem {
+    fn symlink_metadata(&self, path: &Path) -> io::Result<EntryMetadata>;
+    fn read_dir(&self, path: &Path) -> io::Result<DirEntries<'_>>;
+
+    /// Like [`FileSystem::read_dir`], but lists on a separate thread and gives up once
+    /// opening the directory or reading the next entry takes longer than `timeout`, so
+    /// a hung mount cannot stall the caller. `None` when this filesystem cannot list
+    /// off the calling thread; the caller lists inline then.
+    fn read_dir_guarded(
+        &self,
+        _path: &Path,
+        _timeout: Duration,
+    ) -> Option<io::Result<DirEntries<'_>>> {
+        None
+    }
+}
+
+enum Listed {
+    Opened(io::Result<()>),
+    Entry(io::Result<DirEntry>),
+}
+
+/// Entries arriving from a listing thread; see [`FileSystem::read_dir_guarded`].
+struct GuardedEntries {
+    path: PathBuf,
+    entries: mpsc::Receiver<Listed>,
+    timeout: Duration,
+    done: bool,
+}
+
+impl Iterator for GuardedEntries {
+    type Item = io::Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.entries.recv_timeout(self.timeout) {
+            Ok(Listed::Entry(entry)) => Some(entry),
+            Ok(Listed::Opened(_)) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                self.done = true;
+                None
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                self.done = true;
+                Some(Err(NotResponding::error(&self.path, self.timeout)))
+            }
+        }
+    }
+}
+
+/// The real filesystem via `std::fs`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFs;
+
+impl FileSystem for RealFs {
+    fn symlink_metadata(&self, path: &Path) -> io::Result<EntryMetadata> {
+        fs::symlink_metadata(path).map(|meta| EntryMetadata::from(&meta))
+    }
+
+    #[cfg(not(windows))]
+    fn read_dir(&self, path: &Path) -> io::Result<DirEntries<'_>> {
+        let entries = fs::read_dir(path)?;
+        Ok(Box::new(entries.map(|entry| real_entry(entry?))))
+    }
+
+    #[cfg(windows)]
+    fn read_dir(&self, path: &Path) -> io::Result<DirEntries<'_>> {
+        Ok(Box::new(platform::FindIter::open(path)?))
+    }
+
+    /// A thread stuck in the kernel cannot be stopped, so one that times out is left
+    /// behind; it ends by itself if the mount ever answers.
+    fn read_dir_guarded(
+        &self,
+        path: &Path,
+        timeout: Duration,
+    ) -> Option<io::Result<DirEntries<'_>>> {
+        // Bounded, so a fast listing cannot run far ahead of the scan.
+        let (tx, rx) = mpsc::sync_channel(1024);
+        let dir = path.to_path_buf();
+        let spawned = thread::Builder::new()
+            .name("diskcheck-list".to_string())
+            .spawn(move || {
+                let entries = match RealFs.read_dir(&dir) {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        let _ = tx.send(Listed::Opened(Err(e)));
+                        return;
+                    }
+                };
+                if tx.send(Listed::Opened(Ok(()))).is_err() {
+                    return;
+                }
+                for entry in entries {
+                    if tx.send(Listed::Entry(entry)).is_err() {
+                        break;
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            return Some(Err(e));
+        }
+        let opened = match rx.recv_timeout(timeout) {
+            Ok(Listed::Opened(opened)) => opened,
+            Ok(Listed::Entry(_)) | Err(mpsc::RecvTimeoutError::Disconnected) => Ok(()),
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(NotResponding::error(path, timeout)),
+        };
+        Some(opened.map(|()| {
+            Box::new(GuardedEntries {
+                path: path.to_path_buf(),
+                entries: rx,
+                timeout,
+                done: false,
+            }) as DirEntries<'_>
+        }))
+    }
+}
+
+/// Builds a `DirEntry` from what the listing already knows. The file type usually comes
+/// with the entry (d_type), so only files need a stat, and that one goes through the
+/// entry (fstatat relative to the open directory) rather than resolving the full path
+/// again.
+#[cfg(not(windows))]
+fn real_entry(entry: fs::DirEntry) -> io::Result<DirEntry> {
+    let path = entry.path();
+    let with_path = |e: io::Error| EntryError::wrap(path.clone(), e);
+    let kind = kind_of(entry.file_type().map_err(with_path)?);
+    let metadata = match kind {
+        FsNodeKind::File => EntryMetadata::from(&entry.metadata().map_err(with_path)?),
+        _ => EntryMetadata {
+            kind,
+            len: 0,
+            modified: None,
+        },
+    };
+    Ok(DirEntry { path, metadata })
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::{DirEntry, EntryMetadata};
+    use crate::node::FsNodeKind;
+    use std::{
+        ffi::OsString,
+        io,
+        os::windows::ffi::{OsStrExt, OsStringExt},
+        path::{Path, PathBuf},
+        ptr::null,
+        time::{Duration, SystemTime},
+    };
+    use windows_sys::Win32::{
+        Foundation::{
+            ERROR_FILE_NOT_FOUND, ERROR_NO_MORE_FILES, FILETIME, HANDLE, INVALID_HANDLE_VALUE,
+        },
+        Storage::FileSystem::{
+            FindClose, FindExInfoBasic, FindExSearchNameMatch, FindFirstFileExW, FindNextFileW,
+            FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_REPARSE_POINT, FIND_FIRST_EX_LARGE_FETCH,
+            WIN32_FIND_DATAW,
+        },
+    };
+
+    // Reparse tags with this bit set (symlinks, junctions) point at another name; the
+    // rest (OneDrive placeholders, dedup, ...) are ordinary files and folders.
+    const NAME_SURROGATE_BIT: u32 = 0x2000_0000;
+    // Beyond this, the search pattern needs the `\\?\` prefix to get past MAX_PATH.
+    const MAX_PLAIN_PATTERN: usize = 248;
+    // FILETIME counts 100 ns ticks since 1601-01-01.
+    const UNIX_EPOCH_TICKS: u64 = 116_444_736_000_000_000;
+
+    fn system_time(time: &FILETIME) -> Option<SystemTime> {
+        let ticks = (u64::from(time.dwHighDateTime) << 32) | u64::from(time.dwLowDateTime);
+        let since_epoch = ticks.checked_sub(UNIX_EPOCH_TICKS)?;
+        SystemTime::UNIX_EPOCH.checked_add(Duration::from_nanos(since_epoch.saturating_mul(100)))
+    }
+
+    /// `dir\*`, with the verbatim prefix added for long paths.
+    fn search_pattern(dir: &Path) -> Vec<u16> {
+        let pattern = dir.join("*");
+        let mut wide: Vec<u16> = pattern.as_os_str().encode_wide().collect();
+        let text = pattern.to_string_lossy();
+        if wide.len() >= MAX_PLAIN_PATTERN && !text.starts_with(r"\\?\") {
+            // The verbatim form does no separator normalization of its own.
+            for unit in wide.iter_mut() {
+                if *unit == u16::from(b'/') {
+                    *unit = u16::from(b'\\');
+                }
+            }
+            if text.starts_with(r"\\") {
+                // \\server\share -> \\?\UNC\server\share
+                wide.splice(0..2, r"\\?\UNC\".encode_utf16());
+            } else {
+                wide.splice(0..0, r"\\?\".encode_utf16());
+            }
+        }
+        wide.push(0);
+        wide
+    }
+
+    fn metadata(data: &WIN32_FIND_DATAW) -> EntryMetadata {
+        let attributes = data.dwFileAttributes;
+        // For reparse points, dwReserved0 holds the reparse tag.
+        let is_link = attributes & FILE_ATTRIBUTE_REPARSE_POINT != 0
+            && data.dwReserved0 & NAME_SURROGATE_BIT != 0;
+        let kind = if is_link {
+            FsNodeKind::Symlink
+        } else if attributes & FILE_ATTRIBUTE_DIRECTORY != 0 {
+            FsNodeKind::Directory
+        } else {
+            FsNodeKind::File
+        };
+        let len = match kind {
+            FsNodeKind::File => {
+                (u64::from(data.nFileSizeHigh) << 32) | u64::from(data.nFileSizeLow)
+            }
+            _ => 0,
+        };
+        EntryMetadata {
+            kind,
+            len,
+            modified: system_time(&data.ftLastWriteTime),
+        }
+    }
+
+    /// Lists a directory with `FindFirstFileExW(FIND_FIRST_EX_LARGE_FETCH)`. Sizes and
+    /// attributes come straight from `WIN32_FIND_DATAW`, so no entry needs its own
+    /// metadata call, and the larger buffer cuts round trips on network shares.
+    pub(super) struct FindIter {
+        dir: PathBuf,
+        handle: HANDLE,
+        data: WIN32_FIND_DATAW,
+        // Whether `data` holds an entry not yet returned.
+        pending: bool,
+        done: bool,
+    }
+
+    impl FindIter {
+        pub(super) fn open(dir: &Path) -> io::Result<Self> {
+            let pattern = search_pattern(dir);
+            // SAFETY: zeroed is a valid WIN32_FIND_DATAW (plain integers and arrays).
+            let mut data: WIN32_FIND_DATAW = unsafe { std::mem::zeroed() };
+            // SAFETY: `pattern` is NUL-terminated and `data` is valid for writes.
+            let handle = unsafe {
+                FindFirstFileExW(
+                    pattern.as_ptr(),
+                    FindExInfoBasic,
+                    (&mut data as *mut WIN32_FIND_DATAW).cast(),
+                    FindExSearchNameMatch,
+                    null(),
+                    FIND_FIRST_EX_LARGE_FETCH,
+                )
+            };
+            // A drive root has no `.` entry, so an empty one matches nothing at all.
+            let empty = handle == INVALID_HANDLE_VALUE
+                && io::Error::last_os_error().raw_os_error() == Some(ERROR_FILE_NOT_FOUND as i32);
+            if handle == INVALID_HANDLE_VALUE && !empty {
+                let e = io::Error::last_os_error();
+                return Err(io::Error::new(
+                    e.kind(),
+                    format!("{}: {}", dir.to_string_lossy(), e),
+                ));
+            }
+            Ok(FindIter {
+                dir: dir.to_path_buf(),
+                handle,
+                data,
+                pending: !empty,
+                done: empty,
+            })
+        }
+    }
+
+    impl Iterator for FindIter {
+        type Item = io::Result<DirEntry>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            loop {
+                if self.done {
+                    return None;
+                }
+                if !self.pending {
+                    // SAFETY: the handle stays open until drop; `data` is valid for writes.
+                    if unsafe { FindNextFileW(self.handle, &mut self.data) } == 0 {
+                        let e = io::Error::last_os_error();
+                        self.done = true;
+                        if e.raw_os_error() == Some(ERROR_NO_MORE_FILES as i32) {
+                            return None;
+                        }
+                        return Some(Err(io::Error::new(
+                            e.kind(),
+                            format!("{}: {}", self.dir.to_string_lossy(), e),
+                        )));
+                    }
+                }
+                self.pending = false;
+
+                let name = &self.data.cFileName;
+                let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+                match &name[..len] {
+                    // "." and ".."
+                    [0x2e] | [0x2e, 0x2e] => continue,
+                    name => {
+                        return Some(Ok(DirEntry {
+                            path: self.dir.join(OsString::from_wide(name)),
+                            metadata: metadata(&self.data),
+                        }))
+                    }
+                }
+            }
+        }
+    }
+
+    impl Drop for FindIter {
+        fn drop(&mut self) {
+            if self.handle != INVALID_HANDLE_VALUE {
+                // SAFETY: `handle` came from FindFirstFileExW and is closed only here.
+                unsafe { FindClose(self.handle) };
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum MemoryEntry {
+    Entry(EntryMetadata),
+    // Listing the directory fails, e.g. a folder the user may not open.
+    UnreadableDir,
+    // Even stat fails, e.g. an entry deleted between listing and stat.
+    Broken,
+}
+
+/// An in-memory tree for deterministic scans. Entries are listed in path order.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFs {
+    entries: BTreeMap<PathBuf, MemoryEntry>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&mut self, path: &Path, entry: MemoryEntry) -> &mut Self {
+        // Missing parents become plain directories.
+        for ancestor in path.ancestors().skip(1) {
+            self.entries
+                .entry(ancestor.to_path_buf())
+                .or_insert(MemoryEntry::Entry(EntryMetadata {
+                    kind: FsNodeKind::Directory,
+                    len: 0,
+                    modified: None,
+                }));
+        }
+        self.entries.insert(path.to_path_buf(), entry);
+        self
+    }
+
+    pub fn add_file(&mut self, path: impl AsRef<Path>, len: u64) -> &mut Self {
+        self.insert(
+            path.as_ref(),
+            MemoryEntry::Entry(EntryMetadata {
+                kind: FsNodeKind::File,
+                len,
+                modified: None,
+            }),
+        )
+    }
+
+    pub fn add_dir(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.insert(
+            path.as_ref(),
+            MemoryEntry::Entry(EntryMetadata {
+                kind: FsNodeKind::Directory,
+                len: 0,
+                modified: None,
+            }),
+        )
+    }
+
+    pub fn add_symlink(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.insert(
+            path.as_ref(),
+            MemoryEntry::Entry(EntryMetadata {
+                kind: FsNodeKind::Symlink,
+                len: 0,
+                modified: None,
+            }),
+        )
+    }
+
+    pub fn add_unreadable_dir(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.insert(path.as_ref(), MemoryEntry::UnreadableDir)
+    }
+
+    pub fn add_broken_entry(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.insert(path.as_ref(), MemoryEntry::Broken)
+    }
+
+    fn entry(&self, path: &Path) -> io::Result<&MemoryEntry> {
+        self.entries.get(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} does not exist", path.to_string_lossy()),
+            )
+        })
+    }
+}
+
+impl FileSystem for MemoryFs {
+    fn symlink_metadata(&self, path: &Path) -> io::Result<EntryMetadata> {
+        match self.entry(path)? {
+            MemoryEntry::Entry(meta) => Ok(*meta),
+            MemoryEntry::UnreadableDir => Ok(EntryMetadata {
+                kind: FsNodeKind::Directory,
+                len: 0,
+                modified: None,
+            }),
+            MemoryEntry::Broken => Err(io::Error::other(format!(
+                "{} cannot be read",
+                path.to_string_lossy()
+            ))),
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<DirEntries<'_>> {
+        match self.entry(path)? {
+            MemoryEntry::Entry(EntryMetadata {
+                kind: FsNodeKind::Directory,
+                ..
+            }) => {}
+            MemoryEntry::UnreadableDir => {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("{} cannot be listed", path.to_string_lossy()),
+                ))
+            }
+            _ => {
+                return Err(io::Error::other(format!(
+                    "{} is not a directory",
+                    path.to_string_lossy()
+                )))
+            }
+        }
+
+        let path = path.to_path_buf();
+        Ok(Box::new(
+            self.entries
+                .keys()
+                .filter(move |p| p.parent() == Some(path.as_path()))
+                .map(|p| {
+                    self.symlink_metadata(p).map(|metadata| DirEntry {
+                        path: p.clone(),
+                        metadata,
+                    })
+                }),
+        ))
+    }
+}
@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    filesystem::{DirEntries, DirEntry, EntryMetadata},
+    node::FsNodeKind,
+};
+
+// Folders changed this close to the scan may change again within the same mtime tick
+// (coarse on FAT and some network filesystems), so their listing is not trusted later.
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedEntry {
+    name: String,
+    is_dir: bool,
+    len: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedDir {
+    // Nanoseconds since the Unix epoch.
+    modified: u128,
+    entries: Vec<IndexedEntry>,
+}
+
+fn nanos(time: SystemTime) -> Option<u128> {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_nanos())
+}
+
+/// What a scan saw in every folder it listed: the folder's mtime and its files and
+/// subfolders with their sizes. A later scan reuses the listing of any folder whose
+/// mtime is unchanged instead of reading it again; subfolders are still visited, since
+/// their own changes do not touch the parent's mtime. Files rewritten in place do not
+/// change their folder's mtime either, so a reused folder reports their old sizes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanIndex {
+    dirs: HashMap<String, IndexedDir>,
+}
+
+impl ScanIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of folders indexed.
+    pub fn len(&self) -> usize {
+        self.dirs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dirs.is_empty()
+    }
+
+    /// The recorded listing of `dir`, if it was recorded with the same mtime.
+    pub(crate) fn entries(&self, dir: &Path, modified: SystemTime) -> Option<DirEntries<'_>> {
+        let indexed = self.dirs.get(dir.to_str()?)?;
+        if Some(indexed.modified) != nanos(modified) {
+            return None;
+        }
+        let dir = dir.to_path_buf();
+        Some(Box::new(indexed.entries.iter().map(move |entry| {
+            Ok(DirEntry {
+                path: dir.join(&entry.name),
+                metadata: EntryMetadata {
+                    kind: if entry.is_dir {
+                        FsNodeKind::Directory
+                    } else {
+                        FsNodeKind::File
+                    },
+                    len: entry.len,
+                    modified: None,
+                },
+            })
+        })))
+    }
+
+    /// Records the complete listing of `dir` (files and folders only) as seen by a scan
+    /// that started at `scan_started`.
+    pub(crate) fn record(
+        &mut self,
+        dir: &Path,
+        modified: SystemTime,
+        scan_started: SystemTime,
+        entries: &[DirEntry],
+    ) {
+        let (Some(path), Some(modified_nanos)) = (dir.to_str(), nanos(modified)) else {
+            return;
+        };
+        if modified + RACY_WINDOW >= scan_started {
+            return;
+        }
+        let entries = entries
+            .iter()
+            .filter_map(|entry| {
+                let is_dir = match entry.metadata.kind {
+                    FsNodeKind::Directory => true,
+                    FsNodeKind::File => false,
+                    FsNodeKind::Symlink | FsNodeKind::Other => return None,
+                };
+                Some(IndexedEntry {
+                    name: entry.path.file_name()?.to_str()?.to_string(),
+                    is_dir,
+                    len: entry.metadata.len,
+                })
+            })
+            .collect();
+        self.dirs.insert(
+            path.to_string(),
+            IndexedDir {
+                modified: modified_nanos,
+                entries,
+            },
+        );
+    }
+
+    /// Calls `visit` with the path and size of every file recorded at and below `dir`.
+    /// Returns false when `dir` itself was not recorded.
+    fn visit_files(&self, dir: &Path, mut visit: impl FnMut(PathBuf, u64)) -> bool {
+        if !self.contains_dir(dir) {
+            return false;
+        }
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let Some(indexed) = dir.to_str().and_then(|path| self.dirs.get(path)) else {
+                continue;
+            };
+            for entry in &indexed.entries {
+                if entry.is_dir {
+                    pending.push(dir.join(&entry.name));
+                } else {
+                    visit(dir.join(&entry.name), entry.len);
+                }
+            }
+        }
+        true
+    }
+
+    /// Total size of the files recorded at and below `dir`, or `None` when `dir` itself
+    /// was not recorded. Folders left out of the index count as empty, so this can fall
+    /// short of the folder's size.
+    pub fn size_below(&self, dir: &Path) -> Option<u64> {
+        let mut total = 0u64;
+        self.visit_files(dir, |_, len| total = total.saturating_add(len))
+            .then_some(total)
+    }
+
+    /// The files recorded at and below `dir` with their sizes, or `None` when `dir`
+    /// itself was not recorded.
+    pub fn files_below(&self, dir: &Path) -> Option<Vec<(PathBuf, u64)>> {
+        let mut files = vec![];
+        self.visit_files(dir, |path, len| files.push((path, len)))
+            .then_some(files)
+    }
+
+    /// Files and folders recorded below `dir`, or `None` when `dir` itself was not
+    /// recorded. Folders left out of the index count as empty.
+    pub fn count_below(&self, dir: &Path) -> Option<(u64, u64)> {
+        if !self.contains_dir(dir) {
+            return None;
+        }
+        let (mut files, mut dirs) = (0u64, 0u64);
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let Some(indexed) = dir.to_str().and_then(|path| self.dirs.get(path)) else {
+                continue;
+            };
+            for entry in &indexed.entries {
+                if entry.is_dir {
+                    dirs += 1;
+                    pending.push(dir.join(&entry.name));
+                } else {
+                    files += 1;
+                }
+            }
+        }
+        Some((files, dirs))
+    }
+
+    /// Whether the listing of `dir` was recorded.
+    pub fn contains_dir(&self, dir: &Path) -> bool {
+        dir.to_str()
+            .is_some_and(|path| self.dirs.contains_key(path))
+    }
+
+    /// Whether `dir` was recorded here and in `other` with the same mtime. A refresh
+    /// scan then reused `other`'s listing, whose file sizes may have gone stale since.
+    pub fn same_listing(&self, other: &ScanIndex, dir: &Path) -> bool {
+        let Some(path) = dir.to_str() else {
+            return false;
+        };
+        match (self.dirs.get(path), other.dirs.get(path)) {
+            (Some(this), Some(other)) => this.modified == other.modified,
+            _ => false,
+        }
+    }
+
+    /// The recorded size of the file at `path`; `None` when its folder was not recorded
+    /// or did not hold it.
+    pub fn file_len(&self, path: &Path) -> Option<u64> {
+        let (dir, name) = (path.parent()?.to_str()?, path.file_name()?.to_str()?);
+        self.dirs
+            .get(dir)?
+            .entries
+            .iter()
+            .find(|entry| !entry.is_dir && entry.name == name)
+            .map(|entry| entry.len)
+    }
+
+    /// Removes everything recorded at or below `root` and returns it.
+    pub fn take_below(&mut self, root: &Path) -> ScanIndex {
+        let (below, rest) = std::mem::take(&mut self.dirs)
+            .into_iter()
+            .partition(|(path, _)| Path::new(path).starts_with(root));
+        self.dirs = rest;
+        ScanIndex { dirs: below }
+    }
+
+    /// Replaces everything recorded at or below `root` with what `newer` holds, e.g.
+    /// after re-scanning one folder of an indexed volume.
+    pub fn merge(&mut self, root: &Path, newer: ScanIndex) {
+        self.dirs
+            .retain(|path, _| !Path::new(path).starts_with(root));
+        self.dirs.extend(newer.dirs);
+    }
+}
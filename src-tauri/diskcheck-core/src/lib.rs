@@ -0,0 +1,23 @@
+mod archive;
+mod filesystem;
+mod index;
+mod node;
+mod paths;
+mod progress;
+mod prune;
+mod scan;
+mod tree;
+
+pub use archive::{ArchiveEntry, ArchiveLister};
+pub use filesystem::{DirEntries, DirEntry, EntryMetadata, FileSystem, MemoryFs, RealFs};
+pub use index::ScanIndex;
+pub use node::{display_name, file_extension_lower, FsNode, FsNodeKind};
+pub use paths::{long_path, names_equal, relative_to, same_path};
+pub use progress::{NoProgress, ProgressSnapshot, ScanProgress};
+pub use prune::{prune_tree, pruned_view};
+pub use scan::{
+    scan, scan_incremental, scan_with, scan_with_hooks, take_scan_dir, walk, walk_files, Scan,
+    ScanHooks, ScanOptions, ScanStats, SkipReason, SkippedEntry, SubtreeSink,
+    DEFAULT_MIN_NODE_BYTES,
+};
+pub use tree::{Children, NodeRef, ScanTree};
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FsNodeKind {
+    File,
+    Directory,
+    Symlink,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsNode {
+    pub name: String,
+    pub path: String,
+    pub kind: FsNodeKind,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<FsNode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    // Archives and entries listed inside them: the size once extracted. For entries,
+    // `size` is what they take up inside the archive.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uncompressed_size: Option<u64>,
+    // Pruned views: how many nodes below this one, kept or pruned, carry an error, so
+    // the UI can flag totals that are known to be incomplete.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub error_count: u64,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+pub fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+pub fn file_extension_lower(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .filter(|s| !s.is_empty())
+}
@@ -0,0 +1,84 @@
+use std::{
+    borrow::Cow,
+    path::{Component, Path},
+};
+
+// Windows and macOS filesystems are case-insensitive by default, so paths typed or
+// stored with other casing still name the same entry there.
+const CASE_INSENSITIVE: bool = cfg!(any(windows, target_os = "macos"));
+
+/// Whether two entry names refer to the same entry: equal, or equal but for case on
+/// platforms whose filesystems ignore it.
+pub fn names_equal(a: &str, b: &str) -> bool {
+    a == b
+        || (CASE_INSENSITIVE && (a.eq_ignore_ascii_case(b) || a.to_lowercase() == b.to_lowercase()))
+}
+
+fn components_equal(a: Component<'_>, b: Component<'_>) -> bool {
+    a == b
+        || names_equal(
+            &a.as_os_str().to_string_lossy(),
+            &b.as_os_str().to_string_lossy(),
+        )
+}
+
+/// The part of `path` below `root`, compared component by component like
+/// [`names_equal`]. `None` when `path` is not `root` or inside it.
+pub fn relative_to<'a>(root: &str, path: &'a str) -> Option<Vec<Cow<'a, str>>> {
+    let mut path = Path::new(path).components();
+    for root in Path::new(root).components() {
+        if !components_equal(root, path.next()?) {
+            return None;
+        }
+    }
+    Some(path.map(|c| c.as_os_str().to_string_lossy()).collect())
+}
+
+/// Whether `a` and `b` name the same path, ignoring separators, trailing slashes and
+/// (where the filesystem does) case.
+pub fn same_path(a: &str, b: &str) -> bool {
+    relative_to(a, b).is_some_and(|rest| rest.is_empty())
+}
+
+/// `path` with 8.3 short names (`C:\PROGRA~1`) expanded to the long names scans
+/// store. Paths that do not exist, or have no short components, come back unchanged.
+#[cfg(windows)]
+pub fn long_path(path: &str) -> Cow<'_, str> {
+    use std::{
+        ffi::OsString,
+        os::windows::ffi::{OsStrExt, OsStringExt},
+        ptr::null_mut,
+    };
+    use windows_sys::Win32::Storage::FileSystem::GetLongPathNameW;
+
+    if !path.contains('~') {
+        return Cow::Borrowed(path);
+    }
+    let wide: Vec<u16> = Path::new(path)
+        .as_os_str()
+        .encode_wide()
+        .chain([0])
+        .collect();
+    // SAFETY: `wide` is NUL-terminated; a zero-length buffer asks for the needed size.
+    let needed = unsafe { GetLongPathNameW(wide.as_ptr(), null_mut(), 0) };
+    if needed == 0 {
+        return Cow::Borrowed(path);
+    }
+    let mut buf = vec![0u16; needed as usize];
+    // SAFETY: `buf` holds `needed` characters, terminating NUL included.
+    let len = unsafe { GetLongPathNameW(wide.as_ptr(), buf.as_mut_ptr(), needed) };
+    if len == 0 || len >= needed {
+        return Cow::Borrowed(path);
+    }
+    Cow::Owned(
+        OsString::from_wide(&buf[..len as usize])
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+/// `path` unchanged: only Windows has short names.
+#[cfg(not(windows))]
+pub fn long_path(path: &str) -> Cow<'_, str> {
+    Cow::Borrowed(path)
+}
@@ -0,0 +1,102 @@
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressSnapshot<'a> {
+    pub scanned_files: u64,
+    pub scanned_dirs: u64,
+    pub total_bytes: u64,
+    pub current_path: Option<&'a Path>,
+}
+
+/// Receives throttled progress updates while a scan runs.
+pub trait ScanProgress {
+    fn on_progress(&self, snapshot: ProgressSnapshot<'_>);
+}
+
+impl<F: Fn(ProgressSnapshot<'_>)> ScanProgress for F {
+    fn on_progress(&self, snapshot: ProgressSnapshot<'_>) {
+        self(snapshot)
+    }
+}
+
+/// For headless scans that nobody watches.
+pub struct NoProgress;
+
+impl ScanProgress for NoProgress {
+    fn on_progress(&self, _snapshot: ProgressSnapshot<'_>) {}
+}
+
+pub(crate) struct ProgressReporter<'a> {
+    sink: &'a dyn ScanProgress,
+    pub(crate) scanned_files: AtomicU64,
+    pub(crate) scanned_dirs: AtomicU64,
+    total_bytes: AtomicU64,
+    last_emit: Mutex<Instant>,
+    emit_interval: Duration,
+}
+
+impl<'a> ProgressReporter<'a> {
+    pub(crate) fn new(sink: &'a dyn ScanProgress, emit_interval: Duration) -> Self {
+        Self {
+            sink,
+            scanned_files: AtomicU64::new(0),
+            scanned_dirs: AtomicU64::new(0),
+            total_bytes: AtomicU64::new(0),
+            last_emit: Mutex::new(Instant::now()),
+            emit_interval,
+        }
+    }
+
+    pub(crate) fn file_scanned(&self, bytes: u64, current_path: &Path) {
+        let next = self.scanned_files.fetch_add(1, Ordering::Relaxed) + 1;
+        self.total_bytes.fetch_add(bytes, Ordering::Relaxed);
+
+        // Emit infrequently to keep overhead low when scanning millions of files.
+        if next.is_multiple_of(512) {
+            self.maybe_emit(Some(current_path));
+        }
+    }
+
+    pub(crate) fn dir_scanned(&self, current_path: &Path) {
+        let next = self.scanned_dirs.fetch_add(1, Ordering::Relaxed) + 1;
+        if next.is_multiple_of(64) {
+            self.maybe_emit(Some(current_path));
+        }
+    }
+
+    pub(crate) fn emit_force(&self, current_path: Option<&Path>) {
+        self.emit(current_path);
+        if let Ok(mut last_emit) = self.last_emit.lock() {
+            *last_emit = Instant::now();
+        }
+    }
+
+    fn maybe_emit(&self, current_path: Option<&Path>) {
+        let now = Instant::now();
+        let should_emit = self
+            .last_emit
+            .lock()
+            .map(|last| now.duration_since(*last) >= self.emit_interval)
+            .unwrap_or(true);
+
+        if should_emit {
+            self.emit_force(current_path);
+        }
+    }
+
+    fn emit(&self, current_path: Option<&Path>) {
+        self.sink.on_progress(ProgressSnapshot {
+            scanned_files: self.scanned_files.load(Ordering::Relaxed),
+            scanned_dirs: self.scanned_dirs.load(Ordering::Relaxed),
+            total_bytes: self.total_bytes.load(Ordering::Relaxed),
+            current_path,
+        });
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use crate::{
+    node::FsNode,
+    scan::{ScanOptions, DEFAULT_MIN_NODE_BYTES},
+    tree::NodeRef,
+};
+
+struct PruneFrame<'a> {
+    source: NodeRef<'a>,
+    // Children selected for output, largest first; consumed back to front.
+    pending: Vec<NodeRef<'a>>,
+    kept: Vec<FsNode>,
+}
+
+impl<'a> PruneFrame<'a> {
+    fn new(source: NodeRef<'a>, opts: &ScanOptions) -> Self {
+        // Children are stored sorted by size, so the largest candidates come first.
+        let mut pending: Vec<NodeRef<'a>> = source
+            .children()
+            .take_while(|c| c.size() >= opts.min_node_bytes)
+            .take(opts.max_children_per_dir)
+            .collect();
+        pending.reverse();
+        Self {
+            source,
+            pending,
+            kept: vec![],
+        }
+    }
+
+    fn finish(self, error_counts: &HashMap<u32, u64>) -> FsNode {
+        let mut node = self.source.shallow_fs_node(self.kept);
+        node.error_count = error_counts.get(&self.source.id()).copied().unwrap_or(0);
+        node
+    }
+}
+
+/// Produces the IPC-safe view of a full tree: only nodes >= `min_node_bytes`, at most
+/// `max_children_per_dir` per directory and `max_total_nodes` overall. Sizes are
+/// always those of the full tree, and each node counts the errors below it, pruned or
+/// not. Returns whether the node limit was hit.
+pub fn prune_tree(full: NodeRef<'_>, opts: &ScanOptions) -> (FsNode, bool) {
+    let error_counts = full.tree().error_counts();
+    let mut hit_node_limit = false;
+    let mut returned_nodes: usize = 1; // root
+    let mut stack: Vec<PruneFrame> = vec![PruneFrame::new(full, opts)];
+
+    while let Some(frame) = stack.last_mut() {
+        match frame.pending.pop() {
+            Some(child) => {
+                if returned_nodes >= opts.max_total_nodes {
+                    hit_node_limit = true;
+                    continue;
+                }
+                returned_nodes += 1;
+                stack.push(PruneFrame::new(child, opts));
+            }
+            None => {
+                let node = match stack.pop() {
+                    Some(frame) => frame.finish(&error_counts),
+                    None => break,
+                };
+                match stack.last_mut() {
+                    Some(parent) => parent.kept.push(node),
+                    None => return (node, hit_node_limit),
+                }
+            }
+        }
+    }
+
+    (
+        PruneFrame::new(full, opts).finish(&error_counts),
+        hit_node_limit,
+    )
+}
+
+pub(crate) fn truncated_message(opts: &ScanOptions) -> String {
+    format!(
+        "Result truncated to <= {} nodes for stability. Increase the minimum size filter to reduce output.",
+        opts.max_total_nodes
+    )
+}
+
+/// The IPC-safe view of a stored tree or one of its subtrees.
+pub fn pruned_view(full: NodeRef<'_>, min_node_bytes: Option<u64>) -> FsNode {
+    let opts = ScanOptions::local(min_node_bytes.unwrap_or(DEFAULT_MIN_NODE_BYTES));
+    let (mut pruned, hit_node_limit) = prune_tree(full, &opts);
+    if hit_node_limit {
+        pruned.error = Some(truncated_message(&opts));
+    }
+    pruned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{filesystem::MemoryFs, progress::NoProgress, scan::scan_with, tree::ScanTree};
+    use std::path::Path;
+
+    fn sample() -> ScanTree {
+        let mut fs = MemoryFs::new();
+        fs.add_file("/r/big.bin", 300)
+            .add_file("/r/mid.bin", 200)
+            .add_file("/r/small.bin", 100)
+            .add_file("/r/dir/inner.bin", 50);
+        let opts = ScanOptions::local(0);
+        scan_with(&fs, Path::new("/r"), &opts, &NoProgress)
+            .unwrap()
+            .tree
+    }
+
+    fn names(node: &FsNode) -> Vec<&str> {
+        node.children
+            .iter()
+            .map(|child| child.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn drops_nodes_below_the_minimum_size() {
+        let tree = sample();
+        let (pruned, hit_node_limit) = prune_tree(tree.root(), &ScanOptions::local(150));
+
+        assert_eq!(names(&pruned), ["big.bin", "mid.bin"]);
+        // Sizes stay those of the full tree.
+        assert_eq!(pruned.size, 650);
+        assert!(!hit_node_limit);
+    }
+
+    #[test]
+    fn keeps_the_largest_children_per_folder() {
+        let tree = sample();
+        let opts = ScanOptions {
+            max_children_per_dir: 2,
+            ..ScanOptions::local(0)
+        };
+        let (pruned, hit_node_limit) = prune_tree(tree.root(), &opts);
+
+        assert_eq!(names(&pruned), ["big.bin", "mid.bin"]);
+        assert!(!hit_node_limit);
+    }
+
+    #[test]
+    fn stops_at_the_node_limit() {
+        let tree = sample();
+        let opts = ScanOptions {
+            max_total_nodes: 3,
+            ..ScanOptions::local(0)
+        };
+        let (pruned, hit_node_limit) = prune_tree(tree.root(), &opts);
+
+        assert!(hit_node_limit);
+        assert_eq!(names(&pruned), ["big.bin", "mid.bin"]);
+    }
+
+    #[test]
+    fn pruned_view_notes_truncation_on_the_root() {
+        let mut fs = MemoryFs::new();
+        // 11 folders of 1,000 files: more than the default 10,000 nodes.
+        for dir in 0..11 {
+            for file in 0..1_000 {
+                fs.add_file(format!("/r/d{}/f{}", dir, file), DEFAULT_MIN_NODE_BYTES);
+            }
+        }
+        let opts = ScanOptions::local(0);
+        let tree = scan_with(&fs, Path::new("/r"), &opts, &NoProgress)
+            .unwrap()
+            .tree;
+
+        let pruned = pruned_view(tree.root(), None);
+        assert_eq!(pruned.error, Some(truncated_message(&opts)));
+        let nodes = 1
+            + pruned.children.len()
+            + pruned
+                .children
+                .iter()
+                .map(|dir| dir.children.len())
+                .sum::<usize>();
+        assert_eq!(nodes, opts.max_total_nodes);
+        assert!(pruned_view(tree.root(), Some(2 * DEFAULT_MIN_NODE_BYTES))
+            .error
+            .is_none());
+    }
+}
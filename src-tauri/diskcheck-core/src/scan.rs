@@ -0,0 +1,1021 @@
+use serde::Serialize;
+use std::{
+    cell::RefCell,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{
+    archive::{self, ArchiveLister},
+    filesystem::{
+        DirEntries, DirEntry, EntryError, EntryMetadata, FileSystem, NotResponding, RealFs,
+    },
+    index::ScanIndex,
+    node::{display_name, FsNode, FsNodeKind},
+    progress::{ProgressReporter, ScanProgress},
+    prune::{prune_tree, truncated_message},
+    tree::ScanTree,
+};
+
+// NOTE: Returning the full file tree for large folders can crash the WebView IPC
+// serialization. Callers keep the full tree and hand out a defensively pruned copy
+// (with accurate directory sizes).
+pub const DEFAULT_MIN_NODE_BYTES: u64 = 1024 * 1024; // 1 MiB
+const DEFAULT_MAX_CHILDREN_PER_DIR: usize = 1_000;
+const DEFAULT_MAX_TOTAL_NODES: usize = 10_000;
+const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_millis(120);
+// Network mounts: every progress event competes with slow round-trips, and a single
+// unresponsive directory must not stall the whole scan.
+const NETWORK_PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+const NETWORK_DIR_TIME_BUDGET: Duration = Duration::from_secs(30);
+// A busy or briefly unreachable server gets 0.25 + 0.5 + 1 s before an entry is skipped.
+const NETWORK_TRANSIENT_RETRIES: u32 = 3;
+const NETWORK_RETRY_DELAY: Duration = Duration::from_millis(250);
+// A guarded directory that neither opens nor yields an entry for this long is skipped
+// as hung.
+const DEFAULT_HANG_TIMEOUT: Duration = Duration::from_secs(15);
+// Denied folders remembered per scan; past this they are only counted as skipped.
+const MAX_DENIED_DIRS: usize = 1_000;
+// Skipped entries listed per scan; past this they are only counted.
+const MAX_SKIPPED_ENTRIES: usize = 10_000;
+
+/// Walks `root` depth-first without following symlinks and calls `visit` for every
+/// regular file. Unreadable entries are skipped; their count is returned.
+pub fn walk_files(root: &Path, visit: impl FnMut(&Path, &fs::Metadata)) -> u64 {
+    walk(root, |_| true, visit)
+}
+
+/// Whether `err` only says the entry was deleted after it was listed: a race with
+/// whatever removed it, not a reason to report the entry as skipped.
+fn is_vanished(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::NotFound
+}
+
+/// Like [`walk_files`], also calling `enter` for every folder below `root`; folders it
+/// returns false for are left out.
+pub fn walk(
+    root: &Path,
+    mut enter: impl FnMut(&Path) -> bool,
+    mut visit: impl FnMut(&Path, &fs::Metadata),
+) -> u64 {
+    let mut skipped: u64 = 0;
+    let mut pending: Vec<PathBuf> = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let read_dir = match fs::read_dir(&dir) {
+            Ok(rd) => rd,
+            Err(e) => {
+                skipped = skipped.saturating_add(u64::from(!is_vanished(&e)));
+                continue;
+            }
+        };
+
+        for entry in read_dir {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(e) => {
+                    skipped = skipped.saturating_add(u64::from(!is_vanished(&e)));
+                    continue;
+                }
+            };
+            // The listed file type spares a stat for everything but regular files.
+            let file_type = match entry.file_type() {
+                Ok(t) => t,
+                Err(e) => {
+                    skipped = skipped.saturating_add(u64::from(!is_vanished(&e)));
+                    continue;
+                }
+            };
+
+            if file_type.is_dir() {
+                let path = entry.path();
+                if enter(&path) {
+                    pending.push(path);
+                }
+            } else if file_type.is_file() {
+                match entry.metadata() {
+                    Ok(meta) => visit(&entry.path(), &meta),
+                    Err(e) => skipped = skipped.saturating_add(u64::from(!is_vanished(&e))),
+                }
+            }
+        }
+    }
+
+    skipped
+}
+
+thread_local! {
+    // The folder the scan running on this thread is in, to tell where it was after a
+    // panic.
+    static CURRENT_DIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+fn set_current_dir(dir: Option<&Path>) {
+    CURRENT_DIR.with(|current| *current.borrow_mut() = dir.map(Path::to_path_buf));
+}
+
+/// Takes the folder the scan on this thread was in when it panicked, leaving `None`
+/// behind so a later scan on the same (pooled) thread doesn't report it; `None` once a
+/// scan ended normally. For reporting where a scan failed, not for resuming it.
+pub fn take_scan_dir() -> Option<PathBuf> {
+    CURRENT_DIR.with(|current| current.borrow_mut().take())
+}
+
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    pub min_node_bytes: u64,
+    pub max_children_per_dir: usize,
+    pub max_total_nodes: usize,
+    pub progress_interval: Duration,
+    // Maximum time spent enumerating a single directory before giving up on it.
+    pub dir_time_budget: Option<Duration>,
+    // Entry names (e.g. `node_modules`) or full paths that are left out of the scan.
+    pub excludes: Vec<String>,
+    // How often a stat or listing failing with a transient error (see `is_transient`)
+    // is tried again, waiting `retry_delay` and twice as long each further time.
+    pub transient_retries: u32,
+    pub retry_delay: Duration,
+    // Directories at or below these paths (network mounts inside a local scan, or the
+    // root of a network scan) are listed on a separate thread, so one that stops
+    // answering for `hang_timeout` is skipped instead of stalling the scan.
+    pub guarded_paths: Vec<PathBuf>,
+    pub hang_timeout: Duration,
+    // The whole scan stops after this long, keeping what it counted (see `Scan::partial`).
+    pub time_limit: Option<Duration>,
+}
+
+impl ScanOptions {
+    pub fn local(min_node_bytes: u64) -> Self {
+        Self {
+            min_node_bytes,
+            max_children_per_dir: DEFAULT_MAX_CHILDREN_PER_DIR,
+            max_total_nodes: DEFAULT_MAX_TOTAL_NODES,
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+            dir_time_budget: None,
+            excludes: vec![],
+            transient_retries: 0,
+            retry_delay: Duration::ZERO,
+            guarded_paths: vec![],
+            hang_timeout: DEFAULT_HANG_TIMEOUT,
+            time_limit: None,
+        }
+    }
+
+    pub fn network(min_node_bytes: u64) -> Self {
+        Self {
+            progress_interval: NETWORK_PROGRESS_INTERVAL,
+            dir_time_budget: Some(NETWORK_DIR_TIME_BUDGET),
+            transient_retries: NETWORK_TRANSIENT_RETRIES,
+            retry_delay: NETWORK_RETRY_DELAY,
+            ..Self::local(min_node_bytes)
+        }
+    }
+
+    pub fn with_excludes(mut self, excludes: Vec<String>) -> Self {
+        self.excludes = excludes;
+        self
+    }
+
+    pub fn with_guarded_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.guarded_paths = paths;
+        self
+    }
+
+    pub fn with_time_limit(mut self, limit: Option<Duration>) -> Self {
+        self.time_limit = limit;
+        self
+    }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.excludes.iter().any(|pattern| {
+            path.file_name()
+                .is_some_and(|name| name == pattern.as_str())
+                || path == Path::new(pattern)
+        })
+    }
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self::local(DEFAULT_MIN_NODE_BYTES)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanStats {
+    pub file_count: u64,
+    pub dir_count: u64,
+    pub skipped_entries: u64,
+    pub timed_out_dirs: u64,
+    // Guarded directories skipped because they stopped responding.
+    pub hung_dirs: u64,
+    // Entries deleted between being listed and being read. Not errors: not counted
+    // in `skipped_entries`.
+    pub vanished_entries: u64,
+    // Directories whose listing came from the previous scan's index.
+    pub reused_dirs: u64,
+}
+
+/// Why an entry was left out of a scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SkipReason {
+    PermissionDenied,
+    // Removed between being listed and being read.
+    NotFound,
+    // A folder listed only in part before its time budget ran out.
+    TimedOut,
+    Other,
+}
+
+impl SkipReason {
+    fn of(err: &io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::PermissionDenied => SkipReason::PermissionDenied,
+            io::ErrorKind::NotFound => SkipReason::NotFound,
+            io::ErrorKind::TimedOut => SkipReason::TimedOut,
+            _ => SkipReason::Other,
+        }
+    }
+}
+
+/// Errors network filesystems report for a busy or briefly unreachable server, which a
+/// later attempt can get past.
+fn is_transient(err: &io::Error) -> bool {
+    use io::ErrorKind;
+    if NotResponding::is(err) {
+        return false;
+    }
+    if matches!(
+        err.kind(),
+        ErrorKind::WouldBlock
+            | ErrorKind::TimedOut
+            | ErrorKind::Interrupted
+            | ErrorKind::ResourceBusy
+            | ErrorKind::StaleNetworkFileHandle
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+    ) {
+        return true;
+    }
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Foundation::{
+            ERROR_NETNAME_DELETED, ERROR_NETWORK_BUSY, ERROR_SEM_TIMEOUT, ERROR_UNEXP_NET_ERR,
+        };
+        if let Some(code) = err.raw_os_error() {
+            return [
+                ERROR_NETNAME_DELETED,
+                ERROR_NETWORK_BUSY,
+                ERROR_SEM_TIMEOUT,
+                ERROR_UNEXP_NET_ERR,
+            ]
+            .contains(&(code as u32));
+        }
+    }
+    false
+}
+
+/// An entry the walk could not count. Entries that failed to list are reported as
+/// their folder.
+#[derive(Debug, Clone)]
+pub struct SkippedEntry {
+    pub path: PathBuf,
+    pub reason: SkipReason,
+    pub message: String,
+}
+
+/// A finished scan: the complete tree plus what the walk ran into.
+#[derive(Debug, Clone)]
+pub struct Scan {
+    pub tree: ScanTree,
+    pub stats: ScanStats,
+    // Folders left out because reading them was denied, e.g. to rescan with more rights.
+    pub denied: Vec<PathBuf>,
+    // What the walk skipped and why, up to 10,000 entries; `stats` has the full count.
+    pub skipped: Vec<SkippedEntry>,
+    // The scan was cancelled or ran out of time. Finished folders have their full size;
+    // those it was still in are marked with an error and hold what was counted so far.
+    pub partial: bool,
+}
+
+impl Scan {
+    /// The pruned view of the tree, with a note on the root when the view was truncated
+    /// or the walk had to skip entries.
+    pub fn view(&self, opts: &ScanOptions) -> FsNode {
+        let (mut pruned, hit_node_limit) = prune_tree(self.tree.root(), opts);
+        if self.partial {
+            pruned.error =
+                Some("The scan was stopped early; unfinished folders are incomplete.".to_string());
+        } else if hit_node_limit {
+            pruned.error = Some(truncated_message(opts));
+        } else if self.stats.hung_dirs > 0 {
+            pruned.error = Some(format!(
+                "{} directories stopped responding and were skipped.",
+                self.stats.hung_dirs
+            ));
+        } else if self.stats.timed_out_dirs > 0 {
+            pruned.error = Some(format!(
+                "{} directories timed out and were only partially counted.",
+                self.stats.timed_out_dirs
+            ));
+        } else if self.stats.skipped_entries > 0 {
+            pruned.error = Some(format!(
+                "Skipped {} entries due to permission/errors.",
+                self.stats.skipped_entries
+            ));
+        }
+        pruned
+    }
+}
+
+struct DirFrame<'a> {
+    id: u32,
+    path: PathBuf,
+    iter: DirEntries<'a>,
+    started: Instant,
+    // Set when enumeration was abandoned because it exceeded the time budget.
+    timed_out: bool,
+    // The error, when the listing stopped responding midway.
+    hung: Option<String>,
+    // Directory mtime and everything listed so far, when the scan records an index.
+    listing: Option<(SystemTime, Vec<DirEntry>)>,
+    // Set when an entry could not be read, so the listing is not worth recording.
+    incomplete: bool,
+    // Total size of this directory.
+    size: u64,
+    children: Vec<u32>,
+}
+
+/// Takes finished folders out of a running scan, e.g. to move them to disk so the tree
+/// in memory stays bounded. Folders finish bottom-up, so large trees leave in pieces.
+pub trait SubtreeSink {
+    /// Whether to move out a folder that just finished with `nodes` nodes (itself
+    /// included) while the tree holds `resident` nodes.
+    fn wants(&mut self, nodes: usize, resident: usize) -> bool;
+    /// Stores a moved-out folder. The folder stays in the tree with its size, marked as
+    /// spilled; see [`ScanTree::graft`]. On error the folder is put back.
+    fn write(&mut self, subtree: &ScanTree) -> Result<(), String>;
+}
+
+/// Optional extras for [`scan_with_hooks`].
+#[derive(Default)]
+pub struct ScanHooks<'a> {
+    // Listings of an earlier scan, reused for directories whose mtime is unchanged.
+    pub previous: Option<&'a ScanIndex>,
+    // Receives the listings of this scan.
+    pub record: Option<&'a mut ScanIndex>,
+    pub sink: Option<&'a mut dyn SubtreeSink>,
+    // Lists archive files, whose entries then show up as children of the file.
+    pub archives: Option<&'a dyn ArchiveLister>,
+    // Set from another thread to stop the scan early; it then returns what it has.
+    pub cancel: Option<&'a AtomicBool>,
+}
+
+/// Where a scan takes directory listings from and where finished folders go.
+struct Walk<'a> {
+    fs: &'a dyn FileSystem,
+    previous: Option<&'a ScanIndex>,
+    record: Option<&'a mut ScanIndex>,
+    sink: Option<&'a mut dyn SubtreeSink>,
+    archives: Option<&'a dyn ArchiveLister>,
+    started: SystemTime,
+    denied: Vec<PathBuf>,
+    skipped: Vec<SkippedEntry>,
+    transient_retries: u32,
+    retry_delay: Duration,
+    guarded_paths: Vec<PathBuf>,
+    hang_timeout: Duration,
+    cancel: Option<&'a AtomicBool>,
+    deadline: Option<Instant>,
+    // Set once the scan was cancelled or hit its time limit.
+    stopped: bool,
+}
+
+impl<'a> Walk<'a> {
+    fn new(fs: &'a dyn FileSystem, hooks: ScanHooks<'a>, opts: &ScanOptions) -> Self {
+        Walk {
+            fs,
+            previous: hooks.previous,
+            record: hooks.record,
+            sink: hooks.sink,
+            archives: hooks.archives,
+            started: SystemTime::now(),
+            denied: vec![],
+            skipped: vec![],
+            transient_retries: opts.transient_retries,
+            retry_delay: opts.retry_delay,
+            guarded_paths: opts.guarded_paths.clone(),
+            hang_timeout: opts.hang_timeout,
+            cancel: hooks.cancel,
+            deadline: opts.time_limit.map(|limit| Instant::now() + limit),
+            stopped: false,
+        }
+    }
+
+    /// Whether the scan should stop where it is; stays true once it returned true.
+    fn should_stop(&mut self) -> bool {
+        self.stopped = self.stopped
+            || self
+                .cancel
+                .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline);
+        self.stopped
+    }
+
+    fn is_guarded(&self, path: &Path) -> bool {
+        self.guarded_paths
+            .iter()
+            .any(|guarded| path.starts_with(guarded))
+    }
+
+    /// Lists `path`, off this thread and under the hang timeout when it is guarded.
+    fn list(&self, path: &Path) -> io::Result<DirEntries<'a>> {
+        if self.is_guarded(path) {
+            if let Some(listed) = self.fs.read_dir_guarded(path, self.hang_timeout) {
+                return listed;
+            }
+        }
+        self.fs.read_dir(path)
+    }
+
+    /// Repeats `op` while it fails with a transient error, as often as the options
+    /// allow, backing off between attempts. `result` is the outcome of the first one.
+    fn retry<T>(
+        &self,
+        path: &Path,
+        mut result: io::Result<T>,
+        mut op: impl FnMut() -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut delay = self.retry_delay;
+        for attempt in 1..=self.transient_retries {
+            match &result {
+                Err(e) if is_transient(e) => {
+                    tracing::debug!(path = %path.display(), error = %e, attempt, "retrying after transient error");
+                    std::thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                    result = op();
+                }
+                _ => break,
+            }
+        }
+        result
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<EntryMetadata> {
+        self.retry(path, self.fs.symlink_metadata(path), || {
+            self.fs.symlink_metadata(path)
+        })
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<DirEntries<'a>> {
+        self.retry(path, self.list(path), || self.list(path))
+    }
+
+    /// Stats again, by path, a listed entry whose metadata failed to read.
+    fn recover(&self, err: io::Error) -> io::Result<DirEntry> {
+        let Some(path) = EntryError::path_of(&err).filter(|_| self.transient_retries > 0) else {
+            return Err(err);
+        };
+        let path = path.to_path_buf();
+        let metadata = self.retry(&path, Err(err), || self.fs.symlink_metadata(&path))?;
+        Ok(DirEntry { path, metadata })
+    }
+
+    fn skipped(&mut self, path: &Path, reason: SkipReason, message: String) {
+        if self.skipped.len() < MAX_SKIPPED_ENTRIES {
+            self.skipped.push(SkippedEntry {
+                path: path.to_path_buf(),
+                reason,
+                message,
+            });
+        }
+    }
+
+    /// Lists `path`, from the previous index when the directory is unchanged since.
+    /// Returns the mtime to record the listing under, if the scan records one.
+    fn open(
+        &self,
+        path: &Path,
+        modified: Option<SystemTime>,
+        stats: &mut ScanStats,
+    ) -> std::io::Result<(DirEntries<'a>, Option<SystemTime>)> {
+        // The stat for the index would block on a hung mount just like the listing.
+        if (self.previous.is_none() && self.record.is_none()) || self.is_guarded(path) {
+            return Ok((self.read_dir(path)?, None));
+        }
+        // Unix listings carry no mtime for directories, so those cost one stat.
+        let modified = match modified {
+            Some(modified) => Some(modified),
+            None => self.symlink_metadata(path)?.modified,
+        };
+        if let (Some(previous), Some(modified)) = (self.previous, modified) {
+            if let Some(entries) = previous.entries(path, modified) {
+                stats.reused_dirs = stats.reused_dirs.saturating_add(1);
+                return Ok((entries, Some(modified)));
+            }
+        }
+        Ok((self.read_dir(path)?, modified))
+    }
+
+    fn frame(
+        &self,
+        id: u32,
+        path: PathBuf,
+        iter: DirEntries<'a>,
+        modified: Option<SystemTime>,
+    ) -> DirFrame<'a> {
+        DirFrame {
+            id,
+            path,
+            iter,
+            started: Instant::now(),
+            timed_out: false,
+            hung: None,
+            listing: modified
+                .filter(|_| self.record.is_some())
+                .map(|modified| (modified, vec![])),
+            incomplete: false,
+            size: 0,
+            children: vec![],
+        }
+    }
+
+    fn finish(&mut self, frame: &mut DirFrame<'_>) {
+        if let (Some(index), Some((modified, entries))) = (&mut self.record, &frame.listing) {
+            if !frame.timed_out && !frame.incomplete {
+                index.record(&frame.path, *modified, self.started, entries);
+            }
+        }
+    }
+
+    /// Offers the folder `id`, which just finished, to the sink. Everything pushed after
+    /// it is its contents, so moving it out only truncates the arena.
+    fn spill(&mut self, tree: &mut ScanTree, id: u32) {
+        let Some(sink) = self.sink.as_mut() else {
+            return;
+        };
+        let nodes = tree.node_count() - id as usize;
+        if id == 0 || nodes <= 1 || !sink.wants(nodes, tree.node_count()) {
+            return;
+        }
+        let subtree = tree.split_tail(id);
+        if let Err(e) = sink.write(&subtree) {
+            tracing::warn!(path = %subtree.root_path(), error = %e, "failed to move finished folder out of the scan");
+            tree.graft(subtree);
+        }
+    }
+}
+
+/// Adds the file at `path` below `frame`, with the contents of archives listed below it.
+fn push_file(
+    tree: &mut ScanTree,
+    frame: &mut DirFrame<'_>,
+    archives: Option<&dyn ArchiveLister>,
+    progress: &ProgressReporter<'_>,
+    path: &Path,
+    size: u64,
+) {
+    progress.file_scanned(size, path);
+    let id = tree.push(frame.id, &display_name(path), FsNodeKind::File, size);
+    if let Some(entries) = archives.and_then(|archives| archives.list(path, size)) {
+        archive::push_entries(tree, id, entries);
+    }
+    frame.size = frame.size.saturating_add(size);
+    frame.children.push(id);
+}
+
+/// Walks `root` and returns the complete (unpruned) tree.
+fn scan_tree(
+    walk: &mut Walk<'_>,
+    root: &Path,
+    progress: &ProgressReporter<'_>,
+    opts: &ScanOptions,
+) -> Result<(ScanTree, ScanStats), String> {
+    let mut stats = ScanStats::default();
+
+    let meta = walk.symlink_metadata(root).map_err(|e| {
+        format!(
+            "Failed to read metadata for {}: {}",
+            root.to_string_lossy(),
+            e
+        )
+    })?;
+
+    match meta.kind {
+        // Do not follow symlinks (prevents cycles and surprising traversal).
+        FsNodeKind::Symlink | FsNodeKind::Other => {
+            return Ok((ScanTree::new(root, meta.kind, 0), stats))
+        }
+        FsNodeKind::File => {
+            progress.file_scanned(meta.len, root);
+            return Ok((ScanTree::new(root, FsNodeKind::File, meta.len), stats));
+        }
+        FsNodeKind::Directory => {}
+    }
+
+    let (read_dir, modified) = walk
+        .open(root, meta.modified, &mut stats)
+        .map_err(|e| format!("Failed to read directory {}: {}", root.to_string_lossy(), e))?;
+
+    let mut tree = ScanTree::new(root, FsNodeKind::Directory, 0);
+    // Explicit stack to avoid recursion/stack overflows on very deep trees.
+    let mut stack: Vec<DirFrame> =
+        vec![walk.frame(tree.root().id(), root.to_path_buf(), read_dir, modified)];
+
+    progress.dir_scanned(root);
+    set_current_dir(Some(root));
+
+    while let Some(frame) = stack.last_mut() {
+        let over_budget = opts
+            .dir_time_budget
+            .is_some_and(|budget| frame.started.elapsed() > budget);
+        let next_entry = if walk.should_stop() {
+            // Close the folders still open, innermost first, with what they have.
+            frame.incomplete = true;
+            None
+        } else if over_budget {
+            // Treat the directory as finished; what we counted so far stays.
+            frame.timed_out = true;
+            None
+        } else if frame.hung.is_some() {
+            None
+        } else {
+            match frame.iter.next() {
+                Some(Err(e)) => Some(walk.recover(e)),
+                next => next,
+            }
+        };
+
+        match next_entry {
+            Some(Ok(entry)) => {
+                if let Some((_, entries)) = &mut frame.listing {
+                    entries.push(entry.clone());
+                }
+                let child_path = entry.path;
+                if opts.is_excluded(&child_path) {
+                    continue;
+                }
+                let meta = entry.metadata;
+
+                if let FsNodeKind::Symlink = meta.kind {
+                    // Skip symlinks for safety and to reduce noise.
+                    continue;
+                }
+
+                if let FsNodeKind::File = meta.kind {
+                    push_file(
+                        &mut tree,
+                        frame,
+                        walk.archives,
+                        progress,
+                        &child_path,
+                        meta.len,
+                    );
+                    continue;
+                }
+
+                if let FsNodeKind::Directory = meta.kind {
+                    progress.dir_scanned(&child_path);
+
+                    match walk.open(&child_path, meta.modified, &mut stats) {
+                        Ok((rd, modified)) => {
+                            let id = tree.push(
+                                frame.id,
+                                &display_name(&child_path),
+                                FsNodeKind::Directory,
+                                0,
+                            );
+                            set_current_dir(Some(&child_path));
+                            stack.push(walk.frame(id, child_path, rd, modified));
+                        }
+                        Err(e) if is_vanished(&e) => {
+                            // Deleted since its parent was listed: gone, not an error.
+                            stats.vanished_entries = stats.vanished_entries.saturating_add(1);
+                        }
+                        Err(e) if e.kind() == io::ErrorKind::NotADirectory => {
+                            // Replaced by a file since it was listed; counted as what it
+                            // is now.
+                            match walk.symlink_metadata(&child_path) {
+                                Ok(meta) if matches!(meta.kind, FsNodeKind::File) => push_file(
+                                    &mut tree,
+                                    frame,
+                                    walk.archives,
+                                    progress,
+                                    &child_path,
+                                    meta.len,
+                                ),
+                                _ => {
+                                    stats.vanished_entries =
+                                        stats.vanished_entries.saturating_add(1)
+                                }
+                            }
+                        }
+                        Err(e) if NotResponding::is(&e) => {
+                            // Kept as an empty folder so the UI can show it was left out.
+                            tracing::warn!(path = %child_path.display(), error = %e, "skipped hung directory");
+                            stats.hung_dirs = stats.hung_dirs.saturating_add(1);
+                            stats.skipped_entries = stats.skipped_entries.saturating_add(1);
+                            walk.skipped(&child_path, SkipReason::TimedOut, e.to_string());
+                            let id = tree.push(
+                                frame.id,
+                                &display_name(&child_path),
+                                FsNodeKind::Directory,
+                                0,
+                            );
+                            tree.set_error(
+                                id,
+                                "Not responding (hung network mount?); its size is unknown."
+                                    .to_string(),
+                            );
+                            frame.children.push(id);
+                        }
+                        Err(e) => {
+                            // Permission denied / system folder etc. Skip (do not panic, do not include).
+                            tracing::warn!(
+                                path = %child_path.display(),
+                                error = %e,
+                                "skipped unreadable directory"
+                            );
+                            stats.skipped_entries = stats.skipped_entries.saturating_add(1);
+                            walk.skipped(&child_path, SkipReason::of(&e), e.to_string());
+                            if e.kind() == std::io::ErrorKind::PermissionDenied
+                                && walk.denied.len() < MAX_DENIED_DIRS
+                            {
+                                walk.denied.push(child_path);
+                            }
+                        }
+                    }
+                    continue;
+                }
+
+                // Non-file, non-dir: ignore.
+            }
+            Some(Err(e)) if is_vanished(&e) => {
+                // Deleted between being listed and being read; the listing without it
+                // is still accurate.
+                stats.vanished_entries = stats.vanished_entries.saturating_add(1);
+            }
+            Some(Err(e)) if NotResponding::is(&e) => {
+                // The listing stalled midway; what it gave so far stays.
+                frame.hung = Some(e.to_string());
+                frame.incomplete = true;
+            }
+            Some(Err(e)) => {
+                // Error reading a single entry; skip and continue.
+                tracing::debug!(error = %e, "skipped unreadable entry");
+                stats.skipped_entries = stats.skipped_entries.saturating_add(1);
+                frame.incomplete = true;
+                walk.skipped(&frame.path, SkipReason::of(&e), e.to_string());
+            }
+            None => {
+                // Completed this directory; finalize node and attach to parent.
+                let mut completed = match stack.pop() {
+                    Some(f) => f,
+                    None => break,
+                };
+
+                walk.finish(&mut completed);
+                tree.set_size(completed.id, completed.size);
+                tree.link_children(completed.id, &mut completed.children);
+
+                if walk.stopped {
+                    tree.set_error(
+                        completed.id,
+                        "Scan stopped before this folder was finished; its size is incomplete."
+                            .to_string(),
+                    );
+                } else if let Some(error) = completed.hung.take() {
+                    tracing::warn!(path = %completed.path.display(), error = %error, "directory stopped responding");
+                    stats.hung_dirs = stats.hung_dirs.saturating_add(1);
+                    stats.skipped_entries = stats.skipped_entries.saturating_add(1);
+                    walk.skipped(&completed.path, SkipReason::TimedOut, error);
+                    tree.set_error(
+                        completed.id,
+                        "Stopped responding (hung network mount?); its size is incomplete."
+                            .to_string(),
+                    );
+                } else if completed.timed_out {
+                    tracing::warn!(path = %completed.path.display(), "directory listing timed out");
+                    stats.timed_out_dirs = stats.timed_out_dirs.saturating_add(1);
+                    walk.skipped(
+                        &completed.path,
+                        SkipReason::TimedOut,
+                        "Directory listing timed out.".to_string(),
+                    );
+                    tree.set_error(
+                        completed.id,
+                        "Directory listing timed out; its size is incomplete.".to_string(),
+                    );
+                }
+                walk.spill(&mut tree, completed.id);
+
+                match stack.last_mut() {
+                    Some(parent) => {
+                        set_current_dir(Some(&parent.path));
+                        parent.size = parent.size.saturating_add(completed.size);
+                        parent.children.push(completed.id);
+                    }
+                    None => return Ok((tree, stats)),
+                }
+            }
+        }
+    }
+
+    Err("Scan aborted unexpectedly.".to_string())
+}
+
+/// Walks `root` on the current thread and returns the complete tree. Children are
+/// sorted largest first.
+pub fn scan(root: &Path, opts: &ScanOptions, progress: &dyn ScanProgress) -> Result<Scan, String> {
+    scan_with(&RealFs, root, opts, progress)
+}
+
+/// Like [`scan`], but walks `fs` instead of the real filesystem.
+pub fn scan_with(
+    fs: &dyn FileSystem,
+    root: &Path,
+    opts: &ScanOptions,
+    progress: &dyn ScanProgress,
+) -> Result<Scan, String> {
+    scan_with_hooks(fs, root, opts, progress, ScanHooks::default())
+}
+
+/// Like [`scan_with`], but reuses the listing of every directory `previous` recorded
+/// with the directory's current mtime, and returns the index of this scan alongside.
+/// Unchanged directories cost one stat instead of a full listing; files rewritten in
+/// place keep the size `previous` recorded, see [`ScanIndex`].
+pub fn scan_incremental(
+    fs: &dyn FileSystem,
+    root: &Path,
+    opts: &ScanOptions,
+    progress: &dyn ScanProgress,
+    previous: Option<&ScanIndex>,
+) -> Result<(Scan, ScanIndex), String> {
+    let mut index = ScanIndex::new();
+    let hooks = ScanHooks {
+        previous,
+        record: Some(&mut index),
+        ..ScanHooks::default()
+    };
+    let scan = scan_with_hooks(fs, root, opts, progress, hooks)?;
+    Ok((scan, index))
+}
+
+/// Like [`scan_with`], with the extras in `hooks`: reusing and recording listings (as in
+/// [`scan_incremental`]) and moving finished folders out of the tree as it grows.
+pub fn scan_with_hooks<'a>(
+    fs: &'a dyn FileSystem,
+    root: &Path,
+    opts: &ScanOptions,
+    progress: &dyn ScanProgress,
+    hooks: ScanHooks<'a>,
+) -> Result<Scan, String> {
+    set_current_dir(None);
+    let mut walk = Walk::new(fs, hooks, opts);
+    let reporter = ProgressReporter::new(progress, opts.progress_interval);
+    reporter.emit_force(Some(root));
+    let scanned = scan_tree(&mut walk, root, &reporter, opts);
+    set_current_dir(None);
+    let (tree, mut stats) = scanned?;
+    reporter.emit_force(Some(root));
+
+    stats.file_count = reporter.scanned_files.load(Ordering::Relaxed);
+    stats.dir_count = reporter.scanned_dirs.load(Ordering::Relaxed);
+    Ok(Scan {
+        tree,
+        stats,
+        denied: walk.denied,
+        skipped: walk.skipped,
+        partial: walk.stopped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{filesystem::MemoryFs, progress::NoProgress, tree::NodeRef};
+
+    fn scan_memory(fs: &MemoryFs, opts: &ScanOptions) -> Scan {
+        scan_with(fs, Path::new("/r"), opts, &NoProgress).unwrap()
+    }
+
+    fn names(node: NodeRef<'_>) -> Vec<&str> {
+        node.children().map(|child| child.name()).collect()
+    }
+
+    #[test]
+    fn sums_files_into_their_folders_largest_first() {
+        let mut fs = MemoryFs::new();
+        fs.add_file("/r/a/one.bin", 100)
+            .add_file("/r/a/two.bin", 50)
+            .add_file("/r/b.bin", 200);
+        let scan = scan_memory(&fs, &ScanOptions::local(0));
+
+        assert_eq!(scan.tree.size(), 350);
+        assert_eq!(scan.stats.file_count, 3);
+        assert_eq!(scan.stats.skipped_entries, 0);
+        assert_eq!(names(scan.tree.root()), ["b.bin", "a"]);
+        let a = scan.tree.find("/r/a").unwrap();
+        assert_eq!(a.size(), 150);
+        assert_eq!(names(a), ["one.bin", "two.bin"]);
+        assert!(!scan.partial);
+    }
+
+    #[test]
+    fn skips_symlinks() {
+        let mut fs = MemoryFs::new();
+        fs.add_file("/r/data.bin", 100).add_symlink("/r/link");
+        let scan = scan_memory(&fs, &ScanOptions::local(0));
+
+        assert_eq!(names(scan.tree.root()), ["data.bin"]);
+        assert_eq!(scan.tree.size(), 100);
+        assert_eq!(scan.stats.file_count, 1);
+        // Left out on purpose, not an error.
+        assert_eq!(scan.stats.skipped_entries, 0);
+        assert!(scan.skipped.is_empty());
+    }
+
+    #[test]
+    fn records_unreadable_folders_as_denied() {
+        let mut fs = MemoryFs::new();
+        fs.add_file("/r/ok.bin", 10)
+            .add_unreadable_dir("/r/private")
+            .add_file("/r/sub/deep.bin", 5)
+            .add_unreadable_dir("/r/sub/locked");
+        let opts = ScanOptions::local(0);
+        let scan = scan_memory(&fs, &opts);
+
+        assert_eq!(scan.tree.size(), 15);
+        assert!(scan.tree.find("/r/private").is_none());
+        assert_eq!(scan.stats.skipped_entries, 2);
+        assert_eq!(
+            scan.denied,
+            [PathBuf::from("/r/private"), PathBuf::from("/r/sub/locked")]
+        );
+        assert!(scan
+            .skipped
+            .iter()
+            .all(|entry| entry.reason == SkipReason::PermissionDenied));
+        assert_eq!(
+            scan.view(&opts).error.as_deref(),
+            Some("Skipped 2 entries due to permission/errors.")
+        );
+    }
+
+    #[test]
+    fn skips_broken_entries_and_keeps_the_rest() {
+        let mut fs = MemoryFs::new();
+        fs.add_file("/r/dir/ok.bin", 10)
+            .add_broken_entry("/r/dir/gone")
+            .add_file("/r/top.bin", 20);
+        let scan = scan_memory(&fs, &ScanOptions::local(0));
+
+        assert_eq!(scan.tree.size(), 30);
+        assert_eq!(names(scan.tree.find("/r/dir").unwrap()), ["ok.bin"]);
+        assert_eq!(scan.stats.skipped_entries, 1);
+        assert!(scan.denied.is_empty());
+        assert_eq!(scan.skipped.len(), 1);
+        assert_eq!(scan.skipped[0].path, Path::new("/r/dir"));
+        assert_eq!(scan.skipped[0].reason, SkipReason::Other);
+    }
+
+    #[test]
+    fn leaves_out_excluded_names_and_paths() {
+        let mut fs = MemoryFs::new();
+        fs.add_file("/r/app/node_modules/pkg.js", 100)
+            .add_file("/r/app/main.js", 1)
+            .add_file("/r/cache/blob", 50);
+        let opts =
+            ScanOptions::local(0).with_excludes(vec!["node_modules".into(), "/r/cache".into()]);
+        let scan = scan_memory(&fs, &opts);
+
+        assert_eq!(scan.tree.size(), 1);
+        assert_eq!(names(scan.tree.root()), ["app"]);
+        assert_eq!(names(scan.tree.find("/r/app").unwrap()), ["main.js"]);
+    }
+
+    #[test]
+    fn a_cancelled_scan_is_partial() {
+        let mut fs = MemoryFs::new();
+        fs.add_file("/r/a/one.bin", 100);
+        let cancel = AtomicBool::new(true);
+        let hooks = ScanHooks {
+            cancel: Some(&cancel),
+            ..ScanHooks::default()
+        };
+        let opts = ScanOptions::local(0);
+        let scan = scan_with_hooks(&fs, Path::new("/r"), &opts, &NoProgress, hooks).unwrap();
+
+        assert!(scan.partial);
+        assert!(scan.tree.root().error().is_some());
+        assert_eq!(
+            scan.view(&opts).error.as_deref(),
+            Some("The scan was stopped early; unfinished folders are incomplete.")
+        );
+    }
+}
@@ -0,0 +1,825 @@
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    node::{display_name, file_extension_lower, FsNode, FsNodeKind},
+    paths::{long_path, names_equal, relative_to},
+};
+
+// Marks a missing parent, child or sibling link.
+const NONE: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArenaNode {
+    // Byte range of the name in `ScanTree::names`.
+    name_start: u32,
+    name_len: u32,
+    parent: u32,
+    // Children form a singly linked list, largest first.
+    first_child: u32,
+    next_sibling: u32,
+    kind: FsNodeKind,
+    size: u64,
+}
+
+/// A complete scan result in compact form. Nodes live in one arena and their names in
+/// one shared buffer; a node's path is rebuilt from its parent links instead of being
+/// stored, which keeps multi-million-file trees to a few dozen bytes per node.
+///
+/// Serializing a `ScanTree` writes this compact form; use [`NodeRef`] for the `FsNode`
+/// JSON shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanTree {
+    root_path: String,
+    nodes: Vec<ArenaNode>,
+    names: String,
+    // Rare, so kept out of the per-node struct.
+    errors: HashMap<u32, String>,
+    // Archives and their listed entries only; see `FsNode::uncompressed_size`.
+    #[serde(default)]
+    uncompressed: HashMap<u32, u64>,
+    // Folders whose contents were moved out with `split_off`; see `graft`.
+    spilled: HashSet<u32>,
+}
+
+/// A node borrowed from a [`ScanTree`], with the same fields as [`FsNode`].
+#[derive(Clone, Copy)]
+pub struct NodeRef<'a> {
+    tree: &'a ScanTree,
+    id: u32,
+}
+
+/// The children of a node, largest first.
+pub struct Children<'a> {
+    tree: &'a ScanTree,
+    next: u32,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = NodeRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == NONE {
+            return None;
+        }
+        let node = NodeRef {
+            tree: self.tree,
+            id: self.next,
+        };
+        self.next = self.tree.nodes[self.next as usize].next_sibling;
+        Some(node)
+    }
+}
+
+impl<'a> NodeRef<'a> {
+    fn raw(&self) -> &'a ArenaNode {
+        &self.tree.nodes[self.id as usize]
+    }
+
+    pub(crate) fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub(crate) fn tree(&self) -> &'a ScanTree {
+        self.tree
+    }
+
+    pub fn name(&self) -> &'a str {
+        let node = self.raw();
+        let start = node.name_start as usize;
+        &self.tree.names[start..start + node.name_len as usize]
+    }
+
+    /// The full path, rebuilt from the root path and the names of all ancestors.
+    pub fn path(&self) -> String {
+        let mut names = vec![];
+        let mut current = *self;
+        while let Some(parent) = current.parent() {
+            names.push(current.name());
+            current = parent;
+        }
+        let mut path = PathBuf::from(&self.tree.root_path);
+        path.extend(names.iter().rev());
+        path.to_string_lossy().into_owned()
+    }
+
+    pub fn kind(&self) -> FsNodeKind {
+        self.raw().kind
+    }
+
+    pub fn size(&self) -> u64 {
+        self.raw().size
+    }
+
+    pub fn extension(&self) -> Option<String> {
+        match self.kind() {
+            FsNodeKind::Directory => None,
+            _ => file_extension_lower(Path::new(self.name())),
+        }
+    }
+
+    pub fn error(&self) -> Option<&'a str> {
+        self.tree.errors.get(&self.id).map(String::as_str)
+    }
+
+    pub fn uncompressed_size(&self) -> Option<u64> {
+        self.tree.uncompressed.get(&self.id).copied()
+    }
+
+    pub fn parent(&self) -> Option<NodeRef<'a>> {
+        match self.raw().parent {
+            NONE => None,
+            id => Some(NodeRef {
+                tree: self.tree,
+                id,
+            }),
+        }
+    }
+
+    pub fn children(&self) -> Children<'a> {
+        Children {
+            tree: self.tree,
+            next: self.raw().first_child,
+        }
+    }
+
+    pub fn has_children(&self) -> bool {
+        self.raw().first_child != NONE
+    }
+
+    /// Whether this folder's contents were split off (see [`ScanTree::split_off`]); its
+    /// size still covers them.
+    pub fn is_spilled(&self) -> bool {
+        self.tree.spilled.contains(&self.id)
+    }
+
+    /// Materializes this node and everything below it.
+    pub fn to_fs_node(&self) -> FsNode {
+        let mut stack: Vec<(NodeRef<'a>, Children<'a>, Vec<FsNode>)> =
+            vec![(*self, self.children(), vec![])];
+        loop {
+            let next = match stack.last_mut() {
+                Some((_, children, _)) => children.next(),
+                None => unreachable!("the root frame returns before the stack empties"),
+            };
+            match next {
+                Some(child) => stack.push((child, child.children(), vec![])),
+                None => {
+                    let (source, _, children) = stack.pop().expect("stack is not empty");
+                    let node = source.shallow_fs_node(children);
+                    match stack.last_mut() {
+                        Some((_, _, siblings)) => siblings.push(node),
+                        None => return node,
+                    }
+                }
+            }
+        }
+    }
+
+    /// This node as an [`FsNode`] with the given children.
+    pub(crate) fn shallow_fs_node(&self, children: Vec<FsNode>) -> FsNode {
+        FsNode {
+            name: self.name().to_string(),
+            path: self.path(),
+            kind: self.kind(),
+            size: self.size(),
+            children,
+            extension: self.extension(),
+            error: self.error().map(str::to_string),
+            uncompressed_size: self.uncompressed_size(),
+            error_count: 0,
+        }
+    }
+}
+
+/// Serializes exactly like the equivalent [`FsNode`].
+impl Serialize for NodeRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct ChildList<'a>(NodeRef<'a>);
+
+        impl Serialize for ChildList<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_seq(self.0.children())
+            }
+        }
+
+        let extension = self.extension();
+        let error = self.error();
+        let uncompressed_size = self.uncompressed_size();
+        let mut state = serializer.serialize_struct("FsNode", 8)?;
+        state.serialize_field("name", self.name())?;
+        state.serialize_field("path", &self.path())?;
+        state.serialize_field("kind", &self.kind())?;
+        state.serialize_field("size", &self.size())?;
+        if self.has_children() {
+            state.serialize_field("children", &ChildList(*self))?;
+        }
+        if extension.is_some() {
+            state.serialize_field("extension", &extension)?;
+        }
+        if error.is_some() {
+            state.serialize_field("error", &error)?;
+        }
+        if uncompressed_size.is_some() {
+            state.serialize_field("uncompressedSize", &uncompressed_size)?;
+        }
+        state.end()
+    }
+}
+
+impl ScanTree {
+    fn empty(root_path: String) -> Self {
+        ScanTree {
+            root_path,
+            nodes: vec![],
+            names: String::new(),
+            errors: HashMap::new(),
+            uncompressed: HashMap::new(),
+            spilled: HashSet::new(),
+        }
+    }
+
+    /// A tree holding only its root.
+    pub(crate) fn new(root: &Path, kind: FsNodeKind, size: u64) -> Self {
+        let mut tree = ScanTree::empty(root.to_string_lossy().into_owned());
+        tree.push(NONE, &display_name(root), kind, size);
+        tree
+    }
+
+    /// Adds a node below `parent` without linking it; see [`ScanTree::link_children`].
+    pub(crate) fn push(&mut self, parent: u32, name: &str, kind: FsNodeKind, size: u64) -> u32 {
+        let id = u32::try_from(self.nodes.len()).expect("scan tree exceeds u32::MAX nodes");
+        let name_start = u32::try_from(self.names.len()).expect("scan tree names exceed 4 GiB");
+        self.names.push_str(name);
+        self.nodes.push(ArenaNode {
+            name_start,
+            name_len: name.len() as u32,
+            parent,
+            first_child: NONE,
+            next_sibling: NONE,
+            kind,
+            size,
+        });
+        id
+    }
+
+    pub(crate) fn set_size(&mut self, id: u32, size: u64) {
+        self.nodes[id as usize].size = size;
+    }
+
+    pub(crate) fn set_error(&mut self, id: u32, error: String) {
+        self.errors.insert(id, error);
+    }
+
+    pub(crate) fn set_uncompressed(&mut self, id: u32, size: u64) {
+        self.uncompressed.insert(id, size);
+    }
+
+    /// Makes `children` the children of `parent`, sorted largest first.
+    pub(crate) fn link_children(&mut self, parent: u32, children: &mut [u32]) {
+        children.sort_by_key(|&id| std::cmp::Reverse(self.nodes[id as usize].size));
+        let mut next = NONE;
+        for &id in children.iter().rev() {
+            self.nodes[id as usize].next_sibling = next;
+            next = id;
+        }
+        self.nodes[parent as usize].first_child = next;
+    }
+
+    pub fn root(&self) -> NodeRef<'_> {
+        NodeRef { tree: self, id: 0 }
+    }
+
+    pub fn root_path(&self) -> &str {
+        &self.root_path
+    }
+
+    pub fn size(&self) -> u64 {
+        self.root().size()
+    }
+
+    /// Nodes held in the arena, including detached ones not yet reclaimed.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Paths of the folders whose contents are currently split off.
+    pub fn spilled_paths(&self) -> Vec<String> {
+        self.spilled
+            .iter()
+            .map(|&id| NodeRef { tree: self, id }.path())
+            .collect()
+    }
+
+    /// For every folder with errors below it, how many nodes there carry one.
+    pub(crate) fn error_counts(&self) -> HashMap<u32, u64> {
+        let mut counts = HashMap::new();
+        for &id in self.errors.keys() {
+            let mut current = self.nodes[id as usize].parent;
+            while current != NONE {
+                *counts.entry(current).or_insert(0) += 1;
+                current = self.nodes[current as usize].parent;
+            }
+        }
+        counts
+    }
+
+    /// Sets the message shown on the root, e.g. when a view had to be truncated.
+    pub fn set_root_error(&mut self, error: Option<String>) {
+        match error {
+            Some(error) => self.set_error(0, error),
+            None => {
+                self.errors.remove(&0);
+            }
+        }
+    }
+
+    /// The node at `path`, walking down by name from the root.
+    pub fn find(&self, path: &str) -> Option<NodeRef<'_>> {
+        self.walk_to(path, |_| false)
+    }
+
+    /// The topmost split-off folder on the way to `path` (possibly the node at `path`
+    /// itself), i.e. the first one to graft back before `path` can be reached.
+    pub fn spilled_on_path(&self, path: &str) -> Option<NodeRef<'_>> {
+        self.walk_to(path, |node| node.is_spilled())
+            .filter(|node| node.is_spilled())
+    }
+
+    /// Walks down by name towards `path`, stopping early at a node matching `stop`.
+    /// Names compare like [`names_equal`], an exact match winning, and 8.3 short names
+    /// are expanded first.
+    fn walk_to(&self, path: &str, stop: impl Fn(NodeRef) -> bool) -> Option<NodeRef<'_>> {
+        let mut node = self.root();
+        if path == self.root_path || stop(node) {
+            return Some(node);
+        }
+        let path = long_path(path);
+        for name in relative_to(&self.root_path, &path)? {
+            node = node
+                .children()
+                .find(|child| child.name() == name)
+                .or_else(|| {
+                    node.children()
+                        .find(|child| names_equal(child.name(), &name))
+                })?;
+            if stop(node) {
+                break;
+            }
+        }
+        Some(node)
+    }
+
+    /// Moves whole folders of at most `chunk` nodes out of the tree, those with the most
+    /// nodes first, until no more than `max_nodes` remain, and returns them as trees of
+    /// their own. The folders stay behind, with their sizes, marked as spilled. Folders
+    /// containing or inside `keep` are left alone. Best effort: a folder with more than
+    /// `chunk` direct entries is never split.
+    pub fn split_off(&mut self, max_nodes: usize, chunk: usize, keep: Option<&str>) -> Vec<Self> {
+        if self.nodes.len() <= max_nodes {
+            return vec![];
+        }
+        self.compact();
+
+        // Compacted trees list parents before children, so one backward pass sums up
+        // every subtree.
+        let mut counts = vec![1usize; self.nodes.len()];
+        for id in (1..self.nodes.len()).rev() {
+            let parent = self.nodes[id].parent as usize;
+            counts[parent] += counts[id];
+        }
+        let keep = keep.map(Path::new);
+        let mut candidates: Vec<u32> = (1..self.nodes.len() as u32)
+            .filter(|&id| {
+                let node = &self.nodes[id as usize];
+                node.first_child != NONE
+                    && counts[id as usize] <= chunk
+                    && counts[node.parent as usize] > chunk
+            })
+            .filter(|&id| {
+                let path = NodeRef { tree: self, id }.path();
+                keep.is_none_or(|keep| {
+                    !keep.starts_with(&path) && !Path::new(&path).starts_with(keep)
+                })
+            })
+            .collect();
+        candidates.sort_by_key(|&id| std::cmp::Reverse(counts[id as usize]));
+
+        let mut remaining = self.nodes.len();
+        let mut split = vec![];
+        for id in candidates {
+            if remaining <= max_nodes {
+                break;
+            }
+            split.push(self.extract(id));
+            self.nodes[id as usize].first_child = NONE;
+            self.spilled.insert(id);
+            remaining -= counts[id as usize] - 1;
+        }
+        if !split.is_empty() {
+            self.compact();
+        }
+        split
+    }
+
+    /// Moves the contents of `id` out like [`ScanTree::split_off`], for a folder whose
+    /// contents are the last nodes pushed, as when a running scan just finished it. The
+    /// arena is truncated rather than rebuilt, so no other node changes its id.
+    pub(crate) fn split_tail(&mut self, id: u32) -> Self {
+        let subtree = self.extract(id);
+        let node = &self.nodes[id as usize];
+        self.names
+            .truncate((node.name_start + node.name_len) as usize);
+        self.nodes.truncate(id as usize + 1);
+        self.errors.retain(|&error_id, _| error_id <= id);
+        self.uncompressed.retain(|&entry_id, _| entry_id <= id);
+        self.spilled.retain(|&spilled_id| spilled_id < id);
+        self.nodes[id as usize].first_child = NONE;
+        self.spilled.insert(id);
+        subtree
+    }
+
+    /// Puts the contents of a split-off folder back. Returns false (dropping `subtree`)
+    /// unless its root is a spilled folder of this tree.
+    pub fn graft(&mut self, subtree: ScanTree) -> bool {
+        let id = match self.find(&subtree.root_path) {
+            Some(node) if node.is_spilled() => node.id,
+            _ => return false,
+        };
+        self.spilled.remove(&id);
+        self.copy_below(&subtree, 0, id);
+        true
+    }
+
+    /// A standalone copy of the subtree at `id`, rooted at its full path.
+    fn extract(&self, id: u32) -> Self {
+        let mut tree = ScanTree::empty(NodeRef { tree: self, id }.path());
+        tree.copy_node(self, id, NONE);
+        tree.copy_below(self, id, 0);
+        tree
+    }
+
+    /// Rebuilds the arena with only the nodes reachable from the root, parents first.
+    fn compact(&mut self) {
+        let mut tree = ScanTree::empty(std::mem::take(&mut self.root_path));
+        tree.copy_node(self, 0, NONE);
+        tree.copy_below(self, 0, 0);
+        *self = tree;
+    }
+
+    /// Copies one node of `from` below `parent`, unlinked.
+    fn copy_node(&mut self, from: &ScanTree, id: u32, parent: u32) -> u32 {
+        let node = NodeRef { tree: from, id };
+        let copy = self.push(parent, node.name(), node.kind(), node.size());
+        if let Some(error) = node.error() {
+            self.set_error(copy, error.to_string());
+        }
+        if let Some(size) = node.uncompressed_size() {
+            self.set_uncompressed(copy, size);
+        }
+        if node.is_spilled() {
+            self.spilled.insert(copy);
+        }
+        copy
+    }
+
+    /// Copies everything below `src` in `from` under `dst`, which must have no children,
+    /// keeping the child order.
+    fn copy_below(&mut self, from: &ScanTree, src: u32, dst: u32) {
+        let mut pending: VecDeque<(u32, u32)> = VecDeque::from([(src, dst)]);
+        while let Some((src, dst)) = pending.pop_front() {
+            let mut prev = NONE;
+            let mut child = from.nodes[src as usize].first_child;
+            while child != NONE {
+                let copy = self.copy_node(from, child, dst);
+                match prev {
+                    NONE => self.nodes[dst as usize].first_child = copy,
+                    prev => self.nodes[prev as usize].next_sibling = copy,
+                }
+                prev = copy;
+                pending.push_back((child, copy));
+                child = from.nodes[child as usize].next_sibling;
+            }
+        }
+    }
+
+    fn unlink(&mut self, id: u32) {
+        let parent = self.nodes[id as usize].parent;
+        let next = self.nodes[id as usize].next_sibling;
+        if self.nodes[parent as usize].first_child == id {
+            self.nodes[parent as usize].first_child = next;
+        } else {
+            let mut prev = self.nodes[parent as usize].first_child;
+            while self.nodes[prev as usize].next_sibling != id {
+                prev = self.nodes[prev as usize].next_sibling;
+            }
+            self.nodes[prev as usize].next_sibling = next;
+        }
+        self.nodes[id as usize].next_sibling = NONE;
+    }
+
+    /// Links `id` into its parent's children at the place its size calls for.
+    fn link_sorted(&mut self, id: u32) {
+        let parent = self.nodes[id as usize].parent;
+        let size = self.nodes[id as usize].size;
+        let mut prev = NONE;
+        let mut next = self.nodes[parent as usize].first_child;
+        while next != NONE && self.nodes[next as usize].size >= size {
+            prev = next;
+            next = self.nodes[next as usize].next_sibling;
+        }
+        self.nodes[id as usize].next_sibling = next;
+        match prev {
+            NONE => self.nodes[parent as usize].first_child = id,
+            prev => self.nodes[prev as usize].next_sibling = id,
+        }
+    }
+
+    /// Applies a size change of `delta` to every ancestor of `id`, keeping each in its
+    /// largest-first place.
+    fn update_ancestors(&mut self, id: u32, shrink: bool, delta: u64) {
+        let mut current = self.nodes[id as usize].parent;
+        while current != NONE {
+            let node = &mut self.nodes[current as usize];
+            node.size = if shrink {
+                node.size.saturating_sub(delta)
+            } else {
+                node.size.saturating_add(delta)
+            };
+            if node.parent != NONE {
+                self.unlink(current);
+                self.link_sorted(current);
+            }
+            current = self.nodes[current as usize].parent;
+        }
+    }
+
+    /// Detaches the node at `path` and subtracts its size from every ancestor. Returns
+    /// `None` for the root or a path outside the tree. Detached nodes stay in the arena
+    /// until the tree is dropped.
+    pub fn remove_descendant(&mut self, path: &Path) -> Option<FsNode> {
+        let node = self.find(&path.to_string_lossy())?;
+        let (id, removed) = (node.id, node.to_fs_node());
+        if id == 0 {
+            return None;
+        }
+        self.unlink(id);
+        self.update_ancestors(id, true, removed.size);
+        self.forget_errors_below(id);
+        Some(removed)
+    }
+
+    /// Drops the errors of a detached subtree, which keeps its parent links, so they no
+    /// longer count towards its former ancestors.
+    fn forget_errors_below(&mut self, id: u32) {
+        if self.errors.is_empty() {
+            return;
+        }
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            self.errors.remove(&id);
+            let mut child = self.nodes[id as usize].first_child;
+            while child != NONE {
+                pending.push(child);
+                child = self.nodes[child as usize].next_sibling;
+            }
+        }
+    }
+
+    /// Re-attaches `node` under its parent directory and adds its size to every
+    /// ancestor. Returns false (dropping `node`) if the parent is not in the tree.
+    pub fn insert_descendant(&mut self, node: FsNode) -> bool {
+        let parent = match Path::new(&node.path).parent() {
+            Some(parent) => parent.to_string_lossy().into_owned(),
+            None => return false,
+        };
+        let parent = match self.find(&parent) {
+            Some(parent) if matches!(parent.kind(), FsNodeKind::Directory) => parent.id,
+            _ => return false,
+        };
+        let size = node.size;
+        let id = self.append(parent, &node);
+        self.link_sorted(id);
+        self.update_ancestors(id, false, size);
+        true
+    }
+
+    /// Adds a folder scanned on its own below its parent, e.g. one the scan was denied
+    /// at first. Returns false (dropping `subtree`) unless the parent is a folder of this
+    /// tree that does not hold the folder yet.
+    pub fn attach(&mut self, subtree: ScanTree) -> bool {
+        let path = Path::new(&subtree.root_path);
+        if self.find(&subtree.root_path).is_some() {
+            return false;
+        }
+        let parent = match path.parent().and_then(|p| self.find(&p.to_string_lossy())) {
+            Some(parent) if matches!(parent.kind(), FsNodeKind::Directory) => parent.id,
+            _ => return false,
+        };
+        let id = self.copy_node(&subtree, 0, parent);
+        self.copy_below(&subtree, 0, id);
+        self.link_sorted(id);
+        self.update_ancestors(id, false, subtree.size());
+        true
+    }
+
+    /// Pushes `node` itself, without its children.
+    fn push_fs_node(&mut self, parent: u32, node: &FsNode) -> u32 {
+        let id = self.push(parent, &node.name, node.kind, node.size);
+        if let Some(error) = &node.error {
+            self.set_error(id, error.clone());
+        }
+        if let Some(size) = node.uncompressed_size {
+            self.set_uncompressed(id, size);
+        }
+        id
+    }
+
+    /// Copies `node` and its subtree into the arena below `parent`; the copy's root is
+    /// left unlinked.
+    fn append(&mut self, parent: u32, node: &FsNode) -> u32 {
+        let root = self.push_fs_node(parent, node);
+        let mut pending: Vec<(u32, &FsNode)> = vec![(root, node)];
+        while let Some((id, source)) = pending.pop() {
+            let mut children = Vec::with_capacity(source.children.len());
+            for child in &source.children {
+                let child_id = self.push_fs_node(id, child);
+                children.push(child_id);
+                pending.push((child_id, child));
+            }
+            self.link_children(id, &mut children);
+        }
+        root
+    }
+}
+
+/// Converts an imported or deserialized tree into compact form.
+impl From<&FsNode> for ScanTree {
+    fn from(root: &FsNode) -> Self {
+        let mut tree = ScanTree::empty(root.path.clone());
+        tree.append(NONE, root);
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{filesystem::MemoryFs, progress::NoProgress, scan::scan_with, ScanOptions};
+
+    // /r: a/ (one 40, two 30, three 20), b/ (four 60, five 10), c.bin 5.
+    fn sample() -> ScanTree {
+        let mut fs = MemoryFs::new();
+        fs.add_file("/r/a/one", 40)
+            .add_file("/r/a/two", 30)
+            .add_file("/r/a/three", 20)
+            .add_file("/r/b/four", 60)
+            .add_file("/r/b/five", 10)
+            .add_file("/r/c.bin", 5);
+        scan_tree_of(&fs, "/r")
+    }
+
+    fn scan_tree_of(fs: &MemoryFs, root: &str) -> ScanTree {
+        scan_with(fs, Path::new(root), &ScanOptions::local(0), &NoProgress)
+            .unwrap()
+            .tree
+    }
+
+    fn names(node: NodeRef<'_>) -> Vec<&str> {
+        node.children().map(|child| child.name()).collect()
+    }
+
+    #[test]
+    fn split_off_moves_the_largest_folders_out() {
+        let mut tree = sample();
+        assert_eq!(tree.node_count(), 9);
+
+        let split = tree.split_off(6, 4, None);
+        assert_eq!(split.len(), 1);
+        assert_eq!(split[0].root_path(), "/r/a");
+        assert_eq!(names(split[0].root()), ["one", "two", "three"]);
+
+        assert_eq!(tree.node_count(), 6);
+        assert_eq!(tree.spilled_paths(), ["/r/a"]);
+        let a = tree.find("/r/a").unwrap();
+        assert!(a.is_spilled());
+        assert!(!a.has_children());
+        // Sizes stay.
+        assert_eq!(a.size(), 90);
+        assert_eq!(tree.size(), 165);
+    }
+
+    #[test]
+    fn split_off_leaves_small_trees_and_kept_paths_alone() {
+        let mut tree = sample();
+        assert!(tree.split_off(9, 4, None).is_empty());
+
+        let split = tree.split_off(6, 4, Some("/r/a/two"));
+        assert_eq!(split.len(), 1);
+        assert_eq!(split[0].root_path(), "/r/b");
+        assert!(!tree.find("/r/a").unwrap().is_spilled());
+    }
+
+    #[test]
+    fn graft_puts_split_folders_back() {
+        let mut tree = sample();
+        let mut split = tree.split_off(6, 4, None);
+        assert!(tree.graft(split.remove(0)));
+
+        assert!(tree.spilled_paths().is_empty());
+        assert_eq!(names(tree.find("/r/a").unwrap()), ["one", "two", "three"]);
+        assert_eq!(tree.find("/r/a/two").unwrap().size(), 30);
+    }
+
+    #[test]
+    fn graft_refuses_folders_that_were_not_split() {
+        let mut tree = sample();
+        let other = sample().split_off(6, 4, None).remove(0);
+        assert!(!tree.graft(other));
+        assert_eq!(tree.node_count(), 9);
+    }
+
+    #[test]
+    fn split_tail_truncates_the_arena() {
+        let mut tree = ScanTree::new(Path::new("/r"), FsNodeKind::Directory, 0);
+        let file = tree.push(0, "first.bin", FsNodeKind::File, 5);
+        let dir = tree.push(0, "dir", FsNodeKind::Directory, 30);
+        let x = tree.push(dir, "x.bin", FsNodeKind::File, 10);
+        let y = tree.push(dir, "y.bin", FsNodeKind::File, 20);
+        tree.link_children(dir, &mut [x, y]);
+        tree.set_error(y, "unreadable".to_string());
+
+        let subtree = tree.split_tail(dir);
+        assert_eq!(tree.node_count(), 3);
+        assert_eq!(subtree.root_path(), "/r/dir");
+        assert_eq!(names(subtree.root()), ["y.bin", "x.bin"]);
+        assert_eq!(
+            subtree.root().children().next().unwrap().error(),
+            Some("unreadable")
+        );
+
+        tree.set_size(0, 35);
+        tree.link_children(0, &mut [file, dir]);
+        assert_eq!(tree.spilled_paths(), ["/r/dir"]);
+        assert_eq!(tree.error_counts().get(&0), None);
+        assert!(tree.graft(subtree));
+        assert_eq!(names(tree.find("/r/dir").unwrap()), ["y.bin", "x.bin"]);
+        assert_eq!(tree.error_counts().get(&0), Some(&1));
+    }
+
+    #[test]
+    fn remove_descendant_shrinks_and_reorders_ancestors() {
+        let mut tree = sample();
+        assert_eq!(names(tree.root()), ["a", "b", "c.bin"]);
+
+        let removed = tree.remove_descendant(Path::new("/r/a/one")).unwrap();
+        assert_eq!(removed.size, 40);
+        assert_eq!(tree.size(), 125);
+        assert_eq!(tree.find("/r/a").unwrap().size(), 50);
+        assert!(tree.find("/r/a/one").is_none());
+        // a (50) now sorts after b (70).
+        assert_eq!(names(tree.root()), ["b", "a", "c.bin"]);
+    }
+
+    #[test]
+    fn remove_descendant_refuses_the_root_and_unknown_paths() {
+        let mut tree = sample();
+        assert!(tree.remove_descendant(Path::new("/r")).is_none());
+        assert!(tree.remove_descendant(Path::new("/r/missing")).is_none());
+        assert!(tree.remove_descendant(Path::new("/elsewhere/a")).is_none());
+        assert_eq!(tree.size(), 165);
+    }
+
+    #[test]
+    fn attach_adds_a_folder_scanned_on_its_own() {
+        let mut tree = sample();
+        let mut fs = MemoryFs::new();
+        fs.add_file("/r/a/denied/big", 500);
+        let denied = scan_tree_of(&fs, "/r/a/denied");
+
+        assert!(tree.attach(denied.clone()));
+        assert_eq!(tree.find("/r/a/denied/big").unwrap().size(), 500);
+        assert_eq!(tree.find("/r/a").unwrap().size(), 590);
+        assert_eq!(tree.size(), 665);
+        assert_eq!(names(tree.find("/r/a").unwrap())[0], "denied");
+
+        // Already there.
+        assert!(!tree.attach(denied));
+    }
+
+    #[test]
+    fn attach_needs_the_parent_folder() {
+        let mut tree = sample();
+        let mut fs = MemoryFs::new();
+        fs.add_file("/r/missing/denied/big", 500)
+            .add_file("/r/c.bin/denied/big", 500);
+
+        assert!(!tree.attach(scan_tree_of(&fs, "/r/missing/denied")));
+        // Not a folder.
+        assert!(!tree.attach(scan_tree_of(&fs, "/r/c.bin/denied")));
+        assert_eq!(tree.size(), 165);
+    }
+}
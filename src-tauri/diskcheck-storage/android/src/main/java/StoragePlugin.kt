@@ -0,0 +1,325 @@
+package com.johnsmith.diskcheck.storage
+
+import android.Manifest
+import android.app.Activity
+import android.app.usage.StorageStatsManager
+import android.content.Context
+import android.content.Intent
+import android.net.Uri
+import android.os.Build
+import android.os.Environment
+import android.os.StatFs
+import android.os.storage.StorageManager
+import android.os.storage.StorageVolume
+import android.provider.DocumentsContract
+import android.provider.MediaStore
+import androidx.activity.result.ActivityResult
+import app.tauri.PermissionState
+import app.tauri.annotation.ActivityCallback
+import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
+import app.tauri.annotation.Permission
+import app.tauri.annotation.PermissionCallback
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.JSArray
+import app.tauri.plugin.JSObject
+import app.tauri.plugin.Plugin
+import java.io.BufferedOutputStream
+import java.io.FileOutputStream
+import java.io.OutputStream
+
+@InvokeArg
+class PickFolderArgs {
+  // The path of a volume from `listVolumes`, to open the picker there.
+  var volume: String? = null
+}
+
+@InvokeArg
+class ListFolderArgs {
+  lateinit var uri: String
+  lateinit var output: String
+}
+
+@InvokeArg
+class ListMediaArgs {
+  lateinit var output: String
+}
+
+// Listings are written as `find -printf '%y %s %P\0'` records, which the Rust side
+// already reads for ssh and MTP scans; a large tree would not fit through the bridge.
+@TauriPlugin(
+  permissions = [
+    Permission(
+      strings = [
+        Manifest.permission.READ_MEDIA_IMAGES,
+        Manifest.permission.READ_MEDIA_VIDEO,
+        Manifest.permission.READ_MEDIA_AUDIO
+      ],
+      alias = "media"
+    ),
+    Permission(strings = [Manifest.permission.READ_EXTERNAL_STORAGE], alias = "storage")
+  ]
+)
+class StoragePlugin(private val activity: Activity) : Plugin(activity) {
+  private val mediaAlias =
+    if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.TIRAMISU) "media" else "storage"
+
+  /** Lets the user grant a folder through the system picker; resolves without one if cancelled. */
+  @Command
+  fun pickFolder(invoke: Invoke) {
+    val args = invoke.parseArgs(PickFolderArgs::class.java)
+    val volume = args.volume?.let { path ->
+      storageVolumes().firstOrNull { volumeDirectory(it) == path }
+    }
+    val intent = if (volume != null && Build.VERSION.SDK_INT >= Build.VERSION_CODES.Q) {
+      volume.createOpenDocumentTreeIntent()
+    } else {
+      Intent(Intent.ACTION_OPEN_DOCUMENT_TREE)
+    }
+    intent.addFlags(
+      Intent.FLAG_GRANT_READ_URI_PERMISSION or Intent.FLAG_GRANT_PERSISTABLE_URI_PERMISSION
+    )
+    startActivityForResult(invoke, intent, "folderPicked")
+  }
+
+  @ActivityCallback
+  fun folderPicked(invoke: Invoke, result: ActivityResult) {
+    val uri = result.data?.data
+    val response = JSObject()
+    if (result.resultCode != Activity.RESULT_OK || uri == null) {
+      invoke.resolve(response)
+      return
+    }
+    try {
+      // Kept across restarts, so a folder picked once can be scanned again.
+      activity.contentResolver.takePersistableUriPermission(
+        uri,
+        Intent.FLAG_GRANT_READ_URI_PERMISSION
+      )
+      val folder = JSObject()
+      folder.put("uri", uri.toString())
+      folder.put("name", displayName(uri) ?: uri.lastPathSegment ?: "Folder")
+      response.put("folder", folder)
+      invoke.resolve(response)
+    } catch (ex: Exception) {
+      invoke.reject(ex.message ?: "Failed to keep access to the folder")
+    }
+  }
+
+  /** Walks a folder granted through [pickFolder], one children query per folder. */
+  @Command
+  fun listFolder(invoke: Invoke) {
+    val args = invoke.parseArgs(ListFolderArgs::class.java)
+    Thread {
+      try {
+        val tree = Uri.parse(args.uri)
+        var unreadable = 0L
+        BufferedOutputStream(FileOutputStream(args.output)).use { out ->
+          val pending = ArrayDeque<Pair<String, String>>()
+          pending.add(Pair(DocumentsContract.getTreeDocumentId(tree), ""))
+          while (pending.isNotEmpty()) {
+            val (documentId, relative) = pending.removeLast()
+            val children = DocumentsContract.buildChildDocumentsUriUsingTree(tree, documentId)
+            val cursor = try {
+              activity.contentResolver.query(children, DOCUMENT_COLUMNS, null, null, null)
+            } catch (ex: Exception) {
+              null
+            }
+            if (cursor == null) {
+              if (relative.isEmpty()) {
+                throw IllegalStateException("The folder can no longer be read; pick it again.")
+              }
+              unreadable++
+              continue
+            }
+            cursor.use {
+              while (it.moveToNext()) {
+                val name = (it.getString(1) ?: continue).replace('/', '_')
+                val path = if (relative.isEmpty()) name else "$relative/$name"
+                if (it.getString(2) == DocumentsContract.Document.MIME_TYPE_DIR) {
+                  writeRecord(out, 'd', 0, path)
+                  pending.add(Pair(it.getString(0), path))
+                } else {
+                  writeRecord(out, 'f', if (it.isNull(3)) 0 else it.getLong(3), path)
+                }
+              }
+            }
+          }
+        }
+        val response = JSObject()
+        response.put("unreadable", unreadable)
+        invoke.resolve(response)
+      } catch (ex: Exception) {
+        invoke.reject(ex.message ?: "Failed to list the folder")
+      }
+    }.start()
+  }
+
+  /**
+   * Lists the photos, videos and audio in shared storage on every volume, as
+   * `volume/relative path/name`. Asks for the media permissions first.
+   */
+  @Command
+  fun listMedia(invoke: Invoke) {
+    if (getPermissionState(mediaAlias) != PermissionState.GRANTED) {
+      requestPermissionForAlias(mediaAlias, invoke, "mediaPermissionResult")
+      return
+    }
+    listMediaGranted(invoke)
+  }
+
+  @PermissionCallback
+  fun mediaPermissionResult(invoke: Invoke) {
+    if (getPermissionState(mediaAlias) == PermissionState.GRANTED) {
+      listMediaGranted(invoke)
+    } else {
+      invoke.reject("Access to photos, videos and audio was not granted.")
+    }
+  }
+
+  private fun listMediaGranted(invoke: Invoke) {
+    val args = invoke.parseArgs(ListMediaArgs::class.java)
+    Thread {
+      try {
+        BufferedOutputStream(FileOutputStream(args.output)).use { out ->
+          if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.Q) {
+            listMediaByVolume(out)
+          } else {
+            listMediaByPath(out)
+          }
+        }
+        val response = JSObject()
+        response.put("unreadable", 0)
+        invoke.resolve(response)
+      } catch (ex: Exception) {
+        invoke.reject(ex.message ?: "Failed to list media")
+      }
+    }.start()
+  }
+
+  private fun listMediaByVolume(out: OutputStream) {
+    val columns = arrayOf(
+      MediaStore.MediaColumns.VOLUME_NAME,
+      MediaStore.MediaColumns.RELATIVE_PATH,
+      MediaStore.MediaColumns.DISPLAY_NAME,
+      MediaStore.MediaColumns.SIZE
+    )
+    val files = MediaStore.Files.getContentUri(MediaStore.VOLUME_EXTERNAL)
+    activity.contentResolver.query(files, columns, null, null, null)?.use {
+      while (it.moveToNext()) {
+        val name = it.getString(2) ?: continue
+        val volume = it.getString(0) ?: MediaStore.VOLUME_EXTERNAL_PRIMARY
+        val relative = (it.getString(1) ?: "").trim('/')
+        val path = listOf(volume, relative, name).filter { part -> part.isNotEmpty() }
+        writeRecord(out, 'f', if (it.isNull(3)) 0 else it.getLong(3), path.joinToString("/"))
+      }
+    }
+  }
+
+  // Before Android 10 rows carry an absolute path instead of a volume and relative path.
+  @Suppress("DEPRECATION")
+  private fun listMediaByPath(out: OutputStream) {
+    val columns = arrayOf(MediaStore.MediaColumns.DATA, MediaStore.MediaColumns.SIZE)
+    val files = MediaStore.Files.getContentUri("external")
+    activity.contentResolver.query(files, columns, null, null, null)?.use {
+      while (it.moveToNext()) {
+        val path = (it.getString(0) ?: continue).trim('/')
+        if (path.isNotEmpty()) {
+          writeRecord(out, 'f', if (it.isNull(1)) 0 else it.getLong(1), path)
+        }
+      }
+    }
+  }
+
+  /**
+   * The mounted storage volumes: internal storage, SD cards and USB drives, with their
+   * capacity. Android does not say which removable volumes are USB drives; their
+   * description does.
+   */
+  @Command
+  fun listVolumes(invoke: Invoke) {
+    val volumes = JSArray()
+    for (volume in storageVolumes()) {
+      if (volume.state != Environment.MEDIA_MOUNTED &&
+        volume.state != Environment.MEDIA_MOUNTED_READ_ONLY
+      ) {
+        continue
+      }
+      val directory = volumeDirectory(volume) ?: continue
+      val description = volume.getDescription(activity)
+      val kind = when {
+        !volume.isRemovable -> "internal"
+        description.contains("USB", ignoreCase = true) -> "usb"
+        else -> "sdCard"
+      }
+      val entry = JSObject()
+      entry.put("path", directory)
+      entry.put("description", description)
+      entry.put("kind", kind)
+      entry.put("readOnly", volume.state == Environment.MEDIA_MOUNTED_READ_ONLY)
+      try {
+        val (total, free) = volumeSpace(volume, directory)
+        entry.put("totalBytes", total)
+        entry.put("freeBytes", free)
+      } catch (ex: Exception) {
+        continue
+      }
+      volumes.put(entry)
+    }
+    val response = JSObject()
+    response.put("volumes", volumes)
+    invoke.resolve(response)
+  }
+
+  private fun storageVolumes(): List<StorageVolume> {
+    val manager = activity.getSystemService(Context.STORAGE_SERVICE) as StorageManager
+    return manager.storageVolumes
+  }
+
+  // `directory` is Android 11+; before it, volumes are mounted at fixed places.
+  @Suppress("DEPRECATION")
+  private fun volumeDirectory(volume: StorageVolume): String? = when {
+    Build.VERSION.SDK_INT >= Build.VERSION_CODES.R -> volume.directory?.path
+    volume.isPrimary -> Environment.getExternalStorageDirectory().path
+    else -> volume.uuid?.let { "/storage/$it" }
+  }
+
+  // For internal storage the storage stats cover the whole device rather than only the
+  // shared-storage partition.
+  private fun volumeSpace(volume: StorageVolume, directory: String): Pair<Long, Long> {
+    if (volume.isPrimary && Build.VERSION.SDK_INT >= Build.VERSION_CODES.O) {
+      val stats = activity.getSystemService(Context.STORAGE_STATS_SERVICE) as StorageStatsManager
+      return Pair(
+        stats.getTotalBytes(StorageManager.UUID_DEFAULT),
+        stats.getFreeBytes(StorageManager.UUID_DEFAULT)
+      )
+    }
+    val stat = StatFs(directory)
+    return Pair(stat.totalBytes, stat.availableBytes)
+  }
+
+  private fun displayName(tree: Uri): String? {
+    val document = DocumentsContract.buildDocumentUriUsingTree(
+      tree,
+      DocumentsContract.getTreeDocumentId(tree)
+    )
+    val columns = arrayOf(DocumentsContract.Document.COLUMN_DISPLAY_NAME)
+    return activity.contentResolver.query(document, columns, null, null, null)?.use {
+      if (it.moveToFirst()) it.getString(0) else null
+    }
+  }
+
+  private fun writeRecord(out: OutputStream, kind: Char, size: Long, path: String) {
+    out.write("$kind $size $path\u0000".toByteArray(Charsets.UTF_8))
+  }
+
+  companion object {
+    private val DOCUMENT_COLUMNS = arrayOf(
+      DocumentsContract.Document.COLUMN_DOCUMENT_ID,
+      DocumentsContract.Document.COLUMN_DISPLAY_NAME,
+      DocumentsContract.Document.COLUMN_MIME_TYPE,
+      DocumentsContract.Document.COLUMN_SIZE
+    )
+  }
+}
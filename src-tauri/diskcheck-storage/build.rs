@@ -0,0 +1,9 @@
+// Nothing is invoked from the webview; the app calls the native side from Rust.
+const COMMANDS: &[&str] = &[];
+
+fn main() {
+    tauri_plugin::Builder::new(COMMANDS)
+        .android_path("android")
+        .ios_path("ios")
+        .build();
+}
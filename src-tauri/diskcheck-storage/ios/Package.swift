@@ -0,0 +1,27 @@
+// swift-tools-version:5.3
+
+import PackageDescription
+
+let package = Package(
+  name: "tauri-plugin-diskcheck-storage",
+  platforms: [
+    .iOS(.v13),
+  ],
+  products: [
+    .library(
+      name: "tauri-plugin-diskcheck-storage",
+      type: .static,
+      targets: ["tauri-plugin-diskcheck-storage"])
+  ],
+  dependencies: [
+    .package(name: "Tauri", path: "../.tauri/tauri-api")
+  ],
+  targets: [
+    .target(
+      name: "tauri-plugin-diskcheck-storage",
+      dependencies: [
+        .byName(name: "Tauri")
+      ],
+      path: "Sources")
+  ]
+)
@@ -0,0 +1,67 @@
+import UIKit
+import UniformTypeIdentifiers
+import Tauri
+
+// Outside its sandbox an iOS app may only read what the user picks in the Files app.
+// The pick is handed back as a bookmark; the Rust side resolves it and starts access
+// to the folder whenever it is scanned.
+class StoragePlugin: Plugin, UIDocumentPickerDelegate {
+  private var pendingPick: Invoke?
+
+  /// Shows the folder picker; resolves without a `folder` if cancelled.
+  @objc public func pickFolder(_ invoke: Invoke) throws {
+    DispatchQueue.main.async {
+      let picker: UIDocumentPickerViewController
+      if #available(iOS 14.0, *) {
+        picker = UIDocumentPickerViewController(forOpeningContentTypes: [.folder])
+      } else {
+        picker = UIDocumentPickerViewController(documentTypes: ["public.folder"], in: .open)
+      }
+      picker.delegate = self
+      self.pendingPick?.resolve([:])
+      self.pendingPick = invoke
+      self.manager.viewController?.present(picker, animated: true, completion: nil)
+    }
+  }
+
+  func documentPicker(
+    _ controller: UIDocumentPickerViewController, didPickDocumentsAt urls: [URL]
+  ) {
+    guard let invoke = pendingPick else { return }
+    pendingPick = nil
+    guard let url = urls.first else {
+      invoke.resolve([:])
+      return
+    }
+    // The bookmark can only be made while the folder is being accessed.
+    let accessing = url.startAccessingSecurityScopedResource()
+    defer {
+      if accessing {
+        url.stopAccessingSecurityScopedResource()
+      }
+    }
+    do {
+      let bookmark = try url.bookmarkData(
+        options: [], includingResourceValuesForKeys: nil, relativeTo: nil)
+      invoke.resolve([
+        "folder": [
+          "path": url.path,
+          "name": url.lastPathComponent,
+          "bookmark": bookmark.map { String(format: "%02x", $0) }.joined(),
+        ]
+      ])
+    } catch {
+      invoke.reject("Failed to keep access to the folder: \(error.localizedDescription)")
+    }
+  }
+
+  func documentPickerWasCancelled(_ controller: UIDocumentPickerViewController) {
+    pendingPick?.resolve([:])
+    pendingPick = nil
+  }
+}
+
+@_cdecl("init_plugin_diskcheck_storage")
+func initPlugin() -> Plugin {
+  return StoragePlugin()
+}
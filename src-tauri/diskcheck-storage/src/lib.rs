@@ -0,0 +1,189 @@
+//! Device storage for DiskCheck's mobile builds. Scoped storage keeps `std::fs` out of
+//! everything on Android but the app's own folders, so folders are listed by the native
+//! side instead: ones the user grants through the system picker (the storage access
+//! framework), and the shared photos, videos and audio (MediaStore). On iOS the app may
+//! read folders picked in the Files app, which the native side hands back as bookmarks.
+//!
+//! Listings are written to a file as `find -printf '%y %s %P\0'` records, with paths
+//! relative to the listed folder.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::Path;
+use tauri::{
+    plugin::{Builder, TauriPlugin},
+    Manager, Runtime,
+};
+
+#[cfg(target_os = "android")]
+const PLUGIN_IDENTIFIER: &str = "com.johnsmith.diskcheck.storage";
+
+#[cfg(target_os = "ios")]
+tauri::ios_plugin_binding!(init_plugin_diskcheck_storage);
+
+/// A folder the user granted access to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickedFolder {
+    // A `content://` tree URI; access to it survives restarts.
+    pub uri: String,
+    pub name: String,
+}
+
+/// A folder picked in the iOS Files app.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkedFolder {
+    pub path: String,
+    pub name: String,
+    // Hex-encoded bookmark data; resolving it grants access to the folder again after a
+    // restart, and finds it if it has moved.
+    pub bookmark: String,
+}
+
+/// What a storage volume is, as far as Android tells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VolumeKind {
+    Internal,
+    SdCard,
+    Usb,
+}
+
+/// A mounted storage volume.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceVolume {
+    pub path: String,
+    pub description: String,
+    pub kind: VolumeKind,
+    pub read_only: bool,
+    pub total_bytes: u64,
+    // Bytes available to the app.
+    pub free_bytes: u64,
+}
+
+/// What a listing could not cover.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Listed {
+    // Folders that failed to list.
+    pub unreadable: u64,
+}
+
+#[derive(Deserialize)]
+struct PickResponse<T> {
+    folder: Option<T>,
+}
+
+#[derive(Deserialize)]
+struct VolumesResponse {
+    volumes: Vec<DeviceVolume>,
+}
+
+#[derive(Serialize)]
+struct PickArgs<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    volume: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListArgs<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri: Option<&'a str>,
+    output: &'a Path,
+}
+
+/// Access to the native storage APIs; see [`StorageExt`].
+pub struct Storage<R: Runtime> {
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    handle: tauri::plugin::PluginHandle<R>,
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    _runtime: std::marker::PhantomData<fn() -> R>,
+}
+
+impl<R: Runtime> Storage<R> {
+    /// Runs `command` on the native side, blocking until it answers.
+    fn call<T: DeserializeOwned>(&self, command: &str, args: impl Serialize) -> Result<T, String> {
+        #[cfg(any(target_os = "android", target_os = "ios"))]
+        return self
+            .handle
+            .run_mobile_plugin(command, args)
+            .map_err(|err| err.to_string());
+        #[cfg(not(any(target_os = "android", target_os = "ios")))]
+        {
+            let _ = (command, args);
+            Err("Device storage is only reachable this way in the mobile apps.".to_string())
+        }
+    }
+
+    /// Shows the system folder picker, opened on the volume mounted at `volume` if
+    /// given. `None` when the user cancels it. Android only.
+    pub fn pick_folder(&self, volume: Option<&str>) -> Result<Option<PickedFolder>, String> {
+        self.call::<PickResponse<_>>("pickFolder", PickArgs { volume })
+            .map(|response| response.folder)
+    }
+
+    /// Shows the Files app's folder picker and bookmarks the folder picked. `None` when
+    /// the user cancels it. iOS only.
+    pub fn pick_bookmarked_folder(&self) -> Result<Option<BookmarkedFolder>, String> {
+        self.call::<PickResponse<_>>("pickFolder", PickArgs { volume: None })
+            .map(|response| response.folder)
+    }
+
+    /// The mounted storage volumes. Android only.
+    pub fn list_volumes(&self) -> Result<Vec<DeviceVolume>, String> {
+        self.call::<VolumesResponse>("listVolumes", ())
+            .map(|response| response.volumes)
+    }
+
+    /// Lists the picked folder at `uri` and everything below it into `output`. Android
+    /// only.
+    pub fn list_folder(&self, uri: &str, output: &Path) -> Result<Listed, String> {
+        self.call(
+            "listFolder",
+            ListArgs {
+                uri: Some(uri),
+                output,
+            },
+        )
+    }
+
+    /// Lists the media in shared storage into `output`, as `volume/folder/name`, asking
+    /// for the media permissions first. Android only.
+    pub fn list_media(&self, output: &Path) -> Result<Listed, String> {
+        self.call("listMedia", ListArgs { uri: None, output })
+    }
+}
+
+/// Extends the app and its windows with [`Storage`].
+pub trait StorageExt<R: Runtime> {
+    fn storage(&self) -> &Storage<R>;
+}
+
+impl<R: Runtime, T: Manager<R>> StorageExt<R> for T {
+    fn storage(&self) -> &Storage<R> {
+        self.state::<Storage<R>>().inner()
+    }
+}
+
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new("diskcheck-storage")
+        .setup(|app, _api| {
+            #[cfg(target_os = "android")]
+            let storage = Storage {
+                handle: _api.register_android_plugin(PLUGIN_IDENTIFIER, "StoragePlugin")?,
+            };
+            #[cfg(target_os = "ios")]
+            let storage = Storage {
+                handle: _api.register_ios_plugin(init_plugin_diskcheck_storage)?,
+            };
+            #[cfg(not(any(target_os = "android", target_os = "ios")))]
+            let storage = Storage::<R> {
+                _runtime: std::marker::PhantomData,
+            };
+            app.manage(storage);
+            Ok(())
+        })
+        .build()
+}
@@ -0,0 +1,415 @@
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fs,
+    io::BufReader,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    cli::parse_size,
+    fileinfo::iso8601_utc,
+    priority::ScanMode,
+    s3::{curl_request, uri_encode, RequestBody},
+    scanner::{pruned_view, scan_blocking, FsNode, FsNodeKind, ScanTree},
+    snapshot::{read_snapshot, write_snapshot},
+    store::{unix_secs, ScanStore, ScanSummary},
+};
+
+/// Starts the app as the fleet agent instead of the UI.
+pub(crate) const AGENT_FLAG: &str = "--agent";
+const USAGE: &str = "\
+Usage: diskcheck --agent PATH... --to DEST [OPTIONS]
+
+Scans each PATH on a schedule and saves a compact snapshot of it to DEST, for
+\"Import fleet\" in the app to combine with the snapshots of other machines.
+
+DEST is a shared folder, or an http(s) URL that accepts PUT uploads. Snapshots land in
+DEST/<machine>/<path>.dcsnap, replacing the previous one. With DISKCHECK_AGENT_TOKEN
+set, uploads carry it as a bearer token.
+
+Options:
+  --to <DEST>           Where to save snapshots (required)
+  --every <INTERVAL>    Time between scans, e.g. 30m, 6h, 1d (default: 1d)
+  --once                Scan once and exit
+  --name <MACHINE>      Name to file snapshots under (default: the host name)
+  --min-size <SIZE>     Leave out files and folders smaller than this (default: 1M)
+  --background          Scan at the lowest CPU and I/O priority
+  -h, --help            Show this help
+
+Exit codes (with --once): 0 = all snapshots saved, 2 = usage error or a failed scan.";
+
+const EXIT_OK: i32 = 0;
+const EXIT_FAILED: i32 = 2;
+const SNAPSHOT_EXTENSION: &str = "dcsnap";
+const TOKEN_ENV: &str = "DISKCHECK_AGENT_TOKEN";
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_MIN_SIZE: u64 = 1024 * 1024;
+// Where the combined view of a fleet is rooted.
+const FLEET_SCHEME: &str = "fleet://";
+
+#[derive(Debug)]
+struct AgentOptions {
+    paths: Vec<PathBuf>,
+    to: String,
+    every: Duration,
+    once: bool,
+    name: String,
+    min_size: u64,
+    mode: ScanMode,
+}
+
+/// Parses intervals like `30m`, `6h`, `1d` or plain seconds.
+fn parse_interval(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let multiplier: u64 = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+    let secs = number.trim().parse::<u64>().ok()?.checked_mul(multiplier)?;
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// The host name, for filing snapshots under.
+fn machine_name() -> Option<String> {
+    #[cfg(unix)]
+    {
+        let mut buf = [0u8; 256];
+        // SAFETY: the buffer is valid for its length; the name is NUL-terminated on success.
+        if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } == 0 {
+            let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+            let name = String::from_utf8_lossy(&buf[..len]).into_owned();
+            // Only the host part of a fully qualified name.
+            return name
+                .split('.')
+                .next()
+                .filter(|name| !name.is_empty())
+                .map(str::to_string);
+        }
+        None
+    }
+    #[cfg(not(unix))]
+    {
+        std::env::var("COMPUTERNAME")
+            .ok()
+            .filter(|name| !name.is_empty())
+    }
+}
+
+/// A file name for `text`: separators and other characters file systems reject become `_`.
+fn slug(text: &str) -> String {
+    let slug: String = text
+        .trim_matches(['/', '\\'])
+        .chars()
+        .map(|c| {
+            if c.is_control() || r#"/\:*?"<>|"#.contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let slug = slug.trim_matches(['.', ' ', '_']).to_string();
+    if slug.is_empty() {
+        "root".to_string()
+    } else {
+        slug
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<AgentOptions, String> {
+    let mut paths = vec![];
+    let mut to = None;
+    let mut every = DEFAULT_INTERVAL;
+    let mut once = false;
+    let mut name = None;
+    let mut min_size = DEFAULT_MIN_SIZE;
+    let mut mode = ScanMode::Normal;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |flag: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value.", flag))
+        };
+        match arg.as_str() {
+            AGENT_FLAG => {}
+            "--to" => to = Some(value(arg)?),
+            "--every" => {
+                let text = value(arg)?;
+                every =
+                    parse_interval(&text).ok_or_else(|| format!("Invalid interval: {}", text))?;
+            }
+            "--once" => once = true,
+            "--name" => name = Some(slug(&value(arg)?)),
+            "--min-size" => {
+                let text = value(arg)?;
+                min_size = parse_size(&text).ok_or_else(|| format!("Invalid size: {}", text))?;
+            }
+            "--background" => mode = ScanMode::Background,
+            flag if flag.starts_with('-') => return Err(format!("Unknown option: {}", flag)),
+            path => paths.push(PathBuf::from(path)),
+        }
+    }
+    if paths.is_empty() {
+        return Err("Name at least one path to scan.".to_string());
+    }
+    let name = name
+        .or_else(|| machine_name().map(|name| slug(&name)))
+        .ok_or("Could not tell the host name; pass --name.")?;
+    Ok(AgentOptions {
+        paths,
+        to: to.ok_or("--to is required.")?,
+        every,
+        once,
+        name,
+        min_size,
+        mode,
+    })
+}
+
+fn is_url(dest: &str) -> bool {
+    dest.starts_with("https://") || dest.starts_with("http://")
+}
+
+/// Uploads `file` to `DEST/<machine>/<name>` with a PUT.
+fn upload(dest: &str, machine: &str, name: &str, file: &Path) -> Result<(), String> {
+    let url = format!(
+        "{}/{}/{}",
+        dest.trim_end_matches('/'),
+        uri_encode(machine),
+        uri_encode(name)
+    );
+    let headers: Vec<(String, String)> = std::env::var(TOKEN_ENV)
+        .ok()
+        .filter(|token| !token.is_empty())
+        .map(|token| ("Authorization".to_string(), format!("Bearer {}", token)))
+        .into_iter()
+        .collect();
+    let (status, _) = curl_request("PUT", &url, &headers, None, Some(RequestBody::File(file)))?;
+    if !(200..300).contains(&status) {
+        return Err(format!("Uploading to {} failed with HTTP {}.", url, status));
+    }
+    Ok(())
+}
+
+/// Scans `root` once and saves the pruned result as a snapshot.
+fn snapshot_once(options: &AgentOptions, root: &Path) -> Result<String, String> {
+    let finished = scan_blocking(
+        root,
+        Some(options.min_size),
+        vec![],
+        false,
+        options.mode,
+        None,
+        None,
+    )?;
+    let tree = ScanTree::from(&finished.pruned);
+    let name = format!("{}.{}", slug(&root.to_string_lossy()), SNAPSHOT_EXTENSION);
+
+    if is_url(&options.to) {
+        let file = std::env::temp_dir().join(format!("diskcheck-agent-{}", name));
+        let written = crate::export::create_dest(&file)
+            .and_then(|out| write_snapshot(&finished.summary, &tree, None, out))
+            .and_then(|()| upload(&options.to, &options.name, &name, &file));
+        let _ = fs::remove_file(&file);
+        written?;
+        return Ok(format!("{}/{}/{}", options.to, options.name, name));
+    }
+
+    let folder = Path::new(&options.to).join(&options.name);
+    fs::create_dir_all(&folder)
+        .map_err(|e| format!("Failed to create {}: {}", folder.to_string_lossy(), e))?;
+    let dest = folder.join(&name);
+    // Readers on other machines must never see a half-written snapshot.
+    let tmp = dest.with_extension(format!("{}.tmp", SNAPSHOT_EXTENSION));
+    crate::export::create_dest(&tmp)
+        .and_then(|out| write_snapshot(&finished.summary, &tree, None, out))
+        .and_then(|()| fs::rename(&tmp, &dest).map_err(|e| e.to_string()))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })
+        .map_err(|e| format!("Failed to write {}: {}", dest.to_string_lossy(), e))?;
+    Ok(dest.to_string_lossy().into_owned())
+}
+
+/// Runs the agent with the command line `args` and returns its exit code. Without
+/// `--once` it only returns on a usage error.
+pub(crate) fn run_agent(args: &[String]) -> i32 {
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return EXIT_OK;
+    }
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("diskcheck: {}\n\n{}", err, USAGE);
+            return EXIT_FAILED;
+        }
+    };
+    loop {
+        let mut failed = false;
+        for root in &options.paths {
+            let now = iso8601_utc(unix_secs(SystemTime::now()));
+            match snapshot_once(&options, root) {
+                Ok(dest) => println!("{} {} -> {}", now, root.to_string_lossy(), dest),
+                Err(err) => {
+                    eprintln!("{} {}: {}", now, root.to_string_lossy(), err);
+                    failed = true;
+                }
+            }
+        }
+        if options.once {
+            return if failed { EXIT_FAILED } else { EXIT_OK };
+        }
+        std::thread::sleep(options.every);
+    }
+}
+
+/// One machine of an imported fleet.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetMachine {
+    pub name: String,
+    pub total_bytes: u64,
+    // When its most recent snapshot was taken.
+    pub last_scan_secs: u64,
+    pub roots: Vec<ScanSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetImport {
+    pub scan_id: String,
+    pub root: FsNode,
+    // Largest first.
+    pub machines: Vec<FleetMachine>,
+    // Snapshot files that could not be read, with the reason.
+    pub errors: Vec<String>,
+}
+
+fn folder_node(name: String, children: Vec<FsNode>) -> FsNode {
+    FsNode {
+        size: children
+            .iter()
+            .fold(0u64, |acc, child| acc.saturating_add(child.size)),
+        // Only names count when building the tree; paths are derived from them.
+        path: name.clone(),
+        name,
+        kind: FsNodeKind::Directory,
+        children,
+        extension: None,
+        error: None,
+        uncompressed_size: None,
+        error_count: 0,
+    }
+}
+
+/// Reads `<folder>/<machine>/*.dcsnap` into one tree: machines below the root, the
+/// scanned paths of each below the machine.
+fn import_fleet_blocking(
+    folder: &Path,
+) -> Result<(ScanTree, ScanSummary, Vec<FleetMachine>, Vec<String>), String> {
+    let entries = fs::read_dir(folder)
+        .map_err(|e| format!("Failed to read {}: {}", folder.to_string_lossy(), e))?;
+    let mut machines: HashMap<String, (Vec<FsNode>, Vec<ScanSummary>)> = HashMap::new();
+    let mut errors = vec![];
+    for machine_dir in entries.flatten().map(|entry| entry.path()) {
+        let Ok(files) = fs::read_dir(&machine_dir) else {
+            continue;
+        };
+        let machine = crate::scanner::display_name(&machine_dir);
+        for file in files.flatten().map(|entry| entry.path()) {
+            if file.extension().and_then(|e| e.to_str()) != Some(SNAPSHOT_EXTENSION) {
+                continue;
+            }
+            let read = fs::File::open(&file)
+                .map_err(|e| e.to_string())
+                .and_then(|f| read_snapshot(BufReader::new(f)));
+            let (tree, summary, _) = match read {
+                Ok(read) => read,
+                Err(err) => {
+                    errors.push(format!("{}: {}", file.to_string_lossy(), err));
+                    continue;
+                }
+            };
+            let mut node = tree.root().to_fs_node();
+            node.name = slug(&summary.root_path);
+            let slot = machines.entry(machine.clone()).or_default();
+            slot.0.push(node);
+            slot.1.push(summary);
+        }
+    }
+    if machines.is_empty() && errors.is_empty() {
+        return Err(format!(
+            "No agent snapshots found in {}.",
+            folder.to_string_lossy()
+        ));
+    }
+
+    let mut summaries = vec![];
+    let mut nodes = vec![];
+    for (name, (roots, summaries_of)) in machines {
+        let node = folder_node(name.clone(), roots);
+        summaries.push(FleetMachine {
+            name,
+            total_bytes: node.size,
+            last_scan_secs: summaries_of
+                .iter()
+                .map(|s| s.started_at_secs)
+                .max()
+                .unwrap_or(0),
+            roots: summaries_of,
+        });
+        nodes.push(node);
+    }
+    summaries.sort_by_key(|m| std::cmp::Reverse(m.total_bytes));
+
+    let mut root = folder_node(crate::scanner::display_name(folder), nodes);
+    root.path = format!("{}{}", FLEET_SCHEME, root.name);
+    let all = summaries.iter().flat_map(|m| &m.roots);
+    let summary = ScanSummary {
+        root_path: root.path.clone(),
+        started_at_secs: summaries
+            .iter()
+            .map(|m| m.last_scan_secs)
+            .max()
+            .unwrap_or_else(|| unix_secs(SystemTime::now())),
+        duration_ms: 0,
+        total_bytes: root.size,
+        file_count: all.clone().map(|s| s.file_count).sum(),
+        dir_count: all.clone().map(|s| s.dir_count).sum(),
+        skipped_entries: all.map(|s| s.skipped_entries).sum(),
+        denied_dirs: vec![],
+        skipped: vec![],
+        partial: false,
+    };
+    Ok((ScanTree::from(&root), summary, summaries, errors))
+}
+
+/// Combines the snapshots agents saved to `path` into one scan, with a folder per
+/// machine, and stores it like any other.
+pub async fn import_fleet(store: &ScanStore, path: String) -> Result<FleetImport, String> {
+    let folder = PathBuf::from(path);
+    let (tree, summary, machines, errors) =
+        tauri::async_runtime::spawn_blocking(move || import_fleet_blocking(&folder))
+            .await
+            .map_err(|err| err.to_string())??;
+
+    let root = pruned_view(tree.root(), None);
+    let scan_id = store.insert(tree, summary)?;
+    Ok(FleetImport {
+        scan_id,
+        root,
+        machines,
+        errors,
+    })
+}
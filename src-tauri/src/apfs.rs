@@ -0,0 +1,163 @@
+use serde::Serialize;
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeableSpace {
+    pub mount_point: String,
+    // Free space as reported by statfs (what most tools show).
+    pub available_bytes: u64,
+    // Free space as reported by Finder: statfs free plus purgeable caches/snapshots.
+    pub important_usage_available_bytes: u64,
+    pub purgeable_bytes: u64,
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalSnapshot {
+    pub name: String,
+    // `YYYY-MM-DD-HHMMSS`, the identifier `tmutil deletelocalsnapshots` expects.
+    pub date: String,
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{LocalSnapshot, PurgeableSpace};
+    use crate::volumes::{run_tool, space_for_path};
+    use std::{path::Path, process::Command};
+
+    /// Asks Foundation for the "important usage" capacity (the figure Finder shows),
+    /// which includes purgeable space. JXA saves us from linking Objective-C directly.
+    fn important_usage_capacity(mount_point: &str) -> Result<u64, String> {
+        let script = format!(
+            "ObjC.import('Foundation');\
+             var url = $.NSURL.fileURLWithPath({});\
+             var ref = Ref();\
+             url.getResourceValueForKeyError(ref, $.NSURLVolumeAvailableCapacityForImportantUsageKey, null);\
+             String(ref[0].js);",
+            serde_json::to_string(mount_point).map_err(|e| e.to_string())?
+        );
+        let output = Command::new("osascript")
+            .args(["-l", "JavaScript", "-e", &script])
+            .output()
+            .map_err(|e| format!("Failed to run osascript: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to query purgeable space: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse::<f64>()
+            .map(|v| v as u64)
+            .map_err(|_| "Unexpected output while querying purgeable space.".to_string())
+    }
+
+    pub(super) fn purgeable_space(mount_point: &str) -> Result<PurgeableSpace, String> {
+        let space = space_for_path(Path::new(mount_point))?;
+        let important = important_usage_capacity(mount_point)?;
+        Ok(PurgeableSpace {
+            mount_point: mount_point.to_string(),
+            available_bytes: space.available_bytes,
+            important_usage_available_bytes: important,
+            purgeable_bytes: important.saturating_sub(space.available_bytes),
+        })
+    }
+
+    pub(super) fn list_local_snapshots(mount_point: &str) -> Result<Vec<LocalSnapshot>, String> {
+        let output = Command::new("tmutil")
+            .args(["listlocalsnapshots", mount_point])
+            .output()
+            .map_err(|e| format!("Failed to run tmutil: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "tmutil failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        // Lines look like `com.apple.TimeMachine.2024-05-01-093012.local`.
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("com.apple."))
+            .filter_map(|line| {
+                let date = line
+                    .trim_end_matches(".local")
+                    .rsplit('.')
+                    .next()?
+                    .to_string();
+                Some(LocalSnapshot {
+                    name: line.to_string(),
+                    date,
+                })
+            })
+            .collect())
+    }
+
+    pub(super) fn delete_local_snapshot(date: &str) -> Result<(), String> {
+        run_tool("tmutil", &["deletelocalsnapshots", date])
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use super::{LocalSnapshot, PurgeableSpace};
+
+    const UNSUPPORTED: &str =
+        "APFS purgeable space and local snapshots are only available on macOS.";
+
+    pub(super) fn purgeable_space(_mount_point: &str) -> Result<PurgeableSpace, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub(super) fn list_local_snapshots(_mount_point: &str) -> Result<Vec<LocalSnapshot>, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub(super) fn delete_local_snapshot(_date: &str) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+}
+
+fn is_snapshot_date(date: &str) -> bool {
+    // YYYY-MM-DD-HHMMSS
+    date.len() == 17
+        && date.chars().enumerate().all(|(i, c)| {
+            if matches!(i, 4 | 7 | 10) {
+                c == '-'
+            } else {
+                c.is_ascii_digit()
+            }
+        })
+}
+
+pub async fn purgeable_space(mount_point: Option<String>) -> Result<PurgeableSpace, String> {
+    let mount_point = mount_point.unwrap_or_else(|| "/".to_string());
+    tauri::async_runtime::spawn_blocking(move || platform::purgeable_space(&mount_point))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+pub async fn list_local_snapshots(
+    mount_point: Option<String>,
+) -> Result<Vec<LocalSnapshot>, String> {
+    let mount_point = mount_point.unwrap_or_else(|| "/".to_string());
+    tauri::async_runtime::spawn_blocking(move || platform::list_local_snapshots(&mount_point))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+pub async fn delete_local_snapshot(date: String) -> Result<(), String> {
+    // Only ever hand tmutil a well-formed snapshot date, never free-form input.
+    if !is_snapshot_date(&date) {
+        return Err(format!("Invalid snapshot date: {}", date));
+    }
+    tracing::info!(date = %date, "deleting local snapshot");
+    tauri::async_runtime::spawn_blocking(move || platform::delete_local_snapshot(&date))
+        .await
+        .map_err(|err| err.to_string())?
+        .inspect_err(|err| tracing::error!(error = %err, "failed to delete local snapshot"))
+}
@@ -0,0 +1,483 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+use tauri::Manager;
+
+use crate::{
+    metrics,
+    priority::ScanMode,
+    report::{aggregate, ReportKind},
+    scan_index::IndexOptions,
+    scanner::{pruned_view, scan_blocking},
+    settings::SettingsStore,
+    store::{unix_secs, ScanStore},
+};
+
+const DEFAULT_PORT: u16 = 7878;
+// How often the accept loop wakes up to check whether it was stopped.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+// Requests handled at once; more are turned away instead of each getting a thread.
+const MAX_CONNECTIONS: usize = 16;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiServerInfo {
+    pub port: u16,
+    pub url: String,
+    // Required as `Authorization: Bearer <token>`, so web pages cannot drive the API.
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ActiveScan {
+    path: String,
+    started_at_secs: u64,
+}
+
+struct ServerHandle {
+    info: ApiServerInfo,
+    stop: Arc<AtomicBool>,
+}
+
+/// Optional localhost HTTP API so scripts and dashboards can trigger scans and pull
+/// results from the running app.
+#[derive(Default)]
+pub struct ApiServer {
+    current: Mutex<Option<ServerHandle>>,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    token: Option<String>,
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+const JSON_CONTENT_TYPE: &str = "application/json";
+
+impl Response {
+    fn ok(body: impl Serialize) -> Self {
+        match serde_json::to_string(&body) {
+            Ok(body) => Self {
+                status: 200,
+                content_type: JSON_CONTENT_TYPE,
+                body,
+            },
+            Err(e) => Self::error(500, e.to_string()),
+        }
+    }
+
+    fn text(content_type: &'static str, body: String) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body,
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: JSON_CONTENT_TYPE,
+            body: json!({ "error": message.into() }).to_string(),
+        }
+    }
+}
+
+/// 32 bytes from the OS random number generator, hex-encoded.
+fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|e| format!("Failed to generate an API token: {}", e))?;
+    Ok(hex::encode(bytes))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) => out.push(b' '),
+            (b, _) => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, String> {
+    let mut reader = BufReader::new(stream.take(MAX_HEADER_BYTES as u64));
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|e| e.to_string())?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default();
+
+    let mut token = None;
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line).map_err(|e| e.to_string())?;
+        let line = line.trim_end();
+        if read == 0 || line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("authorization") {
+                token = value
+                    .trim()
+                    .strip_prefix("Bearer ")
+                    .map(|t| t.trim().to_string());
+            }
+        }
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+
+    Ok(Request {
+        method,
+        path: percent_decode(path),
+        query,
+        token,
+    })
+}
+
+fn write_response(mut stream: &TcpStream, response: &Response) {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len(),
+        response.body
+    );
+    let _ = stream.flush();
+}
+
+fn min_node_bytes(request: &Request) -> Result<Option<u64>, Response> {
+    match request.query.get("minNodeBytes") {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| Response::error(400, "minNodeBytes must be a number of bytes.")),
+        None => Ok(None),
+    }
+}
+
+fn refresh(request: &Request) -> Result<bool, Response> {
+    match request.query.get("refresh").map(String::as_str) {
+        Some("true") | Some("1") => Ok(true),
+        Some("false") | Some("0") | None => Ok(false),
+        Some(_) => Err(Response::error(400, "refresh must be true or false.")),
+    }
+}
+
+fn scan_mode(request: &Request) -> Result<ScanMode, Response> {
+    match request.query.get("mode") {
+        Some(mode) => serde_json::from_value(Value::String(mode.clone()))
+            .map_err(|_| Response::error(400, "mode must be normal or background.")),
+        None => Ok(ScanMode::Normal),
+    }
+}
+
+struct Context {
+    app: tauri::AppHandle,
+    active: Mutex<Vec<ActiveScan>>,
+}
+
+fn run_scan(ctx: &Context, request: &Request) -> Response {
+    let path = match request.query.get("path") {
+        Some(path) => PathBuf::from(path),
+        None => return Response::error(400, "Missing `path` query parameter."),
+    };
+    if !path.exists() {
+        return Response::error(
+            400,
+            format!("Path does not exist: {}", path.to_string_lossy()),
+        );
+    }
+    let min_node_bytes = match min_node_bytes(request) {
+        Ok(min) => min,
+        Err(response) => return response,
+    };
+    let mode = match scan_mode(request) {
+        Ok(mode) => mode,
+        Err(response) => return response,
+    };
+    let refresh = match refresh(request) {
+        Ok(refresh) => refresh,
+        Err(response) => return response,
+    };
+    let defaults = ctx.app.state::<SettingsStore>().scan();
+
+    let active = ActiveScan {
+        path: path.to_string_lossy().into_owned(),
+        started_at_secs: unix_secs(SystemTime::now()),
+    };
+    if let Ok(mut scans) = ctx.active.lock() {
+        scans.push(active.clone());
+    }
+    let result = scan_blocking(
+        &path,
+        min_node_bytes.or(defaults.min_node_bytes),
+        defaults.excludes,
+        defaults.scan_archives,
+        mode,
+        IndexOptions::new(&ctx.app, refresh),
+        None,
+    );
+    if let Ok(mut scans) = ctx.active.lock() {
+        if let Some(pos) = scans.iter().position(|s| s.path == active.path) {
+            scans.remove(pos);
+        }
+    }
+
+    let store = ctx.app.state::<ScanStore>();
+    match result.map_err(String::from).and_then(|finished| {
+        store
+            .insert_spilled(finished.tree, finished.spill, finished.summary.clone())
+            .map(|scan_id| (scan_id, finished.summary))
+    }) {
+        Ok((scan_id, summary)) => Response::ok(json!({ "scanId": scan_id, "summary": summary })),
+        Err(err) => Response::error(500, err),
+    }
+}
+
+fn route(ctx: &Context, request: &Request) -> Response {
+    let store = ctx.app.state::<ScanStore>();
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["v1", "status"]) => {
+            let active = ctx.active.lock().map(|a| a.clone()).unwrap_or_default();
+            let stored = store.list().map(|s| s.len()).unwrap_or(0);
+            Response::ok(json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "activeScans": active,
+                "storedScans": stored,
+            }))
+        }
+        // Prometheus scrape target; configure the bearer token under `authorization`.
+        ("GET", ["metrics"]) | ("GET", ["v1", "metrics"]) => {
+            let active = ctx.active.lock().map(|a| a.len()).unwrap_or(0);
+            Response::text(metrics::CONTENT_TYPE, metrics::render(&ctx.app, active))
+        }
+        ("POST", ["v1", "scans"]) => run_scan(ctx, request),
+        ("GET", ["v1", "scans"]) => match store.list() {
+            Ok(scans) => Response::ok(
+                scans
+                    .iter()
+                    .map(|scan| json!({ "scanId": scan.id, "summary": scan.summary }))
+                    .collect::<Vec<Value>>(),
+            ),
+            Err(err) => Response::error(500, err),
+        },
+        ("GET", ["v1", "scans", id]) => match store.get(id) {
+            Ok(scan) => Response::ok(json!({ "scanId": scan.id, "summary": scan.summary })),
+            Err(err) => Response::error(404, err),
+        },
+        ("GET", ["v1", "scans", id, "tree"]) => {
+            let path = request.query.get("path").map(String::as_str);
+            let scan = match store.get_for_path(id, path) {
+                Ok(scan) => scan,
+                Err(err) => return Response::error(404, err),
+            };
+            let min_node_bytes = match min_node_bytes(request) {
+                Ok(min) => min,
+                Err(response) => return response,
+            };
+            match scan.node(path) {
+                Ok(node) => Response::ok(pruned_view(node, min_node_bytes)),
+                Err(err) => Response::error(404, err),
+            }
+        }
+        ("GET", ["v1", "scans", id, "reports", kind]) => {
+            let scan = match store.get(id) {
+                Ok(scan) => scan,
+                Err(err) => return Response::error(404, err),
+            };
+            let scan = match scan.complete() {
+                Ok(scan) => scan,
+                Err(err) => return Response::error(500, err),
+            };
+            match serde_json::from_value::<ReportKind>(Value::String(kind.to_string())) {
+                Ok(kind) => Response::ok(aggregate(scan.tree.root(), kind)),
+                Err(_) => Response::error(
+                    404,
+                    "Unknown report; use extension, category, owner or age.",
+                ),
+            }
+        }
+        (_, ["v1", ..]) => Response::error(405, "Unsupported method or endpoint."),
+        _ => Response::error(404, "Not found."),
+    }
+}
+
+/// Compares in time independent of where the two differ, so the token cannot be
+/// guessed byte by byte from response times.
+fn token_matches(given: Option<&str>, token: &str) -> bool {
+    let Some(given) = given else {
+        return false;
+    };
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Frees its connection slot when the request is done, however it ends.
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn handle_connection(ctx: &Context, token: &str, stream: TcpStream) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let response = match read_request(&stream) {
+        Ok(request) if !token_matches(request.token.as_deref(), token) => {
+            Response::error(401, "Missing or invalid bearer token.")
+        }
+        Ok(request) => route(ctx, &request),
+        Err(err) => Response::error(400, err),
+    };
+    write_response(&stream, &response);
+}
+
+fn run_server(listener: TcpListener, ctx: Arc<Context>, token: String, stop: Arc<AtomicBool>) {
+    let open = Arc::new(AtomicUsize::new(0));
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if open.fetch_add(1, Ordering::Relaxed) >= MAX_CONNECTIONS {
+                    open.fetch_sub(1, Ordering::Relaxed);
+                    let _ = stream.set_nonblocking(false);
+                    let _ = stream.set_write_timeout(Some(READ_TIMEOUT));
+                    write_response(&stream, &Response::error(503, "Too many requests at once."));
+                    continue;
+                }
+                let slot = ConnectionSlot(open.clone());
+                let ctx = ctx.clone();
+                let token = token.clone();
+                // Scans can take minutes; never block the accept loop on one.
+                let _ = thread::Builder::new()
+                    .name("api-request".to_string())
+                    .spawn(move || {
+                        let _slot = slot;
+                        handle_connection(&ctx, &token, stream)
+                    });
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(STOP_POLL_INTERVAL);
+            }
+            Err(_) => thread::sleep(STOP_POLL_INTERVAL),
+        }
+    }
+}
+
+impl ApiServer {
+    pub fn start(&self, app: tauri::AppHandle, port: Option<u16>) -> Result<ApiServerInfo, String> {
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = current.take() {
+            previous.stop.store(true, Ordering::Relaxed);
+        }
+
+        // Loopback only: the API is for tools on this machine.
+        let port = port.unwrap_or(DEFAULT_PORT);
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .map_err(|e| format!("Failed to listen on 127.0.0.1:{}: {}", port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+
+        let info = ApiServerInfo {
+            port,
+            url: format!("http://127.0.0.1:{}/v1", port),
+            token: generate_token()?,
+        };
+        let stop = Arc::new(AtomicBool::new(false));
+        let ctx = Arc::new(Context {
+            app,
+            active: Mutex::new(vec![]),
+        });
+        let worker_stop = stop.clone();
+        let token = info.token.clone();
+        thread::Builder::new()
+            .name("api-server".to_string())
+            .spawn(move || run_server(listener, ctx, token, worker_stop))
+            .map_err(|e| format!("Failed to start the API server: {}", e))?;
+
+        tracing::info!(port, "API server started");
+        *current = Some(ServerHandle {
+            info: info.clone(),
+            stop,
+        });
+        Ok(info)
+    }
+
+    pub fn stop(&self) -> Result<(), String> {
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = current.take() {
+            previous.stop.store(true, Ordering::Relaxed);
+            tracing::info!(port = previous.info.port, "API server stopped");
+        }
+        Ok(())
+    }
+
+    pub fn info(&self) -> Result<Option<ApiServerInfo>, String> {
+        let current = self.current.lock().map_err(|e| e.to_string())?;
+        Ok(current.as_ref().map(|h| h.info.clone()))
+    }
+}
@@ -0,0 +1,146 @@
+use flate2::read::GzDecoder;
+use std::{io::Read, path::Path};
+
+use diskcheck_core::{ArchiveEntry, ArchiveLister};
+
+use crate::cloud_sync::open_local;
+
+// Larger archives stay opaque rather than add that many nodes to the tree.
+const MAX_ENTRIES: usize = 100_000;
+// Listing a gzipped tar means decompressing all of it; beyond this it takes too long.
+const MAX_GZIP_TAR_BYTES: u64 = 4 << 30;
+
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    Zip,
+    Tar,
+    GzipTar,
+    SevenZip,
+}
+
+fn format_of(path: &Path) -> Option<Format> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        Some(Format::Zip)
+    } else if name.ends_with(".tar") {
+        Some(Format::Tar)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(Format::GzipTar)
+    } else if name.ends_with(".7z") {
+        Some(Format::SevenZip)
+    } else {
+        None
+    }
+}
+
+fn list_zip(path: &Path) -> Result<Vec<ArchiveEntry>, String> {
+    let file = open_local(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    if archive.len() > MAX_ENTRIES {
+        return Err(format!("{} entries", archive.len()));
+    }
+    (0..archive.len())
+        .map(|index| {
+            // Raw access reads the headers only, nothing is decompressed.
+            let entry = archive.by_index_raw(index).map_err(|e| e.to_string())?;
+            Ok(ArchiveEntry {
+                path: entry.name().to_string(),
+                is_dir: entry.is_dir(),
+                compressed: entry.compressed_size(),
+                uncompressed: entry.size(),
+            })
+        })
+        .collect()
+}
+
+fn list_tar(reader: impl Read) -> Result<Vec<ArchiveEntry>, String> {
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = vec![];
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        if entries.len() == MAX_ENTRIES {
+            return Err(format!("more than {} entries", MAX_ENTRIES));
+        }
+        let size = entry.size();
+        entries.push(ArchiveEntry {
+            path: entry
+                .path()
+                .map_err(|e| e.to_string())?
+                .to_string_lossy()
+                .into_owned(),
+            is_dir: entry.header().entry_type().is_dir(),
+            compressed: size,
+            uncompressed: size,
+        });
+    }
+    Ok(entries)
+}
+
+fn list_seven_zip(path: &Path) -> Result<Vec<ArchiveEntry>, String> {
+    let mut file = open_local(path).map_err(|e| e.to_string())?;
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+    let archive = sevenz_rust::Archive::read(&mut file, len, &[]).map_err(|e| e.to_string())?;
+    if archive.files.len() > MAX_ENTRIES {
+        return Err(format!("{} entries", archive.files.len()));
+    }
+    Ok(archive
+        .files
+        .iter()
+        .filter(|entry| !entry.is_anti_item)
+        .map(|entry| ArchiveEntry {
+            path: entry.name().to_string(),
+            is_dir: entry.is_directory(),
+            compressed: entry.compressed_size,
+            uncompressed: entry.size(),
+        })
+        .collect())
+}
+
+/// Gzipped tars and solid 7z archives compress entries together, so there are no
+/// per-entry compressed sizes; share the archive's size out by uncompressed size.
+fn spread_archive_size(entries: &mut [ArchiveEntry], archive_len: u64) {
+    let total: u64 = entries.iter().map(|e| e.uncompressed).sum();
+    if total == 0 {
+        return;
+    }
+    for entry in entries {
+        entry.compressed =
+            (u128::from(entry.uncompressed) * u128::from(archive_len) / u128::from(total)) as u64;
+    }
+}
+
+/// Lists `.zip`, `.tar`, `.tar.gz`/`.tgz` and `.7z` files.
+pub(crate) struct Archives;
+
+impl ArchiveLister for Archives {
+    fn list(&self, path: &Path, len: u64) -> Option<Vec<ArchiveEntry>> {
+        let format = format_of(path)?;
+        let listed = match format {
+            Format::Zip => list_zip(path),
+            Format::Tar => open_local(path)
+                .map_err(|e| e.to_string())
+                .and_then(list_tar),
+            Format::GzipTar if len > MAX_GZIP_TAR_BYTES => Err("too large to list".to_string()),
+            Format::GzipTar => open_local(path)
+                .map_err(|e| e.to_string())
+                .and_then(|file| list_tar(GzDecoder::new(file))),
+            Format::SevenZip => list_seven_zip(path),
+        };
+        let mut entries = match listed {
+            Ok(entries) => entries,
+            Err(error) => {
+                tracing::debug!(path = %path.display(), ?format, error = %error, "archive left unlisted");
+                return None;
+            }
+        };
+        let per_entry_sizes = match format {
+            Format::GzipTar => false,
+            Format::SevenZip => entries.iter().any(|e| e.compressed > 0),
+            Format::Zip | Format::Tar => true,
+        };
+        if !per_entry_sizes {
+            spread_archive_size(&mut entries, len);
+        }
+        Some(entries)
+    }
+}
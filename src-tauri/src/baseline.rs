@@ -0,0 +1,327 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+use tauri::{Emitter, Manager};
+
+use crate::{
+    scanner::{same_path, FsNodeKind, NodeRef, ScanTree},
+    snapshot::{read_snapshot, write_snapshot},
+    store::{unix_secs, ScanStore, StoredScan},
+};
+
+const BASELINE_DIR: &str = "baselines";
+const BASELINES_FILE: &str = "baselines.json";
+const BASELINE_CHANGES_EVENT: &str = "baseline_changes";
+// New files from this size up are listed.
+const NEW_FILE_MIN_BYTES: u64 = 10 * 1024 * 1024;
+// Folders that grew by at least this much are listed.
+const GROWN_DIR_MIN_BYTES: u64 = 100 * 1024 * 1024;
+// Per list, largest first.
+const MAX_CHANGES: usize = 100;
+
+/// A scan marked as the point later scans of its root are compared against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Baseline {
+    pub root_path: String,
+    pub scanned_at_secs: u64,
+    pub marked_at_secs: u64,
+    pub total_bytes: u64,
+    // The snapshot holding the scan, in the baselines folder.
+    file: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedEntry {
+    pub path: String,
+    // Absent for entries the baseline did not have.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_before: Option<u64>,
+    pub size_after: u64,
+}
+
+/// What changed under a root between its baseline and a later scan.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeReport {
+    pub root_path: String,
+    pub scan_id: String,
+    pub baseline_scanned_at_secs: u64,
+    pub scanned_at_secs: u64,
+    pub total_before: u64,
+    pub total_after: u64,
+    pub new_files: Vec<ChangedEntry>,
+    pub grown_dirs: Vec<ChangedEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BaselineChangesPayload {
+    root_path: String,
+    scan_id: String,
+}
+
+/// Reports computed after scans of a root with a baseline, by root.
+#[derive(Default)]
+pub struct BaselineReports {
+    reports: Mutex<HashMap<String, ChangeReport>>,
+}
+
+fn baseline_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(BASELINE_DIR))
+        .map_err(|e| e.to_string())
+}
+
+fn load(dir: &Path) -> Vec<Baseline> {
+    fs::read(dir.join(BASELINES_FILE))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save(dir: &Path, baselines: &[Baseline]) -> Result<(), String> {
+    let path = dir.join(BASELINES_FILE);
+    let json = serde_json::to_vec_pretty(baselines).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)
+        .and_then(|_| fs::rename(&tmp, &path))
+        .map_err(|e| format!("Failed to save {}: {}", path.to_string_lossy(), e))
+}
+
+/// The baseline of `root`, with its scan.
+fn open(app: &tauri::AppHandle, root: &str) -> Result<Option<(Baseline, ScanTree)>, String> {
+    let dir = baseline_dir(app)?;
+    let Some(baseline) = load(&dir)
+        .into_iter()
+        .find(|b| same_path(&b.root_path, root))
+    else {
+        return Ok(None);
+    };
+    let path = dir.join(&baseline.file);
+    let file = File::open(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.to_string_lossy(), e))?;
+    let (tree, _, _) = read_snapshot(BufReader::new(file))?;
+    Ok(Some((baseline, tree)))
+}
+
+#[derive(Default)]
+struct Changes {
+    new_files: Vec<ChangedEntry>,
+    grown_dirs: Vec<ChangedEntry>,
+}
+
+fn is_dir(node: &NodeRef<'_>) -> bool {
+    matches!(node.kind(), FsNodeKind::Directory)
+}
+
+/// Walks `after` alongside `before`, its counterpart in the baseline if it had one.
+fn walk(after: NodeRef<'_>, before: Option<NodeRef<'_>>, changes: &mut Changes) {
+    let size_before = before.map(|node| node.size());
+    if !is_dir(&after) {
+        if before.is_none() && after.size() >= NEW_FILE_MIN_BYTES {
+            changes.new_files.push(ChangedEntry {
+                path: after.path(),
+                size_before: None,
+                size_after: after.size(),
+            });
+        }
+        return;
+    }
+    // Growth of the root itself is the report's total.
+    if after.parent().is_some()
+        && after.size().saturating_sub(size_before.unwrap_or(0)) >= GROWN_DIR_MIN_BYTES
+    {
+        changes.grown_dirs.push(ChangedEntry {
+            path: after.path(),
+            size_before,
+            size_after: after.size(),
+        });
+    }
+    let mut earlier: HashMap<&str, NodeRef<'_>> = before
+        .filter(is_dir)
+        .map(|node| node.children().map(|c| (c.name(), c)).collect())
+        .unwrap_or_default();
+    for child in after.children() {
+        walk(child, earlier.remove(child.name()), changes);
+    }
+}
+
+fn largest(mut entries: Vec<ChangedEntry>) -> Vec<ChangedEntry> {
+    entries.sort_by_key(|e| {
+        std::cmp::Reverse(e.size_after.saturating_sub(e.size_before.unwrap_or(0)))
+    });
+    entries.truncate(MAX_CHANGES);
+    entries
+}
+
+fn report(baseline: &Baseline, before: &ScanTree, scan: &StoredScan) -> ChangeReport {
+    let mut changes = Changes::default();
+    walk(scan.tree.root(), Some(before.root()), &mut changes);
+    ChangeReport {
+        root_path: baseline.root_path.clone(),
+        scan_id: scan.id.clone(),
+        baseline_scanned_at_secs: baseline.scanned_at_secs,
+        scanned_at_secs: scan.summary.started_at_secs,
+        total_before: before.size(),
+        total_after: scan.tree.size(),
+        new_files: largest(changes.new_files),
+        grown_dirs: largest(changes.grown_dirs),
+    }
+}
+
+/// Compares the scan `scan_id` with its root's baseline and keeps the report, telling
+/// `window` when it is ready. Does nothing for roots without a baseline.
+pub(crate) fn on_scan_finished(window: &tauri::Window, scan_id: &str) {
+    let app = window.app_handle().clone();
+    let label = window.label().to_string();
+    let scan_id = scan_id.to_string();
+    std::thread::spawn(move || {
+        let Ok(scan) = app.state::<ScanStore>().get(&scan_id) else {
+            return;
+        };
+        let root = scan.summary.root_path.clone();
+        let opened = match open(&app, &root) {
+            Ok(Some(opened)) => opened,
+            Ok(None) => return,
+            Err(error) => {
+                tracing::warn!(root = %root, error = %error, "failed to open baseline");
+                return;
+            }
+        };
+        let report = match scan.complete() {
+            Ok(scan) => report(&opened.0, &opened.1, &scan),
+            Err(error) => {
+                tracing::warn!(root = %root, error = %error, "failed to compare with baseline");
+                return;
+            }
+        };
+        if let Ok(mut reports) = app.state::<BaselineReports>().reports.lock() {
+            reports.insert(root.clone(), report);
+        }
+        let _ = app.emit_to(
+            label.as_str(),
+            BASELINE_CHANGES_EVENT,
+            BaselineChangesPayload {
+                root_path: root,
+                scan_id,
+            },
+        );
+    });
+}
+
+/// Marks the stored scan `scan_id` as the baseline of its root, replacing any earlier
+/// one. Later scans of the root are compared against it.
+pub async fn mark_baseline(app: tauri::AppHandle, scan_id: String) -> Result<Baseline, String> {
+    let scan = app.state::<ScanStore>().get(&scan_id)?;
+    if scan.summary.partial {
+        return Err("This scan was stopped early; mark a complete scan as the baseline.".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let scan = scan.complete()?;
+        let dir = baseline_dir(&app)?;
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.to_string_lossy(), e))?;
+        let root = scan.summary.root_path.clone();
+        let key = blake3::hash(root.as_bytes()).to_hex();
+        let file = format!("{}.dcsnap", &key[..32]);
+        let dest = dir.join(&file);
+        // A failed write must not leave the previous baseline half overwritten.
+        let tmp = dest.with_extension("dcsnap.tmp");
+        crate::export::create_dest(&tmp)
+            .and_then(|out| write_snapshot(&scan.summary, &scan.tree, None, out))
+            .and_then(|()| fs::rename(&tmp, &dest).map_err(|e| e.to_string()))
+            .inspect_err(|_| {
+                let _ = fs::remove_file(&tmp);
+            })
+            .map_err(|e| format!("Failed to write {}: {}", dest.to_string_lossy(), e))?;
+
+        let baseline = Baseline {
+            root_path: root.clone(),
+            scanned_at_secs: scan.summary.started_at_secs,
+            marked_at_secs: unix_secs(SystemTime::now()),
+            total_bytes: scan.summary.total_bytes,
+            file,
+        };
+        let mut baselines = load(&dir);
+        baselines.retain(|b| b.root_path != root);
+        baselines.push(baseline.clone());
+        save(&dir, &baselines)?;
+        if let Ok(mut reports) = app.state::<BaselineReports>().reports.lock() {
+            reports.remove(&root);
+        }
+        tracing::info!(root = %root, scan_id = %scan.id, "baseline marked");
+        Ok(baseline)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// The roots with a baseline.
+pub fn baselines(app: &tauri::AppHandle) -> Result<Vec<Baseline>, String> {
+    Ok(load(&baseline_dir(app)?))
+}
+
+/// Drops the baseline of `root`. Returns whether there was one.
+pub fn clear_baseline(app: &tauri::AppHandle, root: &str) -> Result<bool, String> {
+    let dir = baseline_dir(app)?;
+    let mut baselines = load(&dir);
+    let Some(at) = baselines.iter().position(|b| same_path(&b.root_path, root)) else {
+        return Ok(false);
+    };
+    let removed = baselines.remove(at);
+    save(&dir, &baselines)?;
+    let _ = fs::remove_file(dir.join(&removed.file));
+    if let Ok(mut reports) = app.state::<BaselineReports>().reports.lock() {
+        reports.remove(root);
+    }
+    Ok(true)
+}
+
+/// What changed under `root` since its baseline, going by the latest stored scan of
+/// it. `None` when the root has no baseline or no scan since.
+pub async fn changes_since_baseline(
+    app: tauri::AppHandle,
+    root: String,
+) -> Result<Option<ChangeReport>, String> {
+    let Some(scan) = app
+        .state::<ScanStore>()
+        .list()?
+        .into_iter()
+        .rev()
+        .find(|scan| !scan.summary.partial && same_path(&scan.summary.root_path, &root))
+    else {
+        return Ok(None);
+    };
+    let cached = app
+        .state::<BaselineReports>()
+        .reports
+        .lock()
+        .ok()
+        .and_then(|reports| reports.get(&root).cloned())
+        .filter(|report| report.scan_id == scan.id);
+    if cached.is_some() {
+        return Ok(cached);
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some((baseline, before)) = open(&app, &root)? else {
+            return Ok(None);
+        };
+        let scan = scan.complete()?;
+        let report = report(&baseline, &before, &scan);
+        if let Ok(mut reports) = app.state::<BaselineReports>().reports.lock() {
+            reports.insert(root, report.clone());
+        }
+        Ok(Some(report))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,297 @@
+use serde::Serialize;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+use tauri::Manager;
+
+use diskcheck_core::{
+    DirEntries, EntryMetadata, FileSystem, FsNodeKind, NoProgress, RealFs, ScanOptions,
+    DEFAULT_MIN_NODE_BYTES,
+};
+
+use crate::{
+    priority::{self, ScanMode},
+    settings::SettingsStore,
+};
+
+const DEFAULT_DURATION: Duration = Duration::from_secs(10);
+const MAX_DURATION: Duration = Duration::from_secs(120);
+
+#[cfg(windows)]
+const LISTING_METHOD: &str = "FindFirstFileExW (basic info, large fetch)";
+#[cfg(not(windows))]
+const LISTING_METHOD: &str = "readdir + fstatat per file";
+
+/// Filesystem calls made by the scanner during the run.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyscallCounts {
+    // Directories opened for listing.
+    pub directory_listings: u64,
+    // Metadata lookups by path (the root, and folder mtimes for indexed scans).
+    pub metadata_lookups: u64,
+    // Stats of listed entries; listings on Windows already carry sizes.
+    pub entry_stats: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanBenchmark {
+    pub path: String,
+    // File system of the volume, when known.
+    pub file_system: Option<String>,
+    pub mode: ScanMode,
+    pub listing_method: &'static str,
+    pub duration_ms: u64,
+    // False when the time limit ended the walk early.
+    pub completed: bool,
+    pub files: u64,
+    pub dirs: u64,
+    // Sum of the file sizes read from metadata.
+    pub bytes: u64,
+    pub files_per_sec: f64,
+    pub dirs_per_sec: f64,
+    pub metadata_mb_per_sec: f64,
+    pub syscalls: SyscallCounts,
+    // The walk runs on a single thread.
+    pub threads: u32,
+    pub cpu_ms: Option<u64>,
+    // CPU time over wall time; low values mean the walk mostly waited on the disk.
+    pub thread_utilization: Option<f64>,
+}
+
+/// The real filesystem, counting calls and stopping every listing at the deadline.
+struct CountingFs {
+    deadline: Instant,
+    expired: AtomicBool,
+    listings: AtomicU64,
+    lookups: AtomicU64,
+    entry_stats: AtomicU64,
+    files: AtomicU64,
+    dirs: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl CountingFs {
+    fn new(deadline: Instant) -> Self {
+        Self {
+            deadline,
+            expired: AtomicBool::new(false),
+            listings: AtomicU64::new(0),
+            lookups: AtomicU64::new(0),
+            entry_stats: AtomicU64::new(0),
+            files: AtomicU64::new(0),
+            dirs: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+        }
+    }
+
+    fn past_deadline(&self) -> bool {
+        if Instant::now() >= self.deadline {
+            self.expired.store(true, Ordering::Relaxed);
+            return true;
+        }
+        false
+    }
+}
+
+impl FileSystem for CountingFs {
+    fn symlink_metadata(&self, path: &Path) -> io::Result<EntryMetadata> {
+        self.lookups.fetch_add(1, Ordering::Relaxed);
+        RealFs.symlink_metadata(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<DirEntries<'_>> {
+        if self.past_deadline() {
+            return Ok(Box::new(std::iter::empty()));
+        }
+        self.listings.fetch_add(1, Ordering::Relaxed);
+        let entries = RealFs.read_dir(path)?;
+        Ok(Box::new(
+            entries
+                .take_while(|_| !self.past_deadline())
+                .inspect(|entry| {
+                    let Ok(entry) = entry else { return };
+                    match entry.metadata.kind {
+                        FsNodeKind::File => {
+                            if cfg!(not(windows)) {
+                                self.entry_stats.fetch_add(1, Ordering::Relaxed);
+                            }
+                            self.files.fetch_add(1, Ordering::Relaxed);
+                            self.bytes.fetch_add(entry.metadata.len, Ordering::Relaxed);
+                        }
+                        FsNodeKind::Directory => {
+                            self.dirs.fetch_add(1, Ordering::Relaxed);
+                        }
+                        FsNodeKind::Symlink | FsNodeKind::Other => {}
+                    }
+                }),
+        ))
+    }
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::time::Duration;
+
+    /// CPU time consumed by the calling thread so far.
+    pub(super) fn thread_cpu_time() -> Option<Duration> {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: writes into the timespec above.
+        if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) } != 0 {
+            return None;
+        }
+        Some(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::time::Duration;
+    use windows_sys::Win32::{
+        Foundation::FILETIME,
+        System::Threading::{GetCurrentThread, GetThreadTimes},
+    };
+
+    fn ticks(time: &FILETIME) -> u64 {
+        (u64::from(time.dwHighDateTime) << 32) | u64::from(time.dwLowDateTime)
+    }
+
+    /// CPU time (kernel and user) consumed by the calling thread so far.
+    pub(super) fn thread_cpu_time() -> Option<Duration> {
+        let zero = FILETIME {
+            dwLowDateTime: 0,
+            dwHighDateTime: 0,
+        };
+        let (mut creation, mut exit, mut kernel, mut user) = (zero, zero, zero, zero);
+        // SAFETY: GetCurrentThread is a pseudo handle; the out pointers are locals.
+        let ok = unsafe {
+            GetThreadTimes(
+                GetCurrentThread(),
+                &mut creation,
+                &mut exit,
+                &mut kernel,
+                &mut user,
+            )
+        };
+        if ok == 0 {
+            return None;
+        }
+        // 100 ns ticks.
+        Some(Duration::from_nanos(
+            (ticks(&kernel) + ticks(&user)).saturating_mul(100),
+        ))
+    }
+}
+
+fn per_sec(count: f64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        (count / secs * 10.0).round() / 10.0
+    } else {
+        0.0
+    }
+}
+
+/// Walks `root` like a real scan for at most `duration` and measures it. The tree is
+/// thrown away. Runs on a thread of its own so its CPU time can be measured in isolation.
+pub(crate) fn benchmark_scan_blocking(
+    root: &Path,
+    duration: Duration,
+    mode: ScanMode,
+    excludes: Vec<String>,
+) -> Result<ScanBenchmark, String> {
+    let opts = if crate::volumes::is_network_path(root) {
+        ScanOptions::network(DEFAULT_MIN_NODE_BYTES)
+    } else {
+        ScanOptions::local(DEFAULT_MIN_NODE_BYTES)
+    }
+    .with_excludes(excludes);
+
+    let (fs, elapsed, cpu) = std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                if mode == ScanMode::Background {
+                    priority::lower_current_thread();
+                }
+                let cpu_before = platform::thread_cpu_time();
+                let started = Instant::now();
+                let fs = CountingFs::new(started + duration);
+                diskcheck_core::scan_with(&fs, root, &opts, &NoProgress)?;
+                let elapsed = started.elapsed();
+                let cpu = cpu_before
+                    .zip(platform::thread_cpu_time())
+                    .map(|(before, after)| after.saturating_sub(before));
+                Ok::<_, String>((fs, elapsed, cpu))
+            })
+            .join()
+            .unwrap_or_else(|_| Err("The benchmark thread panicked.".to_string()))
+    })?;
+
+    let files = fs.files.load(Ordering::Relaxed);
+    let dirs = fs.dirs.load(Ordering::Relaxed);
+    let bytes = fs.bytes.load(Ordering::Relaxed);
+    let benchmark = ScanBenchmark {
+        path: root.to_string_lossy().into_owned(),
+        file_system: crate::volumes::volume_for_path(root).map(|v| v.file_system),
+        mode,
+        listing_method: LISTING_METHOD,
+        duration_ms: elapsed.as_millis() as u64,
+        completed: !fs.expired.load(Ordering::Relaxed),
+        files,
+        dirs,
+        bytes,
+        files_per_sec: per_sec(files as f64, elapsed),
+        dirs_per_sec: per_sec(dirs as f64, elapsed),
+        metadata_mb_per_sec: per_sec(bytes as f64 / 1e6, elapsed),
+        syscalls: SyscallCounts {
+            directory_listings: fs.listings.load(Ordering::Relaxed),
+            metadata_lookups: fs.lookups.load(Ordering::Relaxed),
+            entry_stats: fs.entry_stats.load(Ordering::Relaxed),
+        },
+        threads: 1,
+        cpu_ms: cpu.map(|cpu| cpu.as_millis() as u64),
+        thread_utilization: cpu
+            .filter(|_| !elapsed.is_zero())
+            .map(|cpu| (cpu.as_secs_f64() / elapsed.as_secs_f64() * 1000.0).round() / 1000.0),
+    };
+    tracing::info!(
+        path = %root.display(),
+        files = benchmark.files,
+        dirs = benchmark.dirs,
+        duration_ms = benchmark.duration_ms,
+        completed = benchmark.completed,
+        "scan benchmark finished"
+    );
+    Ok(benchmark)
+}
+
+/// Benchmarks scanning `path` for up to `duration_secs` (10 s by default, at most 120 s)
+/// with the configured excludes.
+pub async fn benchmark_scan(
+    app: tauri::AppHandle,
+    path: String,
+    duration_secs: Option<u64>,
+    mode: ScanMode,
+) -> Result<ScanBenchmark, String> {
+    let root = PathBuf::from(path);
+    if !root.exists() {
+        return Err(format!("Path does not exist: {}", root.to_string_lossy()));
+    }
+    let duration = duration_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_DURATION)
+        .clamp(Duration::from_secs(1), MAX_DURATION);
+    let excludes = app.state::<SettingsStore>().scan().excludes;
+    tauri::async_runtime::spawn_blocking(move || {
+        benchmark_scan_blocking(&root, duration, mode, excludes)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,296 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{
+    baseline::ChangedEntry,
+    fileinfo::format_bytes,
+    priority::ScanMode,
+    scan_index::IndexOptions,
+    scanner::{scan_blocking, FsNodeKind, NodeRef, ScanTree},
+    settings::SettingsStore,
+    store::StoredScan,
+};
+
+const FOLDER_BUDGET_EXCEEDED_EVENT: &str = "folder_budget_exceeded";
+const DEFAULT_INTERVAL_SECS: u64 = 60 * 60;
+// Every check re-scans the folders in full.
+const MIN_INTERVAL_SECS: u64 = 5 * 60;
+// New content listed per alert, largest first.
+const MAX_NEW_CONTENT: usize = 20;
+// How often the worker wakes up to check whether it was stopped.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The most a folder may take up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderBudget {
+    pub path: String,
+    pub max_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetConfig {
+    pub budgets: Vec<FolderBudget>,
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+    #[serde(default = "default_notify")]
+    pub notify: bool,
+}
+
+fn default_notify() -> bool {
+    true
+}
+
+impl BudgetConfig {
+    fn interval(&self) -> Duration {
+        Duration::from_secs(
+            self.interval_secs
+                .unwrap_or(DEFAULT_INTERVAL_SECS)
+                .max(MIN_INTERVAL_SECS),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetAlert {
+    pub path: String,
+    pub max_bytes: u64,
+    pub size_bytes: u64,
+    // Files added or grown since the previous check; the largest files in the folder
+    // when it was over budget from the first check on.
+    pub new_content: Vec<ChangedEntry>,
+}
+
+/// A folder's size against its budget, as `check_folder_budgets` measured it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub path: String,
+    pub max_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct WatcherHandle {
+    config: BudgetConfig,
+    stop: Arc<AtomicBool>,
+}
+
+/// Checks the folder budgets in the background.
+#[derive(Default)]
+pub struct BudgetWatcher {
+    current: Mutex<Option<WatcherHandle>>,
+}
+
+/// Scans `path` at low priority, spilled folders loaded back. The volume index is not
+/// reused: it would keep the old size of files growing in place, such as logs.
+pub(crate) fn measure_scan(app: &tauri::AppHandle, path: &Path) -> Result<Arc<StoredScan>, String> {
+    let excludes = app.state::<SettingsStore>().scan().excludes;
+    let finished = scan_blocking(
+        path,
+        None,
+        excludes,
+        false,
+        ScanMode::Background,
+        IndexOptions::new(app, false),
+        None,
+    )?;
+    let scan = Arc::new(StoredScan {
+        id: String::new(),
+        summary: finished.summary,
+        tree: finished.tree,
+        spill: finished.spill,
+    });
+    scan.complete()
+}
+
+/// The tree of `path`, measured with `measure_scan`.
+pub(crate) fn measure(app: &tauri::AppHandle, path: &Path) -> Result<ScanTree, String> {
+    let scan = measure_scan(app, path)?;
+    Ok(Arc::try_unwrap(scan)
+        .map(|scan| scan.tree)
+        .unwrap_or_else(|scan| scan.tree.clone()))
+}
+
+/// Files in `after` that `before` lacked or held smaller.
+fn new_content(after: NodeRef<'_>, before: Option<NodeRef<'_>>, found: &mut Vec<ChangedEntry>) {
+    let size_before = before.map(|node| node.size());
+    if !matches!(after.kind(), FsNodeKind::Directory) {
+        if size_before.is_none_or(|size| size < after.size()) {
+            found.push(ChangedEntry {
+                path: after.path(),
+                size_before,
+                size_after: after.size(),
+            });
+        }
+        return;
+    }
+    let mut earlier: HashMap<&str, NodeRef<'_>> = before
+        .filter(|node| matches!(node.kind(), FsNodeKind::Directory))
+        .map(|node| node.children().map(|c| (c.name(), c)).collect())
+        .unwrap_or_default();
+    for child in after.children() {
+        new_content(child, earlier.remove(child.name()), found);
+    }
+}
+
+/// The files of `tree` that `previous` lacked or held smaller, most growth first; all
+/// files, largest first, without `previous`.
+pub(crate) fn grown_files(
+    tree: &ScanTree,
+    previous: Option<&ScanTree>,
+    limit: usize,
+) -> Vec<ChangedEntry> {
+    let mut found = Vec::new();
+    new_content(tree.root(), previous.map(|p| p.root()), &mut found);
+    found.sort_by_key(|e| {
+        std::cmp::Reverse(e.size_after.saturating_sub(e.size_before.unwrap_or(0)))
+    });
+    found.truncate(limit);
+    found
+}
+
+fn alert_for(budget: &FolderBudget, tree: &ScanTree, previous: Option<&ScanTree>) -> BudgetAlert {
+    BudgetAlert {
+        path: budget.path.clone(),
+        max_bytes: budget.max_bytes,
+        size_bytes: tree.size(),
+        new_content: grown_files(tree, previous, MAX_NEW_CONTENT),
+    }
+}
+
+fn notify_over_budget(app: &tauri::AppHandle, alert: &BudgetAlert) {
+    let _ = app
+        .notification()
+        .builder()
+        .title("Folder over budget")
+        .body(format!(
+            "{} takes up {}, over its budget of {}.",
+            alert.path,
+            format_bytes(alert.size_bytes),
+            format_bytes(alert.max_bytes)
+        ))
+        .show();
+}
+
+fn run_watcher(app: tauri::AppHandle, config: BudgetConfig, stop: Arc<AtomicBool>) {
+    // Folders currently over budget; alerted once per crossing, not every check.
+    let mut over: HashSet<String> = HashSet::new();
+    // Each folder's tree at the previous check, to tell what is new.
+    let mut previous: HashMap<String, ScanTree> = HashMap::new();
+    let mut next_check = Instant::now();
+
+    while !stop.load(Ordering::Relaxed) {
+        if Instant::now() < next_check {
+            thread::sleep(STOP_POLL_INTERVAL);
+            continue;
+        }
+        next_check = Instant::now() + config.interval();
+
+        for budget in &config.budgets {
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+            let tree = match measure(&app, Path::new(&budget.path)) {
+                Ok(tree) => tree,
+                // Missing folders (say on an unplugged drive) are skipped until they return.
+                Err(error) => {
+                    tracing::debug!(path = %budget.path, error = %error, "budget check skipped");
+                    continue;
+                }
+            };
+            if tree.size() <= budget.max_bytes {
+                over.remove(&budget.path);
+            } else if over.insert(budget.path.clone()) {
+                let alert = alert_for(budget, &tree, previous.get(&budget.path));
+                tracing::warn!(
+                    path = %alert.path,
+                    size_bytes = alert.size_bytes,
+                    max_bytes = alert.max_bytes,
+                    "folder over budget"
+                );
+                let _ = app.emit(FOLDER_BUDGET_EXCEEDED_EVENT, &alert);
+                if config.notify {
+                    notify_over_budget(&app, &alert);
+                }
+            }
+            previous.insert(budget.path.clone(), tree);
+        }
+    }
+}
+
+impl BudgetWatcher {
+    pub fn start(&self, app: tauri::AppHandle, config: BudgetConfig) -> Result<(), String> {
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = current.take() {
+            previous.stop.store(true, Ordering::Relaxed);
+        }
+        if config.budgets.is_empty() {
+            return Ok(());
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let worker_stop = stop.clone();
+        let worker_config = config.clone();
+        thread::Builder::new()
+            .name("budget-watcher".to_string())
+            .spawn(move || run_watcher(app, worker_config, worker_stop))
+            .map_err(|e| format!("Failed to start the folder budget checks: {}", e))?;
+
+        *current = Some(WatcherHandle { config, stop });
+        Ok(())
+    }
+
+    pub fn stop(&self) -> Result<(), String> {
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = current.take() {
+            previous.stop.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    pub fn config(&self) -> Result<Option<BudgetConfig>, String> {
+        let current = self.current.lock().map_err(|e| e.to_string())?;
+        Ok(current.as_ref().map(|h| h.config.clone()))
+    }
+}
+
+/// Measures every budgeted folder now, without raising alerts.
+pub async fn check_folder_budgets(app: tauri::AppHandle) -> Result<Vec<BudgetStatus>, String> {
+    let budgets = app
+        .state::<BudgetWatcher>()
+        .config()?
+        .map(|config| config.budgets)
+        .unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        budgets
+            .into_iter()
+            .map(|budget| {
+                let measured = measure(&app, Path::new(&budget.path));
+                BudgetStatus {
+                    size_bytes: measured.as_ref().ok().map(|tree| tree.size()),
+                    error: measured.err(),
+                    path: budget.path,
+                    max_bytes: budget.max_bytes,
+                }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
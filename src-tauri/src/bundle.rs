@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, Read, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    export::{create_dest, write_report_to},
+    report::{render_html, ReportKind},
+    scanner::{pruned_view, ScanResult, ScanTree},
+    snapshot::{read_snapshot, write_snapshot},
+    store::{unix_secs, ScanStore, ScanSummary, StoredScan},
+};
+
+// A `.diskcheck` bundle is a zip archive with these entries.
+const BUNDLE_FORMAT: &str = "diskcheck-bundle";
+const BUNDLE_VERSION: u32 = 1;
+const METADATA_ENTRY: &str = "metadata.json";
+const SNAPSHOT_ENTRY: &str = "snapshot.dcsnap";
+const HTML_ENTRY: &str = "report.html";
+const REPORT_ENTRIES: [(ReportKind, &str); 4] = [
+    (ReportKind::Extension, "reports/extensions.csv"),
+    (ReportKind::Category, "reports/categories.csv"),
+    (ReportKind::Owner, "reports/owners.csv"),
+    (ReportKind::Age, "reports/ages.csv"),
+];
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleMetadata {
+    format: String,
+    version: u32,
+    app_version: String,
+    created_at_secs: u64,
+    scan_id: String,
+    summary: ScanSummary,
+}
+
+fn export_blocking(scan: Arc<StoredScan>, dest: &Path) -> Result<(), String> {
+    let scan = scan.complete()?;
+    let mut zip = ZipWriter::new(create_dest(dest)?);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    // The snapshot is zstd-compressed already.
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    let metadata = BundleMetadata {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at_secs: unix_secs(SystemTime::now()),
+        scan_id: scan.id.clone(),
+        summary: scan.summary.clone(),
+    };
+    zip.start_file(METADATA_ENTRY, deflated)
+        .map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(&mut zip, &metadata).map_err(|e| e.to_string())?;
+
+    zip.start_file(SNAPSHOT_ENTRY, stored)
+        .map_err(|e| e.to_string())?;
+    write_snapshot(&scan.summary, &scan.tree, None, &mut zip)?;
+
+    zip.start_file(HTML_ENTRY, deflated)
+        .map_err(|e| e.to_string())?;
+    zip.write_all(render_html(&scan).as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    for (kind, name) in REPORT_ENTRIES {
+        zip.start_file(name, deflated).map_err(|e| e.to_string())?;
+        write_report_to(&scan, kind, false, &mut zip)?;
+    }
+
+    zip.finish()
+        .and_then(|mut out| out.flush().map_err(Into::into))
+        .map_err(|e| e.to_string())
+}
+
+fn open_blocking(path: &Path) -> Result<(ScanTree, ScanSummary), String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.to_string_lossy(), e))?;
+    let mut zip =
+        ZipArchive::new(BufReader::new(file)).map_err(|_| "Not a DiskCheck bundle.".to_string())?;
+
+    let mut metadata = String::new();
+    zip.by_name(METADATA_ENTRY)
+        .map_err(|_| "Not a DiskCheck bundle.".to_string())?
+        .read_to_string(&mut metadata)
+        .map_err(|e| e.to_string())?;
+    let metadata: BundleMetadata =
+        serde_json::from_str(&metadata).map_err(|_| "Not a DiskCheck bundle.".to_string())?;
+    if metadata.format != BUNDLE_FORMAT || metadata.version != BUNDLE_VERSION {
+        return Err(format!(
+            "Unsupported bundle version {} (this build reads version {}).",
+            metadata.version, BUNDLE_VERSION
+        ));
+    }
+
+    let snapshot = zip
+        .by_name(SNAPSHOT_ENTRY)
+        .map_err(|_| "The bundle does not contain a snapshot.".to_string())?;
+    read_snapshot(snapshot).map(|(tree, summary, _)| (tree, summary))
+}
+
+/// Packs a stored scan into a single `.diskcheck` file: snapshot, HTML and CSV reports,
+/// and metadata. Meant to be attached to support requests.
+pub async fn export_bundle(store: &ScanStore, scan_id: String, dest: String) -> Result<(), String> {
+    let scan = store.get(&scan_id)?;
+    let dest = PathBuf::from(dest);
+    tauri::async_runtime::spawn_blocking(move || {
+        export_blocking(scan, &dest)
+            .map_err(|e| format!("Failed to write {}: {}", dest.to_string_lossy(), e))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Loads the snapshot of a `.diskcheck` bundle into the result store.
+pub async fn open_bundle(store: &ScanStore, path: String) -> Result<ScanResult, String> {
+    let path = PathBuf::from(path);
+    let (tree, summary) = tauri::async_runtime::spawn_blocking(move || open_blocking(&path))
+        .await
+        .map_err(|err| err.to_string())??;
+
+    let root = pruned_view(tree.root(), None);
+    let partial = summary.partial;
+    let scan_id = store.insert(tree, summary)?;
+    Ok(ScanResult {
+        scan_id,
+        root,
+        skipped: vec![],
+        usage_check: None,
+        denied_dirs: vec![],
+        partial,
+    })
+}
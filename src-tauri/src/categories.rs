@@ -0,0 +1,84 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileCategory {
+    Installer,
+    Archive,
+    Document,
+    Image,
+    Video,
+    Audio,
+    Code,
+    Other,
+}
+
+impl FileCategory {
+    /// Same as the serialized name.
+    pub fn key(self) -> &'static str {
+        match self {
+            FileCategory::Installer => "installer",
+            FileCategory::Archive => "archive",
+            FileCategory::Document => "document",
+            FileCategory::Image => "image",
+            FileCategory::Video => "video",
+            FileCategory::Audio => "audio",
+            FileCategory::Code => "code",
+            FileCategory::Other => "other",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FileCategory::Installer => "Installers & disk images",
+            FileCategory::Archive => "Archives",
+            FileCategory::Document => "Documents",
+            FileCategory::Image => "Images",
+            FileCategory::Video => "Video",
+            FileCategory::Audio => "Audio",
+            FileCategory::Code => "Code",
+            FileCategory::Other => "Other",
+        }
+    }
+}
+
+pub(crate) const ARCHIVE_EXTENSIONS: &[&str] =
+    &["zip", "7z", "rar", "tar", "gz", "tgz", "bz2", "xz", "zst"];
+
+/// Classifies a lower-case extension (without the dot) into a coarse category.
+pub fn category_for_extension(ext: Option<&str>) -> FileCategory {
+    let ext = match ext {
+        Some(ext) => ext,
+        None => return FileCategory::Other,
+    };
+
+    if crate::installers::is_installer_extension(ext)
+        || matches!(
+            ext,
+            "pkg" | "deb" | "rpm" | "appimage" | "vhd" | "vhdx" | "vmdk"
+        )
+    {
+        return FileCategory::Installer;
+    }
+    if ARCHIVE_EXTENSIONS.contains(&ext) {
+        return FileCategory::Archive;
+    }
+
+    match ext {
+        "pdf" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "odt" | "ods" | "odp"
+        | "txt" | "rtf" | "md" | "csv" | "epub" => FileCategory::Document,
+        "jpg" | "jpeg" | "png" | "gif" | "bmp" | "tif" | "tiff" | "webp" | "heic" | "heif"
+        | "raw" | "cr2" | "nef" | "arw" | "dng" | "svg" | "psd" => FileCategory::Image,
+        "mp4" | "mkv" | "mov" | "avi" | "wmv" | "webm" | "m4v" | "flv" | "mpg" | "mpeg" => {
+            FileCategory::Video
+        }
+        "mp3" | "flac" | "wav" | "aac" | "m4a" | "ogg" | "opus" | "wma" | "aiff" => {
+            FileCategory::Audio
+        }
+        "rs" | "js" | "ts" | "tsx" | "jsx" | "py" | "java" | "c" | "cpp" | "h" | "hpp" | "cs"
+        | "go" | "rb" | "php" | "json" | "toml" | "yaml" | "yml" | "html" | "css" => {
+            FileCategory::Code
+        }
+        _ => FileCategory::Other,
+    }
+}
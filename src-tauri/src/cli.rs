@@ -0,0 +1,215 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::{
+    agent, elevated, priority::ScanMode, scanner::scan_blocking, store::ScanSummary,
+    volumes::space_for_path,
+};
+
+// Exit codes for scripted use (cron jobs, CI runners).
+const EXIT_OK: i32 = 0;
+const EXIT_THRESHOLD_EXCEEDED: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+
+const USAGE: &str = "\
+Usage: diskcheck --headless [PATH] [OPTIONS]
+
+Checks PATH (default: the current directory) and prints a JSON summary.
+
+Options:
+  --fail-if-used-over <PERCENT>   Exit 1 if the volume containing PATH is fuller than this (e.g. 90%)
+  --fail-if-dir-over <SIZE>       Exit 1 if PATH is larger than this (e.g. 50G, 500M, 1.5T)
+  --background                    Scan at the lowest CPU and I/O priority
+  -h, --help                      Show this help
+
+Exit codes: 0 = all checks passed, 1 = a threshold was exceeded, 2 = usage or scan error.";
+
+#[derive(Debug, Default)]
+struct CliOptions {
+    path: Option<PathBuf>,
+    max_used_percent: Option<f64>,
+    max_dir_bytes: Option<u64>,
+    mode: ScanMode,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckResult {
+    name: &'static str,
+    threshold: f64,
+    actual: f64,
+    passed: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CliSummary {
+    path: String,
+    passed: bool,
+    checks: Vec<CheckResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scan: Option<ScanSummary>,
+}
+
+fn parse_percent(text: &str) -> Option<f64> {
+    let value: f64 = text.trim().trim_end_matches('%').parse().ok()?;
+    (0.0..=100.0).contains(&value).then_some(value)
+}
+
+/// Parses sizes like `50G`, `500MB`, `1.5T` or plain bytes (binary units).
+pub(crate) fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        _ => return None,
+    };
+    let value: f64 = number.trim().parse().ok()?;
+    (value >= 0.0).then_some((value * multiplier as f64) as u64)
+}
+
+fn parse_args(args: &[String]) -> Result<CliOptions, String> {
+    let mut options = CliOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |flag: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value.", flag))
+        };
+        match arg.as_str() {
+            "--headless" => {}
+            "--background" => options.mode = ScanMode::Background,
+            "--fail-if-used-over" => {
+                let text = value(arg)?;
+                options.max_used_percent = Some(
+                    parse_percent(&text).ok_or_else(|| format!("Invalid percentage: {}", text))?,
+                );
+            }
+            "--fail-if-dir-over" => {
+                let text = value(arg)?;
+                options.max_dir_bytes =
+                    Some(parse_size(&text).ok_or_else(|| format!("Invalid size: {}", text))?);
+            }
+            flag if flag.starts_with('-') => return Err(format!("Unknown option: {}", flag)),
+            path if options.path.is_none() => options.path = Some(PathBuf::from(path)),
+            extra => return Err(format!("Unexpected argument: {}", extra)),
+        }
+    }
+    Ok(options)
+}
+
+fn run_checks(options: CliOptions) -> Result<CliSummary, String> {
+    let path = match options.path {
+        Some(path) => path,
+        None => std::env::current_dir().map_err(|e| e.to_string())?,
+    };
+    if !path.exists() {
+        return Err(format!("Path does not exist: {}", path.to_string_lossy()));
+    }
+
+    let mut checks = vec![];
+    if let Some(max_percent) = options.max_used_percent {
+        let space = space_for_path(&path)?;
+        let used_percent = if space.total_bytes == 0 {
+            0.0
+        } else {
+            space.used_bytes() as f64 * 100.0 / space.total_bytes as f64
+        };
+        checks.push(CheckResult {
+            name: "volumeUsedPercent",
+            threshold: max_percent,
+            actual: (used_percent * 10.0).round() / 10.0,
+            passed: used_percent <= max_percent,
+        });
+    }
+
+    let mut scan = None;
+    if let Some(max_bytes) = options.max_dir_bytes {
+        let summary = scan_blocking(&path, None, vec![], false, options.mode, None, None)?.summary;
+        checks.push(CheckResult {
+            name: "dirBytes",
+            threshold: max_bytes as f64,
+            actual: summary.total_bytes as f64,
+            passed: summary.total_bytes <= max_bytes,
+        });
+        scan = Some(summary);
+    }
+
+    Ok(CliSummary {
+        path: path.to_string_lossy().into_owned(),
+        passed: checks.iter().all(|c| c.passed),
+        checks,
+        scan,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // Release builds use the GUI subsystem; borrow the caller's console for output.
+    // SAFETY: plain Win32 call without pointers.
+    unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
+
+/// Runs headless when the command line asks for it and returns the process exit code;
+/// returns `None` to start the GUI as usual.
+pub fn run_cli() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some(elevated::HELPER_FLAG) {
+        return Some(elevated::run_helper(&args[1..]));
+    }
+    if args.first().map(String::as_str) == Some(agent::AGENT_FLAG) {
+        attach_console();
+        return Some(agent::run_agent(&args));
+    }
+    #[cfg(target_os = "macos")]
+    if std::env::var_os(elevated::ASKPASS_ENV).is_some() {
+        return Some(elevated::ask_password());
+    }
+    let headless = args.iter().any(|a| {
+        matches!(
+            a.as_str(),
+            "--headless" | "--fail-if-used-over" | "--fail-if-dir-over" | "-h" | "--help"
+        )
+    });
+    if !headless {
+        return None;
+    }
+    attach_console();
+
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return Some(EXIT_OK);
+    }
+    let summary = match parse_args(&args).and_then(run_checks) {
+        Ok(summary) => summary,
+        Err(err) => {
+            eprintln!("diskcheck: {}\n\n{}", err, USAGE);
+            return Some(EXIT_USAGE);
+        }
+    };
+
+    match serde_json::to_string_pretty(&summary) {
+        Ok(json) => println!("{}", json),
+        Err(err) => {
+            eprintln!("diskcheck: {}", err);
+            return Some(EXIT_USAGE);
+        }
+    }
+    Some(if summary.passed {
+        EXIT_OK
+    } else {
+        EXIT_THRESHOLD_EXCEEDED
+    })
+}
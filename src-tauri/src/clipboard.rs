@@ -0,0 +1,181 @@
+use serde::Deserialize;
+use std::{
+    io::{ErrorKind, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::fileinfo::format_bytes;
+
+/// How `copy_path` writes a path.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PathFormat {
+    Plain,
+    // Quoted for the platform's shell.
+    Shell,
+    Uri,
+}
+
+/// A selected node, as the frontend knows it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectedItem {
+    pub path: String,
+    pub size_bytes: u64,
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    /// `clip` reads UTF-16 when the input starts with a byte order mark, and the ANSI
+    /// code page otherwise.
+    pub(super) fn encode(text: &str) -> Vec<u8> {
+        std::iter::once(0xFEFF)
+            .chain(text.encode_utf16())
+            .flat_map(u16::to_le_bytes)
+            .collect()
+    }
+
+    pub(super) const TOOLS: &[&[&str]] = &[&["clip.exe"]];
+
+    /// cmd and PowerShell both take a double-quoted path; Windows paths cannot hold `"`.
+    pub(super) fn shell_quote(path: &str) -> String {
+        format!("\"{}\"", path)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    pub(super) fn encode(text: &str) -> Vec<u8> {
+        text.as_bytes().to_vec()
+    }
+
+    pub(super) const TOOLS: &[&[&str]] = &[&["pbcopy"]];
+
+    pub(super) fn shell_quote(path: &str) -> String {
+        format!("'{}'", path.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    pub(super) fn encode(text: &str) -> Vec<u8> {
+        text.as_bytes().to_vec()
+    }
+
+    // wl-copy only works under Wayland, and xclip and xsel only under X11 (or
+    // XWayland); the first that is installed and works wins.
+    pub(super) const TOOLS: &[&[&str]] = &[
+        &["wl-copy"],
+        &["xclip", "-selection", "clipboard"],
+        &["xsel", "--clipboard", "--input"],
+    ];
+
+    pub(super) fn shell_quote(path: &str) -> String {
+        format!("'{}'", path.replace('\'', "'\\''"))
+    }
+}
+
+/// A `file://` URI for the absolute `path`, with everything but the unreserved
+/// characters and separators percent-encoded.
+pub(crate) fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let (prefix, path) = if cfg!(windows) {
+        // `C:\x` becomes `file:///C:/x`, and `\\server\share` `file://server/share`.
+        match path.strip_prefix(r"\\") {
+            Some(unc) => ("file://", unc.replace('\\', "/")),
+            None => ("file:///", path.replace('\\', "/")),
+        }
+    } else {
+        ("file://", path.into_owned())
+    };
+    let mut uri = String::from(prefix);
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric()
+            || b"-_.~/".contains(&byte)
+            || (cfg!(windows) && byte == b':')
+        {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+/// Pipes `text` into the platform's clipboard tool.
+fn copy_text(text: &str) -> Result<(), String> {
+    let input = platform::encode(text);
+    let mut last_error = None;
+    for tool in platform::TOOLS {
+        let mut command = Command::new(tool[0]);
+        command
+            .args(&tool[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        // pbcopy assumes the legacy Mac encoding without a UTF-8 locale.
+        if cfg!(target_os = "macos") {
+            command.env("LC_CTYPE", "UTF-8");
+        }
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to run {}: {}", tool[0], e)),
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(&input)
+                .map_err(|e| format!("Failed to write to {}: {}", tool[0], e))?;
+        }
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        if output.status.success() {
+            return Ok(());
+        }
+        // Most likely the wrong display server; try the next tool.
+        last_error = Some(format!(
+            "{} failed: {}",
+            tool[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Err(last_error.unwrap_or_else(|| {
+        "No clipboard program was found; install wl-clipboard or xclip.".to_string()
+    }))
+}
+
+/// Copies `path` to the clipboard, as is, quoted for the shell or as a `file://` URI.
+pub fn copy_path(path: &str, format: PathFormat) -> Result<(), String> {
+    let text = match format {
+        PathFormat::Plain => path.to_string(),
+        PathFormat::Shell => platform::shell_quote(path),
+        PathFormat::Uri => file_uri(Path::new(path)),
+    };
+    copy_text(&text)
+}
+
+/// Copies the selection as tab-separated path, size and bytes, with a total row, for
+/// pasting into a ticket or spreadsheet. Tabs and line breaks in names become spaces.
+pub fn copy_selection(items: &[SelectedItem]) -> Result<(), String> {
+    if items.is_empty() {
+        return Err("Nothing is selected.".to_string());
+    }
+    let mut text = String::from("Path\tSize\tBytes\n");
+    for item in items {
+        let path = item.path.replace(['\t', '\r', '\n'], " ");
+        text.push_str(&format!(
+            "{}\t{}\t{}\n",
+            path,
+            format_bytes(item.size_bytes),
+            item.size_bytes
+        ));
+    }
+    let total: u64 = items.iter().map(|item| item.size_bytes).sum();
+    text.push_str(&format!(
+        "Total ({} items)\t{}\t{}\n",
+        items.len(),
+        format_bytes(total),
+        total
+    ));
+    copy_text(&text)
+}
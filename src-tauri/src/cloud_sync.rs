@@ -0,0 +1,357 @@
+use serde::Serialize;
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+use tauri::Manager;
+
+use crate::scanner::walk_files;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CloudProvider {
+    OneDrive,
+    Dropbox,
+    #[cfg_attr(all(unix, not(target_os = "macos")), allow(dead_code))]
+    ICloud,
+    // A folder passed in that is not under a known sync folder.
+    Other,
+}
+
+/// How much of a sync folder is on this machine.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudSyncReport {
+    pub provider: CloudProvider,
+    pub path: String,
+    // On disk and uploaded: what the sync client's "free up space" would reclaim.
+    pub local_files: u64,
+    pub local_bytes: u64,
+    // Placeholders whose contents are only in the cloud, at their full size.
+    pub cloud_only_files: u64,
+    pub cloud_only_bytes: u64,
+    // On disk but not uploaded yet, so not freeable. Absent where the platform does not
+    // tell; such files count as local.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_upload_files: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_upload_bytes: Option<u64>,
+    pub skipped_entries: u64,
+}
+
+/// Where a file's contents are.
+#[cfg_attr(all(unix, not(target_os = "macos")), allow(dead_code))]
+enum SyncState {
+    Local,
+    // With the size of the contents left in the cloud.
+    CloudOnly(u64),
+    PendingUpload,
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{CloudProvider, SyncState};
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::{
+        fs,
+        os::windows::fs::MetadataExt,
+        path::{Path, PathBuf},
+    };
+    use windows_sys::Win32::Storage::FileSystem::{
+        FILE_ATTRIBUTE_OFFLINE, FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS,
+        FILE_ATTRIBUTE_RECALL_ON_OPEN, FILE_ATTRIBUTE_REPARSE_POINT, FILE_FLAG_OPEN_NO_RECALL,
+    };
+
+    pub(super) const KNOWS_PENDING: bool = true;
+
+    /// OneDrive, Dropbox and iCloud all sync through the Cloud Files API: every synced
+    /// file is a placeholder (a reparse point), and one whose data is not on disk is
+    /// marked for recall. A file the client has not turned into a placeholder yet has
+    /// not been uploaded.
+    pub(super) fn sync_state(_path: &Path, meta: &fs::Metadata) -> SyncState {
+        if is_dataless(meta) {
+            SyncState::CloudOnly(meta.len())
+        } else if meta.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT == 0 {
+            SyncState::PendingUpload
+        } else {
+            SyncState::Local
+        }
+    }
+
+    pub(super) fn is_dataless(meta: &fs::Metadata) -> bool {
+        meta.file_attributes()
+            & (FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS
+                | FILE_ATTRIBUTE_RECALL_ON_OPEN
+                | FILE_ATTRIBUTE_OFFLINE)
+            != 0
+    }
+
+    /// Leaves the data of a file that turns into a placeholder after the check in the
+    /// cloud: reads of it fail instead of recalling it.
+    pub(super) fn no_recall(options: &mut fs::OpenOptions) {
+        options.custom_flags(FILE_FLAG_OPEN_NO_RECALL);
+    }
+
+    pub(super) fn sync_roots(home: &Path) -> Vec<(CloudProvider, PathBuf)> {
+        let mut roots: Vec<(CloudProvider, PathBuf)> =
+            ["OneDriveConsumer", "OneDriveCommercial", "OneDrive"]
+                .iter()
+                .filter_map(|var| std::env::var_os(var))
+                .map(|path| (CloudProvider::OneDrive, PathBuf::from(path)))
+                .collect();
+        for var in ["LOCALAPPDATA", "APPDATA"] {
+            if let Some(dir) = std::env::var_os(var) {
+                roots.extend(super::dropbox_roots(&PathBuf::from(dir).join("Dropbox")));
+            }
+        }
+        roots.push((CloudProvider::ICloud, home.join("iCloudDrive")));
+        roots
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{CloudProvider, SyncState};
+    use std::{
+        fs,
+        os::macos::fs::MetadataExt,
+        path::{Path, PathBuf},
+    };
+
+    // From <sys/stat.h>: the file's contents are not on disk and are fetched on access.
+    const SF_DATALESS: u32 = 0x4000_0000;
+    const ICLOUD_STUB_EXTENSION: &str = "icloud";
+
+    // Uploads are only reported through Foundation's ubiquity keys.
+    pub(super) const KNOWS_PENDING: bool = false;
+
+    /// The size recorded in an iCloud stub, the `.<name>.icloud` file older macOS
+    /// versions leave in place of an evicted file.
+    fn stub_size(path: &Path) -> Option<u64> {
+        let stub = plist::Value::from_file(path).ok()?;
+        stub.as_dictionary()?
+            .get("NSURLFileSizeKey")?
+            .as_unsigned_integer()
+    }
+
+    pub(super) fn is_dataless(meta: &fs::Metadata) -> bool {
+        meta.st_flags() & SF_DATALESS != 0
+    }
+
+    /// No open flag keeps macOS from materializing a dataless file; the caller checks
+    /// the flag first.
+    pub(super) fn no_recall(_options: &mut fs::OpenOptions) {}
+
+    pub(super) fn sync_state(path: &Path, meta: &fs::Metadata) -> SyncState {
+        if is_dataless(meta) {
+            return SyncState::CloudOnly(meta.len());
+        }
+        let is_stub = path
+            .extension()
+            .is_some_and(|ext| ext == ICLOUD_STUB_EXTENSION)
+            && path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        match is_stub.then(|| stub_size(path)).flatten() {
+            Some(size) => SyncState::CloudOnly(size),
+            None => SyncState::Local,
+        }
+    }
+
+    pub(super) fn sync_roots(home: &Path) -> Vec<(CloudProvider, PathBuf)> {
+        let mut roots = vec![(CloudProvider::ICloud, home.join("Library/Mobile Documents"))];
+        // File Provider clients keep their folders here.
+        if let Ok(entries) = fs::read_dir(home.join("Library/CloudStorage")) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if name.starts_with("OneDrive") {
+                    roots.push((CloudProvider::OneDrive, entry.path()));
+                } else if name.starts_with("Dropbox") {
+                    roots.push((CloudProvider::Dropbox, entry.path()));
+                }
+            }
+        }
+        roots.push((CloudProvider::OneDrive, home.join("OneDrive")));
+        roots.extend(super::dropbox_roots(&home.join(".dropbox")));
+        roots
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use super::{CloudProvider, SyncState};
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    // The Linux clients keep full copies and leave no trace of what is uploaded.
+    pub(super) const KNOWS_PENDING: bool = false;
+
+    pub(super) fn sync_state(_path: &Path, _meta: &fs::Metadata) -> SyncState {
+        SyncState::Local
+    }
+
+    pub(super) fn is_dataless(_meta: &fs::Metadata) -> bool {
+        false
+    }
+
+    pub(super) fn no_recall(_options: &mut fs::OpenOptions) {}
+
+    pub(super) fn sync_roots(home: &Path) -> Vec<(CloudProvider, PathBuf)> {
+        let mut roots = vec![(CloudProvider::OneDrive, home.join("OneDrive"))];
+        roots.extend(super::dropbox_roots(&home.join(".dropbox")));
+        roots
+    }
+}
+
+/// Whether the file `meta` describes is a placeholder whose contents are only in the
+/// cloud, so reading it would download it.
+pub(crate) fn is_cloud_only(meta: &fs::Metadata) -> bool {
+    platform::is_dataless(meta)
+}
+
+/// Opens `path` for reading unless its contents are only in the cloud. Analysis
+/// (hashing, previews, searches) opens files through this, so it never downloads them.
+pub(crate) fn open_local(path: &Path) -> io::Result<File> {
+    open_checked(path, is_cloud_only)
+}
+
+fn open_checked(path: &Path, cloud_only: impl Fn(&fs::Metadata) -> bool) -> io::Result<File> {
+    if cloud_only(&fs::metadata(path)?) {
+        return Err(io::Error::other(format!(
+            "{} is only in the cloud; reading it would download it.",
+            path.to_string_lossy()
+        )));
+    }
+    let mut options = fs::OpenOptions::new();
+    options.read(true);
+    platform::no_recall(&mut options);
+    options.open(path)
+}
+
+/// Reads all of `path` like [`open_local`].
+pub(crate) fn read_local(path: &Path) -> io::Result<Vec<u8>> {
+    let mut data = vec![];
+    open_local(path)?.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// The personal and business folders listed in Dropbox's `info.json` in `config_dir`.
+fn dropbox_roots(config_dir: &Path) -> Vec<(CloudProvider, PathBuf)> {
+    let info = std::fs::read(config_dir.join("info.json"))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok());
+    let Some(accounts) = info.as_ref().and_then(|info| info.as_object()) else {
+        return vec![];
+    };
+    accounts
+        .values()
+        .filter_map(|account| account.get("path")?.as_str())
+        .map(|path| (CloudProvider::Dropbox, PathBuf::from(path)))
+        .collect()
+}
+
+/// The sync folders of this machine that exist, without duplicates.
+pub(crate) fn sync_folders(app: &tauri::AppHandle) -> Vec<(CloudProvider, PathBuf)> {
+    let Ok(home) = app.path().home_dir() else {
+        return vec![];
+    };
+    let mut folders: Vec<(CloudProvider, PathBuf)> = vec![];
+    for (provider, path) in platform::sync_roots(&home) {
+        if path.is_dir() && !folders.iter().any(|(_, known)| *known == path) {
+            folders.push((provider, path));
+        }
+    }
+    folders
+}
+
+fn report_blocking(provider: CloudProvider, root: &Path) -> CloudSyncReport {
+    let mut report = CloudSyncReport {
+        provider,
+        path: root.to_string_lossy().into_owned(),
+        local_files: 0,
+        local_bytes: 0,
+        cloud_only_files: 0,
+        cloud_only_bytes: 0,
+        pending_upload_files: platform::KNOWS_PENDING.then_some(0),
+        pending_upload_bytes: platform::KNOWS_PENDING.then_some(0),
+        skipped_entries: 0,
+    };
+    report.skipped_entries =
+        walk_files(root, |path, meta| match platform::sync_state(path, meta) {
+            SyncState::Local => {
+                report.local_files += 1;
+                report.local_bytes = report.local_bytes.saturating_add(meta.len());
+            }
+            SyncState::CloudOnly(size) => {
+                report.cloud_only_files += 1;
+                report.cloud_only_bytes = report.cloud_only_bytes.saturating_add(size);
+            }
+            SyncState::PendingUpload => {
+                if let Some(files) = report.pending_upload_files.as_mut() {
+                    *files += 1;
+                }
+                if let Some(bytes) = report.pending_upload_bytes.as_mut() {
+                    *bytes = bytes.saturating_add(meta.len());
+                }
+            }
+        });
+    report
+}
+
+/// How much of each OneDrive, Dropbox and iCloud folder is on disk, only in the cloud,
+/// or waiting to upload; of `path` alone when given. Placeholders are read from their
+/// attributes, never downloaded.
+pub async fn cloud_sync_report(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<Vec<CloudSyncReport>, String> {
+    let folders = sync_folders(&app);
+    let targets = match path {
+        Some(path) => {
+            let path = PathBuf::from(path);
+            if !path.is_dir() {
+                return Err(format!("Not a directory: {}", path.to_string_lossy()));
+            }
+            let provider = folders
+                .iter()
+                .find(|(_, root)| path.starts_with(root))
+                .map_or(CloudProvider::Other, |(provider, _)| *provider);
+            vec![(provider, path)]
+        }
+        None => folders,
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        targets
+            .into_iter()
+            .map(|(provider, root)| report_blocking(provider, &root))
+            .collect()
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_checked_refuses_dataless_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("placeholder.bin");
+        fs::write(&path, b"contents").unwrap();
+
+        let err = open_checked(&path, |_| true).unwrap_err();
+        assert!(err.to_string().contains("only in the cloud"));
+        assert!(open_checked(&path, |_| false).is_ok());
+    }
+
+    #[test]
+    fn open_checked_reports_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = open_checked(&dir.path().join("missing"), |_| true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
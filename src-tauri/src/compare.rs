@@ -0,0 +1,335 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path, sync::Arc};
+use tauri::Manager;
+
+use crate::{
+    priority::ScanMode,
+    scan_history::{annotations_of, ScanAnnotation},
+    scanner::{self, FsNodeKind, NodeRef},
+    store::{ScanStore, StoredScan},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffStatus {
+    Same,
+    OnlyInA,
+    OnlyInB,
+    // Present on both sides with a different size or kind, or (for folders) different
+    // contents.
+    Changed,
+}
+
+/// A node of the merged tree. Only differences are listed: unchanged entries are left
+/// out, and a folder present on one side only is not expanded.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffNode {
+    pub name: String,
+    // Relative to both compared folders; empty for the root.
+    pub path: String,
+    pub kind: FsNodeKind,
+    pub status: DiffStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_a: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_b: Option<u64>,
+    // Largest difference first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<DiffNode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comparison {
+    pub a_scan_id: String,
+    pub b_scan_id: String,
+    // Notes and tags users gave either scan in the history.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub a_annotation: Option<ScanAnnotation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub b_annotation: Option<ScanAnnotation>,
+    pub root: DiffNode,
+    pub only_in_a_bytes: u64,
+    pub only_in_b_bytes: u64,
+    // Files on both sides whose sizes differ.
+    pub changed_files: u64,
+}
+
+/// One side of `compare_volumes`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum VolumeSource {
+    // A stored scan, such as one imported from another machine.
+    Scan {
+        #[serde(rename = "scanId")]
+        scan_id: String,
+    },
+    // A mounted volume or folder, read from its latest stored scan or scanned anew.
+    Path {
+        path: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeSide {
+    pub scan_id: String,
+    pub path: String,
+    pub total_bytes: u64,
+    pub scanned_at_secs: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<ScanAnnotation>,
+}
+
+/// A top-level entry of either side, with its size on each.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakdownRow {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_a: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_b: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeComparison {
+    pub a: VolumeSide,
+    pub b: VolumeSide,
+    // Aligned by name, largest difference first.
+    pub rows: Vec<BreakdownRow>,
+}
+
+#[derive(Default)]
+struct Totals {
+    only_in_a_bytes: u64,
+    only_in_b_bytes: u64,
+    changed_files: u64,
+}
+
+impl DiffNode {
+    fn difference(&self) -> u64 {
+        self.size_a.unwrap_or(0).abs_diff(self.size_b.unwrap_or(0))
+    }
+}
+
+fn is_dir(node: &NodeRef<'_>) -> bool {
+    matches!(node.kind(), FsNodeKind::Directory)
+}
+
+fn one_side(node: NodeRef<'_>, path: String, status: DiffStatus, totals: &mut Totals) -> DiffNode {
+    let size = node.size();
+    let (size_a, size_b) = match status {
+        DiffStatus::OnlyInA => {
+            totals.only_in_a_bytes += size;
+            (Some(size), None)
+        }
+        _ => {
+            totals.only_in_b_bytes += size;
+            (None, Some(size))
+        }
+    };
+    DiffNode {
+        name: node.name().to_string(),
+        path,
+        kind: node.kind(),
+        status,
+        size_a,
+        size_b,
+        children: Vec::new(),
+    }
+}
+
+/// Compares `a` and `b`, both at `path`, by name, kind and size. File contents are
+/// not read: files of the same size count as the same.
+fn diff(a: NodeRef<'_>, b: NodeRef<'_>, path: String, totals: &mut Totals) -> DiffNode {
+    let mut children = Vec::new();
+    if is_dir(&a) && is_dir(&b) {
+        let mut in_b: HashMap<&str, NodeRef<'_>> =
+            b.children().map(|child| (child.name(), child)).collect();
+        for child in a.children() {
+            let child_path = Path::new(&path)
+                .join(child.name())
+                .to_string_lossy()
+                .into_owned();
+            let node = match in_b.remove(child.name()) {
+                Some(other) => diff(child, other, child_path, totals),
+                None => one_side(child, child_path, DiffStatus::OnlyInA, totals),
+            };
+            if node.status != DiffStatus::Same {
+                children.push(node);
+            }
+        }
+        for (name, child) in in_b {
+            let child_path = Path::new(&path).join(name).to_string_lossy().into_owned();
+            children.push(one_side(child, child_path, DiffStatus::OnlyInB, totals));
+        }
+        children.sort_by_key(|child| std::cmp::Reverse(child.difference()));
+    }
+    let same = children.is_empty()
+        && a.size() == b.size()
+        && std::mem::discriminant(&a.kind()) == std::mem::discriminant(&b.kind());
+    if !same && !is_dir(&a) {
+        totals.changed_files += 1;
+    }
+    DiffNode {
+        name: a.name().to_string(),
+        path,
+        kind: a.kind(),
+        status: if same {
+            DiffStatus::Same
+        } else {
+            DiffStatus::Changed
+        },
+        size_a: Some(a.size()),
+        size_b: Some(b.size()),
+        children,
+    }
+}
+
+/// The most recent stored scan holding `path`, or a new scan of it.
+async fn find_or_scan(
+    window: &tauri::Window,
+    store: &ScanStore,
+    path: &str,
+) -> Result<Arc<StoredScan>, String> {
+    let stored = store
+        .list()?
+        .into_iter()
+        .rev()
+        .find(|scan| scan.tree.find(path).is_some());
+    if let Some(scan) = stored {
+        return store.get_for_path(&scan.id, Some(path));
+    }
+    let result = scanner::scan_directory(
+        window.clone(),
+        store,
+        path.to_string(),
+        None,
+        ScanMode::Normal,
+        false,
+    )
+    .await?;
+    store.get_for_path(&result.scan_id, Some(path))
+}
+
+/// Compares the folders `a` and `b` (say a backup and its source), reusing stored
+/// scans that cover them and scanning them otherwise. Returns what is only in `a`, only
+/// in `b`, and present in both with a different size.
+pub async fn compare_directories(
+    window: tauri::Window,
+    store: &ScanStore,
+    a: String,
+    b: String,
+) -> Result<Comparison, String> {
+    // The comparison walks both folders whole, spilled parts included.
+    let scan_a = find_or_scan(&window, store, &a).await?.complete()?;
+    let scan_b = find_or_scan(&window, store, &b).await?.complete()?;
+    let (a_scan_id, b_scan_id) = (scan_a.id.clone(), scan_b.id.clone());
+    let [a_annotation, b_annotation] =
+        annotations_of(window.app_handle(), [&scan_a.summary, &scan_b.summary]).await?;
+    let (root, totals) = tauri::async_runtime::spawn_blocking(move || {
+        let node_a = scan_a.node(Some(&a))?;
+        let node_b = scan_b.node(Some(&b))?;
+        if !is_dir(&node_a) || !is_dir(&node_b) {
+            return Err("Only folders can be compared.".to_string());
+        }
+        let mut totals = Totals::default();
+        let root = diff(node_a, node_b, String::new(), &mut totals);
+        Ok((root, totals))
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+    tracing::info!(
+        only_in_a_bytes = totals.only_in_a_bytes,
+        only_in_b_bytes = totals.only_in_b_bytes,
+        changed_files = totals.changed_files,
+        "compared folders"
+    );
+    Ok(Comparison {
+        a_scan_id,
+        b_scan_id,
+        a_annotation,
+        b_annotation,
+        root,
+        only_in_a_bytes: totals.only_in_a_bytes,
+        only_in_b_bytes: totals.only_in_b_bytes,
+        changed_files: totals.changed_files,
+    })
+}
+
+/// The scan and the path within it that `source` names.
+async fn resolve(
+    window: &tauri::Window,
+    store: &ScanStore,
+    source: VolumeSource,
+) -> Result<(Arc<StoredScan>, String), String> {
+    match source {
+        VolumeSource::Scan { scan_id } => {
+            let scan = store.get(&scan_id)?;
+            let path = scan.summary.root_path.clone();
+            Ok((scan, path))
+        }
+        VolumeSource::Path { path } => Ok((find_or_scan(window, store, &path).await?, path)),
+    }
+}
+
+fn side(
+    scan: &StoredScan,
+    node: NodeRef<'_>,
+    path: String,
+    annotation: Option<ScanAnnotation>,
+) -> VolumeSide {
+    VolumeSide {
+        scan_id: scan.id.clone(),
+        path,
+        total_bytes: node.size(),
+        scanned_at_secs: scan.summary.started_at_secs,
+        annotation,
+    }
+}
+
+/// Lines up the top-level breakdowns of two volumes, such as the same drive on two
+/// machines, to show where one uses more space than the other.
+pub async fn compare_volumes(
+    window: tauri::Window,
+    store: &ScanStore,
+    a: VolumeSource,
+    b: VolumeSource,
+) -> Result<VolumeComparison, String> {
+    let (scan_a, path_a) = resolve(&window, store, a).await?;
+    let (scan_b, path_b) = resolve(&window, store, b).await?;
+    let [annotation_a, annotation_b] =
+        annotations_of(window.app_handle(), [&scan_a.summary, &scan_b.summary]).await?;
+    let node_a = scan_a.node(Some(&path_a))?;
+    let node_b = scan_b.node(Some(&path_b))?;
+
+    let mut in_b: HashMap<&str, u64> = node_b
+        .children()
+        .map(|child| (child.name(), child.size()))
+        .collect();
+    let mut rows: Vec<BreakdownRow> = node_a
+        .children()
+        .map(|child| BreakdownRow {
+            name: child.name().to_string(),
+            size_a: Some(child.size()),
+            size_b: in_b.remove(child.name()),
+        })
+        .collect();
+    rows.extend(in_b.into_iter().map(|(name, size)| BreakdownRow {
+        name: name.to_string(),
+        size_a: None,
+        size_b: Some(size),
+    }));
+    rows.sort_by_key(|row| {
+        std::cmp::Reverse(row.size_a.unwrap_or(0).abs_diff(row.size_b.unwrap_or(0)))
+    });
+
+    Ok(VolumeComparison {
+        a: side(&scan_a, node_a, path_a, annotation_a),
+        b: side(&scan_b, node_b, path_b, annotation_b),
+        rows,
+    })
+}
@@ -0,0 +1,204 @@
+use regex::bytes::Regex;
+use serde::Serialize;
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::{cloud_sync::open_local, scanner::walk_files};
+
+// Bytes searched per file unless the caller asks otherwise.
+const DEFAULT_MAX_SIZE: u64 = 1 << 30;
+const DEFAULT_MAX_MATCHES: usize = 1_000;
+// Files whose start holds a NUL are binary and skipped, as grep does.
+const BINARY_SNIFF_BYTES: u64 = 8 * 1024;
+// Longer lines are matched and returned only up to here, so a file without line breaks
+// cannot be pulled into memory whole.
+const MAX_LINE_BYTES: usize = 64 * 1024;
+// Characters of a matching line sent back to the UI.
+const MAX_PREVIEW_CHARS: usize = 500;
+const READ_BUFFER_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentMatch {
+    pub path: String,
+    // 1-based.
+    pub line_number: u64,
+    // Of the start of the line.
+    pub byte_offset: u64,
+    // Lossily decoded and cut to 500 characters.
+    pub line: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentSearchReport {
+    pub matches: Vec<ContentMatch>,
+    pub searched_files: u64,
+    pub searched_bytes: u64,
+    // Files only searched up to `max_size` bytes.
+    pub partially_searched_files: u64,
+    pub binary_files: u64,
+    pub unreadable_files: u64,
+    // The search stopped at `max_matches`; more may follow.
+    pub limit_reached: bool,
+    pub skipped_entries: u64,
+}
+
+fn is_binary(path: &Path) -> std::io::Result<bool> {
+    let mut head = vec![];
+    open_local(path)?
+        .take(BINARY_SNIFF_BYTES)
+        .read_to_end(&mut head)?;
+    Ok(head.contains(&0))
+}
+
+/// Reads the next line into `line`, without its line break and cut at `MAX_LINE_BYTES`.
+/// Returns the bytes consumed, 0 at the end.
+fn read_line(reader: &mut impl BufRead, line: &mut Vec<u8>) -> std::io::Result<usize> {
+    line.clear();
+    let mut consumed = 0;
+    loop {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            return Ok(consumed);
+        }
+        let (end, used, done) = match available.iter().position(|b| *b == b'\n') {
+            Some(at) => (at, at + 1, true),
+            None => (available.len(), available.len(), false),
+        };
+        let room = MAX_LINE_BYTES.saturating_sub(line.len());
+        line.extend_from_slice(&available[..end.min(room)]);
+        reader.consume(used);
+        consumed += used;
+        if done {
+            return Ok(consumed);
+        }
+    }
+}
+
+fn preview(line: &[u8]) -> String {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let text = String::from_utf8_lossy(line);
+    match text.char_indices().nth(MAX_PREVIEW_CHARS) {
+        Some((cut, _)) => text[..cut].to_string(),
+        None => text.into_owned(),
+    }
+}
+
+fn search_file(
+    path: &Path,
+    pattern: &Regex,
+    max_size: u64,
+    max_matches: usize,
+    report: &mut ContentSearchReport,
+) -> std::io::Result<()> {
+    if is_binary(path)? {
+        report.binary_files += 1;
+        return Ok(());
+    }
+    let file = open_local(path)?;
+    let len = file.metadata()?.len();
+    let mut reader = BufReader::with_capacity(READ_BUFFER_BYTES, file.take(max_size));
+    let mut line = Vec::with_capacity(256);
+    let (mut line_number, mut offset) = (0u64, 0u64);
+    let name = path.to_string_lossy();
+    report.searched_files += 1;
+    report.partially_searched_files += u64::from(len > max_size);
+    loop {
+        let read = read_line(&mut reader, &mut line)?;
+        if read == 0 {
+            break;
+        }
+        line_number += 1;
+        if pattern.is_match(&line) {
+            if report.matches.len() >= max_matches {
+                report.limit_reached = true;
+                break;
+            }
+            report.matches.push(ContentMatch {
+                path: name.clone().into_owned(),
+                line_number,
+                byte_offset: offset,
+                line: preview(&line),
+            });
+        }
+        offset += read as u64;
+    }
+    report.searched_bytes = report.searched_bytes.saturating_add(offset);
+    Ok(())
+}
+
+fn search_blocking(
+    root: &Path,
+    pattern: &Regex,
+    max_size: u64,
+    max_matches: usize,
+) -> ContentSearchReport {
+    let mut report = ContentSearchReport {
+        matches: vec![],
+        searched_files: 0,
+        searched_bytes: 0,
+        partially_searched_files: 0,
+        binary_files: 0,
+        unreadable_files: 0,
+        limit_reached: false,
+        skipped_entries: 0,
+    };
+    let search = |path: &Path, report: &mut ContentSearchReport| {
+        if report.limit_reached {
+            return;
+        }
+        if let Err(err) = search_file(path, pattern, max_size, max_matches, report) {
+            tracing::debug!(path = %path.display(), error = %err, "failed to search file");
+            report.unreadable_files += 1;
+        }
+    };
+    if root.is_file() {
+        search(root, &mut report);
+        return report;
+    }
+    let mut files = vec![];
+    report.skipped_entries = walk_files(root, |path, _| files.push(path.to_path_buf()));
+    files.sort();
+    for file in files {
+        search(&file, &mut report);
+    }
+    report
+}
+
+/// Searches a file, or every text file under a folder, for lines matching the regular
+/// expression `pattern`, like grep. Each file is searched up to `max_size` bytes (1 GiB
+/// by default) and the search stops after `max_matches` lines (1000 by default), so a
+/// 10 GB log can be checked without reading all of it.
+pub async fn search_in_files(
+    root: String,
+    pattern: String,
+    max_size: Option<u64>,
+    max_matches: Option<usize>,
+) -> Result<ContentSearchReport, String> {
+    let root = PathBuf::from(root);
+    if !root.exists() {
+        return Err(format!("Path does not exist: {}", root.to_string_lossy()));
+    }
+    let pattern = Regex::new(&pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+    let max_size = max_size.unwrap_or(DEFAULT_MAX_SIZE);
+    let max_matches = max_matches.unwrap_or(DEFAULT_MAX_MATCHES);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = std::time::Instant::now();
+        let report = search_blocking(&root, &pattern, max_size, max_matches);
+        tracing::info!(
+            path = %root.display(),
+            matches = report.matches.len(),
+            searched_files = report.searched_files,
+            searched_bytes = report.searched_bytes,
+            duration_ms = started.elapsed().as_millis() as u64,
+            "content search finished"
+        );
+        report
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+
+// Name the entries are filed under in the OS keychain.
+const SERVICE: &str = "DiskCheck";
+
+/// A user name and password, kept in the OS keychain: Credential Manager on Windows, the
+/// login keychain on macOS and the Secret Service (through `secret-tool`) elsewhere.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Credential {
+    // `DOMAIN\user` where the server wants a domain.
+    pub username: String,
+    pub password: String,
+}
+
+/// The credential saved for `account`, if any. Keychain failures count as none.
+pub(crate) fn load(account: &str) -> Option<Credential> {
+    let secret = match platform::read(account) {
+        Ok(secret) => secret?,
+        Err(error) => {
+            tracing::warn!(account = %account, error = %error, "failed to read credential");
+            return None;
+        }
+    };
+    serde_json::from_str(&secret).ok()
+}
+
+/// Saves `credential` for `account`, replacing any saved before.
+pub(crate) fn save(account: &str, credential: &Credential) -> Result<(), String> {
+    let secret = serde_json::to_string(credential).map_err(|e| e.to_string())?;
+    platform::write(account, &secret)?;
+    tracing::info!(account = %account, "credential saved");
+    Ok(())
+}
+
+/// Removes the credential saved for `account`; nothing saved is not an error.
+pub(crate) fn delete(account: &str) -> Result<(), String> {
+    platform::delete(account)?;
+    tracing::info!(account = %account, "credential removed");
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::ffi::OsStr;
+    use windows_sys::Win32::{
+        Foundation::{GetLastError, ERROR_NOT_FOUND},
+        Security::Credentials::{
+            CredDeleteW, CredFree, CredReadW, CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE,
+            CRED_TYPE_GENERIC,
+        },
+    };
+
+    use super::SERVICE;
+    use crate::volumes::to_wide;
+
+    fn target(account: &str) -> Vec<u16> {
+        to_wide(OsStr::new(&format!("{}:{}", SERVICE, account)))
+    }
+
+    pub(super) fn read(account: &str) -> Result<Option<String>, String> {
+        let target = target(account);
+        let mut credential: *mut CREDENTIALW = std::ptr::null_mut();
+        // SAFETY: `target` is NUL-terminated; on success the credential is freed below.
+        if unsafe { CredReadW(target.as_ptr(), CRED_TYPE_GENERIC, 0, &mut credential) } == 0 {
+            let code = unsafe { GetLastError() };
+            if code == ERROR_NOT_FOUND {
+                return Ok(None);
+            }
+            return Err(format!("CredReadW failed ({})", code));
+        }
+        // SAFETY: CredReadW succeeded, so the pointer and its blob are valid until CredFree.
+        let secret = unsafe {
+            let blob = std::slice::from_raw_parts(
+                (*credential).CredentialBlob,
+                (*credential).CredentialBlobSize as usize,
+            );
+            let secret = String::from_utf8_lossy(blob).into_owned();
+            CredFree(credential as *const _);
+            secret
+        };
+        Ok(Some(secret))
+    }
+
+    pub(super) fn write(account: &str, secret: &str) -> Result<(), String> {
+        let mut target = target(account);
+        let mut blob = secret.as_bytes().to_vec();
+        // SAFETY: all-zero is a valid CREDENTIALW; the pointers set below outlive the call.
+        let mut credential: CREDENTIALW = unsafe { std::mem::zeroed() };
+        credential.Type = CRED_TYPE_GENERIC;
+        credential.TargetName = target.as_mut_ptr();
+        credential.CredentialBlobSize = blob.len() as u32;
+        credential.CredentialBlob = blob.as_mut_ptr();
+        credential.Persist = CRED_PERSIST_LOCAL_MACHINE;
+        // SAFETY: see above.
+        if unsafe { CredWriteW(&credential, 0) } == 0 {
+            return Err(format!("CredWriteW failed ({})", unsafe { GetLastError() }));
+        }
+        Ok(())
+    }
+
+    pub(super) fn delete(account: &str) -> Result<(), String> {
+        let target = target(account);
+        // SAFETY: `target` is NUL-terminated.
+        if unsafe { CredDeleteW(target.as_ptr(), CRED_TYPE_GENERIC, 0) } == 0 {
+            let code = unsafe { GetLastError() };
+            if code != ERROR_NOT_FOUND {
+                return Err(format!("CredDeleteW failed ({})", code));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{
+        io::Write,
+        process::{Command, Stdio},
+    };
+
+    use super::SERVICE;
+
+    const SECURITY: &str = "/usr/bin/security";
+    // `security` exits with this when there is no matching item.
+    const ITEM_NOT_FOUND: i32 = 44;
+
+    pub(super) fn read(account: &str) -> Result<Option<String>, String> {
+        let output = Command::new(SECURITY)
+            .args(["find-generic-password", "-s", SERVICE, "-a", account, "-w"])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to run security: {}", e))?;
+        if output.status.code() == Some(ITEM_NOT_FOUND) {
+            return Ok(None);
+        }
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        let hex_secret = String::from_utf8_lossy(&output.stdout);
+        let secret = hex::decode(hex_secret.trim()).map_err(|e| e.to_string())?;
+        Ok(Some(String::from_utf8_lossy(&secret).into_owned()))
+    }
+
+    pub(super) fn write(account: &str, secret: &str) -> Result<(), String> {
+        if account.contains(['"', '\\', '\n']) {
+            return Err(format!("Unsupported account name: {}", account));
+        }
+        // Commands go through stdin (`-i`) so the secret never appears in the process
+        // list; hex keeps it clear of the command parser's quoting rules.
+        let mut child = Command::new(SECURITY)
+            .arg("-i")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run security: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            writeln!(
+                stdin,
+                "add-generic-password -U -s {} -a \"{}\" -w {}",
+                SERVICE,
+                account,
+                hex::encode(secret)
+            )
+            .map_err(|e| format!("Failed to run security: {}", e))?;
+        }
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // Interactive mode exits 0 even when a command fails, so check what it said.
+        if !output.status.success() || !stderr.trim().is_empty() {
+            return Err(format!("Failed to save to the keychain: {}", stderr.trim()));
+        }
+        Ok(())
+    }
+
+    pub(super) fn delete(account: &str) -> Result<(), String> {
+        let output = Command::new(SECURITY)
+            .args(["delete-generic-password", "-s", SERVICE, "-a", account])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to run security: {}", e))?;
+        if !output.status.success() && output.status.code() != Some(ITEM_NOT_FOUND) {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::{
+        io::{self, Write},
+        process::{Command, Stdio},
+    };
+
+    use super::SERVICE;
+
+    const SECRET_TOOL: &str = "secret-tool";
+
+    fn run_error(e: io::Error) -> String {
+        if e.kind() == io::ErrorKind::NotFound {
+            "secret-tool was not found. Install libsecret-tools to save passwords.".to_string()
+        } else {
+            format!("Failed to run secret-tool: {}", e)
+        }
+    }
+
+    pub(super) fn read(account: &str) -> Result<Option<String>, String> {
+        let output = Command::new(SECRET_TOOL)
+            .args(["lookup", "service", SERVICE, "account", account])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(run_error)?;
+        // `lookup` exits with 1 and prints nothing when there is no such item.
+        if !output.status.success() || output.stdout.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
+    pub(super) fn write(account: &str, secret: &str) -> Result<(), String> {
+        // `store` reads the secret from stdin, keeping it out of the process list.
+        let mut child = Command::new(SECRET_TOOL)
+            .arg("store")
+            .arg(format!("--label={}: {}", SERVICE, account))
+            .args(["service", SERVICE, "account", account])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(run_error)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(secret.as_bytes()).map_err(run_error)?;
+        }
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to save to the keyring: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    pub(super) fn delete(account: &str) -> Result<(), String> {
+        let output = Command::new(SECRET_TOOL)
+            .args(["clear", "service", SERVICE, "account", account])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(run_error)?;
+        // `clear` also exits with 1 when nothing matched.
+        if !output.status.success() && !output.stderr.is_empty() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        Ok(())
+    }
+}
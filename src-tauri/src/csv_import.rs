@@ -0,0 +1,312 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    scanner::{file_extension_lower, FsNode, FsNodeKind},
+    store::ScanSummary,
+};
+
+// Column headers used by WizTree ("File Name") and TreeSize ("Full Path"/"Path").
+const PATH_HEADERS: &[&str] = &["file name", "full path", "path"];
+const SIZE_HEADERS: &[&str] = &["size", "size (bytes)"];
+// Both tools print a few lines of report metadata before the header row.
+const MAX_PREAMBLE_LINES: usize = 16;
+
+struct BuildNode {
+    path: String,
+    is_dir: bool,
+    // Size from the export. Folder rows carry their total, which also covers files the
+    // export left out, so it wins over the sum of the listed children when larger.
+    reported: Option<u64>,
+    children: Vec<usize>,
+}
+
+fn decode(bytes: &[u8]) -> String {
+    // TreeSize can export "Unicode" (UTF-16LE) CSV files.
+    if let Some(utf16) = bytes.strip_prefix(&[0xff, 0xfe]) {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    let bytes = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]).unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn split_row(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// European locales export with `;`; pick whichever delimiter splits the header best.
+fn detect_delimiter(line: &str) -> char {
+    [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|&d| split_row(line, d).len())
+        .unwrap_or(',')
+}
+
+fn find_column(header: &[String], names: &[&str]) -> Option<usize> {
+    header
+        .iter()
+        .position(|h| names.contains(&h.trim().to_lowercase().as_str()))
+}
+
+/// Parses sizes as either raw byte counts or locale-formatted values such as `1.234,5 MB`.
+fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text.find(|c: char| c.is_alphabetic()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let multiplier: u64 = match unit.trim().to_lowercase().as_str() {
+        "" | "b" | "bytes" => 1,
+        "kb" | "kib" => 1 << 10,
+        "mb" | "mib" => 1 << 20,
+        "gb" | "gib" => 1 << 30,
+        "tb" | "tib" => 1 << 40,
+        _ => return None,
+    };
+    let number: String = number
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\u{a0}')
+        .collect();
+
+    if multiplier == 1 {
+        // Whole bytes: any separators are thousands separators.
+        let digits: String = number.chars().filter(char::is_ascii_digit).collect();
+        return digits.parse().ok();
+    }
+
+    // The last separator is the decimal point; earlier ones group thousands.
+    let normalized: String = match number.rfind(['.', ',']) {
+        Some(idx) => number
+            .char_indices()
+            .filter_map(|(i, c)| match c {
+                '.' | ',' if i == idx => Some('.'),
+                '.' | ',' => None,
+                c => Some(c),
+            })
+            .collect(),
+        None => number,
+    };
+    let value: f64 = normalized.parse().ok()?;
+    Some((value * multiplier as f64) as u64)
+}
+
+fn is_separator(c: char) -> bool {
+    c == '\\' || c == '/'
+}
+
+/// Lookup key for a path: no trailing separator, except for roots like `/` or `C:\`.
+fn path_key(path: &str) -> String {
+    let trimmed = path.trim_end_matches(is_separator);
+    if trimmed.is_empty() || trimmed.ends_with(':') {
+        path.to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+fn parent_key(key: &str) -> Option<String> {
+    let trimmed = key.trim_end_matches(is_separator);
+    let idx = trimmed.rfind(is_separator)?;
+    // Keep the separator for roots (`/`, `C:\`).
+    let parent = &trimmed[..idx];
+    if parent.is_empty() || parent.ends_with(':') {
+        Some(trimmed[..=idx].to_string())
+    } else {
+        Some(parent.to_string())
+    }
+}
+
+// Exports are often opened on another OS than they were made on, so split paths by
+// hand rather than with `Path`, which only knows the host's separator.
+fn node_name(key: &str) -> String {
+    let trimmed = key.trim_end_matches(is_separator);
+    match trimmed.rfind(is_separator) {
+        Some(idx) if idx + 1 < trimmed.len() => trimmed[idx + 1..].to_string(),
+        _ => key.to_string(),
+    }
+}
+
+// Index 0 is the exported root.
+struct TreeBuilder {
+    nodes: Vec<BuildNode>,
+    index: HashMap<String, usize>,
+}
+
+impl TreeBuilder {
+    fn new(root_path: &str) -> Self {
+        let key = path_key(root_path);
+        Self {
+            nodes: vec![BuildNode {
+                path: key.clone(),
+                is_dir: true,
+                reported: None,
+                children: vec![],
+            }],
+            index: HashMap::from([(key, 0)]),
+        }
+    }
+
+    /// Returns the node for `key`, creating missing ancestors up to the root. Parents are
+    /// always created before their children, so indices grow with depth.
+    fn ensure(&mut self, key: &str, is_dir: bool) -> Option<usize> {
+        if let Some(&idx) = self.index.get(key) {
+            return Some(idx);
+        }
+        let parent = self.ensure(&parent_key(key)?, true)?;
+        let idx = self.nodes.len();
+        self.nodes.push(BuildNode {
+            path: key.to_string(),
+            is_dir,
+            reported: None,
+            children: vec![],
+        });
+        self.nodes[parent].children.push(idx);
+        self.index.insert(key.to_string(), idx);
+        Some(idx)
+    }
+
+    fn finish(self) -> Result<(FsNode, u64, u64), String> {
+        let mut files = 0;
+        let mut dirs = 0;
+        let mut built: Vec<Option<FsNode>> = (0..self.nodes.len()).map(|_| None).collect();
+
+        // Children have larger indices than their parents, so build back to front.
+        for (idx, node) in self.nodes.iter().enumerate().rev() {
+            let mut children: Vec<FsNode> = node
+                .children
+                .iter()
+                .filter_map(|&child| built[child].take())
+                .collect();
+            children.sort_by_key(|c| std::cmp::Reverse(c.size));
+
+            let name = node_name(&node.path);
+            let fs_node = if node.is_dir {
+                dirs += 1;
+                let listed = children
+                    .iter()
+                    .fold(0u64, |acc, c| acc.saturating_add(c.size));
+                FsNode {
+                    name,
+                    path: node.path.clone(),
+                    kind: FsNodeKind::Directory,
+                    size: node.reported.unwrap_or(0).max(listed),
+                    children,
+                    extension: None,
+                    error: None,
+                    uncompressed_size: None,
+                    error_count: 0,
+                }
+            } else {
+                files += 1;
+                FsNode {
+                    extension: file_extension_lower(Path::new(&name)),
+                    name,
+                    path: node.path.clone(),
+                    kind: FsNodeKind::File,
+                    size: node.reported.unwrap_or(0),
+                    children: vec![],
+                    error: None,
+                    uncompressed_size: None,
+                    error_count: 0,
+                }
+            };
+            built[idx] = Some(fs_node);
+        }
+
+        let root = built
+            .into_iter()
+            .next()
+            .flatten()
+            .ok_or_else(|| "The export contains no entries.".to_string())?;
+        Ok((root, files, dirs))
+    }
+}
+
+/// Parses a WizTree or TreeSize CSV export. Folder rows end with a path separator;
+/// the first data row is the scanned root.
+pub(crate) fn parse_export(bytes: &[u8]) -> Result<(FsNode, ScanSummary), String> {
+    let text = decode(bytes);
+    let mut lines = text.lines();
+
+    let mut columns = None;
+    for line in lines.by_ref().take(MAX_PREAMBLE_LINES) {
+        let delimiter = detect_delimiter(line);
+        let header = split_row(line, delimiter);
+        if let (Some(path_col), Some(size_col)) = (
+            find_column(&header, PATH_HEADERS),
+            find_column(&header, SIZE_HEADERS),
+        ) {
+            columns = Some((delimiter, path_col, size_col));
+            break;
+        }
+    }
+    let (delimiter, path_col, size_col) = columns.ok_or_else(|| {
+        "Unrecognized export format: no WizTree/TreeSize CSV header found.".to_string()
+    })?;
+
+    let mut builder: Option<TreeBuilder> = None;
+    let mut skipped: u64 = 0;
+    for line in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let row = split_row(line, delimiter);
+        let (path, size) = match (
+            row.get(path_col),
+            row.get(size_col).and_then(|s| parse_size(s)),
+        ) {
+            (Some(path), Some(size)) if !path.trim().is_empty() => (path.trim(), size),
+            _ => {
+                skipped += 1;
+                continue;
+            }
+        };
+
+        let is_dir = path.ends_with(is_separator);
+        let builder = builder.get_or_insert_with(|| {
+            if is_dir {
+                TreeBuilder::new(path)
+            } else {
+                TreeBuilder::new(&parent_key(&path_key(path)).unwrap_or_default())
+            }
+        });
+        // Rows outside the exported root (e.g. a report spanning drives) are left out.
+        match builder.ensure(&path_key(path), is_dir) {
+            Some(idx) => builder.nodes[idx].reported = Some(size),
+            None => skipped += 1,
+        }
+    }
+
+    let builder = builder.ok_or_else(|| "The export contains no entries.".to_string())?;
+    let (root, file_count, dir_count) = builder.finish()?;
+    let summary = ScanSummary {
+        root_path: root.path.clone(),
+        started_at_secs: 0,
+        duration_ms: 0,
+        total_bytes: root.size,
+        file_count,
+        dir_count,
+        skipped_entries: skipped,
+        denied_dirs: vec![],
+        skipped: vec![],
+        partial: false,
+    };
+    Ok((root, summary))
+}
@@ -0,0 +1,138 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+use tauri::Manager;
+use tauri_plugin_diskcheck_storage::{Listed, PickedFolder, StorageExt};
+
+use diskcheck_core::{ScanOptions, DEFAULT_MIN_NODE_BYTES};
+
+use crate::{
+    remote::{self, RemoteListing},
+    scanner::{skipped_preview, FinishedScan, ScanResult, WindowProgress},
+    settings::SettingsStore,
+    store::ScanStore,
+};
+
+// Root of the shared media, whose first level is the storage volumes.
+const MEDIA_ROOT: &str = "media://device";
+
+/// Lists `root` through the native side into a records file in the app's cache, then
+/// builds the scan from it like a remote listing.
+fn scan_device_blocking(
+    window: tauri::Window,
+    root: PathBuf,
+    min_node_bytes: Option<u64>,
+    excludes: Vec<String>,
+    list: impl FnOnce(&tauri::Window, &Path) -> Result<Listed, String>,
+) -> Result<FinishedScan, String> {
+    let started_at = SystemTime::now();
+    let started = Instant::now();
+    let opts = ScanOptions::local(min_node_bytes.unwrap_or(DEFAULT_MIN_NODE_BYTES))
+        .with_excludes(excludes);
+    let cache = window
+        .app_handle()
+        .path()
+        .app_cache_dir()
+        .map_err(|err| err.to_string())?;
+    fs::create_dir_all(&cache).map_err(|err| err.to_string())?;
+    let records = cache.join(format!("listing-{}.bin", unix_nanos(started_at)));
+
+    tracing::info!(root = %root.display(), "device storage scan started");
+    let listed = list(&window, &records);
+    let read = listed.and_then(|listed| {
+        let file = File::open(&records).map_err(|err| err.to_string())?;
+        let mut listing = RemoteListing::new(root.clone());
+        remote::read_records(file, &mut listing, &WindowProgress::new(window, &root))
+            .map_err(|err| err.to_string())?;
+        Ok((listing, listed.unreadable))
+    });
+    let _ = fs::remove_file(&records);
+    let (listing, unreadable) = read.inspect_err(
+        |err| tracing::error!(root = %root.display(), error = %err, "device storage scan failed"),
+    )?;
+    remote::scan_listing(listing, &opts, started_at, started, unreadable)
+}
+
+fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0)
+}
+
+async fn scan_device(
+    window: tauri::Window,
+    store: &ScanStore,
+    root: PathBuf,
+    min_node_bytes: Option<u64>,
+    list: impl FnOnce(&tauri::Window, &Path) -> Result<Listed, String> + Send + 'static,
+) -> Result<ScanResult, String> {
+    let defaults = window.state::<SettingsStore>().scan();
+    let min_node_bytes = min_node_bytes.or(defaults.min_node_bytes);
+    let finished = tauri::async_runtime::spawn_blocking(move || {
+        scan_device_blocking(window, root, min_node_bytes, defaults.excludes, list)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    let skipped = skipped_preview(&finished.summary);
+    let scan_id = store.insert_spilled(finished.tree, finished.spill, finished.summary)?;
+    Ok(ScanResult {
+        scan_id,
+        root: finished.pruned,
+        skipped,
+        usage_check: None,
+        denied_dirs: vec![],
+        partial: finished.partial,
+    })
+}
+
+/// Lets the user grant a folder of the device's storage through the system picker,
+/// opened on `volume` (a mount point from `list_volumes`) when given. `None` when they cancel.
+/// Only on Android.
+pub async fn pick_storage_folder(
+    app: tauri::AppHandle,
+    volume: Option<String>,
+) -> Result<Option<PickedFolder>, String> {
+    tauri::async_runtime::spawn_blocking(move || app.storage().pick_folder(volume.as_deref()))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+/// Scans a folder granted with `pick_storage_folder`. The tree is rooted at
+/// `storage://<name>`. Outside Android, the plugin fails every call.
+pub async fn scan_storage_folder(
+    window: tauri::Window,
+    store: &ScanStore,
+    folder: PickedFolder,
+    min_node_bytes: Option<u64>,
+) -> Result<ScanResult, String> {
+    let root = PathBuf::from(format!("storage://{}", folder.name.replace('/', "_")));
+    scan_device(
+        window,
+        store,
+        root,
+        min_node_bytes,
+        move |window, records| window.storage().list_folder(&folder.uri, records),
+    )
+    .await
+}
+
+/// Scans the photos, videos and audio in the device's shared storage, which needs no
+/// folder grant, only the media permissions. The tree is rooted at `media://device`,
+/// with a folder per storage volume.
+pub async fn scan_media_storage(
+    window: tauri::Window,
+    store: &ScanStore,
+    min_node_bytes: Option<u64>,
+) -> Result<ScanResult, String> {
+    scan_device(
+        window,
+        store,
+        PathBuf::from(MEDIA_ROOT),
+        min_node_bytes,
+        |window, records| window.storage().list_media(records),
+    )
+    .await
+}
@@ -0,0 +1,204 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+    time::SystemTime,
+};
+use tauri::Manager;
+use tracing::Level;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    export::create_dest,
+    logging::{log_dir, recent_logs_blocking, LogEntry},
+    scanner::FsNodeKind,
+    settings::{Settings, SettingsStore},
+    store::{unix_secs, ScanStore, ScanSummary, StoredScan},
+};
+
+const SYSTEM_ENTRY: &str = "system.json";
+const SETTINGS_ENTRY: &str = "settings.json";
+const LOGS_ENTRY: &str = "logs.json";
+const LAST_SCAN_ENTRY: &str = "last-scan.json";
+const MAX_LOG_ENTRIES: usize = 2_000;
+const MAX_TOP_LEVEL_ENTRIES: usize = 50;
+const REDACTED: &str = "<redacted>";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SystemInfo {
+    app_version: &'static str,
+    os: &'static str,
+    os_family: &'static str,
+    arch: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    os_version: Option<String>,
+    created_at_secs: u64,
+    includes_paths: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TopLevelEntry {
+    name: String,
+    kind: FsNodeKind,
+    size: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LastScan {
+    summary: ScanSummary,
+    // Names of the scanned root's immediate children; only with paths opted in.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    top_level: Vec<TopLevelEntry>,
+}
+
+fn os_version() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    let (program, args) = ("cmd", ["/C", "ver"].as_slice());
+    #[cfg(target_os = "macos")]
+    let (program, args) = ("sw_vers", ["-productVersion"].as_slice());
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let (program, args) = ("uname", ["-sr"].as_slice());
+
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !version.is_empty()).then_some(version)
+}
+
+fn redact_log(mut entry: LogEntry) -> LogEntry {
+    for (key, value) in entry.fields.iter_mut() {
+        if key == "path" {
+            *value = Value::String(REDACTED.to_string());
+        }
+    }
+    entry
+}
+
+fn last_scan(scan: &StoredScan, include_paths: bool) -> LastScan {
+    let mut summary = scan.summary.clone();
+    if !include_paths {
+        summary.root_path = REDACTED.to_string();
+        return LastScan {
+            summary,
+            top_level: vec![],
+        };
+    }
+    LastScan {
+        summary,
+        top_level: scan
+            .tree
+            .root()
+            .children()
+            .take(MAX_TOP_LEVEL_ENTRIES)
+            .map(|child| TopLevelEntry {
+                name: child.name().to_string(),
+                kind: child.kind(),
+                size: child.size(),
+            })
+            .collect(),
+    }
+}
+
+struct DiagnosticInputs {
+    settings: Settings,
+    log_dir: Option<PathBuf>,
+    last_scan: Option<Arc<StoredScan>>,
+    include_paths: bool,
+}
+
+fn write_bundle(inputs: DiagnosticInputs, dest: &Path) -> Result<(), String> {
+    let mut zip = ZipWriter::new(create_dest(dest)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let system = SystemInfo {
+        app_version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        os_family: std::env::consts::FAMILY,
+        arch: std::env::consts::ARCH,
+        os_version: os_version(),
+        created_at_secs: unix_secs(SystemTime::now()),
+        includes_paths: inputs.include_paths,
+    };
+    zip.start_file(SYSTEM_ENTRY, options)
+        .map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(&mut zip, &system).map_err(|e| e.to_string())?;
+
+    zip.start_file(SETTINGS_ENTRY, options)
+        .map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(&mut zip, &inputs.settings).map_err(|e| e.to_string())?;
+
+    let logs: Vec<LogEntry> = match inputs.log_dir {
+        Some(dir) => recent_logs_blocking(dir, Level::INFO, MAX_LOG_ENTRIES)?,
+        None => vec![],
+    }
+    .into_iter()
+    .map(|entry| {
+        if inputs.include_paths {
+            entry
+        } else {
+            redact_log(entry)
+        }
+    })
+    .collect();
+    zip.start_file(LOGS_ENTRY, options)
+        .map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(&mut zip, &logs).map_err(|e| e.to_string())?;
+
+    if let Some(scan) = inputs.last_scan {
+        zip.start_file(LAST_SCAN_ENTRY, options)
+            .map_err(|e| e.to_string())?;
+        serde_json::to_writer_pretty(&mut zip, &last_scan(&scan, inputs.include_paths))
+            .map_err(|e| e.to_string())?;
+    }
+
+    zip.finish()
+        .and_then(|mut out| out.flush().map_err(Into::into))
+        .map_err(|e| e.to_string())
+}
+
+/// Zips app/OS versions, settings, recent logs and the last scan's summary for a bug
+/// report. Scanned paths are redacted unless `include_paths` is set. Returns the path
+/// of the written file (the Downloads folder when `dest` is omitted).
+pub async fn create_diagnostic_bundle(
+    app: tauri::AppHandle,
+    dest: Option<String>,
+    include_paths: bool,
+) -> Result<String, String> {
+    let dest = match dest {
+        Some(dest) => PathBuf::from(dest),
+        None => app
+            .path()
+            .download_dir()
+            .map_err(|e| e.to_string())?
+            .join(format!(
+                "diskcheck-diagnostics-{}.zip",
+                unix_secs(SystemTime::now())
+            )),
+    };
+    let inputs = DiagnosticInputs {
+        settings: app.state::<SettingsStore>().get()?,
+        log_dir: log_dir(&app).ok(),
+        last_scan: app.state::<ScanStore>().list()?.pop(),
+        include_paths,
+    };
+
+    let written = dest.clone();
+    tauri::async_runtime::spawn_blocking(move || write_bundle(inputs, &dest))
+        .await
+        .map_err(|err| err.to_string())??;
+    tracing::info!(include_paths, "diagnostic bundle created");
+    Ok(written.to_string_lossy().into_owned())
+}
@@ -0,0 +1,86 @@
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tauri::{Emitter, Manager};
+
+use diskcheck_core::ScanOptions;
+
+use crate::{scanner::walk, settings::SettingsStore};
+
+const DIRECTORY_SIZE_PROGRESS_EVENT: &str = "directory_size_progress";
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(120);
+
+/// A folder's size, file and folder counts, without a tree.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectorySize {
+    pub path: String,
+    pub total_bytes: u64,
+    pub file_count: u64,
+    // Folders below `path`, not counting it.
+    pub dir_count: u64,
+    pub skipped_entries: u64,
+}
+
+fn size_blocking(
+    root: &Path,
+    excludes: Vec<String>,
+    window: Option<&tauri::Window>,
+) -> DirectorySize {
+    let opts = ScanOptions::local(0).with_excludes(excludes);
+    let mut size = DirectorySize {
+        path: root.to_string_lossy().into_owned(),
+        total_bytes: 0,
+        file_count: 0,
+        dir_count: 0,
+        skipped_entries: 0,
+    };
+    let mut dir_count = 0u64;
+    let mut last_progress = Instant::now();
+    let skipped_entries = walk(
+        root,
+        |dir| {
+            let included = !opts.is_excluded(dir);
+            dir_count += u64::from(included);
+            included
+        },
+        |file, meta| {
+            if opts.is_excluded(file) {
+                return;
+            }
+            size.file_count += 1;
+            size.total_bytes = size.total_bytes.saturating_add(meta.len());
+            if let Some(window) = window.filter(|_| last_progress.elapsed() >= PROGRESS_INTERVAL) {
+                last_progress = Instant::now();
+                let _ = window.emit_to(window.label(), DIRECTORY_SIZE_PROGRESS_EVENT, &size);
+            }
+        },
+    );
+    size.dir_count = dir_count;
+    size.skipped_entries = skipped_entries;
+    size
+}
+
+/// Adds up the files below `path`, honouring the scan excludes, for a quick "size of
+/// this folder" or to refresh one node of a scan. Nothing is kept. With `progress`, the
+/// running totals go to the calling window as `directory_size_progress` events.
+pub async fn compute_directory_size(
+    window: tauri::Window,
+    path: String,
+    progress: bool,
+) -> Result<DirectorySize, String> {
+    let root = PathBuf::from(path);
+    // Held until the walk ends; on iOS a picked folder is unreadable without it.
+    let _access = crate::sandbox::open_for_scan(window.app_handle(), &root)?;
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.to_string_lossy()));
+    }
+    let excludes = window.state::<SettingsStore>().scan().excludes;
+    tauri::async_runtime::spawn_blocking(move || {
+        size_blocking(&root, excludes, progress.then_some(&window))
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
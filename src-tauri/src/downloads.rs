@@ -0,0 +1,249 @@
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tauri::Manager;
+
+use crate::{
+    categories::{category_for_extension, FileCategory},
+    installers::{age_buckets, age_days, AgeBucket},
+    scanner::{display_name, file_extension_lower, walk_files},
+};
+
+// Installers this old have almost certainly been installed (or abandoned) already.
+const STALE_INSTALLER_DAYS: u64 = 90;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CleanupReason {
+    StaleInstaller,
+    ExtractedArchive,
+    DuplicateArchive,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupCandidate {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_days: Option<u64>,
+    pub reason: CleanupReason,
+    // Human-readable justification, e.g. the folder the archive was extracted to.
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategorySummary {
+    pub category: FileCategory,
+    pub label: String,
+    pub count: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadsReport {
+    pub root: String,
+    pub total_files: u64,
+    pub total_bytes: u64,
+    pub skipped_entries: u64,
+    pub age_buckets: Vec<AgeBucket>,
+    pub categories: Vec<CategorySummary>,
+    pub candidates: Vec<CleanupCandidate>,
+    pub reclaimable_bytes: u64,
+}
+
+struct DownloadEntry {
+    path: PathBuf,
+    extension: Option<String>,
+    size: u64,
+    age_days: Option<u64>,
+}
+
+/// Strips archive extensions (including double ones like `.tar.gz`) from a file name.
+fn archive_stem(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_string_lossy().into_owned();
+    let stem = stem.strip_suffix(".tar").unwrap_or(&stem).to_string();
+    Some(stem).filter(|s| !s.is_empty())
+}
+
+/// Normalizes browser-style copy names such as `setup (1).zip` back to `setup.zip`.
+fn normalized_copy_name(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let trimmed = match stem.rfind(" (") {
+        Some(idx)
+            if stem.ends_with(')')
+                && stem[idx + 2..stem.len() - 1]
+                    .chars()
+                    .all(|c| c.is_ascii_digit()) =>
+        {
+            &stem[..idx]
+        }
+        _ => stem.as_str(),
+    };
+    format!("{}.{}", trimmed.to_lowercase(), ext)
+}
+
+fn find_candidates(entries: &[DownloadEntry]) -> Vec<CleanupCandidate> {
+    let mut candidates: Vec<CleanupCandidate> = vec![];
+    // (folder, normalized name, size) -> entries, to spot repeated downloads of the same archive.
+    let mut archive_copies: HashMap<(PathBuf, String, u64), Vec<&DownloadEntry>> = HashMap::new();
+
+    for entry in entries {
+        let category = category_for_extension(entry.extension.as_deref());
+
+        if category == FileCategory::Installer
+            && entry
+                .age_days
+                .is_some_and(|days| days > STALE_INSTALLER_DAYS)
+        {
+            candidates.push(CleanupCandidate {
+                name: display_name(&entry.path),
+                path: entry.path.to_string_lossy().into_owned(),
+                size: entry.size,
+                age_days: entry.age_days,
+                reason: CleanupReason::StaleInstaller,
+                detail: format!("Installer older than {} days", STALE_INSTALLER_DAYS),
+            });
+            continue;
+        }
+
+        if category != FileCategory::Archive {
+            continue;
+        }
+
+        let extracted = entry
+            .path
+            .parent()
+            .zip(archive_stem(&entry.path))
+            .map(|(parent, stem)| parent.join(stem))
+            .filter(|dir| dir.is_dir());
+        if let Some(dir) = extracted {
+            candidates.push(CleanupCandidate {
+                name: display_name(&entry.path),
+                path: entry.path.to_string_lossy().into_owned(),
+                size: entry.size,
+                age_days: entry.age_days,
+                reason: CleanupReason::ExtractedArchive,
+                detail: format!("Already extracted to {}", dir.to_string_lossy()),
+            });
+            continue;
+        }
+
+        let folder = entry
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        archive_copies
+            .entry((folder, normalized_copy_name(&entry.path), entry.size))
+            .or_default()
+            .push(entry);
+    }
+
+    for (_, mut copies) in archive_copies {
+        if copies.len() < 2 {
+            continue;
+        }
+        // Keep the oldest copy (largest age); everything else is a re-download.
+        copies.sort_by_key(|c| std::cmp::Reverse(c.age_days));
+        let original = display_name(&copies[0].path);
+        for copy in copies.into_iter().skip(1) {
+            candidates.push(CleanupCandidate {
+                name: display_name(&copy.path),
+                path: copy.path.to_string_lossy().into_owned(),
+                size: copy.size,
+                age_days: copy.age_days,
+                reason: CleanupReason::DuplicateArchive,
+                detail: format!("Same size as {}", original),
+            });
+        }
+    }
+
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.size));
+    candidates
+}
+
+pub(crate) fn analyze_downloads_blocking(root: &Path) -> DownloadsReport {
+    let now = SystemTime::now();
+    let mut entries: Vec<DownloadEntry> = vec![];
+
+    let skipped_entries = walk_files(root, |path, meta| {
+        entries.push(DownloadEntry {
+            path: path.to_path_buf(),
+            extension: file_extension_lower(path),
+            size: meta.len(),
+            age_days: age_days(meta, now),
+        });
+    });
+
+    let mut by_category: BTreeMap<FileCategory, (u64, u64)> = BTreeMap::new();
+    for entry in &entries {
+        let slot = by_category
+            .entry(category_for_extension(entry.extension.as_deref()))
+            .or_default();
+        slot.0 += 1;
+        slot.1 = slot.1.saturating_add(entry.size);
+    }
+    let mut categories: Vec<CategorySummary> = by_category
+        .into_iter()
+        .map(|(category, (count, bytes))| CategorySummary {
+            category,
+            label: category.label().to_string(),
+            count,
+            bytes,
+        })
+        .collect();
+    categories.sort_by_key(|c| std::cmp::Reverse(c.bytes));
+
+    let candidates = find_candidates(&entries);
+    let reclaimable_bytes = candidates
+        .iter()
+        .fold(0u64, |acc, c| acc.saturating_add(c.size));
+
+    DownloadsReport {
+        root: root.to_string_lossy().into_owned(),
+        total_files: entries.len() as u64,
+        total_bytes: entries
+            .iter()
+            .fold(0u64, |acc, e| acc.saturating_add(e.size)),
+        skipped_entries,
+        age_buckets: age_buckets(entries.iter().map(|e| (e.age_days, e.size))),
+        categories,
+        candidates,
+        reclaimable_bytes,
+    }
+}
+
+pub async fn analyze_downloads(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<DownloadsReport, String> {
+    let root = match path {
+        Some(path) => PathBuf::from(path),
+        None => app
+            .path()
+            .download_dir()
+            .map_err(|e| format!("Failed to locate the Downloads folder: {}", e))?,
+    };
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.to_string_lossy()));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || analyze_downloads_blocking(&root))
+        .await
+        .map_err(|err| err.to_string())
+}
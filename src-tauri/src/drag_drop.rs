@@ -0,0 +1,86 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{DragDropEvent, Emitter, Manager, WindowEvent};
+
+use crate::{error::CommandError, priority::ScanMode, scanner, store::ScanStore};
+
+const SCAN_STARTED_EVENT: &str = "scan_started";
+const SCAN_FINISHED_EVENT: &str = "scan_finished";
+const SCAN_FAILED_EVENT: &str = "scan_failed";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanStartedPayload {
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanFailedPayload {
+    path: String,
+    error: CommandError,
+}
+
+/// The first dropped directory, with symlinks resolved. Dropped files are ignored.
+fn dropped_directory(paths: &[PathBuf]) -> Option<PathBuf> {
+    paths
+        .iter()
+        .filter_map(|p| p.canonicalize().ok())
+        .find(|p| p.is_dir())
+}
+
+// canonicalize() yields verbatim `\\?\C:\...` paths on Windows; show the familiar form.
+fn display_path(path: &Path) -> String {
+    let text = path.to_string_lossy();
+    let plain = text
+        .strip_prefix(r"\\?\")
+        .filter(|rest| !rest.starts_with(r"UNC\"))
+        .map(str::to_string);
+    plain.unwrap_or_else(|| text.into_owned())
+}
+
+/// Starts a scan when a folder is dropped onto `window`. Results arrive as
+/// `scan_finished` with the same `ScanResult` (scan ID included) as `scan_directory`.
+pub fn handle_window_event(window: &tauri::Window, event: &WindowEvent) {
+    let paths = match event {
+        WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) => paths,
+        _ => return,
+    };
+    let root = match dropped_directory(paths) {
+        Some(root) => root,
+        None => return,
+    };
+    let path = display_path(&root);
+    let _ = window.emit_to(
+        window.label(),
+        SCAN_STARTED_EVENT,
+        ScanStartedPayload { path: path.clone() },
+    );
+
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        let app = window.app_handle().clone();
+        let store = app.state::<ScanStore>();
+        match scanner::scan_directory(
+            window.clone(),
+            &store,
+            path.clone(),
+            None,
+            ScanMode::Normal,
+            false,
+        )
+        .await
+        {
+            Ok(result) => {
+                let _ = window.emit_to(window.label(), SCAN_FINISHED_EVENT, result);
+            }
+            Err(error) => {
+                let _ = window.emit_to(
+                    window.label(),
+                    SCAN_FAILED_EVENT,
+                    ScanFailedPayload { path, error },
+                );
+            }
+        }
+    });
+}
@@ -0,0 +1,397 @@
+use memmap2::Mmap;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+};
+
+use crate::{
+    cloud_sync::open_local,
+    scanner::{FsNodeKind, NodeRef},
+    similar_images::{
+        find_similar_images, ImageCandidate, SimilarImages, DEFAULT_SIMILARITY, IMAGE_EXTENSIONS,
+        IMAGE_MIN_BYTES,
+    },
+    store::ScanStore,
+};
+
+// Smaller files are rarely worth the I/O when hunting for wasted space.
+const DEFAULT_MIN_FILE_BYTES: u64 = 1 << 20;
+// Fixed size of the chunks readers hand to the hashing stage.
+const CHUNK_BYTES: u64 = 1 << 20;
+// Chunks queued between readers and hashing; bounds memory at about 32 MiB.
+const QUEUED_CHUNKS: usize = 32;
+// First pass: most same-size files already differ in their first bytes.
+const PREFIX_BYTES: u64 = 64 << 10;
+// Full-file passes map files at least this large instead of reading them.
+const MMAP_MIN_BYTES: u64 = 64 << 20;
+// Concurrent readers per device. Spinning disks get one, so reads stay sequential.
+const HDD_READERS: usize = 1;
+const SSD_READERS: usize = 4;
+const UNKNOWN_DEVICE_READERS: usize = 2;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub size: u64,
+    // BLAKE3 of the content, hex.
+    pub hash: String,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateReport {
+    // Largest savings first.
+    pub groups: Vec<DuplicateGroup>,
+    // Bytes freed by keeping one copy of every group.
+    pub wasted_bytes: u64,
+    pub hashed_files: u64,
+    // Candidates that could not be read (removed since the scan, permissions).
+    pub unreadable_files: u64,
+    // Look-alike images, when the similarity pass was asked for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similar_images: Option<SimilarImages>,
+}
+
+struct Candidate {
+    path: PathBuf,
+    size: u64,
+}
+
+enum Chunk {
+    Bytes(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl Chunk {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Chunk::Bytes(bytes) => bytes,
+            Chunk::Mapped(map) => map,
+        }
+    }
+}
+
+enum Message {
+    Data(usize, Chunk),
+    Done(usize),
+    Failed(usize),
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::{fs, os::unix::fs::MetadataExt, path::Path};
+
+    pub(super) fn device_key(path: &Path) -> Option<String> {
+        fs::metadata(path).ok().map(|meta| meta.dev().to_string())
+    }
+
+    /// Whether the block device holding `path` has a seek penalty, from sysfs.
+    pub(super) fn is_rotational(path: &Path) -> Option<bool> {
+        let dev = fs::metadata(path).ok()?.dev();
+        // glibc's gnu_dev_major/gnu_dev_minor encoding.
+        let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+        let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+        let device = format!("/sys/dev/block/{}:{}", major, minor);
+        // Partitions have no queue of their own; their parent disk does.
+        let flag = fs::read_to_string(format!("{}/queue/rotational", device))
+            .or_else(|_| fs::read_to_string(format!("{}/../queue/rotational", device)))
+            .ok()?;
+        Some(flag.trim() == "1")
+    }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+mod platform {
+    use std::{fs, os::unix::fs::MetadataExt, path::Path};
+
+    pub(super) fn device_key(path: &Path) -> Option<String> {
+        fs::metadata(path).ok().map(|meta| meta.dev().to_string())
+    }
+
+    pub(super) fn is_rotational(_path: &Path) -> Option<bool> {
+        None
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::path::{Component, Path};
+
+    /// The drive or share the path lives on.
+    pub(super) fn device_key(path: &Path) -> Option<String> {
+        match path.components().next()? {
+            Component::Prefix(prefix) => Some(prefix.as_os_str().to_string_lossy().to_lowercase()),
+            _ => None,
+        }
+    }
+
+    pub(super) fn is_rotational(_path: &Path) -> Option<bool> {
+        None
+    }
+}
+
+fn readers_for(path: &Path) -> usize {
+    match platform::is_rotational(path) {
+        Some(true) => HDD_READERS,
+        Some(false) => SSD_READERS,
+        None => UNKNOWN_DEVICE_READERS,
+    }
+}
+
+/// Streams one file to the hashing stage: all of it, or its first `limit` bytes.
+fn read_file(
+    index: usize,
+    candidate: &Candidate,
+    limit: Option<u64>,
+    tx: &mpsc::SyncSender<Message>,
+) -> std::io::Result<()> {
+    let mut file = open_local(&candidate.path)?;
+    if limit.is_none() && candidate.size >= MMAP_MIN_BYTES {
+        // SAFETY: the mapping is read-only and dropped once hashed. A file changed
+        // meanwhile yields a hash that matches nothing, which only hides a duplicate.
+        let map = unsafe { Mmap::map(&file)? };
+        let _ = tx.send(Message::Data(index, Chunk::Mapped(map)));
+        return Ok(());
+    }
+
+    let mut remaining = limit.unwrap_or(u64::MAX);
+    while remaining > 0 {
+        let want = remaining.min(CHUNK_BYTES);
+        let mut bytes = Vec::with_capacity(want as usize);
+        let read = (&mut file).take(want).read_to_end(&mut bytes)?;
+        if read == 0 {
+            break;
+        }
+        remaining -= read as u64;
+        if tx.send(Message::Data(index, Chunk::Bytes(bytes))).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Hashes `files` (whole, or their first `limit` bytes) as a bounded pipeline: reader
+/// threads, limited per device, feed fixed-size chunks through a bounded queue to the
+/// hashing stage, which spreads each chunk over the rayon pool. `None` marks files that
+/// could not be read.
+fn hash_files(files: &[Candidate], limit: Option<u64>) -> Vec<Option<blake3::Hash>> {
+    let mut by_device: HashMap<Option<String>, VecDeque<usize>> = HashMap::new();
+    for (index, file) in files.iter().enumerate() {
+        by_device
+            .entry(platform::device_key(&file.path))
+            .or_default()
+            .push_back(index);
+    }
+
+    let mut hashes: Vec<Option<blake3::Hash>> = vec![None; files.len()];
+    let (tx, rx) = mpsc::sync_channel::<Message>(QUEUED_CHUNKS);
+    std::thread::scope(|scope| {
+        for queue in by_device.into_values() {
+            let readers = queue
+                .front()
+                .map_or(1, |&first| readers_for(&files[first].path));
+            let queue = Arc::new(Mutex::new(queue));
+            for _ in 0..readers {
+                let (queue, tx) = (queue.clone(), tx.clone());
+                scope.spawn(move || loop {
+                    let next = queue.lock().ok().and_then(|mut q| q.pop_front());
+                    let Some(index) = next else { break };
+                    let message = match read_file(index, &files[index], limit, &tx) {
+                        Ok(()) => Message::Done(index),
+                        Err(err) => {
+                            tracing::debug!(path = %files[index].path.display(), error = %err, "failed to hash file");
+                            Message::Failed(index)
+                        }
+                    };
+                    if tx.send(message).is_err() {
+                        break;
+                    }
+                });
+            }
+        }
+        drop(tx);
+
+        // Each file is read by one reader, so its chunks arrive in order.
+        let mut hashers: HashMap<usize, blake3::Hasher> = HashMap::new();
+        for message in rx {
+            match message {
+                Message::Data(index, chunk) => {
+                    hashers
+                        .entry(index)
+                        .or_default()
+                        .update_rayon(chunk.as_slice());
+                }
+                Message::Done(index) => {
+                    hashes[index] = Some(hashers.remove(&index).unwrap_or_default().finalize());
+                }
+                Message::Failed(index) => {
+                    hashers.remove(&index);
+                }
+            }
+        }
+    });
+    hashes
+}
+
+/// Splits each group by the hash of its members, keeping only those still holding
+/// two or more files. Unreadable files are counted and dropped.
+fn split_by_hash(
+    groups: Vec<Vec<Candidate>>,
+    limit: Option<u64>,
+    unreadable: &mut u64,
+    hashed: &mut u64,
+) -> Vec<(blake3::Hash, Vec<Candidate>)> {
+    let (files, group_of): (Vec<Candidate>, Vec<usize>) = groups
+        .into_iter()
+        .enumerate()
+        .flat_map(|(group, files)| files.into_iter().map(move |file| (file, group)))
+        .unzip();
+    let hashes = hash_files(&files, limit);
+    *hashed += files.len() as u64;
+
+    let mut split: HashMap<(usize, blake3::Hash), Vec<Candidate>> = HashMap::new();
+    for ((file, group), hash) in files.into_iter().zip(group_of).zip(hashes) {
+        match hash {
+            Some(hash) => split.entry((group, hash)).or_default().push(file),
+            None => *unreadable += 1,
+        }
+    }
+    split
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|((_, hash), files)| (hash, files))
+        .collect()
+}
+
+fn is_image(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Finds byte-identical files, and with `similarity` set also images that look alike
+/// at that similarity (0 to 1).
+pub(crate) fn find_duplicates_blocking(
+    root: NodeRef<'_>,
+    min_file_bytes: u64,
+    similarity: Option<f64>,
+) -> DuplicateReport {
+    // Only files of equal size can be equal; the scan already knows every size.
+    let mut by_size: HashMap<u64, Vec<Candidate>> = HashMap::new();
+    let mut images: Vec<ImageCandidate> = vec![];
+    let mut pending = vec![root];
+    while let Some(node) = pending.pop() {
+        match node.kind() {
+            FsNodeKind::File => {
+                if similarity.is_some() && node.size() >= IMAGE_MIN_BYTES && is_image(node.name()) {
+                    images.push(ImageCandidate {
+                        path: PathBuf::from(node.path()),
+                        size: node.size(),
+                    });
+                }
+                if node.size() >= min_file_bytes.max(1) {
+                    by_size.entry(node.size()).or_default().push(Candidate {
+                        path: PathBuf::from(node.path()),
+                        size: node.size(),
+                    });
+                }
+            }
+            FsNodeKind::Directory => pending.extend(node.children()),
+            _ => {}
+        }
+    }
+    let same_size: Vec<Vec<Candidate>> = by_size
+        .into_values()
+        .filter(|files| files.len() > 1)
+        .collect();
+
+    let (mut unreadable, mut hashed) = (0, 0);
+    let mut confirmed = vec![];
+    let mut need_full = vec![];
+    for (hash, files) in split_by_hash(same_size, Some(PREFIX_BYTES), &mut unreadable, &mut hashed)
+    {
+        // For small files the prefix was the whole content.
+        if files[0].size <= PREFIX_BYTES {
+            confirmed.push((hash, files));
+        } else {
+            need_full.push(files);
+        }
+    }
+    confirmed.extend(split_by_hash(need_full, None, &mut unreadable, &mut hashed));
+
+    let mut groups: Vec<DuplicateGroup> = confirmed
+        .into_iter()
+        .map(|(hash, files)| {
+            let mut paths: Vec<String> = files
+                .iter()
+                .map(|f| f.path.to_string_lossy().into_owned())
+                .collect();
+            paths.sort();
+            DuplicateGroup {
+                size: files[0].size,
+                hash: hash.to_hex().to_string(),
+                paths,
+            }
+        })
+        .collect();
+    let wasted = |g: &DuplicateGroup| g.size.saturating_mul(g.paths.len() as u64 - 1);
+    groups.sort_by_key(|g| std::cmp::Reverse(wasted(g)));
+
+    let similar_images = similarity.map(|similarity| {
+        let group_of: HashMap<&str, usize> = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(index, g)| g.paths.iter().map(move |path| (path.as_str(), index)))
+            .collect();
+        find_similar_images(images, similarity, |a, b| {
+            group_of
+                .get(a)
+                .is_some_and(|group| group_of.get(b) == Some(group))
+        })
+    });
+
+    DuplicateReport {
+        wasted_bytes: groups.iter().map(wasted).sum(),
+        groups,
+        hashed_files: hashed,
+        unreadable_files: unreadable,
+        similar_images,
+    }
+}
+
+/// Finds files with identical content among the files of a stored scan that are at
+/// least `min_file_bytes` large (1 MiB by default). With `similar_images`, JPEG, PNG and
+/// WebP images are also grouped by perceptual hash at `similarity` (0.9 by default).
+pub async fn find_duplicates(
+    store: &ScanStore,
+    scan_id: String,
+    min_file_bytes: Option<u64>,
+    similar_images: bool,
+    similarity: Option<f64>,
+) -> Result<DuplicateReport, String> {
+    let scan = store.get(&scan_id)?;
+    let min_file_bytes = min_file_bytes.unwrap_or(DEFAULT_MIN_FILE_BYTES);
+    let similarity = similar_images.then(|| similarity.unwrap_or(DEFAULT_SIMILARITY));
+    tauri::async_runtime::spawn_blocking(move || {
+        let scan = scan.complete()?;
+        let started = std::time::Instant::now();
+        let report = find_duplicates_blocking(scan.tree.root(), min_file_bytes, similarity);
+        tracing::info!(
+            scan_id = %scan.id,
+            groups = report.groups.len(),
+            wasted_bytes = report.wasted_bytes,
+            hashed_files = report.hashed_files,
+            similar_image_groups = report.similar_images.as_ref().map_or(0, |s| s.groups.len()),
+            duration_ms = started.elapsed().as_millis() as u64,
+            "duplicate search finished"
+        );
+        Ok(report)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,365 @@
+use serde::{Deserialize, Serialize};
+#[cfg(unix)]
+use std::process::{Command, Stdio};
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::Path,
+};
+
+use diskcheck_core::{NoProgress, ScanOptions, DEFAULT_MIN_NODE_BYTES};
+
+use crate::{
+    scanner::{pruned_view, skipped_preview, ScanResult, ScanTree},
+    store::ScanStore,
+};
+
+/// Starts the app as the rescan helper instead of the UI:
+/// `--rescan-denied [--output FILE] PATH...`. Results go to FILE, or to stdout.
+pub(crate) const HELPER_FLAG: &str = "--rescan-denied";
+const OUTPUT_FLAG: &str = "--output";
+// Set for sudo, which then runs the app as its askpass program; see `ask_password`.
+#[cfg(target_os = "macos")]
+pub(crate) const ASKPASS_ENV: &str = "DISKCHECK_ASKPASS";
+
+// Exit codes of the helper.
+const EXIT_OK: i32 = 0;
+const EXIT_FAILED: i32 = 2;
+
+/// One folder as the helper scanned it.
+#[derive(Serialize, Deserialize)]
+struct RescannedDir {
+    tree: ScanTree,
+    file_count: u64,
+    dir_count: u64,
+}
+
+/// Scans each of `paths` and writes the results to `out` one after another. Folders
+/// that still cannot be read are left out.
+fn write_scans(paths: &[String], mut out: impl Write) -> Result<(), String> {
+    let opts = ScanOptions::local(DEFAULT_MIN_NODE_BYTES);
+    for path in paths {
+        let scan = match diskcheck_core::scan(Path::new(path), &opts, &NoProgress) {
+            Ok(scan) => scan,
+            Err(error) => {
+                eprintln!("diskcheck: {}", error);
+                continue;
+            }
+        };
+        let dir = RescannedDir {
+            file_count: scan.stats.file_count,
+            dir_count: scan.stats.dir_count,
+            tree: scan.tree,
+        };
+        bincode::serialize_into(&mut out, &dir).map_err(|e| e.to_string())?;
+    }
+    out.flush().map_err(|e| e.to_string())
+}
+
+/// Reads what [`write_scans`] wrote, up to the end of `input`.
+fn read_scans(input: impl Read) -> Result<Vec<RescannedDir>, String> {
+    let mut input = BufReader::new(input);
+    let mut dirs = vec![];
+    while !input.fill_buf().map_err(|e| e.to_string())?.is_empty() {
+        dirs.push(bincode::deserialize_from(&mut input).map_err(|e| e.to_string())?);
+    }
+    Ok(dirs)
+}
+
+/// Runs the helper with the arguments after [`HELPER_FLAG`] and returns its exit code.
+pub(crate) fn run_helper(args: &[String]) -> i32 {
+    let result = match args {
+        [flag, output, paths @ ..] if flag == OUTPUT_FLAG => {
+            // Only ever written into a file the caller created, never a new one.
+            fs::OpenOptions::new()
+                .write(true)
+                .truncate(true)
+                .open(output)
+                .map_err(|e| format!("Failed to open {}: {}", output, e))
+                .and_then(|file| write_scans(paths, io::BufWriter::new(file)))
+        }
+        paths => write_scans(paths, io::BufWriter::new(io::stdout().lock())),
+    };
+    match result {
+        Ok(()) => EXIT_OK,
+        Err(error) => {
+            eprintln!("diskcheck: {}", error);
+            EXIT_FAILED
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) use platform::ask_password;
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::{ffi::OsStr, fs, path::PathBuf};
+    use windows_sys::Win32::{
+        Foundation::{CloseHandle, GetLastError, ERROR_CANCELLED},
+        System::Threading::{GetExitCodeProcess, WaitForSingleObject, INFINITE},
+        UI::Shell::{
+            ShellExecuteExW, SEE_MASK_NOASYNC, SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW,
+        },
+    };
+
+    use super::{read_scans, RescannedDir, HELPER_FLAG, OUTPUT_FLAG};
+    use crate::volumes::to_wide;
+
+    const SW_HIDE: i32 = 0;
+
+    /// Quotes `arg` for the command line parser of the Rust runtime (the MSVC rules):
+    /// backslashes only escape when a quote follows them.
+    fn quote(arg: &str) -> String {
+        let mut quoted = String::from('"');
+        let mut backslashes = 0;
+        for c in arg.chars() {
+            if c == '\\' {
+                backslashes += 1;
+                continue;
+            }
+            let escapes = if c == '"' {
+                backslashes * 2 + 1
+            } else {
+                backslashes
+            };
+            quoted.push_str(&"\\".repeat(escapes));
+            quoted.push(c);
+            backslashes = 0;
+        }
+        quoted.push_str(&"\\".repeat(backslashes * 2));
+        quoted.push('"');
+        quoted
+    }
+
+    /// Deletes the helper's output file however the rescan ends.
+    struct TempFile(PathBuf);
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    /// Starts this executable as the helper through the "runas" verb, which shows the
+    /// UAC prompt, and waits for it. The helper writes into a file created here, so the
+    /// results land somewhere this (unelevated) process can read.
+    pub(super) fn rescan(paths: &[String]) -> Result<Vec<RescannedDir>, String> {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let output = TempFile(std::env::temp_dir().join(format!(
+            "diskcheck-rescan-{}-{}.bin",
+            std::process::id(),
+            crate::store::unix_secs(std::time::SystemTime::now())
+        )));
+        fs::File::create(&output.0)
+            .map_err(|e| format!("Failed to create {}: {}", output.0.to_string_lossy(), e))?;
+
+        let mut parameters = format!(
+            "{} {} {}",
+            HELPER_FLAG,
+            OUTPUT_FLAG,
+            quote(&output.0.to_string_lossy())
+        );
+        for path in paths {
+            parameters.push(' ');
+            parameters.push_str(&quote(path));
+        }
+        let verb = to_wide(OsStr::new("runas"));
+        let file = to_wide(exe.as_os_str());
+        let parameters = to_wide(OsStr::new(&parameters));
+
+        // SAFETY: all-zero is a valid SHELLEXECUTEINFOW; the strings set below outlive
+        // the call.
+        let mut info: SHELLEXECUTEINFOW = unsafe { std::mem::zeroed() };
+        info.cbSize = std::mem::size_of::<SHELLEXECUTEINFOW>() as u32;
+        info.fMask = SEE_MASK_NOCLOSEPROCESS | SEE_MASK_NOASYNC;
+        info.lpVerb = verb.as_ptr();
+        info.lpFile = file.as_ptr();
+        info.lpParameters = parameters.as_ptr();
+        info.nShow = SW_HIDE;
+        // SAFETY: see above.
+        if unsafe { ShellExecuteExW(&mut info) } == 0 {
+            let code = unsafe { GetLastError() };
+            if code == ERROR_CANCELLED {
+                return Err("The administrator prompt was declined.".to_string());
+            }
+            return Err(format!("Failed to start the elevated helper ({})", code));
+        }
+        if info.hProcess.is_null() {
+            return Err("Failed to start the elevated helper.".to_string());
+        }
+        let mut exit_code = 0u32;
+        // SAFETY: `hProcess` was returned by ShellExecuteExW and is closed once here.
+        unsafe {
+            WaitForSingleObject(info.hProcess, INFINITE);
+            GetExitCodeProcess(info.hProcess, &mut exit_code);
+            CloseHandle(info.hProcess);
+        }
+        if exit_code != 0 {
+            return Err(format!(
+                "The elevated helper failed (exit code {}).",
+                exit_code
+            ));
+        }
+        let file = fs::File::open(&output.0).map_err(|e| e.to_string())?;
+        read_scans(file)
+    }
+}
+
+/// Runs the helper through `command`, which asks for the password and starts it as
+/// root, and reads the results from its stdout. `runner` names the command in errors.
+#[cfg(unix)]
+fn rescan_piped(mut command: Command, runner: &str) -> Result<Vec<RescannedDir>, String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", runner, e))?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or("Failed to read from the elevated helper.")?;
+    let dirs = read_scans(stdout);
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        return dirs;
+    }
+    match dirs {
+        // Authentication failed or was cancelled before the helper wrote anything.
+        Ok(dirs) if dirs.is_empty() => Err(format!(
+            "The administrator password was not given: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Ok(dirs) => Ok(dirs),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::process::{Command, Stdio};
+
+    use super::{rescan_piped, RescannedDir, ASKPASS_ENV, HELPER_FLAG};
+
+    const SUDO: &str = "/usr/bin/sudo";
+    const OSASCRIPT: &str = "/usr/bin/osascript";
+    const ASKPASS_SCRIPT: &str = r#"display dialog "DiskCheck needs an administrator password to scan folders you cannot read." default answer "" with hidden answer with title "DiskCheck" with icon caution
+text returned of result"#;
+
+    /// Starts this executable as the helper through `sudo -A`, with this executable
+    /// again as the askpass program that shows the password dialog.
+    pub(super) fn rescan(paths: &[String]) -> Result<Vec<RescannedDir>, String> {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let mut command = Command::new(SUDO);
+        command
+            .env("SUDO_ASKPASS", &exe)
+            .env(ASKPASS_ENV, "1")
+            .args(["-A", "--"])
+            .arg(&exe)
+            .arg(HELPER_FLAG)
+            .args(paths);
+        rescan_piped(command, "sudo")
+    }
+
+    /// Asks for the password in a dialog and prints it for sudo. Exits with 1 when the
+    /// dialog is cancelled.
+    pub(crate) fn ask_password() -> i32 {
+        let output = Command::new(OSASCRIPT)
+            .args(["-e", ASKPASS_SCRIPT])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {
+                print!("{}", String::from_utf8_lossy(&output.stdout));
+                0
+            }
+            _ => 1,
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::process::Command;
+
+    use super::{rescan_piped, RescannedDir, HELPER_FLAG};
+
+    const PKEXEC: &str = "pkexec";
+
+    /// Starts this executable as the helper through polkit, whose agent asks for the
+    /// password.
+    pub(super) fn rescan(paths: &[String]) -> Result<Vec<RescannedDir>, String> {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let mut command = Command::new(PKEXEC);
+        command.arg(&exe).arg(HELPER_FLAG).args(paths);
+        rescan_piped(command, PKEXEC)
+    }
+}
+
+/// The folders the scan `scan_id` was denied and left out.
+pub fn list_denied_dirs(store: &ScanStore, scan_id: &str) -> Result<Vec<String>, String> {
+    Ok(store.get(scan_id)?.summary.denied_dirs.clone())
+}
+
+/// Rescans folders the scan `scan_id` was denied with administrator rights (after a UAC
+/// prompt on Windows, a password dialog elsewhere) and adds what it finds to the stored
+/// scan. `paths` picks some of
+/// the denied folders; all of them by default.
+pub async fn rescan_denied(
+    store: &ScanStore,
+    scan_id: String,
+    paths: Option<Vec<String>>,
+    min_node_bytes: Option<u64>,
+) -> Result<ScanResult, String> {
+    let denied = list_denied_dirs(store, &scan_id)?;
+    let paths = match paths {
+        Some(paths) => {
+            // Only folders the scan reported; the helper runs with rights the UI lacks.
+            if let Some(path) = paths.iter().find(|path| !denied.contains(path)) {
+                return Err(format!("Not a denied folder of this scan: {}", path));
+            }
+            paths
+        }
+        None => denied,
+    };
+    if paths.is_empty() {
+        return Err("This scan has no denied folders to rescan.".to_string());
+    }
+
+    tracing::info!(scan_id = %scan_id, folders = paths.len(), "elevated rescan started");
+    let rescanned = {
+        let paths = paths.clone();
+        tauri::async_runtime::spawn_blocking(move || platform::rescan(&paths))
+            .await
+            .map_err(|err| err.to_string())?
+            .inspect_err(|err| tracing::warn!(error = %err, "elevated rescan failed"))?
+    };
+
+    let mut attached = 0;
+    for dir in rescanned {
+        // The helper's output is trusted no further than the folders it was asked for.
+        if !paths.iter().any(|path| path == dir.tree.root_path()) {
+            continue;
+        }
+        let path = dir.tree.root_path().to_string();
+        // The scan already counted the denied folder itself.
+        let dir_count = dir.dir_count.saturating_sub(1);
+        if store.attach(&scan_id, dir.tree, dir.file_count, dir_count)? {
+            attached += 1;
+        } else {
+            tracing::warn!(path = %path, "rescanned folder no longer fits the scan");
+        }
+    }
+    tracing::info!(scan_id = %scan_id, attached, "elevated rescan finished");
+    let scan = store.get(&scan_id)?;
+    Ok(ScanResult {
+        scan_id,
+        root: pruned_view(scan.tree.root(), min_node_bytes),
+        skipped: skipped_preview(&scan.summary),
+        usage_check: None,
+        denied_dirs: scan.summary.denied_dirs.clone(),
+        partial: scan.summary.partial,
+    })
+}
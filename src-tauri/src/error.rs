@@ -0,0 +1,73 @@
+use serde::Serialize;
+use std::{any::Any, fmt, path::Path};
+
+/// Error returned by commands that modify the disk, reach a network share or scan, so
+/// the UI can tell a policy refusal, a credential prompt or a crash apart from an
+/// ordinary failure.
+/// Serialises as `{ "kind": ..., "message": ... }`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum CommandError {
+    /// Safe mode is on; nothing was touched.
+    SafeModeEnabled(String),
+    /// The share wants a user name and password, or refused the saved ones.
+    CredentialsRequired(String),
+    /// Any other failure, with a message for the user.
+    Failed(String),
+    /// A bug: the task panicked. Serialises its message as `{ message, path? }`.
+    Internal(InternalError),
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::Failed(message)
+    }
+}
+
+impl From<CommandError> for String {
+    fn from(error: CommandError) -> Self {
+        match error {
+            CommandError::SafeModeEnabled(message)
+            | CommandError::CredentialsRequired(message)
+            | CommandError::Failed(message) => message,
+            CommandError::Internal(error) => error.to_string(),
+        }
+    }
+}
+
+/// A panic caught in a background task, e.g. a scan, with the path it was working on.
+/// The task is abandoned; the app carries on and the next one starts afresh.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InternalError {
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl InternalError {
+    /// From the payload of a caught panic, with the message `panic!` was given when it
+    /// was a string.
+    pub fn from_panic(payload: Box<dyn Any + Send>, path: Option<&Path>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&str>() {
+                Ok(message) => message.to_string(),
+                Err(_) => "unknown panic".to_string(),
+            },
+        };
+        Self {
+            message,
+            path: path.map(|path| path.to_string_lossy().into_owned()),
+        }
+    }
+}
+
+impl fmt::Display for InternalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "Internal error at {}: {}", path, self.message),
+            None => write!(f, "Internal error: {}", self.message),
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tauri::Manager;
+
+use crate::{
+    scan_index::{self, IndexOptions},
+    store::ScanStore,
+};
+
+// Small folders are counted outright within this time.
+const COUNT_BUDGET: Duration = Duration::from_millis(300);
+// Then random descents estimate the rest for at most this long.
+const SAMPLE_BUDGET: Duration = Duration::from_millis(500);
+const MAX_PROBES: usize = 5_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EstimateSource {
+    // The last scan of the same folder this session.
+    PreviousScan,
+    // The volume index a scan of the folder or one above it left.
+    Index,
+    // Walked in full.
+    Counted,
+    // Extrapolated from random descents.
+    Sampled,
+}
+
+/// How many files and folders a scan of `path` is likely to visit, as the denominator
+/// of its progress before it has one of its own.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryEstimate {
+    pub path: String,
+    pub files: u64,
+    pub dirs: u64,
+    pub source: EstimateSource,
+}
+
+/// Files and subfolders of one folder; symlinks count as files, as scans do not follow
+/// them. Unreadable folders count as empty.
+struct Listing {
+    files: u64,
+    subdirs: Vec<PathBuf>,
+}
+
+fn list(dir: &Path) -> Listing {
+    let mut listing = Listing {
+        files: 0,
+        subdirs: vec![],
+    };
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return listing;
+    };
+    for entry in read_dir.flatten() {
+        if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            listing.subdirs.push(entry.path());
+        } else {
+            listing.files += 1;
+        }
+    }
+    listing
+}
+
+/// Knuth's estimator: a random descent from the root, where every folder on the way
+/// stands for all its siblings, gives an unbiased guess of the tree's size; the mean of
+/// many descents is close. Listings are cached, so the counted top of the tree is not
+/// read twice.
+fn sample(root: &Path, listings: &mut HashMap<PathBuf, Listing>) -> (u64, u64) {
+    let mut rng = fastrand::Rng::new();
+    let deadline = Instant::now() + SAMPLE_BUDGET;
+    let (mut files, mut dirs, mut probes) = (0f64, 0f64, 0usize);
+    while probes < MAX_PROBES && (probes == 0 || Instant::now() < deadline) {
+        let mut dir = root.to_path_buf();
+        let mut weight = 1f64;
+        loop {
+            let listing = listings.entry(dir.clone()).or_insert_with(|| list(&dir));
+            files += weight * listing.files as f64;
+            dirs += weight * listing.subdirs.len() as f64;
+            if listing.subdirs.is_empty() {
+                break;
+            }
+            weight *= listing.subdirs.len() as f64;
+            dir = listing.subdirs[rng.usize(..listing.subdirs.len())].clone();
+        }
+        probes += 1;
+    }
+    let probes = probes as f64;
+    (
+        (files / probes).round() as u64,
+        (dirs / probes).round() as u64,
+    )
+}
+
+/// Counts `root` breadth first; past `COUNT_BUDGET` falls back to sampling.
+fn count_or_sample(root: &Path) -> (u64, u64, EstimateSource) {
+    let deadline = Instant::now() + COUNT_BUDGET;
+    let mut listings: HashMap<PathBuf, Listing> = HashMap::new();
+    let mut pending = VecDeque::from([root.to_path_buf()]);
+    let (mut files, mut dirs) = (0u64, 0u64);
+    while let Some(dir) = pending.pop_front() {
+        if Instant::now() >= deadline {
+            let (files, dirs) = sample(root, &mut listings);
+            return (files, dirs, EstimateSource::Sampled);
+        }
+        let listing = list(&dir);
+        files += listing.files;
+        dirs += listing.subdirs.len() as u64;
+        pending.extend(listing.subdirs.iter().cloned());
+        listings.insert(dir, listing);
+    }
+    (files, dirs, EstimateSource::Counted)
+}
+
+fn from_index(app: &tauri::AppHandle, path: &Path) -> Option<(u64, u64)> {
+    let file = IndexOptions::new(app, false)?.file_for(path)?;
+    scan_index::load(&file)?.count_below(path)
+}
+
+/// Estimates the files and folders under `path` from the last scan of it, the volume
+/// index, or a short count that turns into sampling on large trees (under a second in
+/// all).
+pub async fn estimate_entries(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<EntryEstimate, String> {
+    let root = PathBuf::from(&path);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", path));
+    }
+    if let Some(summary) = app.state::<ScanStore>().latest_summary(&path) {
+        return Ok(EntryEstimate {
+            path,
+            files: summary.file_count,
+            dirs: summary.dir_count,
+            source: EstimateSource::PreviousScan,
+        });
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let (files, dirs, source) = match from_index(&app, &root) {
+            Some((files, dirs)) => (files, dirs, EstimateSource::Index),
+            None => count_or_sample(&root),
+        };
+        EntryEstimate {
+            path,
+            files,
+            dirs,
+            source,
+        }
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    fileinfo::{file_details, iso8601_utc, owner_name},
+    ncdu,
+    report::{self, ReportKind, ReportRow},
+    scanner::{FsNodeKind, NodeRef},
+    store::{ScanStore, ScanSummary, StoredScan},
+};
+
+const JSON_EXPORT_VERSION: u32 = 1;
+const CSV_HEADER: &str = "path,size,allocated_size,modified,extension,owner";
+// Lets Excel detect UTF-8 instead of assuming the system code page.
+pub(crate) const UTF8_BOM: &str = "\u{feff}";
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    Json,
+    JsonPretty,
+    // Flat list of files, one row each.
+    Csv,
+    // For browsing with `ncdu -f` on machines without a GUI.
+    Ncdu,
+    // Standalone report with summary, top files, extensions and a treemap.
+    Html,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonExport<'a> {
+    version: u32,
+    scan_id: &'a str,
+    summary: &'a ScanSummary,
+    tree: NodeRef<'a>,
+}
+
+pub(crate) fn create_dest(dest: &Path) -> Result<BufWriter<File>, String> {
+    if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if !parent.is_dir() {
+            return Err(format!(
+                "Destination folder does not exist: {}",
+                parent.to_string_lossy()
+            ));
+        }
+    }
+    File::create(dest)
+        .map(BufWriter::new)
+        .map_err(|e| format!("Failed to create {}: {}", dest.to_string_lossy(), e))
+}
+
+fn write_json(scan: &StoredScan, dest: &Path, pretty: bool) -> Result<(), String> {
+    let mut out = create_dest(dest)?;
+    let export = JsonExport {
+        version: JSON_EXPORT_VERSION,
+        scan_id: &scan.id,
+        summary: &scan.summary,
+        tree: scan.tree.root(),
+    };
+    let result = if pretty {
+        serde_json::to_writer_pretty(&mut out, &export)
+    } else {
+        serde_json::to_writer(&mut out, &export)
+    };
+    result.map_err(|e| format!("Failed to write {}: {}", dest.to_string_lossy(), e))?;
+    out.flush()
+        .map_err(|e| format!("Failed to write {}: {}", dest.to_string_lossy(), e))
+}
+
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Streams one row per file >= `min_file_bytes`. Files are re-statted for the columns
+/// the tree does not carry; files that vanished since the scan are left out.
+fn write_csv(scan: &StoredScan, dest: &Path, min_file_bytes: u64) -> Result<(), String> {
+    let write_err =
+        |e: std::io::Error| format!("Failed to write {}: {}", dest.to_string_lossy(), e);
+    let mut out = create_dest(dest)?;
+    writeln!(out, "{}{}", UTF8_BOM, CSV_HEADER).map_err(write_err)?;
+
+    let mut pending: Vec<NodeRef> = vec![scan.tree.root()];
+    while let Some(node) = pending.pop() {
+        if !matches!(node.kind(), FsNodeKind::File) {
+            let children: Vec<NodeRef> = node.children().collect();
+            pending.extend(children.into_iter().rev());
+            continue;
+        }
+        if node.size() < min_file_bytes {
+            continue;
+        }
+
+        let node_path = node.path();
+        let path = Path::new(&node_path);
+        let details = match file_details(path) {
+            Some(details) => details,
+            None => continue,
+        };
+        writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            csv_field(&node_path),
+            details.size,
+            details.allocated_bytes,
+            details.modified_secs.map(iso8601_utc).unwrap_or_default(),
+            csv_field(node.extension().as_deref().unwrap_or("")),
+            csv_field(&owner_name(path).unwrap_or_default()),
+        )
+        .map_err(write_err)?;
+    }
+
+    out.flush().map_err(write_err)
+}
+
+fn write_ncdu(scan: &StoredScan, dest: &Path) -> Result<(), String> {
+    let mut out = create_dest(dest)?;
+    ncdu::write_export(scan, &mut out)
+        .and_then(|()| out.flush().map_err(|e| e.to_string()))
+        .map_err(|e| format!("Failed to write {}: {}", dest.to_string_lossy(), e))
+}
+
+fn write_html(scan: &StoredScan, dest: &Path) -> Result<(), String> {
+    let mut out = create_dest(dest)?;
+    out.write_all(report::render_html(scan).as_bytes())
+        .and_then(|()| out.flush())
+        .map_err(|e| format!("Failed to write {}: {}", dest.to_string_lossy(), e))
+}
+
+fn export_blocking(
+    scan: Arc<StoredScan>,
+    format: ExportFormat,
+    dest: &Path,
+    min_file_bytes: u64,
+) -> Result<(), String> {
+    let scan = scan.complete()?;
+    match format {
+        ExportFormat::Json => write_json(&scan, dest, false),
+        ExportFormat::JsonPretty => write_json(&scan, dest, true),
+        ExportFormat::Csv => write_csv(&scan, dest, min_file_bytes),
+        ExportFormat::Ncdu => write_ncdu(&scan, dest),
+        ExportFormat::Html => write_html(&scan, dest),
+    }
+}
+
+/// Writes the full stored tree to `dest` from the backend, bypassing IPC size limits.
+/// `min_file_bytes` filters the rows of flat (file list) formats.
+pub async fn export_scan(
+    store: &ScanStore,
+    scan_id: String,
+    format: ExportFormat,
+    dest: String,
+    min_file_bytes: Option<u64>,
+) -> Result<(), String> {
+    let scan = store.get(&scan_id)?;
+    let dest = PathBuf::from(dest);
+    let min_file_bytes = min_file_bytes.unwrap_or(0);
+    tauri::async_runtime::spawn_blocking(move || {
+        export_blocking(scan, format, &dest, min_file_bytes)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonReport<'a> {
+    version: u32,
+    scan_id: &'a str,
+    kind: ReportKind,
+    summary: &'a ScanSummary,
+    rows: &'a [ReportRow],
+}
+
+/// Writes a report as JSON or CSV to any writer (a file, or an entry of a bundle).
+pub(crate) fn write_report_to(
+    scan: &StoredScan,
+    kind: ReportKind,
+    as_json: bool,
+    out: &mut impl Write,
+) -> Result<(), String> {
+    let rows = report::aggregate(scan.tree.root(), kind);
+    if as_json {
+        let report = JsonReport {
+            version: JSON_EXPORT_VERSION,
+            scan_id: &scan.id,
+            kind,
+            summary: &scan.summary,
+            rows: &rows,
+        };
+        return serde_json::to_writer_pretty(out, &report).map_err(|e| e.to_string());
+    }
+
+    let io_err = |e: std::io::Error| e.to_string();
+    writeln!(out, "{}key,label,files,bytes,percent", UTF8_BOM).map_err(io_err)?;
+    for row in &rows {
+        writeln!(
+            out,
+            "{},{},{},{},{:.2}",
+            csv_field(&row.key),
+            csv_field(&row.label),
+            row.files,
+            row.bytes,
+            row.percent
+        )
+        .map_err(io_err)?;
+    }
+    Ok(())
+}
+
+fn write_report(scan: &StoredScan, kind: ReportKind, dest: &Path) -> Result<(), String> {
+    let mut out = create_dest(dest)?;
+    // The format follows the file name so the save dialog alone decides it.
+    let as_json = dest
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    write_report_to(scan, kind, as_json, &mut out)
+        .and_then(|()| out.flush().map_err(|e| e.to_string()))
+        .map_err(|e| format!("Failed to write {}: {}", dest.to_string_lossy(), e))
+}
+
+/// Writes an aggregation report (extensions, categories, owners or ages) of a stored
+/// scan as CSV, or as JSON when `dest` ends in `.json`.
+pub async fn export_report(
+    store: &ScanStore,
+    scan_id: String,
+    kind: ReportKind,
+    dest: String,
+) -> Result<(), String> {
+    let scan = store.get(&scan_id)?;
+    let dest = PathBuf::from(dest);
+    tauri::async_runtime::spawn_blocking(move || {
+        let scan = scan.complete()?;
+        write_report(&scan, kind, &dest)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,203 @@
+use std::{fs, path::Path};
+
+use crate::store::unix_secs;
+
+/// Per-file details that are not part of the scanned tree and are looked up on demand.
+#[derive(Debug, Clone)]
+pub(crate) struct FileDetails {
+    pub size: u64,
+    // Space actually occupied on disk (cluster rounding, compression, sparse files).
+    pub allocated_bytes: u64,
+    pub modified_secs: Option<u64>,
+}
+
+pub(crate) fn file_details(path: &Path) -> Option<FileDetails> {
+    let meta = fs::symlink_metadata(path).ok()?;
+    Some(FileDetails {
+        size: meta.len(),
+        allocated_bytes: platform::allocated_bytes(path, &meta),
+        modified_secs: meta.modified().ok().map(unix_secs),
+    })
+}
+
+pub(crate) use platform::owner_name;
+
+/// Human-readable size, matching `formatBytes` in the frontend.
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "KB", "MB", "GB", "TB", "PB"];
+    if bytes == 0 {
+        return "0 B".to_string();
+    }
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let decimals = if value >= 100.0 || unit == 0 {
+        0
+    } else if value >= 10.0 {
+        1
+    } else {
+        2
+    };
+    format!("{:.*} {}", decimals, value, UNITS[unit])
+}
+
+/// Formats seconds since the Unix epoch as an ISO-8601 UTC timestamp.
+pub(crate) fn iso8601_utc(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil-from-days (Howard Hinnant), valid for every date after the epoch.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        (rem % 3_600) / 60,
+        rem % 60
+    )
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::{
+        collections::HashMap,
+        ffi::CStr,
+        fs,
+        os::unix::fs::MetadataExt,
+        path::Path,
+        sync::{Mutex, OnceLock},
+    };
+
+    // st_blocks is always counted in 512-byte units, whatever the filesystem block size.
+    const STAT_BLOCK_SIZE: u64 = 512;
+
+    pub(super) fn allocated_bytes(_path: &Path, meta: &fs::Metadata) -> u64 {
+        meta.blocks().saturating_mul(STAT_BLOCK_SIZE)
+    }
+
+    fn lookup_user(uid: u32) -> Option<String> {
+        let mut buf = vec![0 as libc::c_char; 4096];
+        let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::passwd = std::ptr::null_mut();
+        // SAFETY: all pointers refer to live, correctly sized buffers owned by this frame.
+        let rc =
+            unsafe { libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+        if rc != 0 || result.is_null() {
+            return None;
+        }
+        // SAFETY: on success pw_name points into `buf` and is NUL-terminated.
+        let name = unsafe { CStr::from_ptr(pwd.pw_name) };
+        Some(name.to_string_lossy().into_owned())
+    }
+
+    /// Owner user name, falling back to the numeric uid. Lookups are cached since
+    /// exports ask for the same handful of users over and over.
+    pub(crate) fn owner_name(path: &Path) -> Option<String> {
+        static CACHE: OnceLock<Mutex<HashMap<u32, String>>> = OnceLock::new();
+
+        let uid = fs::symlink_metadata(path).ok()?.uid();
+        let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut cache = cache.lock().ok()?;
+        Some(
+            cache
+                .entry(uid)
+                .or_insert_with(|| lookup_user(uid).unwrap_or_else(|| uid.to_string()))
+                .clone(),
+        )
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use crate::volumes::{from_wide, to_wide};
+    use std::{ffi::c_void, fs, path::Path, ptr::null_mut};
+    use windows_sys::Win32::{
+        Foundation::{GetLastError, LocalFree, NO_ERROR},
+        Security::{
+            Authorization::{GetNamedSecurityInfoW, SE_FILE_OBJECT},
+            LookupAccountSidW, OWNER_SECURITY_INFORMATION,
+        },
+        Storage::FileSystem::{GetCompressedFileSizeW, INVALID_FILE_SIZE},
+    };
+
+    /// Compressed/sparse-aware size; falls back to the logical size on failure.
+    pub(super) fn allocated_bytes(path: &Path, meta: &fs::Metadata) -> u64 {
+        let wide = to_wide(path.as_os_str());
+        let mut high: u32 = 0;
+        // SAFETY: `wide` is NUL-terminated and `high` is a valid out pointer.
+        let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+        // INVALID_FILE_SIZE is also a legal low word, so check the last error too.
+        if low == INVALID_FILE_SIZE && unsafe { GetLastError() } != NO_ERROR {
+            return meta.len();
+        }
+        (u64::from(high) << 32) | u64::from(low)
+    }
+
+    /// `DOMAIN\user` of the file's owner SID.
+    pub(crate) fn owner_name(path: &Path) -> Option<String> {
+        let wide = to_wide(path.as_os_str());
+        let mut owner: *mut c_void = null_mut();
+        let mut descriptor: *mut c_void = null_mut();
+        // SAFETY: out pointers are valid; `descriptor` is released with LocalFree below.
+        let rc = unsafe {
+            GetNamedSecurityInfoW(
+                wide.as_ptr(),
+                SE_FILE_OBJECT,
+                OWNER_SECURITY_INFORMATION,
+                &mut owner,
+                null_mut(),
+                null_mut(),
+                null_mut(),
+                &mut descriptor,
+            )
+        };
+        if rc != NO_ERROR {
+            return None;
+        }
+
+        let mut name = [0u16; 256];
+        let mut domain = [0u16; 256];
+        let mut name_len = name.len() as u32;
+        let mut domain_len = domain.len() as u32;
+        let mut sid_use = 0;
+        // SAFETY: `owner` points into `descriptor`, which is still alive.
+        let ok = unsafe {
+            LookupAccountSidW(
+                std::ptr::null(),
+                owner,
+                name.as_mut_ptr(),
+                &mut name_len,
+                domain.as_mut_ptr(),
+                &mut domain_len,
+                &mut sid_use,
+            )
+        };
+        // SAFETY: `descriptor` was allocated by GetNamedSecurityInfoW.
+        unsafe { LocalFree(descriptor) };
+        if ok == 0 {
+            return None;
+        }
+
+        let name = from_wide(&name);
+        let domain = from_wide(&domain);
+        Some(if domain.is_empty() {
+            name
+        } else {
+            format!("{}\\{}", domain, name)
+        })
+    }
+}
@@ -0,0 +1,357 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::Emitter;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{
+    baseline::ChangedEntry,
+    budgets::{grown_files, measure},
+    fileinfo::format_bytes,
+    scanner::{FsNodeKind, ScanTree},
+};
+
+const RAPID_GROWTH_EVENT: &str = "rapid_growth";
+const HOT_DIRECTORIES_EVENT: &str = "hot_directories";
+const DEFAULT_INTERVAL_SECS: u64 = 5 * 60;
+// Each sample re-scans the folder.
+const MIN_INTERVAL_SECS: u64 = 60;
+// Growth is measured against the oldest sample this recent, and only once the samples
+// span a while, so one burst between two samples does not count as a sustained rate.
+const RATE_WINDOW: Duration = Duration::from_secs(60 * 60);
+const MIN_RATE_SPAN: Duration = Duration::from_secs(15 * 60);
+const SECS_PER_HOUR: f64 = 3600.0;
+// Fastest-growing files listed per alert.
+const MAX_GROWN_FILES: usize = 10;
+// Per watched folder.
+const MAX_HOT_DIRECTORIES: usize = 20;
+// How often the worker wakes up to check whether it was stopped.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A folder and the growth rate it may not exceed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrowthWatch {
+    pub path: String,
+    pub max_bytes_per_hour: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrowthConfig {
+    pub folders: Vec<GrowthWatch>,
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+    #[serde(default = "default_notify")]
+    pub notify: bool,
+}
+
+fn default_notify() -> bool {
+    true
+}
+
+impl GrowthConfig {
+    fn interval(&self) -> Duration {
+        Duration::from_secs(
+            self.interval_secs
+                .unwrap_or(DEFAULT_INTERVAL_SECS)
+                .max(MIN_INTERVAL_SECS),
+        )
+    }
+}
+
+/// A watched folder's latest size and growth rate.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrowthRate {
+    pub path: String,
+    pub size_bytes: u64,
+    // Absent until the samples span long enough to tell.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_per_hour: Option<i64>,
+    pub max_bytes_per_hour: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrowthAlert {
+    pub path: String,
+    pub size_bytes: u64,
+    pub bytes_per_hour: i64,
+    pub max_bytes_per_hour: u64,
+    // Files grown the most since the previous sample.
+    pub grown_files: Vec<ChangedEntry>,
+}
+
+/// Samples the size of watched folders and alerts when one grows too fast.
+#[derive(Default)]
+pub struct GrowthMonitor {
+    // Stops the running worker.
+    current: Mutex<Option<Arc<AtomicBool>>>,
+    rates: Arc<Mutex<HashMap<String, GrowthRate>>>,
+    // By watched folder.
+    hot: Arc<Mutex<HashMap<String, Vec<HotDirectory>>>>,
+}
+
+/// A folder's top-level directory, by how fast it grew over the recent samples.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotDirectory {
+    pub path: String,
+    // The watched folder it is in.
+    pub watched_path: String,
+    pub size_bytes: u64,
+    pub bytes_per_hour: i64,
+}
+
+/// Recent sizes, oldest first.
+#[derive(Default)]
+struct History {
+    sizes: VecDeque<(Instant, u64)>,
+}
+
+impl History {
+    fn add(&mut self, at: Instant, size: u64) {
+        self.sizes.push_back((at, size));
+        while self
+            .sizes
+            .front()
+            .is_some_and(|(first, _)| at.duration_since(*first) > RATE_WINDOW)
+        {
+            self.sizes.pop_front();
+        }
+    }
+
+    /// Bytes per hour since the oldest sample in the window, once the samples span at
+    /// least `min_span`.
+    fn rate(&self, min_span: Duration) -> Option<i64> {
+        let (first_at, first) = self.sizes.front()?;
+        let (last_at, last) = self.sizes.back()?;
+        let span = last_at.duration_since(*first_at);
+        if span.is_zero() || span < min_span {
+            return None;
+        }
+        let hours = span.as_secs_f64() / SECS_PER_HOUR;
+        Some(((*last as f64 - *first as f64) / hours) as i64)
+    }
+}
+
+/// A watched folder's recent sizes and those of its top-level directories, and its tree
+/// at the last sample.
+#[derive(Default)]
+struct Samples {
+    sizes: History,
+    children: HashMap<String, History>,
+    tree: Option<ScanTree>,
+}
+
+impl Samples {
+    fn add(&mut self, at: Instant, tree: &ScanTree) {
+        self.sizes.add(at, tree.size());
+        let mut children = HashMap::new();
+        for child in tree.root().children() {
+            if !matches!(child.kind(), FsNodeKind::Directory) {
+                continue;
+            }
+            let path = child.path();
+            let mut history = self.children.remove(&path).unwrap_or_default();
+            history.add(at, child.size());
+            children.insert(path, history);
+        }
+        // Directories gone since the last sample are dropped.
+        self.children = children;
+    }
+
+    /// The top-level directories that grew over the window, fastest first.
+    fn hot(&self, watched_path: &str) -> Vec<HotDirectory> {
+        let mut hot: Vec<HotDirectory> = self
+            .children
+            .iter()
+            .filter_map(|(path, history)| {
+                let bytes_per_hour = history.rate(Duration::ZERO).filter(|rate| *rate > 0)?;
+                Some(HotDirectory {
+                    path: path.clone(),
+                    watched_path: watched_path.to_string(),
+                    size_bytes: history.sizes.back().map_or(0, |(_, size)| *size),
+                    bytes_per_hour,
+                })
+            })
+            .collect();
+        hot.sort_by_key(|dir| std::cmp::Reverse(dir.bytes_per_hour));
+        hot.truncate(MAX_HOT_DIRECTORIES);
+        hot
+    }
+}
+
+fn notify_rapid_growth(app: &tauri::AppHandle, alert: &GrowthAlert) {
+    let _ = app
+        .notification()
+        .builder()
+        .title("Folder growing fast")
+        .body(format!(
+            "{} is growing by {} an hour (limit {}).",
+            alert.path,
+            format_bytes(alert.bytes_per_hour.max(0) as u64),
+            format_bytes(alert.max_bytes_per_hour)
+        ))
+        .show();
+}
+
+/// Every watched folder's hot directories together, fastest first.
+fn all_hot(hot: &HashMap<String, Vec<HotDirectory>>) -> Vec<HotDirectory> {
+    let mut all: Vec<HotDirectory> = hot.values().flatten().cloned().collect();
+    all.sort_by_key(|dir| std::cmp::Reverse(dir.bytes_per_hour));
+    all
+}
+
+fn run_monitor(
+    app: tauri::AppHandle,
+    config: GrowthConfig,
+    rates: Arc<Mutex<HashMap<String, GrowthRate>>>,
+    hot: Arc<Mutex<HashMap<String, Vec<HotDirectory>>>>,
+    stop: Arc<AtomicBool>,
+) {
+    // Folders currently over their rate; alerted once per crossing, not every sample.
+    let mut fast: HashSet<String> = HashSet::new();
+    let mut samples: HashMap<String, Samples> = HashMap::new();
+    let mut next_check = Instant::now();
+
+    while !stop.load(Ordering::Relaxed) {
+        if Instant::now() < next_check {
+            thread::sleep(STOP_POLL_INTERVAL);
+            continue;
+        }
+        next_check = Instant::now() + config.interval();
+
+        for watch in &config.folders {
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+            let tree = match measure(&app, Path::new(&watch.path)) {
+                Ok(tree) => tree,
+                // Missing folders are skipped until they return, with a fresh history.
+                Err(error) => {
+                    tracing::debug!(path = %watch.path, error = %error, "growth sample skipped");
+                    samples.remove(&watch.path);
+                    if let Ok(mut hot) = hot.lock() {
+                        hot.remove(&watch.path);
+                    }
+                    continue;
+                }
+            };
+            let sampled = samples.entry(watch.path.clone()).or_default();
+            sampled.add(Instant::now(), &tree);
+            let bytes_per_hour = sampled.sizes.rate(MIN_RATE_SPAN);
+            if let Ok(mut rates) = rates.lock() {
+                rates.insert(
+                    watch.path.clone(),
+                    GrowthRate {
+                        path: watch.path.clone(),
+                        size_bytes: tree.size(),
+                        bytes_per_hour,
+                        max_bytes_per_hour: watch.max_bytes_per_hour,
+                    },
+                );
+            }
+            if let Ok(mut hot) = hot.lock() {
+                hot.insert(watch.path.clone(), sampled.hot(&watch.path));
+            }
+
+            match bytes_per_hour {
+                Some(rate) if rate > 0 && rate as u64 > watch.max_bytes_per_hour => {
+                    if fast.insert(watch.path.clone()) {
+                        let alert = GrowthAlert {
+                            path: watch.path.clone(),
+                            size_bytes: tree.size(),
+                            bytes_per_hour: rate,
+                            max_bytes_per_hour: watch.max_bytes_per_hour,
+                            grown_files: grown_files(&tree, sampled.tree.as_ref(), MAX_GROWN_FILES),
+                        };
+                        tracing::warn!(
+                            path = %alert.path,
+                            bytes_per_hour = alert.bytes_per_hour,
+                            max_bytes_per_hour = alert.max_bytes_per_hour,
+                            "folder growing fast"
+                        );
+                        let _ = app.emit(RAPID_GROWTH_EVENT, &alert);
+                        if config.notify {
+                            notify_rapid_growth(&app, &alert);
+                        }
+                    }
+                }
+                _ => {
+                    fast.remove(&watch.path);
+                }
+            }
+            sampled.tree = Some(tree);
+        }
+
+        if let Ok(hot) = hot.lock() {
+            let _ = app.emit(HOT_DIRECTORIES_EVENT, all_hot(&hot));
+        }
+    }
+}
+
+impl GrowthMonitor {
+    pub fn start(&self, app: tauri::AppHandle, config: GrowthConfig) -> Result<(), String> {
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = current.take() {
+            previous.store(true, Ordering::Relaxed);
+        }
+        self.clear();
+        if config.folders.is_empty() {
+            return Ok(());
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let worker_stop = stop.clone();
+        let rates = self.rates.clone();
+        let hot = self.hot.clone();
+        thread::Builder::new()
+            .name("growth-monitor".to_string())
+            .spawn(move || run_monitor(app, config, rates, hot, worker_stop))
+            .map_err(|e| format!("Failed to start the growth monitor: {}", e))?;
+
+        *current = Some(stop);
+        Ok(())
+    }
+
+    pub fn stop(&self) -> Result<(), String> {
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = current.take() {
+            previous.store(true, Ordering::Relaxed);
+        }
+        self.clear();
+        Ok(())
+    }
+
+    fn clear(&self) {
+        if let Ok(mut rates) = self.rates.lock() {
+            rates.clear();
+        }
+        if let Ok(mut hot) = self.hot.lock() {
+            hot.clear();
+        }
+    }
+
+    /// The watched folders' latest sizes and growth rates, as far as sampled yet.
+    pub fn rates(&self) -> Result<Vec<GrowthRate>, String> {
+        let rates = self.rates.lock().map_err(|e| e.to_string())?;
+        Ok(rates.values().cloned().collect())
+    }
+
+    /// The top-level directories of the watched folders that grew over the last hour of
+    /// samples, fastest first. Rates need two samples of a directory.
+    pub fn hot_directories(&self) -> Result<Vec<HotDirectory>, String> {
+        let hot = self.hot.lock().map_err(|e| e.to_string())?;
+        Ok(all_hot(&hot))
+    }
+}
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    io::Read,
+    path::Path,
+    time::{Duration, Instant},
+};
+use tauri::Emitter;
+
+use crate::cloud_sync::open_local;
+
+const HASH_PROGRESS_EVENT: &str = "hash_progress";
+const READ_BUFFER_BYTES: usize = 1024 * 1024;
+// Smaller files hash too fast for progress to be worth showing.
+const PROGRESS_MIN_BYTES: u64 = 64 * 1024 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HashAlgorithm {
+    #[default]
+    Blake3,
+    Sha256,
+}
+
+enum Hasher {
+    Blake3(Box<blake3::Hasher>),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Blake3 => Self::Blake3(Box::default()),
+            HashAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Blake3(hasher) => {
+                hasher.update(data);
+            }
+            Self::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finish(self) -> String {
+        match self {
+            Self::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+            Self::Sha256(hasher) => hex::encode(hasher.finalize()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileHash {
+    pub path: String,
+    pub algorithm: HashAlgorithm,
+    // Lowercase hex.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HashProgressPayload<'a> {
+    path: &'a str,
+    hashed_bytes: u64,
+    total_bytes: u64,
+}
+
+/// Hashes the file at `path` in one streaming pass, calling `progress` with the bytes
+/// hashed so far now and then. Returns the hex digest and the number of bytes read.
+pub(crate) fn hash_path(
+    path: &Path,
+    algorithm: HashAlgorithm,
+    mut progress: impl FnMut(u64),
+) -> Result<(String, u64), String> {
+    let read_err = |e: std::io::Error| format!("Failed to read {}: {}", path.to_string_lossy(), e);
+    let mut file = open_local(path).map_err(read_err)?;
+    let mut hasher = Hasher::new(algorithm);
+    let mut buffer = vec![0u8; READ_BUFFER_BYTES];
+    let mut hashed = 0u64;
+    let mut last_progress = Instant::now();
+    loop {
+        let read = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(read_err(e)),
+        };
+        hasher.update(&buffer[..read]);
+        hashed += read as u64;
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            progress(hashed);
+        }
+    }
+    Ok((hasher.finish(), hashed))
+}
+
+fn hash_blocking(window: &tauri::Window, path: String, algorithm: HashAlgorithm) -> FileHash {
+    let total_bytes = std::fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+    let hashed = hash_path(Path::new(&path), algorithm, |hashed_bytes| {
+        if total_bytes >= PROGRESS_MIN_BYTES {
+            let payload = HashProgressPayload {
+                path: &path,
+                hashed_bytes,
+                total_bytes,
+            };
+            let _ = window.emit_to(window.label(), HASH_PROGRESS_EVENT, payload);
+        }
+    });
+    match hashed {
+        Ok((hash, size)) => FileHash {
+            path,
+            algorithm,
+            hash: Some(hash),
+            size_bytes: Some(size),
+            error: None,
+        },
+        Err(error) => FileHash {
+            path,
+            algorithm,
+            hash: None,
+            size_bytes: None,
+            error: Some(error),
+        },
+    }
+}
+
+/// Hashes a file with BLAKE3 (the default) or SHA-256, to check a download against a
+/// published checksum. Files from 64 MiB up report `hash_progress` to the window.
+pub async fn hash_file(
+    window: tauri::Window,
+    path: String,
+    algorithm: HashAlgorithm,
+) -> Result<FileHash, String> {
+    if !Path::new(&path).is_file() {
+        return Err(format!("Not a file: {}", path));
+    }
+    let hashed =
+        tauri::async_runtime::spawn_blocking(move || hash_blocking(&window, path, algorithm))
+            .await
+            .map_err(|err| err.to_string())?;
+    match hashed.error {
+        Some(error) => Err(error),
+        None => Ok(hashed),
+    }
+}
+
+/// Hashes several files one after the other, say suspected duplicates, in the order
+/// given. A file that cannot be read gets an error instead of a hash.
+pub async fn hash_files(
+    window: tauri::Window,
+    paths: Vec<String>,
+    algorithm: HashAlgorithm,
+) -> Result<Vec<FileHash>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        paths
+            .into_iter()
+            .map(|path| hash_blocking(&window, path, algorithm))
+            .collect()
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
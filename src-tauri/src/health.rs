@@ -0,0 +1,193 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::process::Command;
+
+// SMART data is read through smartmontools, which knows every vendor quirk we would
+// otherwise have to reimplement per platform (ATA passthrough, NVMe log pages, USB bridges).
+const SMARTCTL: &str = "smartctl";
+
+// Thresholds that turn raw counters into user-facing warnings.
+const WARN_TEMPERATURE_CELSIUS: i64 = 60;
+const WARN_WEAR_PERCENT_USED: i64 = 90;
+
+const ATTR_REALLOCATED_SECTORS: i64 = 5;
+const ATTR_PENDING_SECTORS: i64 = 197;
+// Vendors report SSD wear under different attribute ids (normalized value = % life left).
+const ATTR_WEAR_LEVELING: &[i64] = &[177, 231, 233];
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskHealth {
+    pub device: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature_celsius: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power_on_hours: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reallocated_sectors: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_sectors: Option<i64>,
+    // Percentage of rated endurance used (SSD/NVMe only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wear_percent_used: Option<i64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn run_smartctl(args: &[&str]) -> Result<Value, String> {
+    let output = Command::new(SMARTCTL).args(args).output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            "smartctl was not found. Install smartmontools to read disk health data.".to_string()
+        } else {
+            format!("Failed to run smartctl: {}", e)
+        }
+    })?;
+
+    // smartctl's exit status is a bitmask that is non-zero for perfectly usable reports
+    // (e.g. "some attributes were past threshold"), so judge by the JSON instead.
+    serde_json::from_slice(&output.stdout).map_err(|_| {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        format!("smartctl returned no readable output: {}", stderr.trim())
+    })
+}
+
+fn smartctl_messages(report: &Value) -> Option<String> {
+    let messages: Vec<&str> = report
+        .pointer("/smartctl/messages")?
+        .as_array()?
+        .iter()
+        .filter(|m| m.get("severity").and_then(Value::as_str) == Some("error"))
+        .filter_map(|m| m.get("string").and_then(Value::as_str))
+        .collect();
+    Some(messages.join("; ")).filter(|s| !s.is_empty())
+}
+
+fn ata_attribute(report: &Value, id: i64) -> Option<&Value> {
+    report
+        .pointer("/ata_smart_attributes/table")?
+        .as_array()?
+        .iter()
+        .find(|attr| attr.get("id").and_then(Value::as_i64) == Some(id))
+}
+
+fn ata_raw(report: &Value, id: i64) -> Option<i64> {
+    ata_attribute(report, id)?
+        .pointer("/raw/value")
+        .and_then(Value::as_i64)
+}
+
+pub(crate) fn parse_health(device: &str, report: &Value) -> DiskHealth {
+    let str_field = |ptr: &str| {
+        report
+            .pointer(ptr)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let int_field = |ptr: &str| report.pointer(ptr).and_then(Value::as_i64);
+
+    let wear_percent_used = int_field("/nvme_smart_health_information_log/percentage_used")
+        .or_else(|| {
+            ATTR_WEAR_LEVELING
+                .iter()
+                .find_map(|&id| ata_attribute(report, id)?.get("value")?.as_i64())
+                .map(|life_left| (100 - life_left).clamp(0, 100))
+        });
+
+    let mut health = DiskHealth {
+        device: device.to_string(),
+        model: str_field("/model_name"),
+        serial: str_field("/serial_number"),
+        capacity_bytes: report
+            .pointer("/user_capacity/bytes")
+            .and_then(Value::as_u64),
+        passed: report
+            .pointer("/smart_status/passed")
+            .and_then(Value::as_bool),
+        temperature_celsius: int_field("/temperature/current"),
+        power_on_hours: int_field("/power_on_time/hours"),
+        reallocated_sectors: ata_raw(report, ATTR_REALLOCATED_SECTORS),
+        pending_sectors: ata_raw(report, ATTR_PENDING_SECTORS)
+            .or_else(|| int_field("/nvme_smart_health_information_log/media_errors")),
+        wear_percent_used,
+        warnings: vec![],
+        error: smartctl_messages(report),
+    };
+
+    if health.passed == Some(false) {
+        health.warnings.push(
+            "The drive reports a failing SMART overall-health status. Back up now.".to_string(),
+        );
+    }
+    if let Some(n) = health.reallocated_sectors.filter(|&n| n > 0) {
+        health.warnings.push(format!(
+            "{} reallocated sectors: the surface is degrading.",
+            n
+        ));
+    }
+    if let Some(n) = health.pending_sectors.filter(|&n| n > 0) {
+        health
+            .warnings
+            .push(format!("{} pending/unreadable sectors reported.", n));
+    }
+    if let Some(t) = health
+        .temperature_celsius
+        .filter(|&t| t >= WARN_TEMPERATURE_CELSIUS)
+    {
+        health.warnings.push(format!("Running hot at {} °C.", t));
+    }
+    if let Some(w) = health
+        .wear_percent_used
+        .filter(|&w| w >= WARN_WEAR_PERCENT_USED)
+    {
+        health
+            .warnings
+            .push(format!("{}% of rated write endurance used.", w));
+    }
+
+    health
+}
+
+pub(crate) fn disk_health_blocking() -> Result<Vec<DiskHealth>, String> {
+    let scan = run_smartctl(&["--scan", "-j"])?;
+    let devices = scan
+        .get("devices")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    let mut disks = vec![];
+    for device in devices {
+        let name = match device.get("name").and_then(Value::as_str) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        let dev_type = device.get("type").and_then(Value::as_str).unwrap_or("auto");
+
+        let report = run_smartctl(&["-a", "-j", "-d", dev_type, &name]);
+        match report {
+            Ok(report) => disks.push(parse_health(&name, &report)),
+            Err(err) => disks.push(DiskHealth {
+                device: name,
+                error: Some(err),
+                ..DiskHealth::default()
+            }),
+        }
+    }
+    Ok(disks)
+}
+
+pub async fn disk_health() -> Result<Vec<DiskHealth>, String> {
+    tauri::async_runtime::spawn_blocking(disk_health_blocking)
+        .await
+        .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,67 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    csv_import, ncdu,
+    scanner::{pruned_view, ScanResult, ScanTree},
+    store::{unix_secs, ScanStore, ScanSummary},
+};
+
+fn import_blocking(path: &Path) -> Result<(ScanTree, ScanSummary), String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.to_string_lossy(), e))?;
+    // CSV exports carry no reliable scan date; the file's own mtime is the best guess.
+    let exported_at = file
+        .metadata()
+        .and_then(|meta| meta.modified())
+        .map(unix_secs)
+        .unwrap_or(0);
+    let mut reader = BufReader::new(file);
+
+    // Sniff the format from the first meaningful byte instead of trusting the extension.
+    let first = reader
+        .fill_buf()
+        .map_err(|e| format!("Failed to read {}: {}", path.to_string_lossy(), e))?
+        .iter()
+        .copied()
+        .find(|b| !b.is_ascii_whitespace());
+    let (tree, mut summary) = match first {
+        Some(b'[') => ncdu::parse_export(reader)?,
+        // Anything else may be a WizTree/TreeSize CSV; that parser rejects non-CSV input.
+        Some(_) => {
+            let mut bytes = vec![];
+            reader
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed to read {}: {}", path.to_string_lossy(), e))?;
+            csv_import::parse_export(&bytes)?
+        }
+        None => return Err(format!("The file is empty: {}", path.to_string_lossy())),
+    };
+    if summary.started_at_secs == 0 {
+        summary.started_at_secs = exported_at;
+    }
+    Ok((ScanTree::from(&tree), summary))
+}
+
+/// Loads an export made elsewhere into the result store so it can be explored like a
+/// local scan. Only the export file itself is read.
+pub async fn import_scan(store: &ScanStore, path: String) -> Result<ScanResult, String> {
+    let path = PathBuf::from(path);
+    let (tree, summary) = tauri::async_runtime::spawn_blocking(move || import_blocking(&path))
+        .await
+        .map_err(|err| err.to_string())??;
+
+    let root = pruned_view(tree.root(), None);
+    let scan_id = store.insert(tree, summary)?;
+    Ok(ScanResult {
+        scan_id,
+        root,
+        skipped: vec![],
+        usage_check: None,
+        denied_dirs: vec![],
+        partial: false,
+    })
+}
@@ -0,0 +1,198 @@
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::scanner::{display_name, file_extension_lower, walk_files};
+
+// Disk images and installers that tend to pile up in Downloads folders.
+pub(crate) const INSTALLER_EXTENSIONS: &[&str] = &["iso", "img", "dmg", "msi", "exe"];
+const DEFAULT_MIN_INSTALLER_BYTES: u64 = 50 * 1024 * 1024; // 50 MiB
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+// Upper bounds (inclusive, in days) of the age buckets. The last bucket is open-ended.
+const AGE_BUCKETS: &[(&str, Option<u64>)] = &[
+    ("< 30 days", Some(30)),
+    ("30-90 days", Some(90)),
+    ("90-365 days", Some(365)),
+    ("> 1 year", None),
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallerFile {
+    pub name: String,
+    pub path: String,
+    pub extension: String,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_days: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallerGroup {
+    pub folder: String,
+    pub total_bytes: u64,
+    pub files: Vec<InstallerFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgeBucket {
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_days: Option<u64>,
+    pub count: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallerReport {
+    pub root: String,
+    pub min_bytes: u64,
+    pub total_files: u64,
+    pub total_bytes: u64,
+    pub skipped_entries: u64,
+    pub groups: Vec<InstallerGroup>,
+    pub age_buckets: Vec<AgeBucket>,
+}
+
+pub(crate) fn is_installer_extension(ext: &str) -> bool {
+    INSTALLER_EXTENSIONS.contains(&ext)
+}
+
+pub(crate) fn modified_secs(meta: &std::fs::Metadata) -> Option<u64> {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+pub(crate) fn age_days(meta: &std::fs::Metadata, now: SystemTime) -> Option<u64> {
+    let modified = meta.modified().ok()?;
+    // Files dated in the future count as brand new.
+    Some(
+        now.duration_since(modified)
+            .map(|d| d.as_secs() / SECS_PER_DAY)
+            .unwrap_or(0),
+    )
+}
+
+/// Buckets `(age_days, size)` pairs into the standard age histogram.
+pub(crate) fn age_buckets(items: impl IntoIterator<Item = (Option<u64>, u64)>) -> Vec<AgeBucket> {
+    let mut buckets: Vec<AgeBucket> = AGE_BUCKETS
+        .iter()
+        .map(|(label, max_days)| AgeBucket {
+            label: label.to_string(),
+            max_days: *max_days,
+            count: 0,
+            bytes: 0,
+        })
+        .collect();
+    let mut unknown = AgeBucket {
+        label: "Unknown".to_string(),
+        max_days: None,
+        count: 0,
+        bytes: 0,
+    };
+
+    for (age_days, size) in items {
+        let bucket = match age_days {
+            Some(days) => buckets.iter_mut().find(|b| match b.max_days {
+                Some(max) => days <= max,
+                None => true,
+            }),
+            None => None,
+        };
+        let bucket = bucket.unwrap_or(&mut unknown);
+        bucket.count += 1;
+        bucket.bytes = bucket.bytes.saturating_add(size);
+    }
+
+    if unknown.count > 0 {
+        buckets.push(unknown);
+    }
+    buckets
+}
+
+pub(crate) fn find_installers_blocking(root: &Path, min_bytes: u64) -> InstallerReport {
+    let now = SystemTime::now();
+    let mut files: Vec<InstallerFile> = vec![];
+
+    let skipped_entries = walk_files(root, |path, meta| {
+        let size = meta.len();
+        if size < min_bytes {
+            return;
+        }
+        let extension = match file_extension_lower(path) {
+            Some(ext) if is_installer_extension(&ext) => ext,
+            _ => return,
+        };
+        files.push(InstallerFile {
+            name: display_name(path),
+            path: path.to_string_lossy().into_owned(),
+            extension,
+            size,
+            modified_secs: modified_secs(meta),
+            age_days: age_days(meta, now),
+        });
+    });
+
+    let age_buckets = age_buckets(files.iter().map(|f| (f.age_days, f.size)));
+    let total_files = files.len() as u64;
+    let total_bytes = files.iter().fold(0u64, |acc, f| acc.saturating_add(f.size));
+
+    let mut by_folder: BTreeMap<String, Vec<InstallerFile>> = BTreeMap::new();
+    for file in files {
+        let folder = Path::new(&file.path)
+            .parent()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        by_folder.entry(folder).or_default().push(file);
+    }
+
+    let mut groups: Vec<InstallerGroup> = by_folder
+        .into_iter()
+        .map(|(folder, mut files)| {
+            files.sort_by_key(|f| std::cmp::Reverse(f.size));
+            let total_bytes = files.iter().fold(0u64, |acc, f| acc.saturating_add(f.size));
+            InstallerGroup {
+                folder,
+                total_bytes,
+                files,
+            }
+        })
+        .collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.total_bytes));
+
+    InstallerReport {
+        root: root.to_string_lossy().into_owned(),
+        min_bytes,
+        total_files,
+        total_bytes,
+        skipped_entries,
+        groups,
+        age_buckets,
+    }
+}
+
+pub async fn find_installers(
+    path: String,
+    min_bytes: Option<u64>,
+) -> Result<InstallerReport, String> {
+    let root = PathBuf::from(path);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.to_string_lossy()));
+    }
+
+    let min_bytes = min_bytes.unwrap_or(DEFAULT_MIN_INSTALLER_BYTES);
+    tauri::async_runtime::spawn_blocking(move || find_installers_blocking(&root, min_bytes))
+        .await
+        .map_err(|err| err.to_string())
+}
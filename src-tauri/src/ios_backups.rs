@@ -0,0 +1,240 @@
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tauri::Manager;
+
+use crate::{
+    installers::modified_secs,
+    scanner::walk_files,
+    sqlite::{Database, Value},
+};
+
+const MANIFEST_DB: &str = "Manifest.db";
+const FILES_TABLE: &str = "Files";
+// `flags` of a row whose contents are stored in the backup.
+const FLAG_FILE: i64 = 1;
+// Domains of app data: `AppDomain-<bundle id>`, `AppDomainGroup-<group id>`, ...
+const APP_DOMAIN_PREFIXES: &[&str] = &["AppDomain-", "AppDomainGroup-", "AppDomainPlugin-"];
+
+/// Files and bytes one backup domain (an app, or a system area like the camera roll)
+/// takes up.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupDomain {
+    pub domain: String,
+    // Bundle, group or extension id for app domains.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    pub files: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IosBackup {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ios_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_backup_secs: Option<u64>,
+    // Encrypted backups keep their manifest encrypted too, so only the total is known.
+    pub encrypted: bool,
+    // Everything in the backup folder.
+    pub total_bytes: u64,
+    // Largest first; empty when the manifest could not be read.
+    pub domains: Vec<BackupDomain>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IosBackupReport {
+    // Folders searched for backups.
+    pub roots: Vec<String>,
+    // Largest first.
+    pub backups: Vec<IosBackup>,
+    pub total_bytes: u64,
+}
+
+/// Where iTunes, the Apple Devices app and Finder keep backups.
+fn default_roots(app: &tauri::AppHandle) -> Vec<PathBuf> {
+    let mut roots = vec![];
+    if cfg!(target_os = "windows") {
+        roots.extend(
+            app.path()
+                .data_dir()
+                .ok()
+                .map(|dir| dir.join("Apple Computer").join("MobileSync").join("Backup")),
+        );
+        // The Microsoft Store build of iTunes and the Apple Devices app.
+        roots.extend(
+            app.path()
+                .home_dir()
+                .ok()
+                .map(|dir| dir.join("Apple").join("MobileSync").join("Backup")),
+        );
+    } else if cfg!(target_os = "macos") {
+        roots.extend(app.path().home_dir().ok().map(|dir| {
+            dir.join("Library")
+                .join("Application Support")
+                .join("MobileSync")
+                .join("Backup")
+        }));
+    }
+    roots
+}
+
+fn plist_dict(path: &Path) -> Option<plist::Dictionary> {
+    plist::Value::from_file(path).ok()?.into_dictionary()
+}
+
+fn plist_string(dict: Option<&plist::Dictionary>, key: &str) -> Option<String> {
+    dict?.get(key)?.as_string().map(str::to_string)
+}
+
+/// Bytes per domain of the files listed in the backup's `Manifest.db`. Each file is
+/// stored as `<first two hex digits of its id>/<id>`.
+fn domain_sizes(backup: &Path) -> Result<Vec<BackupDomain>, String> {
+    let database = Database::open(&backup.join(MANIFEST_DB))?;
+    let mut domains: HashMap<String, (u64, u64)> = HashMap::new();
+    // Columns: fileID, domain, relativePath, flags, file.
+    database.rows(FILES_TABLE, |row| {
+        let (Some(Value::Text(id)), Some(Value::Text(domain)), Some(Value::Integer(flags))) =
+            (row.first(), row.get(1), row.get(3))
+        else {
+            return;
+        };
+        if *flags != FLAG_FILE || id.len() < 2 || !id.is_ascii() {
+            return;
+        }
+        let Ok(meta) = std::fs::metadata(backup.join(&id[..2]).join(id)) else {
+            return;
+        };
+        let slot = domains.entry(domain.clone()).or_default();
+        slot.0 += 1;
+        slot.1 = slot.1.saturating_add(meta.len());
+    })?;
+
+    let mut domains: Vec<BackupDomain> = domains
+        .into_iter()
+        .map(|(domain, (files, bytes))| BackupDomain {
+            app: APP_DOMAIN_PREFIXES
+                .iter()
+                .find_map(|prefix| domain.strip_prefix(prefix))
+                .map(str::to_string),
+            domain,
+            files,
+            bytes,
+        })
+        .collect();
+    domains.sort_by_key(|d| std::cmp::Reverse(d.bytes));
+    Ok(domains)
+}
+
+/// Reads one backup folder: device details from `Info.plist`, encryption from
+/// `Manifest.plist`, sizes per domain from `Manifest.db`.
+fn analyze_backup(path: &Path) -> IosBackup {
+    let info = plist_dict(&path.join("Info.plist"));
+    let encrypted = plist_dict(&path.join("Manifest.plist"))
+        .and_then(|manifest| manifest.get("IsEncrypted")?.as_boolean())
+        .unwrap_or(false);
+    let mut total_bytes = 0u64;
+    walk_files(path, |_, meta| {
+        total_bytes = total_bytes.saturating_add(meta.len())
+    });
+
+    let (domains, error) = if encrypted {
+        (vec![], None)
+    } else {
+        match domain_sizes(path) {
+            Ok(domains) => (domains, None),
+            Err(error) => {
+                tracing::warn!(path = %path.display(), error = %error, "failed to read backup manifest");
+                (vec![], Some(error))
+            }
+        }
+    };
+    let last_backup_secs = info
+        .as_ref()
+        .and_then(|info| info.get("Last Backup Date")?.as_date())
+        .map(SystemTime::from)
+        .and_then(|date| date.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .or_else(|| std::fs::metadata(path).ok().and_then(|m| modified_secs(&m)));
+    IosBackup {
+        path: path.to_string_lossy().into_owned(),
+        device_name: plist_string(info.as_ref(), "Device Name")
+            .or_else(|| plist_string(info.as_ref(), "Display Name")),
+        product_type: plist_string(info.as_ref(), "Product Type"),
+        ios_version: plist_string(info.as_ref(), "Product Version"),
+        last_backup_secs,
+        encrypted,
+        total_bytes,
+        domains,
+        error,
+    }
+}
+
+fn is_backup(path: &Path) -> bool {
+    path.join(MANIFEST_DB).is_file() || path.join("Manifest.plist").is_file()
+}
+
+/// Analyzes every backup in `roots`; a root that is itself a backup counts as one.
+pub(crate) fn analyze_ios_backups_blocking(roots: &[PathBuf]) -> IosBackupReport {
+    let mut backups = vec![];
+    for root in roots {
+        if is_backup(root) {
+            backups.push(analyze_backup(root));
+            continue;
+        }
+        let Ok(entries) = std::fs::read_dir(root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() && is_backup(&path) {
+                backups.push(analyze_backup(&path));
+            }
+        }
+    }
+    backups.sort_by_key(|b| std::cmp::Reverse(b.total_bytes));
+    IosBackupReport {
+        roots: roots
+            .iter()
+            .map(|root| root.to_string_lossy().into_owned())
+            .collect(),
+        total_bytes: backups
+            .iter()
+            .fold(0u64, |acc, b| acc.saturating_add(b.total_bytes)),
+        backups,
+    }
+}
+
+/// Finds iOS device backups in the usual places, or in `path` (a backup folder or a
+/// folder of them), and reports what each app takes up inside them.
+pub async fn analyze_ios_backups(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<IosBackupReport, String> {
+    let roots = match path {
+        Some(path) => {
+            let root = PathBuf::from(path);
+            if !root.is_dir() {
+                return Err(format!("Not a directory: {}", root.to_string_lossy()));
+            }
+            vec![root]
+        }
+        None => default_roots(&app),
+    };
+    tauri::async_runtime::spawn_blocking(move || analyze_ios_backups_blocking(&roots))
+        .await
+        .map_err(|err| err.to_string())
+}
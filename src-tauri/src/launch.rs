@@ -0,0 +1,83 @@
+use serde::Serialize;
+use std::{path::Path, sync::Mutex};
+use tauri::{Emitter, Manager, Url};
+
+const START_SCAN_EVENT: &str = "start_scan";
+const DEEP_LINK_SCHEME: &str = "diskcheck";
+pub(crate) const MAIN_WINDOW: &str = "main";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StartScanPayload {
+    path: String,
+}
+
+/// A scan requested before the frontend was ready to listen (e.g. the deep link that
+/// launched the app). The UI collects it once on startup.
+#[derive(Default)]
+pub struct PendingLaunch {
+    path: Mutex<Option<String>>,
+}
+
+impl PendingLaunch {
+    pub fn take(&self) -> Option<String> {
+        self.path.lock().ok().and_then(|mut path| path.take())
+    }
+}
+
+/// Extracts the folder of `diskcheck://scan?path=...`.
+fn path_from_url(url: &Url) -> Option<String> {
+    if url.scheme() != DEEP_LINK_SCHEME || url.host_str() != Some("scan") {
+        return None;
+    }
+    url.query_pairs()
+        .find(|(key, _)| key == "path")
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Asks the UI to scan `path`: focuses the main window and emits `start_scan` to it.
+/// Only existing folders are accepted, whoever sent the request.
+pub fn request_scan(app: &tauri::AppHandle, path: String) {
+    if !Path::new(&path).is_dir() {
+        return;
+    }
+    if let Some(pending) = app.try_state::<PendingLaunch>() {
+        if let Ok(mut slot) = pending.path.lock() {
+            *slot = Some(path.clone());
+        }
+    }
+    focus_main_window(app);
+    let _ = app.emit_to(MAIN_WINDOW, START_SCAN_EVENT, StartScanPayload { path });
+}
+
+pub fn focus_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+pub fn handle_urls(app: &tauri::AppHandle, urls: &[Url]) {
+    for path in urls.iter().filter_map(path_from_url) {
+        request_scan(app, path);
+    }
+}
+
+/// Handles a folder passed on the command line (shell context menus, "Open with", or a
+/// second launch forwarded by the single-instance plugin). Relative paths resolve
+/// against `cwd`. `diskcheck://` arguments are left to the deep-link plugin.
+pub fn handle_args(app: &tauri::AppHandle, args: impl IntoIterator<Item = String>, cwd: &Path) {
+    if let Some(arg) = args
+        .into_iter()
+        .find(|arg| !arg.starts_with('-') && !arg.starts_with("diskcheck:"))
+    {
+        // A quoted drive root ("D:\") arrives as `D:"`: its backslash escaped the quote.
+        // Windows file names cannot end in a quote, so it is never part of the path.
+        let arg = match arg.strip_suffix('"') {
+            Some(stripped) if cfg!(windows) => stripped,
+            _ => &arg,
+        };
+        request_scan(app, cwd.join(arg).to_string_lossy().into_owned());
+    }
+}
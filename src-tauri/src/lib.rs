@@ -1,63 +1,1139 @@
+mod agent;
+mod apfs;
+mod api;
+mod archives;
+mod baseline;
+mod benchmark;
+mod budgets;
+mod bundle;
+mod categories;
+mod cli;
+mod clipboard;
+mod cloud_sync;
+mod compare;
+mod content_search;
+mod credentials;
+mod csv_import;
+mod device_storage;
+mod diagnostics;
+mod dir_size;
+mod downloads;
+mod drag_drop;
+mod duplicates;
+mod elevated;
+mod error;
+mod estimate;
+mod export;
+mod fileinfo;
+mod growth;
+mod hashing;
+mod health;
+mod import;
+mod installers;
+mod ios_backups;
+mod launch;
+mod logging;
+mod manifest;
+mod metrics;
+mod monitor;
+mod mtp;
+mod ncdu;
+mod new_files;
+mod open_with;
+mod preview;
+mod priority;
+mod protected;
+mod quota;
+mod remote;
+mod report;
+mod reserved;
+mod reveal;
+mod s3;
+mod sandbox;
+mod savings;
+mod scan_history;
+mod scan_index;
+mod scan_windows;
 mod scanner;
+mod scheduled_report;
+mod settings;
+mod shell_integration;
+mod shortcut;
+mod similar_images;
+mod smb;
+mod snapshot;
+mod spill;
+mod sqlite;
+mod store;
+mod terminal;
+mod trash;
+mod tray;
+mod treemap;
+mod treemap_image;
+mod usage_check;
+mod version_info;
+mod volume_watch;
+mod volumes;
+mod webdav;
+mod well_known;
 
 #[tauri::command]
 async fn scan_directory(
     window: tauri::Window,
+    store: tauri::State<'_, store::ScanStore>,
     path: String,
     min_node_bytes: Option<u64>,
-) -> Result<scanner::FsNode, String> {
-    scanner::scan_directory(window, path, min_node_bytes).await
+    mode: Option<priority::ScanMode>,
+    refresh: Option<bool>,
+) -> Result<scanner::ScanResult, error::CommandError> {
+    scanner::scan_directory(
+        window,
+        &store,
+        path,
+        min_node_bytes,
+        mode.unwrap_or_default(),
+        refresh.unwrap_or(false),
+    )
+    .await
 }
 
 #[tauri::command]
-fn reveal_in_explorer(path: String) -> Result<(), String> {
-    use std::{path::PathBuf, process::Command};
+fn cancel_scan(
+    window: tauri::Window,
+    scans: tauri::State<'_, scanner::RunningScans>,
+) -> Result<bool, String> {
+    scans.cancel(window.label())
+}
 
-    let target = PathBuf::from(path);
-    if !target.exists() {
-        return Err(format!(
-            "Path does not exist: {}",
-            target.to_string_lossy()
-        ));
-    }
+#[tauri::command]
+async fn estimate_entries(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<estimate::EntryEstimate, String> {
+    estimate::estimate_entries(app, path).await
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        Command::new("explorer")
-            .arg("/select,")
-            .arg(&target)
-            .spawn()
-            .map(|_| ())
-            .map_err(|e| e.to_string())
-    }
+#[tauri::command]
+async fn compute_directory_size(
+    window: tauri::Window,
+    path: String,
+    progress: Option<bool>,
+) -> Result<dir_size::DirectorySize, String> {
+    dir_size::compute_directory_size(window, path, progress.unwrap_or(false)).await
+}
+
+#[tauri::command]
+async fn compare_directories(
+    window: tauri::Window,
+    store: tauri::State<'_, store::ScanStore>,
+    a: String,
+    b: String,
+) -> Result<compare::Comparison, String> {
+    compare::compare_directories(window, &store, a, b).await
+}
+
+#[tauri::command]
+async fn compare_volumes(
+    window: tauri::Window,
+    store: tauri::State<'_, store::ScanStore>,
+    a: compare::VolumeSource,
+    b: compare::VolumeSource,
+) -> Result<compare::VolumeComparison, String> {
+    compare::compare_volumes(window, &store, a, b).await
+}
+
+#[tauri::command]
+async fn mark_baseline(
+    app: tauri::AppHandle,
+    scan_id: String,
+) -> Result<baseline::Baseline, String> {
+    baseline::mark_baseline(app, scan_id).await
+}
+
+#[tauri::command]
+fn baselines(app: tauri::AppHandle) -> Result<Vec<baseline::Baseline>, String> {
+    baseline::baselines(&app)
+}
+
+#[tauri::command]
+fn clear_baseline(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, settings::SettingsStore>,
+    root: String,
+) -> Result<bool, error::CommandError> {
+    settings.ensure_mutable()?;
+    Ok(baseline::clear_baseline(&app, &root)?)
+}
+
+#[tauri::command]
+async fn changes_since_baseline(
+    app: tauri::AppHandle,
+    root: String,
+) -> Result<Option<baseline::ChangeReport>, String> {
+    baseline::changes_since_baseline(app, root).await
+}
+
+#[tauri::command]
+async fn new_large_files(
+    app: tauri::AppHandle,
+    root: String,
+    min_bytes: Option<u64>,
+) -> Result<Option<new_files::NewLargeFiles>, String> {
+    new_files::new_large_files(app, root, min_bytes).await
+}
+
+#[tauri::command]
+async fn scan_remote(
+    window: tauri::Window,
+    store: tauri::State<'_, store::ScanStore>,
+    host: String,
+    port: Option<u16>,
+    path: String,
+    min_node_bytes: Option<u64>,
+) -> Result<scanner::ScanResult, String> {
+    remote::scan_remote(window, &store, host, port, path, min_node_bytes).await
+}
+
+#[tauri::command]
+async fn scan_bucket(
+    window: tauri::Window,
+    store: tauri::State<'_, store::ScanStore>,
+    target: s3::BucketTarget,
+    min_node_bytes: Option<u64>,
+) -> Result<s3::BucketScanResult, String> {
+    s3::scan_bucket(window, &store, target, min_node_bytes).await
+}
+
+#[tauri::command]
+async fn scan_webdav(
+    window: tauri::Window,
+    store: tauri::State<'_, store::ScanStore>,
+    url: String,
+    min_node_bytes: Option<u64>,
+) -> Result<scanner::ScanResult, error::CommandError> {
+    webdav::scan_webdav(window, &store, url, min_node_bytes).await
+}
+
+#[tauri::command]
+async fn save_dav_credentials(
+    url: String,
+    username: String,
+    password: String,
+) -> Result<(), String> {
+    webdav::save_dav_credentials(url, username, password).await
+}
+
+#[tauri::command]
+async fn forget_dav_credentials(url: String) -> Result<(), String> {
+    webdav::forget_dav_credentials(url).await
+}
+
+#[tauri::command]
+async fn scan_share(
+    window: tauri::Window,
+    store: tauri::State<'_, store::ScanStore>,
+    target: String,
+    min_node_bytes: Option<u64>,
+    mode: Option<priority::ScanMode>,
+) -> Result<scanner::ScanResult, error::CommandError> {
+    smb::scan_share(
+        window,
+        &store,
+        target,
+        min_node_bytes,
+        mode.unwrap_or_default(),
+    )
+    .await
+}
+
+#[tauri::command]
+async fn list_mtp_devices() -> Result<Vec<mtp::MtpDevice>, String> {
+    mtp::list_mtp_devices().await
+}
+
+#[tauri::command]
+async fn scan_mtp_device(
+    window: tauri::Window,
+    store: tauri::State<'_, store::ScanStore>,
+    id: String,
+    min_node_bytes: Option<u64>,
+    mode: Option<priority::ScanMode>,
+) -> Result<scanner::ScanResult, String> {
+    mtp::scan_mtp_device(window, &store, id, min_node_bytes, mode.unwrap_or_default()).await
+}
+
+#[tauri::command]
+async fn pick_storage_folder(
+    app: tauri::AppHandle,
+    volume: Option<String>,
+) -> Result<Option<tauri_plugin_diskcheck_storage::PickedFolder>, String> {
+    device_storage::pick_storage_folder(app, volume).await
+}
+
+#[tauri::command]
+async fn scan_storage_folder(
+    window: tauri::Window,
+    store: tauri::State<'_, store::ScanStore>,
+    folder: tauri_plugin_diskcheck_storage::PickedFolder,
+    min_node_bytes: Option<u64>,
+) -> Result<scanner::ScanResult, String> {
+    device_storage::scan_storage_folder(window, &store, folder, min_node_bytes).await
+}
+
+#[tauri::command]
+async fn scan_media_storage(
+    window: tauri::Window,
+    store: tauri::State<'_, store::ScanStore>,
+    min_node_bytes: Option<u64>,
+) -> Result<scanner::ScanResult, String> {
+    device_storage::scan_media_storage(window, &store, min_node_bytes).await
+}
+
+#[tauri::command]
+async fn grant_folder(
+    app: tauri::AppHandle,
+) -> Result<Option<tauri_plugin_diskcheck_storage::BookmarkedFolder>, String> {
+    sandbox::grant_folder(app).await
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        Command::new("open")
-            .arg("-R")
-            .arg(&target)
-            .spawn()
-            .map(|_| ())
-            .map_err(|e| e.to_string())
+#[tauri::command]
+fn granted_folders(app: tauri::AppHandle) -> Vec<tauri_plugin_diskcheck_storage::BookmarkedFolder> {
+    sandbox::granted_folders(&app)
+}
+
+#[tauri::command]
+fn revoke_folder(app: tauri::AppHandle, path: String) -> Result<bool, String> {
+    sandbox::revoke_folder(&app, &path)
+}
+
+#[tauri::command]
+async fn save_share_credentials(
+    target: String,
+    username: String,
+    password: String,
+) -> Result<(), String> {
+    smb::save_share_credentials(target, username, password).await
+}
+
+#[tauri::command]
+async fn forget_share_credentials(target: String) -> Result<(), String> {
+    smb::forget_share_credentials(target).await
+}
+
+#[tauri::command]
+fn list_skipped_entries(
+    store: tauri::State<'_, store::ScanStore>,
+    scan_id: String,
+) -> Result<Vec<store::SkippedPath>, String> {
+    scanner::list_skipped_entries(&store, &scan_id)
+}
+
+#[tauri::command]
+fn list_denied_dirs(
+    store: tauri::State<'_, store::ScanStore>,
+    scan_id: String,
+) -> Result<Vec<String>, String> {
+    elevated::list_denied_dirs(&store, &scan_id)
+}
+
+#[tauri::command]
+async fn rescan_denied(
+    store: tauri::State<'_, store::ScanStore>,
+    scan_id: String,
+    paths: Option<Vec<String>>,
+    min_node_bytes: Option<u64>,
+) -> Result<scanner::ScanResult, String> {
+    elevated::rescan_denied(&store, scan_id, paths, min_node_bytes).await
+}
+
+#[tauri::command]
+fn get_scan(
+    store: tauri::State<'_, store::ScanStore>,
+    scan_id: String,
+    min_node_bytes: Option<u64>,
+) -> Result<scanner::ScanResult, String> {
+    scanner::get_scan(&store, &scan_id, min_node_bytes)
+}
+
+#[tauri::command]
+async fn get_children(
+    app: tauri::AppHandle,
+    scan_id: String,
+    path: String,
+    min_node_bytes: Option<u64>,
+) -> Result<Vec<scanner::FsNode>, String> {
+    use tauri::Manager;
+
+    // Loading spilled folders back reads from disk.
+    tauri::async_runtime::spawn_blocking(move || {
+        let store = app.state::<store::ScanStore>();
+        scanner::get_children(&store, &scan_id, &path, min_node_bytes)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+async fn export_scan(
+    store: tauri::State<'_, store::ScanStore>,
+    scan_id: String,
+    format: export::ExportFormat,
+    dest: String,
+    min_file_bytes: Option<u64>,
+) -> Result<(), String> {
+    export::export_scan(&store, scan_id, format, dest, min_file_bytes).await
+}
+
+#[tauri::command]
+async fn export_report(
+    store: tauri::State<'_, store::ScanStore>,
+    scan_id: String,
+    kind: report::ReportKind,
+    dest: String,
+) -> Result<(), String> {
+    export::export_report(&store, scan_id, kind, dest).await
+}
+
+#[tauri::command]
+async fn export_treemap_image(
+    store: tauri::State<'_, store::ScanStore>,
+    scan_id: String,
+    path: Option<String>,
+    format: treemap_image::ImageFormat,
+    dest: String,
+    width: Option<u32>,
+    height: Option<u32>,
+) -> Result<(), String> {
+    treemap_image::export_treemap_image(&store, scan_id, path, format, dest, width, height).await
+}
+
+#[tauri::command]
+async fn import_scan(
+    store: tauri::State<'_, store::ScanStore>,
+    path: String,
+) -> Result<scanner::ScanResult, String> {
+    import::import_scan(&store, path).await
+}
+
+#[tauri::command]
+async fn save_snapshot(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, store::ScanStore>,
+    history: tauri::State<'_, scan_history::ScanHistory>,
+    scan_id: String,
+    dest: String,
+) -> Result<(), String> {
+    let summary = store.get(&scan_id)?.summary.clone();
+    let [annotation] = scan_history::annotations_of(&app, [&summary]).await?;
+    snapshot::save_snapshot(&store, scan_id, annotation, dest.clone()).await?;
+    history.record_snapshot(&app, &summary, std::path::Path::new(&dest));
+    Ok(())
+}
+
+#[tauri::command]
+async fn open_snapshot(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, store::ScanStore>,
+    history: tauri::State<'_, scan_history::ScanHistory>,
+    path: String,
+) -> Result<scanner::ScanResult, String> {
+    let (result, annotation) = snapshot::open_snapshot(&store, path.clone()).await?;
+    let summary = store.get(&result.scan_id)?.summary.clone();
+    history.record_snapshot(&app, &summary, std::path::Path::new(&path));
+    // Notes made on this machine since win over the ones saved with the snapshot.
+    if let Some(annotation) = annotation {
+        let [local] = scan_history::annotations_of(&app, [&summary]).await?;
+        if local.is_none() {
+            history.annotate(
+                &app,
+                &summary.root_path,
+                summary.started_at_secs,
+                annotation,
+            )?;
+        }
     }
+    Ok(result)
+}
+
+#[tauri::command]
+fn list_recent_scans(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, store::ScanStore>,
+    history: tauri::State<'_, scan_history::ScanHistory>,
+) -> Result<Vec<scan_history::RecentScan>, String> {
+    history.recent(&app, &store)
+}
+
+#[tauri::command]
+fn annotate_scan(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, scan_history::ScanHistory>,
+    root_path: String,
+    started_at_secs: u64,
+    annotation: scan_history::ScanAnnotation,
+) -> Result<(), String> {
+    history.annotate(&app, &root_path, started_at_secs, annotation)
+}
+
+#[tauri::command]
+async fn import_fleet(
+    store: tauri::State<'_, store::ScanStore>,
+    path: String,
+) -> Result<agent::FleetImport, String> {
+    agent::import_fleet(&store, path).await
+}
+
+#[tauri::command]
+async fn export_bundle(
+    store: tauri::State<'_, store::ScanStore>,
+    scan_id: String,
+    dest: String,
+) -> Result<(), String> {
+    bundle::export_bundle(&store, scan_id, dest).await
+}
+
+#[tauri::command]
+async fn open_bundle(
+    store: tauri::State<'_, store::ScanStore>,
+    path: String,
+) -> Result<scanner::ScanResult, String> {
+    bundle::open_bundle(&store, path).await
+}
+
+#[tauri::command]
+async fn find_installers(
+    path: String,
+    min_bytes: Option<u64>,
+) -> Result<installers::InstallerReport, String> {
+    installers::find_installers(path, min_bytes).await
+}
+
+#[tauri::command]
+async fn analyze_downloads(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<downloads::DownloadsReport, String> {
+    downloads::analyze_downloads(app, path).await
+}
+
+#[tauri::command]
+async fn cloud_sync_report(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<Vec<cloud_sync::CloudSyncReport>, String> {
+    cloud_sync::cloud_sync_report(app, path).await
+}
+
+#[tauri::command]
+async fn analyze_ios_backups(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<ios_backups::IosBackupReport, String> {
+    ios_backups::analyze_ios_backups(app, path).await
+}
+
+#[tauri::command]
+async fn find_duplicates(
+    store: tauri::State<'_, store::ScanStore>,
+    scan_id: String,
+    min_file_bytes: Option<u64>,
+    similar_images: Option<bool>,
+    similarity: Option<f64>,
+) -> Result<duplicates::DuplicateReport, String> {
+    duplicates::find_duplicates(
+        &store,
+        scan_id,
+        min_file_bytes,
+        similar_images.unwrap_or(false),
+        similarity,
+    )
+    .await
+}
 
-    #[cfg(all(unix, not(target_os = "macos")))]
-    {
-        let dir = target.parent().unwrap_or(&target);
-        Command::new("xdg-open")
-            .arg(dir)
-            .spawn()
-            .map(|_| ())
-            .map_err(|e| e.to_string())
+#[tauri::command]
+async fn hash_file(
+    window: tauri::Window,
+    path: String,
+    algorithm: Option<hashing::HashAlgorithm>,
+) -> Result<hashing::FileHash, String> {
+    hashing::hash_file(window, path, algorithm.unwrap_or_default()).await
+}
+
+#[tauri::command]
+async fn hash_files(
+    window: tauri::Window,
+    paths: Vec<String>,
+    algorithm: Option<hashing::HashAlgorithm>,
+) -> Result<Vec<hashing::FileHash>, String> {
+    hashing::hash_files(window, paths, algorithm.unwrap_or_default()).await
+}
+
+#[tauri::command]
+async fn search_in_files(
+    root: String,
+    pattern: String,
+    max_size: Option<u64>,
+    max_matches: Option<usize>,
+) -> Result<content_search::ContentSearchReport, String> {
+    content_search::search_in_files(root, pattern, max_size, max_matches).await
+}
+
+#[tauri::command]
+async fn executable_version(path: String) -> Result<version_info::ExecutableVersion, String> {
+    version_info::executable_version(path).await
+}
+
+#[tauri::command]
+async fn preview_info(path: String) -> Result<preview::PreviewInfo, String> {
+    preview::preview_info(path).await
+}
+
+#[tauri::command]
+async fn create_checksum_manifest(
+    window: tauri::Window,
+    path: String,
+    dest: Option<String>,
+) -> Result<manifest::ManifestSummary, String> {
+    manifest::create_checksum_manifest(window, path, dest).await
+}
+
+#[tauri::command]
+async fn verify_checksum_manifest(
+    window: tauri::Window,
+    manifest: String,
+    root: Option<String>,
+) -> Result<manifest::ManifestVerification, String> {
+    manifest::verify_checksum_manifest(window, manifest, root).await
+}
+
+#[tauri::command]
+async fn benchmark_scan(
+    app: tauri::AppHandle,
+    path: String,
+    duration_secs: Option<u64>,
+    mode: Option<priority::ScanMode>,
+) -> Result<benchmark::ScanBenchmark, String> {
+    benchmark::benchmark_scan(app, path, duration_secs, mode.unwrap_or_default()).await
+}
+
+#[tauri::command]
+async fn list_volumes(app: tauri::AppHandle) -> Result<Vec<volumes::VolumeInfo>, String> {
+    volumes::list_volumes(app).await
+}
+
+#[tauri::command]
+async fn well_known_paths(app: tauri::AppHandle) -> Result<Vec<well_known::WellKnownPath>, String> {
+    well_known::well_known_paths(app).await
+}
+
+#[tauri::command]
+async fn disk_health() -> Result<Vec<health::DiskHealth>, String> {
+    health::disk_health().await
+}
+
+#[tauri::command]
+async fn volume_info(path: String) -> Result<volumes::VolumeDetails, String> {
+    volumes::volume_info(path).await
+}
+
+#[tauri::command]
+async fn disk_usage(path: String) -> Result<volumes::DiskUsage, String> {
+    volumes::disk_usage(path).await
+}
+
+#[tauri::command]
+async fn eject_volume(
+    settings: tauri::State<'_, settings::SettingsStore>,
+    mount_point: String,
+) -> Result<(), error::CommandError> {
+    settings.ensure_mutable()?;
+    Ok(volumes::eject_volume(mount_point).await?)
+}
+
+#[tauri::command]
+async fn quota_info(path: String) -> Result<quota::QuotaInfo, String> {
+    quota::quota_info(path).await
+}
+
+#[tauri::command]
+async fn reserved_space(
+    mount_point: Option<String>,
+) -> Result<Vec<reserved::ReservedSpaceReport>, String> {
+    reserved::reserved_space(mount_point).await
+}
+
+#[tauri::command]
+async fn purgeable_space(mount_point: Option<String>) -> Result<apfs::PurgeableSpace, String> {
+    apfs::purgeable_space(mount_point).await
+}
+
+#[tauri::command]
+async fn list_local_snapshots(
+    mount_point: Option<String>,
+) -> Result<Vec<apfs::LocalSnapshot>, String> {
+    apfs::list_local_snapshots(mount_point).await
+}
+
+#[tauri::command]
+async fn delete_local_snapshot(
+    settings: tauri::State<'_, settings::SettingsStore>,
+    date: String,
+) -> Result<(), error::CommandError> {
+    settings.ensure_mutable()?;
+    Ok(apfs::delete_local_snapshot(date).await?)
+}
+
+#[tauri::command]
+fn start_space_monitor(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, settings::SettingsStore>,
+    config: monitor::MonitorConfig,
+) -> Result<(), String> {
+    settings::update_settings(&app, &settings, |s| s.space_monitor = Some(config))
+}
+
+#[tauri::command]
+async fn check_folder_budgets(app: tauri::AppHandle) -> Result<Vec<budgets::BudgetStatus>, String> {
+    budgets::check_folder_budgets(app).await
+}
+
+#[tauri::command]
+fn growth_rates(
+    growth: tauri::State<'_, growth::GrowthMonitor>,
+) -> Result<Vec<growth::GrowthRate>, String> {
+    growth.rates()
+}
+
+#[tauri::command]
+fn hot_directories(
+    growth: tauri::State<'_, growth::GrowthMonitor>,
+) -> Result<Vec<growth::HotDirectory>, String> {
+    growth.hot_directories()
+}
+
+#[tauri::command]
+async fn generate_scheduled_report(
+    app: tauri::AppHandle,
+) -> Result<scheduled_report::ScheduledReport, String> {
+    scheduled_report::generate_scheduled_report(app).await
+}
+
+#[tauri::command]
+fn stop_space_monitor(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, settings::SettingsStore>,
+) -> Result<(), String> {
+    settings::update_settings(&app, &settings, |s| s.space_monitor = None)
+}
+
+#[tauri::command]
+fn get_space_monitor(
+    monitor: tauri::State<'_, monitor::SpaceMonitor>,
+) -> Result<Option<monitor::MonitorConfig>, String> {
+    monitor.config()
+}
+
+#[tauri::command]
+fn start_api_server(
+    app: tauri::AppHandle,
+    server: tauri::State<'_, api::ApiServer>,
+    settings: tauri::State<'_, settings::SettingsStore>,
+    port: Option<u16>,
+) -> Result<api::ApiServerInfo, String> {
+    settings::update_settings(&app, &settings, |s| {
+        s.api_server = settings::ApiServerSettings {
+            enabled: true,
+            port,
+        }
+    })?;
+    server
+        .info()?
+        .ok_or_else(|| "The API server did not start.".to_string())
+}
+
+#[tauri::command]
+fn stop_api_server(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, settings::SettingsStore>,
+) -> Result<(), String> {
+    settings::update_settings(&app, &settings, |s| s.api_server.enabled = false)
+}
+
+#[tauri::command]
+fn get_api_server(
+    server: tauri::State<'_, api::ApiServer>,
+) -> Result<Option<api::ApiServerInfo>, String> {
+    server.info()
+}
+
+#[tauri::command]
+fn set_scan_shortcut(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, settings::SettingsStore>,
+    config: Option<shortcut::ScanShortcutConfig>,
+) -> Result<(), String> {
+    settings::update_settings(&app, &settings, |s| s.scan_shortcut = config)
+}
+
+#[tauri::command]
+fn get_scan_shortcut(
+    shortcut: tauri::State<'_, shortcut::ScanShortcut>,
+) -> Result<Option<shortcut::ScanShortcutConfig>, String> {
+    shortcut.config()
+}
+
+#[tauri::command]
+fn set_close_to_tray(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, settings::SettingsStore>,
+    enabled: bool,
+) -> Result<(), String> {
+    settings::update_settings(&app, &settings, |s| s.close_to_tray = enabled)
+}
+
+#[tauri::command]
+fn get_settings(
+    settings: tauri::State<'_, settings::SettingsStore>,
+) -> Result<settings::Settings, String> {
+    settings.get()
+}
+
+#[tauri::command]
+fn set_settings(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, settings::SettingsStore>,
+    settings: settings::Settings,
+) -> Result<(), String> {
+    settings::set_settings(&app, &store, settings)
+}
+
+#[tauri::command]
+async fn get_recent_logs(
+    app: tauri::AppHandle,
+    level: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<logging::LogEntry>, String> {
+    logging::get_recent_logs(app, level, limit).await
+}
+
+#[tauri::command]
+fn open_log_folder(app: tauri::AppHandle) -> Result<(), String> {
+    logging::open_log_folder(&app)
+}
+
+#[tauri::command]
+async fn create_diagnostic_bundle(
+    app: tauri::AppHandle,
+    dest: Option<String>,
+    include_paths: Option<bool>,
+) -> Result<String, String> {
+    diagnostics::create_diagnostic_bundle(app, dest, include_paths.unwrap_or(false)).await
+}
+
+#[tauri::command]
+async fn move_to_trash(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, store::ScanStore>,
+    log: tauri::State<'_, trash::TrashLog>,
+    settings: tauri::State<'_, settings::SettingsStore>,
+    scan_id: Option<String>,
+    paths: Vec<String>,
+    allow_protected: Option<bool>,
+) -> Result<trash::TrashOperation, error::CommandError> {
+    use tauri::Manager;
+
+    settings.ensure_mutable()?;
+    let protected = settings.protected_paths();
+    let allow_protected = allow_protected.unwrap_or(false);
+    let report =
+        trash::move_to_trash(&store, &log, &protected, scan_id, paths, allow_protected).await?;
+    app.state::<savings::Savings>().record(
+        &app,
+        report.freed_bytes as i64,
+        report.trashed.len() as i64,
+    );
+    Ok(report)
+}
+
+#[tauri::command]
+fn protected_paths(settings: tauri::State<'_, settings::SettingsStore>) -> Vec<String> {
+    settings.protected_paths().list()
+}
+
+#[tauri::command]
+async fn restore_from_trash(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, store::ScanStore>,
+    log: tauri::State<'_, trash::TrashLog>,
+    settings: tauri::State<'_, settings::SettingsStore>,
+    operation_id: String,
+) -> Result<trash::RestoreReport, error::CommandError> {
+    use tauri::Manager;
+
+    settings.ensure_mutable()?;
+    let report = trash::restore_from_trash(&store, &log, operation_id).await?;
+    app.state::<savings::Savings>().record(
+        &app,
+        -(report.restored_bytes as i64),
+        -(report.restored.len() as i64),
+    );
+    Ok(report)
+}
+
+#[tauri::command]
+fn savings_report(
+    app: tauri::AppHandle,
+    savings: tauri::State<'_, savings::Savings>,
+) -> Result<savings::SavingsReport, String> {
+    savings.report(&app)
+}
+
+#[tauri::command]
+fn take_launch_path(
+    window: tauri::Window,
+    pending: tauri::State<'_, launch::PendingLaunch>,
+    windows: tauri::State<'_, scan_windows::ScanWindows>,
+) -> Option<String> {
+    if window.label() == launch::MAIN_WINDOW {
+        pending.take()
+    } else {
+        windows.take_path(window.label())
     }
 }
 
+#[tauri::command]
+async fn open_scan_window(app: tauri::AppHandle, path: Option<String>) -> Result<String, String> {
+    scan_windows::open_scan_window(app, path).await
+}
+
+#[tauri::command]
+async fn register_context_menu(
+    settings: tauri::State<'_, settings::SettingsStore>,
+) -> Result<shell_integration::ContextMenuStatus, error::CommandError> {
+    settings.ensure_mutable()?;
+    Ok(shell_integration::register_context_menu().await?)
+}
+
+#[tauri::command]
+async fn unregister_context_menu(
+    settings: tauri::State<'_, settings::SettingsStore>,
+) -> Result<shell_integration::ContextMenuStatus, error::CommandError> {
+    settings.ensure_mutable()?;
+    Ok(shell_integration::unregister_context_menu().await?)
+}
+
+#[tauri::command]
+async fn context_menu_status() -> Result<shell_integration::ContextMenuStatus, String> {
+    shell_integration::context_menu_status().await
+}
+
+#[tauri::command]
+async fn reveal_in_explorer(path: String) -> Result<(), String> {
+    reveal::reveal_in_explorer(path).await
+}
+
+#[tauri::command]
+fn open_terminal(path: String) -> Result<(), String> {
+    terminal::open_terminal(&path)
+}
+
+#[tauri::command]
+async fn open_with_apps(path: String) -> Result<Vec<open_with::OpenWithApp>, String> {
+    open_with::open_with_apps(path).await
+}
+
+#[tauri::command]
+async fn open_with(path: String, app: Option<String>) -> Result<(), String> {
+    open_with::open_with(path, app).await
+}
+
+#[tauri::command]
+fn copy_path(path: String, format: clipboard::PathFormat) -> Result<(), String> {
+    clipboard::copy_path(&path, format)
+}
+
+#[tauri::command]
+fn copy_selection(items: Vec<clipboard::SelectedItem>) -> Result<(), String> {
+    clipboard::copy_selection(&items)
+}
+
+pub use cli::run_cli;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        // Must be registered first: a second launch hands its arguments (a folder or a
+        // diskcheck:// link) to the running instance and exits.
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            launch::focus_main_window(app);
+            launch::handle_args(app, argv.into_iter().skip(1), std::path::Path::new(&cwd));
+        }))
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![scan_directory, reveal_in_explorer])
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_diskcheck_storage::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(shortcut::handle)
+                .build(),
+        )
+        .manage(monitor::SpaceMonitor::default())
+        .manage(budgets::BudgetWatcher::default())
+        .manage(growth::GrowthMonitor::default())
+        .manage(scheduled_report::ReportScheduler::default())
+        .manage(store::ScanStore::default())
+        .manage(scanner::RunningScans::default())
+        .manage(api::ApiServer::default())
+        .manage(launch::PendingLaunch::default())
+        .manage(shortcut::ScanShortcut::default())
+        .manage(tray::CloseToTray::default())
+        .manage(settings::SettingsStore::default())
+        .manage(trash::TrashLog::default())
+        .manage(savings::Savings::default())
+        .manage(scan_history::ScanHistory::default())
+        .manage(scan_windows::ScanWindows::default())
+        .manage(baseline::BaselineReports::default())
+        .setup(|app| {
+            use tauri::Manager;
+            use tauri_plugin_deep_link::DeepLinkExt;
+
+            if let Err(err) = logging::init(app.handle()) {
+                eprintln!("Logging is disabled: {}", err);
+            }
+            tracing::info!(version = env!("CARGO_PKG_VERSION"), "DiskCheck started");
+
+            volume_watch::spawn(app.handle().clone());
+            tray::init(app.handle())?;
+            let settings = app.state::<settings::SettingsStore>().load(app.handle())?;
+            // A shortcut taken by another app must not keep DiskCheck from starting.
+            if let Err(err) = settings::apply(app.handle(), &settings) {
+                tracing::warn!(error = %err, "failed to apply settings");
+            }
+
+            // Installers register the scheme on Windows/Linux; this covers portable
+            // and development builds.
+            #[cfg(any(windows, target_os = "linux"))]
+            let _ = app.deep_link().register_all();
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                launch::handle_urls(app.handle(), &urls);
+            }
+            if let Ok(cwd) = std::env::current_dir() {
+                launch::handle_args(app.handle(), std::env::args().skip(1), &cwd);
+            }
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                launch::handle_urls(&handle, &event.urls());
+            });
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            drag_drop::handle_window_event(window, event);
+            tray::handle_window_event(window, event);
+            scan_windows::handle_window_event(window, event);
+        })
+        .invoke_handler(tauri::generate_handler![
+            scan_directory,
+            cancel_scan,
+            compute_directory_size,
+            estimate_entries,
+            compare_directories,
+            compare_volumes,
+            mark_baseline,
+            baselines,
+            clear_baseline,
+            changes_since_baseline,
+            new_large_files,
+            scan_remote,
+            scan_bucket,
+            scan_webdav,
+            save_dav_credentials,
+            forget_dav_credentials,
+            scan_share,
+            save_share_credentials,
+            forget_share_credentials,
+            list_mtp_devices,
+            scan_mtp_device,
+            pick_storage_folder,
+            scan_storage_folder,
+            scan_media_storage,
+            grant_folder,
+            granted_folders,
+            revoke_folder,
+            list_skipped_entries,
+            list_denied_dirs,
+            rescan_denied,
+            get_scan,
+            get_children,
+            export_scan,
+            export_report,
+            export_treemap_image,
+            import_scan,
+            save_snapshot,
+            open_snapshot,
+            list_recent_scans,
+            annotate_scan,
+            import_fleet,
+            export_bundle,
+            open_bundle,
+            find_installers,
+            analyze_downloads,
+            cloud_sync_report,
+            analyze_ios_backups,
+            find_duplicates,
+            hash_file,
+            hash_files,
+            preview_info,
+            search_in_files,
+            executable_version,
+            create_checksum_manifest,
+            verify_checksum_manifest,
+            benchmark_scan,
+            list_volumes,
+            well_known_paths,
+            volume_info,
+            disk_usage,
+            eject_volume,
+            quota_info,
+            reserved_space,
+            purgeable_space,
+            list_local_snapshots,
+            delete_local_snapshot,
+            start_space_monitor,
+            stop_space_monitor,
+            get_space_monitor,
+            check_folder_budgets,
+            growth_rates,
+            hot_directories,
+            generate_scheduled_report,
+            start_api_server,
+            stop_api_server,
+            get_api_server,
+            set_scan_shortcut,
+            get_scan_shortcut,
+            set_close_to_tray,
+            get_settings,
+            set_settings,
+            get_recent_logs,
+            open_log_folder,
+            create_diagnostic_bundle,
+            move_to_trash,
+            restore_from_trash,
+            savings_report,
+            protected_paths,
+            take_launch_path,
+            open_scan_window,
+            register_context_menu,
+            unregister_context_menu,
+            context_menu_status,
+            disk_health,
+            reveal_in_explorer,
+            open_terminal,
+            open_with_apps,
+            open_with,
+            copy_path,
+            copy_selection
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    fs,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+};
+use tauri::Manager;
+use tauri_plugin_opener::OpenerExt;
+use tracing::Level;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+const LOG_FILE_PREFIX: &str = "diskcheck";
+const LOG_FILE_SUFFIX: &str = "log";
+// One file per day; a week is plenty to look back at a bad scan.
+const MAX_LOG_FILES: usize = 7;
+const DEFAULT_LIMIT: usize = 200;
+const MAX_LIMIT: usize = 5_000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    // Structured fields such as `path` or `error`.
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+}
+
+/// One line of the JSON log file as written by `tracing_subscriber`.
+#[derive(Deserialize)]
+struct LogLine {
+    timestamp: String,
+    level: String,
+    #[serde(default)]
+    target: String,
+    #[serde(default)]
+    fields: Map<String, Value>,
+}
+
+pub(crate) fn log_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path().app_log_dir().map_err(|e| e.to_string())
+}
+
+/// Routes `tracing` events at INFO and above to daily-rotated JSON files in the app log
+/// dir.
+pub fn init(app: &tauri::AppHandle) -> Result<(), String> {
+    let dir = log_dir(app)?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .map_err(|e| format!("Failed to open the log in {}: {}", dir.to_string_lossy(), e))?;
+    tracing_subscriber::fmt()
+        .json()
+        .with_ansi(false)
+        .with_max_level(Level::INFO)
+        .with_writer(appender)
+        .try_init()
+        .map_err(|e| e.to_string())
+}
+
+fn parse_line(line: &str) -> Option<LogEntry> {
+    let mut line: LogLine = serde_json::from_str(line).ok()?;
+    let message = match line.fields.remove("message") {
+        Some(Value::String(message)) => message,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    Some(LogEntry {
+        timestamp: line.timestamp,
+        level: line.level,
+        target: line.target,
+        message,
+        fields: line.fields,
+    })
+}
+
+pub(crate) fn recent_logs_blocking(
+    dir: PathBuf,
+    min_level: Level,
+    limit: usize,
+) -> Result<Vec<LogEntry>, String> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(&dir) {
+        Ok(rd) => rd
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().starts_with(LOG_FILE_PREFIX))
+                    .unwrap_or(false)
+            })
+            .collect(),
+        // Nothing logged yet.
+        Err(_) => return Ok(vec![]),
+    };
+    // File names carry the date, so the newest file sorts last.
+    files.sort();
+
+    let mut entries = vec![];
+    for path in files.iter().rev() {
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(_) => continue,
+        };
+        let mut file_entries: Vec<LogEntry> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| parse_line(&line))
+            // Lower levels are the more severe ones (ERROR < WARN < INFO).
+            .filter(|entry| {
+                entry
+                    .level
+                    .parse::<Level>()
+                    .is_ok_and(|level| level <= min_level)
+            })
+            .collect();
+        file_entries.reverse();
+        entries.extend(file_entries);
+        if entries.len() >= limit {
+            break;
+        }
+    }
+    entries.truncate(limit);
+    Ok(entries)
+}
+
+/// The newest log entries at `level` or more severe, newest first.
+pub async fn get_recent_logs(
+    app: tauri::AppHandle,
+    level: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    let min_level = match level {
+        Some(level) => level
+            .parse::<Level>()
+            .map_err(|_| format!("Unknown log level: {}", level))?,
+        None => Level::INFO,
+    };
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let dir = log_dir(&app)?;
+
+    tauri::async_runtime::spawn_blocking(move || recent_logs_blocking(dir, min_level, limit))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+pub fn open_log_folder(app: &tauri::AppHandle) -> Result<(), String> {
+    let dir = log_dir(app)?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.to_string_lossy(), e))?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| e.to_string())
+}
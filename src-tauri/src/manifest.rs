@@ -0,0 +1,350 @@
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tauri::Emitter;
+
+use crate::{
+    export::create_dest,
+    hashing::{hash_path, HashAlgorithm},
+    scanner::walk_files,
+};
+
+const MANIFEST_PROGRESS_EVENT: &str = "manifest_progress";
+// Written into the folder when no destination is given, as `sha256sum` users name it.
+const DEFAULT_MANIFEST_NAME: &str = "SHA256SUMS";
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A manifest written by `create_checksum_manifest`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestSummary {
+    pub manifest: String,
+    pub files: u64,
+    pub total_bytes: u64,
+    // Files that could not be read, left out of the manifest.
+    pub unreadable: Vec<ManifestFailure>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FailureKind {
+    Mismatch,
+    Missing,
+    Unreadable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestFailure {
+    // As written in the manifest, relative to its root.
+    pub path: String,
+    pub kind: FailureKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestVerification {
+    pub manifest: String,
+    pub root: String,
+    // Every listed file is present with the listed checksum, and every line was read.
+    pub ok: bool,
+    pub passed: u64,
+    pub failures: Vec<ManifestFailure>,
+    // Files in the folder the manifest does not list, so nothing vouches for them.
+    pub unlisted: Vec<String>,
+    pub malformed_lines: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestProgressPayload<'a> {
+    manifest: &'a str,
+    done_files: u64,
+    total_files: u64,
+    current_path: &'a str,
+}
+
+/// `path` below `root`, with `/` between components as in manifests written anywhere.
+fn relative_name(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// One manifest line. Like GNU sha256sum, names holding a backslash or line break are
+/// escaped and the line is marked with a leading backslash.
+fn manifest_line(hash: &str, name: &str) -> String {
+    if !name.contains(['\\', '\n', '\r']) {
+        return format!("{}  {}", hash, name);
+    }
+    let escaped = name
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    format!("\\{}  {}", hash, escaped)
+}
+
+fn unescape(name: &str) -> Option<String> {
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => out.push('\\'),
+            'n' => out.push('\n'),
+            'r' => out.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+/// The checksum and file name of a `sha256sum` line, in text (`hash  name`) or binary
+/// (`hash *name`) mode.
+fn parse_line(line: &str) -> Option<(String, String)> {
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (hash, rest) = line.split_once(' ')?;
+    let name = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*'))?;
+    if hash.is_empty() || !hash.chars().all(|c| c.is_ascii_hexdigit()) || name.is_empty() {
+        return None;
+    }
+    let name = if escaped {
+        unescape(name)?
+    } else {
+        name.to_string()
+    };
+    Some((hash.to_ascii_lowercase(), name))
+}
+
+fn emit_progress(
+    window: &tauri::Window,
+    manifest: &str,
+    done_files: u64,
+    total_files: u64,
+    current_path: &str,
+) {
+    let payload = ManifestProgressPayload {
+        manifest,
+        done_files,
+        total_files,
+        current_path,
+    };
+    let _ = window.emit_to(window.label(), MANIFEST_PROGRESS_EVENT, payload);
+}
+
+fn create_blocking(
+    window: &tauri::Window,
+    root: &Path,
+    dest: &Path,
+) -> Result<ManifestSummary, String> {
+    let tmp = dest.with_extension("tmp");
+    let mut files: Vec<(String, PathBuf)> = vec![];
+    walk_files(root, |path, _| {
+        if path == dest || path == tmp {
+            return;
+        }
+        if let Some(name) = relative_name(root, path) {
+            files.push((name, path.to_path_buf()));
+        }
+    });
+    files.sort();
+
+    let manifest = dest.to_string_lossy().into_owned();
+    let total_files = files.len() as u64;
+    let mut summary = ManifestSummary {
+        manifest: manifest.clone(),
+        files: 0,
+        total_bytes: 0,
+        unreadable: vec![],
+    };
+    let mut lines = String::new();
+    let mut last_progress = Instant::now();
+    for (done, (name, path)) in files.iter().enumerate() {
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            emit_progress(window, &manifest, done as u64, total_files, name);
+        }
+        match hash_path(path, HashAlgorithm::Sha256, |_| {}) {
+            Ok((hash, size)) => {
+                lines.push_str(&manifest_line(&hash, name));
+                lines.push('\n');
+                summary.files += 1;
+                summary.total_bytes = summary.total_bytes.saturating_add(size);
+            }
+            Err(error) => summary.unreadable.push(ManifestFailure {
+                path: name.clone(),
+                kind: FailureKind::Unreadable,
+                expected: None,
+                actual: None,
+                error: Some(error),
+            }),
+        }
+    }
+
+    // A failed write must not leave a truncated manifest that later verifies "fine".
+    create_dest(&tmp)
+        .and_then(|mut out| {
+            out.write_all(lines.as_bytes())
+                .and_then(|()| out.flush())
+                .map_err(|e| e.to_string())
+        })
+        .and_then(|()| fs::rename(&tmp, dest).map_err(|e| e.to_string()))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })
+        .map_err(|e| format!("Failed to write {}: {}", manifest, e))?;
+    tracing::info!(
+        path = %dest.display(),
+        files = summary.files,
+        unreadable = summary.unreadable.len(),
+        "checksum manifest written"
+    );
+    Ok(summary)
+}
+
+/// Writes a SHA-256 manifest of every file under `path` that `sha256sum -c` can check,
+/// to `dest` or to `SHA256SUMS` in the folder. Names are relative to the folder, so the
+/// manifest also verifies a copy of it elsewhere.
+pub async fn create_checksum_manifest(
+    window: tauri::Window,
+    path: String,
+    dest: Option<String>,
+) -> Result<ManifestSummary, String> {
+    let root = PathBuf::from(path);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.to_string_lossy()));
+    }
+    let dest = dest
+        .map(PathBuf::from)
+        .unwrap_or_else(|| root.join(DEFAULT_MANIFEST_NAME));
+    tauri::async_runtime::spawn_blocking(move || create_blocking(&window, &root, &dest))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+fn verify_blocking(
+    window: &tauri::Window,
+    manifest: &Path,
+    root: &Path,
+) -> Result<ManifestVerification, String> {
+    let text = fs::read_to_string(manifest)
+        .map_err(|e| format!("Failed to read {}: {}", manifest.to_string_lossy(), e))?;
+    let mut entries = vec![];
+    let mut malformed_lines = 0u64;
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        match parse_line(line) {
+            Some(entry) => entries.push(entry),
+            None => malformed_lines += 1,
+        }
+    }
+
+    let manifest_name = manifest.to_string_lossy().into_owned();
+    let total_files = entries.len() as u64;
+    let mut report = ManifestVerification {
+        manifest: manifest_name.clone(),
+        root: root.to_string_lossy().into_owned(),
+        ok: false,
+        passed: 0,
+        failures: vec![],
+        unlisted: vec![],
+        malformed_lines,
+    };
+    let mut last_progress = Instant::now();
+    for (done, (expected, name)) in entries.iter().enumerate() {
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            emit_progress(window, &manifest_name, done as u64, total_files, name);
+        }
+        let path = root.join(name);
+        let failure = |kind, actual, error| ManifestFailure {
+            path: name.clone(),
+            kind,
+            expected: Some(expected.clone()),
+            actual,
+            error,
+        };
+        if !path.is_file() {
+            report
+                .failures
+                .push(failure(FailureKind::Missing, None, None));
+            continue;
+        }
+        match hash_path(&path, HashAlgorithm::Sha256, |_| {}) {
+            Ok((actual, _)) if actual == *expected => report.passed += 1,
+            Ok((actual, _)) => {
+                report
+                    .failures
+                    .push(failure(FailureKind::Mismatch, Some(actual), None))
+            }
+            Err(error) => report
+                .failures
+                .push(failure(FailureKind::Unreadable, None, Some(error))),
+        }
+    }
+
+    let listed: HashSet<&str> = entries.iter().map(|(_, name)| name.as_str()).collect();
+    walk_files(root, |path, _| {
+        if path == manifest {
+            return;
+        }
+        if let Some(name) = relative_name(root, path).filter(|n| !listed.contains(n.as_str())) {
+            report.unlisted.push(name);
+        }
+    });
+    report.unlisted.sort();
+    report.ok = report.failures.is_empty() && report.malformed_lines == 0;
+    tracing::info!(
+        path = %manifest.display(),
+        passed = report.passed,
+        failed = report.failures.len(),
+        unlisted = report.unlisted.len(),
+        "checksum manifest verified"
+    );
+    Ok(report)
+}
+
+/// Checks the files listed in a `sha256sum` manifest against their checksums, relative
+/// to `root` or to the manifest's folder, e.g. before deleting the original of a copy.
+/// Also lists the files under that folder the manifest leaves out.
+pub async fn verify_checksum_manifest(
+    window: tauri::Window,
+    manifest: String,
+    root: Option<String>,
+) -> Result<ManifestVerification, String> {
+    let manifest = PathBuf::from(manifest);
+    if !manifest.is_file() {
+        return Err(format!("Not a file: {}", manifest.to_string_lossy()));
+    }
+    let root = match root {
+        Some(root) => PathBuf::from(root),
+        None => manifest.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.to_string_lossy()));
+    }
+    tauri::async_runtime::spawn_blocking(move || verify_blocking(&window, &manifest, &root))
+        .await
+        .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,172 @@
+use std::{collections::BTreeMap, fmt::Write, path::Path};
+use tauri::Manager;
+
+use crate::{
+    monitor::SpaceMonitor,
+    store::ScanStore,
+    volumes::{list_volumes_blocking, space_for_path},
+};
+
+pub(crate) const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Escapes a label value per the Prometheus text exposition format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// One metric family: HELP/TYPE header followed by its samples.
+struct Family {
+    name: &'static str,
+    help: &'static str,
+    samples: Vec<(Vec<(&'static str, String)>, f64)>,
+}
+
+impl Family {
+    fn gauge(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            samples: vec![],
+        }
+    }
+
+    fn add(&mut self, labels: Vec<(&'static str, String)>, value: f64) {
+        self.samples.push((labels, value));
+    }
+
+    fn write_to(&self, out: &mut String) {
+        // Families without samples would only add noise to the scrape.
+        if self.samples.is_empty() {
+            return;
+        }
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} gauge", self.name);
+        for (labels, value) in &self.samples {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+                .collect();
+            if labels.is_empty() {
+                let _ = writeln!(out, "{} {}", self.name, value);
+            } else {
+                let _ = writeln!(out, "{}{{{}}} {}", self.name, labels.join(","), value);
+            }
+        }
+    }
+}
+
+/// Renders free-space gauges per volume and per monitored path, plus the totals of the
+/// latest stored scan of each root, in the Prometheus text format.
+pub(crate) fn render(app: &tauri::AppHandle, active_scans: usize) -> String {
+    let mut volume_size = Family::gauge(
+        "diskcheck_volume_size_bytes",
+        "Total size of the volume in bytes.",
+    );
+    let mut volume_avail = Family::gauge(
+        "diskcheck_volume_avail_bytes",
+        "Free bytes on the volume available to the current user.",
+    );
+    let mut volume_used = Family::gauge("diskcheck_volume_used_bytes", "Used bytes on the volume.");
+    for volume in list_volumes_blocking().unwrap_or_default() {
+        let labels = vec![
+            ("mountpoint", volume.mount_point.clone()),
+            ("fstype", volume.file_system.clone()),
+        ];
+        volume_size.add(labels.clone(), volume.total_bytes as f64);
+        volume_avail.add(labels.clone(), volume.free_bytes as f64);
+        volume_used.add(labels, volume.used_bytes as f64);
+    }
+
+    let mut watched_size = Family::gauge(
+        "diskcheck_watched_path_size_bytes",
+        "Total size of the volume holding a monitored path.",
+    );
+    let mut watched_avail = Family::gauge(
+        "diskcheck_watched_path_avail_bytes",
+        "Free bytes available to the current user at a monitored path.",
+    );
+    let watched = app
+        .state::<SpaceMonitor>()
+        .config()
+        .ok()
+        .flatten()
+        .map(|config| config.mount_points)
+        .unwrap_or_default();
+    for path in watched {
+        if let Ok(space) = space_for_path(Path::new(&path)) {
+            watched_size.add(vec![("path", path.clone())], space.total_bytes as f64);
+            watched_avail.add(vec![("path", path)], space.available_bytes as f64);
+        }
+    }
+
+    let mut scan_bytes = Family::gauge(
+        "diskcheck_scan_size_bytes",
+        "Total size found by the latest scan of the root.",
+    );
+    let mut scan_files = Family::gauge(
+        "diskcheck_scan_files",
+        "Files counted by the latest scan of the root.",
+    );
+    let mut scan_dirs = Family::gauge(
+        "diskcheck_scan_directories",
+        "Directories counted by the latest scan of the root.",
+    );
+    let mut scan_skipped = Family::gauge(
+        "diskcheck_scan_skipped_entries",
+        "Entries the latest scan of the root could not read.",
+    );
+    let mut scan_duration = Family::gauge(
+        "diskcheck_scan_duration_seconds",
+        "Duration of the latest scan of the root.",
+    );
+    let mut scan_timestamp = Family::gauge(
+        "diskcheck_scan_timestamp_seconds",
+        "Unix time the latest scan of the root started.",
+    );
+    // Scans are listed oldest first, so later scans of a root replace earlier ones.
+    let latest: BTreeMap<String, _> = app
+        .state::<ScanStore>()
+        .list()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|scan| (scan.summary.root_path.clone(), scan))
+        .collect();
+    for (root, scan) in &latest {
+        let summary = &scan.summary;
+        let labels = vec![("root", root.clone())];
+        scan_bytes.add(labels.clone(), summary.total_bytes as f64);
+        scan_files.add(labels.clone(), summary.file_count as f64);
+        scan_dirs.add(labels.clone(), summary.dir_count as f64);
+        scan_skipped.add(labels.clone(), summary.skipped_entries as f64);
+        scan_duration.add(labels.clone(), summary.duration_ms as f64 / 1000.0);
+        scan_timestamp.add(labels, summary.started_at_secs as f64);
+    }
+
+    let mut active = Family::gauge(
+        "diskcheck_active_scans",
+        "Scans currently running through the API.",
+    );
+    active.add(vec![], active_scans as f64);
+
+    let mut out = String::new();
+    for family in [
+        &volume_size,
+        &volume_avail,
+        &volume_used,
+        &watched_size,
+        &watched_avail,
+        &scan_bytes,
+        &scan_files,
+        &scan_dirs,
+        &scan_skipped,
+        &scan_duration,
+        &scan_timestamp,
+        &active,
+    ] {
+        family.write_to(&mut out);
+    }
+    out
+}
@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::Emitter;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::volumes::space_for_path;
+
+const LOW_DISK_SPACE_EVENT: &str = "low_disk_space";
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const MIN_INTERVAL_SECS: u64 = 5;
+const DEFAULT_MIN_FREE_PERCENT: f64 = 10.0;
+// How often the worker wakes up to check whether it was stopped.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorConfig {
+    pub mount_points: Vec<String>,
+    #[serde(default)]
+    pub min_free_bytes: Option<u64>,
+    #[serde(default)]
+    pub min_free_percent: Option<f64>,
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+    #[serde(default = "default_notify")]
+    pub notify: bool,
+}
+
+fn default_notify() -> bool {
+    true
+}
+
+impl MonitorConfig {
+    fn interval(&self) -> Duration {
+        Duration::from_secs(
+            self.interval_secs
+                .unwrap_or(DEFAULT_INTERVAL_SECS)
+                .max(MIN_INTERVAL_SECS),
+        )
+    }
+
+    /// Free-space floor for a volume of `total_bytes`; the stricter of both limits wins.
+    fn threshold_bytes(&self, total_bytes: u64) -> u64 {
+        let percent = match (self.min_free_bytes, self.min_free_percent) {
+            (Some(_), None) => 0.0,
+            (_, Some(p)) => p.clamp(0.0, 100.0),
+            (None, None) => DEFAULT_MIN_FREE_PERCENT,
+        };
+        let by_percent = (total_bytes as f64 * percent / 100.0) as u64;
+        by_percent.max(self.min_free_bytes.unwrap_or(0))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LowSpaceAlert {
+    pub mount_point: String,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+    pub threshold_bytes: u64,
+}
+
+struct MonitorHandle {
+    config: MonitorConfig,
+    stop: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+pub struct SpaceMonitor {
+    current: Mutex<Option<MonitorHandle>>,
+}
+
+fn format_gib(bytes: u64) -> String {
+    format!("{:.1} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+}
+
+fn notify_low_space(app: &tauri::AppHandle, alert: &LowSpaceAlert) {
+    let _ = app
+        .notification()
+        .builder()
+        .title("Low disk space")
+        .body(format!(
+            "{} has only {} free (limit {}).",
+            alert.mount_point,
+            format_gib(alert.free_bytes),
+            format_gib(alert.threshold_bytes)
+        ))
+        .show();
+}
+
+fn run_monitor(app: tauri::AppHandle, config: MonitorConfig, stop: Arc<AtomicBool>) {
+    // Volumes currently below their threshold; we alert once per crossing, not every tick.
+    let mut low: HashSet<String> = HashSet::new();
+    let mut next_check = Instant::now();
+
+    while !stop.load(Ordering::Relaxed) {
+        if Instant::now() < next_check {
+            thread::sleep(STOP_POLL_INTERVAL);
+            continue;
+        }
+        next_check = Instant::now() + config.interval();
+
+        for mount_point in &config.mount_points {
+            let space = match space_for_path(Path::new(mount_point)) {
+                Ok(space) => space,
+                // Unplugged / unmounted volumes are simply skipped until they return.
+                Err(_) => continue,
+            };
+
+            let threshold_bytes = config.threshold_bytes(space.total_bytes);
+            if space.available_bytes >= threshold_bytes {
+                low.remove(mount_point);
+                continue;
+            }
+            if !low.insert(mount_point.clone()) {
+                continue;
+            }
+
+            let alert = LowSpaceAlert {
+                mount_point: mount_point.clone(),
+                total_bytes: space.total_bytes,
+                free_bytes: space.available_bytes,
+                threshold_bytes,
+            };
+            tracing::warn!(
+                mount_point = %alert.mount_point,
+                free_bytes = alert.free_bytes,
+                threshold_bytes = alert.threshold_bytes,
+                "low disk space"
+            );
+            let _ = app.emit(LOW_DISK_SPACE_EVENT, &alert);
+            if config.notify {
+                notify_low_space(&app, &alert);
+            }
+        }
+    }
+}
+
+impl SpaceMonitor {
+    pub fn start(&self, app: tauri::AppHandle, config: MonitorConfig) -> Result<(), String> {
+        if config.mount_points.is_empty() {
+            return Err("Select at least one volume to monitor.".to_string());
+        }
+
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = current.take() {
+            previous.stop.store(true, Ordering::Relaxed);
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let worker_stop = stop.clone();
+        let worker_config = config.clone();
+        thread::Builder::new()
+            .name("space-monitor".to_string())
+            .spawn(move || run_monitor(app, worker_config, worker_stop))
+            .map_err(|e| format!("Failed to start the disk space monitor: {}", e))?;
+
+        *current = Some(MonitorHandle { config, stop });
+        Ok(())
+    }
+
+    pub fn stop(&self) -> Result<(), String> {
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = current.take() {
+            previous.stop.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    pub fn config(&self) -> Result<Option<MonitorConfig>, String> {
+        let current = self.current.lock().map_err(|e| e.to_string())?;
+        Ok(current.as_ref().map(|h| h.config.clone()))
+    }
+}
@@ -0,0 +1,323 @@
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::{
+    priority::ScanMode,
+    scanner::{skipped_preview, FinishedScan, ScanResult},
+    settings::SettingsStore,
+    store::ScanStore,
+};
+
+/// A phone, camera or player attached over MTP.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MtpDevice {
+    // Passed back to `scan_mtp_device`: a shell path on Windows, an `mtp://` URI elsewhere.
+    pub id: String,
+    pub name: String,
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::{
+        os::windows::process::CommandExt,
+        path::PathBuf,
+        process::{Command, Stdio},
+        time::{Instant, SystemTime},
+    };
+
+    use diskcheck_core::{NoProgress, ScanOptions, ScanProgress, DEFAULT_MIN_NODE_BYTES};
+
+    use super::MtpDevice;
+    use crate::{
+        priority::ScanMode,
+        remote::{self, RemoteListing},
+        scanner::{FinishedScan, WindowProgress},
+    };
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    // Portable devices sit in "This PC" (namespace 17) as non-file-system folders whose
+    // shell path names the device; the shell reads them through WPD.
+    const LIST_SCRIPT: &str = r#"
+[Console]::OutputEncoding = [Text.Encoding]::UTF8
+foreach ($item in (New-Object -ComObject Shell.Application).Namespace(17).Items()) {
+  if (-not $item.IsFileSystem -and $item.IsFolder -and $item.Path -like '*\\?\*') {
+    [Console]::Out.Write("$($item.Path)`t$($item.Name)`n")
+  }
+}
+"#;
+
+    // Walks the device and prints `find -printf '%y %s %P\0'` records, paths relative to
+    // the device with `\` separators. FolderItem.Size is 32-bit, System.Size is not.
+    const SCAN_SCRIPT: &str = r#"
+[Console]::OutputEncoding = [Text.Encoding]::UTF8
+$device = (New-Object -ComObject Shell.Application).Namespace(17).Items() |
+  Where-Object { $_.Path -eq '__DEVICE__' } | Select-Object -First 1
+if (-not $device) { [Console]::Error.WriteLine('The device is no longer connected.'); exit 2 }
+$pending = New-Object System.Collections.Stack
+$pending.Push(@($device.GetFolder, ''))
+while ($pending.Count -gt 0) {
+  $folder, $prefix = $pending.Pop()
+  if (-not $folder) { continue }
+  foreach ($item in $folder.Items()) {
+    $path = $prefix + $item.Name
+    if ($item.IsFolder) {
+      [Console]::Out.Write("d 0 $path`0")
+      $pending.Push(@($item.GetFolder, "$path\"))
+    } else {
+      [Console]::Out.Write("f $([uint64]$item.ExtendedProperty('System.Size')) $path`0")
+    }
+  }
+}
+"#;
+
+    fn powershell(script: &str) -> Command {
+        let mut command = Command::new("powershell");
+        command
+            .args(["-NoProfile", "-NonInteractive", "-Command", script])
+            .stdin(Stdio::null())
+            .creation_flags(CREATE_NO_WINDOW);
+        command
+    }
+
+    pub(super) fn list() -> Result<Vec<MtpDevice>, String> {
+        let output = powershell(LIST_SCRIPT)
+            .output()
+            .map_err(|e| format!("Failed to run powershell: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to list devices: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(id, name)| MtpDevice {
+                id: id.to_string(),
+                name: name.to_string(),
+            })
+            .collect())
+    }
+
+    /// Lists the whole device through the shell and builds the scan from that listing.
+    /// The tree is rooted at `mtp://<device name>`.
+    pub(super) fn scan(
+        id: &str,
+        min_node_bytes: Option<u64>,
+        excludes: Vec<String>,
+        _mode: ScanMode,
+        window: Option<tauri::Window>,
+    ) -> Result<FinishedScan, String> {
+        let device = list()?
+            .into_iter()
+            .find(|device| device.id == id)
+            .ok_or("The device is no longer connected.")?;
+        let started_at = SystemTime::now();
+        let started = Instant::now();
+        let root = PathBuf::from(format!("mtp://{}", device.name));
+        let opts = ScanOptions::network(min_node_bytes.unwrap_or(DEFAULT_MIN_NODE_BYTES))
+            .with_excludes(excludes);
+        let progress: Box<dyn ScanProgress> = match window {
+            Some(window) => Box::new(WindowProgress::new(window, &root)),
+            None => Box::new(NoProgress),
+        };
+
+        let mut child = powershell(&SCAN_SCRIPT.replace("__DEVICE__", &id.replace('\'', "''")))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run powershell: {}", e))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or("Failed to read the device listing.")?;
+        let mut listing = RemoteListing::new(root);
+        let (files, dirs) = remote::read_records(stdout, &mut listing, progress.as_ref())
+            .map_err(|e| format!("Failed to read the device listing: {}", e))?;
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        if !output.status.success() && files + dirs == 0 {
+            return Err(format!(
+                "Failed to scan {}: {}",
+                device.name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        remote::scan_listing(listing, &opts, started_at, started, 0)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::MtpDevice;
+    use crate::{priority::ScanMode, scanner::FinishedScan};
+
+    // macOS ships no MTP stack; devices only show up in vendor tools.
+    pub(super) fn list() -> Result<Vec<MtpDevice>, String> {
+        Ok(vec![])
+    }
+
+    pub(super) fn scan(
+        _id: &str,
+        _min_node_bytes: Option<u64>,
+        _excludes: Vec<String>,
+        _mode: ScanMode,
+        _window: Option<tauri::Window>,
+    ) -> Result<FinishedScan, String> {
+        Err("Scanning MTP devices is not supported on macOS.".to_string())
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::{
+        path::PathBuf,
+        process::{Command, Stdio},
+    };
+
+    use super::MtpDevice;
+    use crate::{
+        priority::ScanMode,
+        scanner::{self, FinishedScan},
+    };
+
+    const GIO: &str = "gio";
+
+    /// Devices in `gio mount -li` output: unmounted ones as volumes with an
+    /// `activation_root=mtp://...` line, mounted ones as `Mount(n): name -> mtp://...`.
+    fn parse_devices(listing: &str) -> Vec<MtpDevice> {
+        let mut devices: Vec<MtpDevice> = vec![];
+        let mut volume_name = None;
+        for line in listing.lines().map(str::trim) {
+            let header = line
+                .split_once("): ")
+                .filter(|(kind, _)| kind.starts_with("Volume(") || kind.starts_with("Mount("));
+            if let Some((kind, rest)) = header {
+                if kind.starts_with("Volume(") {
+                    volume_name = Some(rest.to_string());
+                }
+            }
+            let Some(at) = line.find("mtp://") else {
+                continue;
+            };
+            let uri = line[at..]
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .trim_end_matches('/')
+                .to_string();
+            let name = match header {
+                Some((kind, rest)) if kind.starts_with("Mount(") => {
+                    rest.split(" -> ").next().unwrap_or(rest).to_string()
+                }
+                _ => volume_name.clone().unwrap_or_else(|| uri.clone()),
+            };
+            if !devices.iter().any(|device| device.id == uri) {
+                devices.push(MtpDevice { id: uri, name });
+            }
+        }
+        devices
+    }
+
+    pub(super) fn list() -> Result<Vec<MtpDevice>, String> {
+        let output = Command::new(GIO)
+            .args(["mount", "-li"])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to run gio: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to list devices: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(parse_devices(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Where gvfs exposes the device through its FUSE daemon.
+    fn gvfs_path(host: &str) -> PathBuf {
+        let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                // SAFETY: getuid has no preconditions.
+                PathBuf::from(format!("/run/user/{}", unsafe { libc::getuid() }))
+            });
+        runtime_dir.join("gvfs").join(format!("mtp:host={}", host))
+    }
+
+    /// Mounts the device through gvfs if the desktop has not already, then scans its
+    /// FUSE path like a network volume.
+    pub(super) fn scan(
+        id: &str,
+        min_node_bytes: Option<u64>,
+        excludes: Vec<String>,
+        mode: ScanMode,
+        window: Option<tauri::Window>,
+    ) -> Result<FinishedScan, String> {
+        let host = id
+            .strip_prefix("mtp://")
+            .map(|host| host.trim_end_matches('/'))
+            .filter(|host| !host.is_empty() && !host.contains('/'))
+            .ok_or_else(|| format!("Not an MTP device: {}", id))?;
+        let root = gvfs_path(host);
+        if !root.is_dir() {
+            let output = Command::new(GIO)
+                .args(["mount", &format!("mtp://{}/", host)])
+                .stdin(Stdio::null())
+                .output()
+                .map_err(|e| format!("Failed to run gio: {}", e))?;
+            if !output.status.success() {
+                return Err(format!(
+                    "Failed to open the device: {}. Unlock the phone and allow file transfer.",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+        }
+        if !root.is_dir() {
+            return Err(format!(
+                "The device was opened, but {} is missing. Is gvfs-fuse running?",
+                root.to_string_lossy()
+            ));
+        }
+        scanner::scan_blocking(&root, min_node_bytes, excludes, false, mode, None, window)
+            .map_err(String::from)
+    }
+}
+
+pub async fn list_mtp_devices() -> Result<Vec<MtpDevice>, String> {
+    tauri::async_runtime::spawn_blocking(platform::list)
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+/// Scans the storage of an MTP device from `list_mtp_devices`. Phones answer slowly, so
+/// this runs with the network scan settings.
+pub async fn scan_mtp_device(
+    window: tauri::Window,
+    store: &ScanStore,
+    id: String,
+    min_node_bytes: Option<u64>,
+    mode: ScanMode,
+) -> Result<ScanResult, String> {
+    let defaults = window.state::<SettingsStore>().scan();
+    let min_node_bytes = min_node_bytes.or(defaults.min_node_bytes);
+    tracing::info!(device = %id, "device scan started");
+    let finished: FinishedScan = tauri::async_runtime::spawn_blocking(move || {
+        platform::scan(&id, min_node_bytes, defaults.excludes, mode, Some(window))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .inspect_err(|err| tracing::error!(error = %err, "device scan failed"))?;
+
+    let skipped = skipped_preview(&finished.summary);
+    let scan_id = store.insert_spilled(finished.tree, finished.spill, finished.summary)?;
+    Ok(ScanResult {
+        scan_id,
+        root: finished.pruned,
+        skipped,
+        usage_check: None,
+        denied_dirs: vec![],
+        partial: finished.partial,
+    })
+}
@@ -0,0 +1,317 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    io::{Read, Write},
+    path::Path,
+    slice::Iter,
+};
+
+use crate::{
+    fileinfo::file_details,
+    scanner::{file_extension_lower, Children, FsNode, FsNodeKind, NodeRef},
+    store::{ScanSummary, StoredScan},
+};
+
+// ncdu's JSON export format (`ncdu -o` / `ncdu -f`), see https://dev.yorhel.nl/ncdu/jsonfmt.
+// A directory is an array whose first element describes the directory itself, followed
+// by its entries; files are plain objects.
+const MAJOR_VERSION: u32 = 1;
+const MINOR_VERSION: u32 = 2;
+
+#[derive(Serialize)]
+struct Metadata<'a> {
+    progname: &'a str,
+    progver: &'a str,
+    timestamp: u64,
+}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    asize: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dsize: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mtime: Option<u64>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    read_error: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    notreg: bool,
+}
+
+fn write_entry(out: &mut impl Write, node: NodeRef<'_>, name: &str) -> Result<(), String> {
+    let entry = match node.kind() {
+        // ncdu sums directory totals itself; the directory's own entry size is unknown.
+        FsNodeKind::Directory => Entry {
+            name,
+            asize: None,
+            dsize: None,
+            mtime: None,
+            read_error: node.error().is_some(),
+            notreg: false,
+        },
+        FsNodeKind::File => {
+            // ncdu shows disk usage by default, so look up the allocated size.
+            let details = file_details(Path::new(&node.path()));
+            Entry {
+                name,
+                asize: Some(node.size()),
+                dsize: Some(
+                    details
+                        .as_ref()
+                        .map(|d| d.allocated_bytes)
+                        .unwrap_or(node.size()),
+                ),
+                mtime: details.and_then(|d| d.modified_secs),
+                read_error: false,
+                notreg: false,
+            }
+        }
+        FsNodeKind::Symlink | FsNodeKind::Other => Entry {
+            name,
+            asize: Some(0),
+            dsize: Some(0),
+            mtime: None,
+            read_error: false,
+            notreg: true,
+        },
+    };
+    serde_json::to_writer(out, &entry).map_err(|e| e.to_string())
+}
+
+/// Writes `scan` as an ncdu export. Iterative so very deep trees cannot overflow the stack.
+pub(crate) fn write_export(scan: &StoredScan, out: &mut impl Write) -> Result<(), String> {
+    let root = scan.tree.root();
+    if !matches!(root.kind(), FsNodeKind::Directory) {
+        return Err("ncdu exports require a folder scan.".to_string());
+    }
+
+    let io_err = |e: std::io::Error| e.to_string();
+    let metadata = Metadata {
+        progname: "diskcheck",
+        progver: env!("CARGO_PKG_VERSION"),
+        timestamp: scan.summary.started_at_secs,
+    };
+    write!(out, "[{},{},", MAJOR_VERSION, MINOR_VERSION).map_err(io_err)?;
+    serde_json::to_writer(&mut *out, &metadata).map_err(|e| e.to_string())?;
+
+    // The root entry carries the full path; everything below it just the file name.
+    write!(out, ",[").map_err(io_err)?;
+    write_entry(out, root, &scan.summary.root_path)?;
+    let mut stack: Vec<Children> = vec![root.children()];
+
+    while let Some(children) = stack.last_mut() {
+        match children.next() {
+            Some(child) if matches!(child.kind(), FsNodeKind::Directory) => {
+                write!(out, ",[").map_err(io_err)?;
+                write_entry(out, child, child.name())?;
+                stack.push(child.children());
+            }
+            Some(child) => {
+                write!(out, ",").map_err(io_err)?;
+                write_entry(out, child, child.name())?;
+            }
+            None => {
+                stack.pop();
+                write!(out, "]").map_err(io_err)?;
+            }
+        }
+    }
+
+    writeln!(out, "]").map_err(io_err)
+}
+
+struct ImportFrame<'a> {
+    name: String,
+    path: String,
+    entries: Iter<'a, Value>,
+    read_error: bool,
+    size: u64,
+    children: Vec<FsNode>,
+}
+
+#[derive(Default)]
+struct ImportCounts {
+    files: u64,
+    dirs: u64,
+    read_errors: u64,
+}
+
+fn entry_name(info: &Value) -> Result<String, String> {
+    info.get("name")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| "Invalid ncdu export: entry without a name.".to_string())
+}
+
+fn entry_flag(info: &Value, key: &str) -> bool {
+    info.get(key).and_then(Value::as_bool).unwrap_or(false)
+}
+
+// Exports made on Windows use backslash paths.
+fn path_separator(root: &str) -> char {
+    if root.contains('\\') && !root.contains('/') {
+        '\\'
+    } else {
+        '/'
+    }
+}
+
+fn join_path(parent: &str, name: &str, sep: char) -> String {
+    if parent.ends_with(sep) {
+        format!("{}{}", parent, name)
+    } else {
+        format!("{}{}{}", parent, sep, name)
+    }
+}
+
+fn open_dir<'a>(
+    dir: &'a [Value],
+    parent_path: Option<&str>,
+    sep: char,
+) -> Result<ImportFrame<'a>, String> {
+    let (info, entries) = dir
+        .split_first()
+        .ok_or_else(|| "Invalid ncdu export: empty directory entry.".to_string())?;
+    let name = entry_name(info)?;
+    let path = match parent_path {
+        Some(parent) => join_path(parent, &name, sep),
+        None => name.clone(),
+    };
+    Ok(ImportFrame {
+        name,
+        path,
+        entries: entries.iter(),
+        read_error: entry_flag(info, "read_error"),
+        size: 0,
+        children: vec![],
+    })
+}
+
+/// Parses an ncdu export into a tree shaped like a live scan, without touching the
+/// filesystem it describes. Hard links are counted once, as ncdu does.
+pub(crate) fn parse_export(reader: impl Read) -> Result<(FsNode, ScanSummary), String> {
+    let doc: Value =
+        serde_json::from_reader(reader).map_err(|e| format!("Invalid ncdu export: {}", e))?;
+    let top = doc
+        .as_array()
+        .filter(|top| top.len() >= 4)
+        .ok_or_else(|| "Invalid ncdu export: unexpected top-level structure.".to_string())?;
+    if top[0].as_u64() != Some(u64::from(MAJOR_VERSION)) {
+        return Err(format!("Unsupported ncdu export version: {}", top[0]));
+    }
+    let timestamp = top[2].get("timestamp").and_then(Value::as_u64).unwrap_or(0);
+    let root_dir = top[3]
+        .as_array()
+        .ok_or_else(|| "Invalid ncdu export: the root is not a directory.".to_string())?;
+
+    let root_path = root_dir
+        .first()
+        .map(entry_name)
+        .transpose()?
+        .ok_or_else(|| "Invalid ncdu export: empty directory entry.".to_string())?;
+    let sep = path_separator(&root_path);
+    let mut counts = ImportCounts::default();
+    let mut seen_links: HashSet<(u64, u64)> = HashSet::new();
+    let mut stack: Vec<ImportFrame> = vec![open_dir(root_dir, None, sep)?];
+
+    let root = loop {
+        let next = match stack.last_mut() {
+            Some(frame) => frame.entries.next(),
+            None => return Err("Invalid ncdu export: empty tree.".to_string()),
+        };
+
+        match next {
+            Some(Value::Array(dir)) => {
+                let parent = stack.last().map(|f| f.path.clone());
+                stack.push(open_dir(dir, parent.as_deref(), sep)?);
+                counts.dirs += 1;
+            }
+            Some(info) => {
+                // Entries excluded from the original scan carry no size information.
+                if info.get("excluded").is_some() {
+                    continue;
+                }
+                let name = entry_name(info)?;
+                let mut size = info.get("asize").and_then(Value::as_u64).unwrap_or(0);
+                if entry_flag(info, "hlnkc") {
+                    let dev = info.get("dev").and_then(Value::as_u64).unwrap_or(0);
+                    let ino = info.get("ino").and_then(Value::as_u64).unwrap_or(0);
+                    if !seen_links.insert((dev, ino)) {
+                        size = 0;
+                    }
+                }
+                let kind = if entry_flag(info, "notreg") {
+                    FsNodeKind::Other
+                } else {
+                    counts.files += 1;
+                    FsNodeKind::File
+                };
+
+                if let Some(frame) = stack.last_mut() {
+                    let path = join_path(&frame.path, &name, sep);
+                    frame.size = frame.size.saturating_add(size);
+                    frame.children.push(FsNode {
+                        extension: file_extension_lower(Path::new(&name)),
+                        name,
+                        path,
+                        kind,
+                        size,
+                        children: vec![],
+                        error: None,
+                        uncompressed_size: None,
+                        error_count: 0,
+                    });
+                }
+            }
+            None => {
+                let completed = match stack.pop() {
+                    Some(frame) => frame,
+                    None => return Err("Invalid ncdu export: empty tree.".to_string()),
+                };
+                let mut children = completed.children;
+                children.sort_by_key(|c| std::cmp::Reverse(c.size));
+
+                let mut node = FsNode {
+                    name: completed.name,
+                    path: completed.path,
+                    kind: FsNodeKind::Directory,
+                    size: completed.size,
+                    children,
+                    extension: None,
+                    error: None,
+                    uncompressed_size: None,
+                    error_count: 0,
+                };
+                if completed.read_error {
+                    counts.read_errors += 1;
+                    node.error = Some("Could not be read when the export was made.".to_string());
+                }
+
+                match stack.last_mut() {
+                    Some(parent) => {
+                        parent.size = parent.size.saturating_add(node.size);
+                        parent.children.push(node);
+                    }
+                    None => break node,
+                }
+            }
+        }
+    };
+
+    let summary = ScanSummary {
+        root_path,
+        started_at_secs: timestamp,
+        duration_ms: 0,
+        total_bytes: root.size,
+        file_count: counts.files,
+        dir_count: counts.dirs + 1,
+        skipped_entries: counts.read_errors,
+        denied_dirs: vec![],
+        skipped: vec![],
+        partial: false,
+    };
+    Ok((root, summary))
+}
@@ -0,0 +1,94 @@
+use serde::Serialize;
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+    baseline::ChangedEntry,
+    scan_index::{self, IndexOptions},
+};
+
+const DEFAULT_MIN_BYTES: u64 = 100 * 1024 * 1024;
+// Largest first.
+const MAX_FILES: usize = 200;
+
+/// Large files the latest scan of a folder found that the scan before did not have at
+/// that size.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewLargeFiles {
+    pub root_path: String,
+    pub min_bytes: u64,
+    // Files that appeared, or grew from below `min_bytes`; `size_before` is absent for
+    // the former.
+    pub files: Vec<ChangedEntry>,
+    // Of all such files, listed or not.
+    pub total_files: u64,
+    pub total_bytes: u64,
+}
+
+/// Lists the files of at least `min_bytes` (100 MiB by default) under `root` that
+/// appeared or grew past that size between its previous scan and its latest one, going
+/// by the listings both left in the volume's index; no scan needs to be in memory.
+/// `None` when `root` was not scanned twice yet, or is on a network volume, which is
+/// not indexed. Folders that changed while the previous scan ran were not indexed by
+/// it, so their files count as new. Files in folders whose listing the latest scan
+/// reused are stat'ed again, as files grown in place leave their folder's mtime alone.
+pub async fn new_large_files(
+    app: tauri::AppHandle,
+    root: String,
+    min_bytes: Option<u64>,
+) -> Result<Option<NewLargeFiles>, String> {
+    let min_bytes = min_bytes.unwrap_or(DEFAULT_MIN_BYTES);
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&root);
+        let file = IndexOptions::new(&app, false).and_then(|index| index.file_for(path))?;
+        let (latest, previous) = scan_index::load_with_previous(&file)?;
+        if !previous.contains_dir(path) {
+            return None;
+        }
+        let listed = latest.files_below(path)?;
+        // Folder -> whether the latest scan reused its listing.
+        let mut reused: HashMap<&Path, bool> = HashMap::new();
+        let mut files: Vec<ChangedEntry> = vec![];
+        for (file, size) in &listed {
+            let Some(dir) = file.parent() else {
+                continue;
+            };
+            let stale = *reused
+                .entry(dir)
+                .or_insert_with(|| latest.same_listing(&previous, dir));
+            let size = if stale {
+                match fs::symlink_metadata(file) {
+                    Ok(meta) => meta.len(),
+                    // Gone since: not a new file.
+                    Err(_) => continue,
+                }
+            } else {
+                *size
+            };
+            if size < min_bytes {
+                continue;
+            }
+            let size_before = previous.file_len(file);
+            if size_before.is_none_or(|before| before < min_bytes) {
+                files.push(ChangedEntry {
+                    path: file.to_string_lossy().into_owned(),
+                    size_before,
+                    size_after: size,
+                });
+            }
+        }
+        files.sort_by_key(|file| std::cmp::Reverse(file.size_after));
+        let total_files = files.len() as u64;
+        let total_bytes = files.iter().map(|file| file.size_after).sum();
+        files.truncate(MAX_FILES);
+        Some(NewLargeFiles {
+            root_path: root,
+            min_bytes,
+            files,
+            total_files,
+            total_bytes,
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
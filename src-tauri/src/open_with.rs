@@ -0,0 +1,290 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// An application registered to open a file's type.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenWithApp {
+    // What to pass back to `open_with`: the .app bundle's path on macOS, the desktop
+    // entry's id on Linux.
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::OpenWithApp;
+    use std::{path::Path, process::Command};
+
+    /// Windows lists handlers in its own dialog; see `open_with`.
+    pub(super) fn apps(_path: &Path) -> Result<Vec<OpenWithApp>, String> {
+        Ok(Vec::new())
+    }
+
+    pub(super) fn open_with(path: &Path, app: Option<&str>) -> Result<(), String> {
+        if app.is_some() {
+            return Err("On Windows the app is picked in the Open with dialog.".to_string());
+        }
+        Command::new("rundll32.exe")
+            .arg("shell32.dll,OpenAs_RunDLL")
+            .arg(path)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("Failed to show the Open with dialog: {}", e))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::OpenWithApp;
+    use std::{
+        ffi::{c_void, CStr, OsStr},
+        os::unix::ffi::OsStrExt,
+        path::{Path, PathBuf},
+        ptr,
+    };
+
+    type CFTypeRef = *const c_void;
+    type CFURLRef = *const c_void;
+    type CFArrayRef = *const c_void;
+    type CFIndex = isize;
+    type Boolean = u8;
+
+    // kLSRolesAll: viewers, editors and shell handlers alike.
+    const LS_ROLES_ALL: u32 = 0xFFFF_FFFF;
+    const MAX_PATH_BYTES: usize = 4096;
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFURLCreateFromFileSystemRepresentation(
+            allocator: CFTypeRef,
+            buffer: *const u8,
+            length: CFIndex,
+            is_directory: Boolean,
+        ) -> CFURLRef;
+        fn CFURLGetFileSystemRepresentation(
+            url: CFURLRef,
+            resolve_against_base: Boolean,
+            buffer: *mut u8,
+            max_len: CFIndex,
+        ) -> Boolean;
+        fn CFArrayGetCount(array: CFArrayRef) -> CFIndex;
+        fn CFArrayGetValueAtIndex(array: CFArrayRef, index: CFIndex) -> CFTypeRef;
+        fn CFRelease(cf: CFTypeRef);
+    }
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        fn LSCopyApplicationURLsForURL(url: CFURLRef, roles: u32) -> CFArrayRef;
+        fn LSCopyDefaultApplicationURLForURL(
+            url: CFURLRef,
+            roles: u32,
+            error: *mut CFTypeRef,
+        ) -> CFURLRef;
+    }
+
+    fn path_of(url: CFURLRef) -> Option<PathBuf> {
+        let mut buffer = vec![0u8; MAX_PATH_BYTES];
+        // SAFETY: `url` is a live CFURL and `buffer` holds `MAX_PATH_BYTES` bytes.
+        let ok = unsafe {
+            CFURLGetFileSystemRepresentation(url, 1, buffer.as_mut_ptr(), buffer.len() as CFIndex)
+        };
+        if ok == 0 {
+            return None;
+        }
+        let path = CStr::from_bytes_until_nul(&buffer).ok()?;
+        Some(PathBuf::from(OsStr::from_bytes(path.to_bytes())))
+    }
+
+    /// The apps Launch Services would offer in Finder's Open With menu.
+    pub(super) fn apps(path: &Path) -> Result<Vec<OpenWithApp>, String> {
+        let bytes = path.as_os_str().as_bytes();
+        let (default, found) = unsafe {
+            let url = CFURLCreateFromFileSystemRepresentation(
+                ptr::null(),
+                bytes.as_ptr(),
+                bytes.len() as CFIndex,
+                path.is_dir() as Boolean,
+            );
+            if url.is_null() {
+                return Err(format!("Failed to look up {}", path.to_string_lossy()));
+            }
+            let default_url = LSCopyDefaultApplicationURLForURL(url, LS_ROLES_ALL, ptr::null_mut());
+            let default = (!default_url.is_null()).then(|| {
+                let path = path_of(default_url);
+                CFRelease(default_url);
+                path
+            });
+            let array = LSCopyApplicationURLsForURL(url, LS_ROLES_ALL);
+            let mut found = Vec::new();
+            if !array.is_null() {
+                for i in 0..CFArrayGetCount(array) {
+                    found.extend(path_of(CFArrayGetValueAtIndex(array, i)));
+                }
+                CFRelease(array);
+            }
+            CFRelease(url);
+            (default.flatten(), found)
+        };
+        Ok(found
+            .into_iter()
+            .map(|app| OpenWithApp {
+                name: app
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                is_default: default.as_ref() == Some(&app),
+                id: app.to_string_lossy().into_owned(),
+            })
+            .collect())
+    }
+
+    pub(super) fn open_with(path: &Path, app: Option<&str>) -> Result<(), String> {
+        let Some(app) = app else {
+            return Err("macOS has no Open with dialog; pick one of the listed apps.".to_string());
+        };
+        crate::volumes::run_tool("open", &["-a", app, &path.to_string_lossy()])
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use super::OpenWithApp;
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        process::Command,
+    };
+
+    fn gio(args: &[&str]) -> Result<String, String> {
+        let output = Command::new("gio")
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run gio: {}", e))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Where desktop entries are installed, most specific first.
+    fn application_dirs() -> Vec<PathBuf> {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home.map(|home| home.join(".local/share")));
+        let data_dirs = std::env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|dirs| !dirs.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+        data_home
+            .into_iter()
+            .chain(data_dirs.split(':').map(PathBuf::from))
+            .map(|dir| dir.join("applications"))
+            .collect()
+    }
+
+    /// The unlocalised `Name` of the desktop entry `id`.
+    fn entry_name(dirs: &[PathBuf], id: &str) -> Option<String> {
+        let text = dirs
+            .iter()
+            .find_map(|dir| fs::read_to_string(dir.join(id)).ok())?;
+        let mut in_entry = false;
+        for line in text.lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                in_entry = line == "[Desktop Entry]";
+            } else if in_entry {
+                if let Some(name) = line.strip_prefix("Name=") {
+                    return Some(name.to_string());
+                }
+            }
+        }
+        None
+    }
+
+    /// The apps registered for the file's MIME type, as `gio mime` lists them.
+    pub(super) fn apps(path: &Path) -> Result<Vec<OpenWithApp>, String> {
+        let info = gio(&[
+            "info",
+            "-a",
+            "standard::content-type",
+            &path.to_string_lossy(),
+        ])?;
+        let content_type = info
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("standard::content-type:"))
+            .map(str::trim)
+            .ok_or_else(|| format!("No file type known for {}", path.to_string_lossy()))?;
+        let mime = gio(&["mime", content_type])?;
+        let mut default = None;
+        let mut ids: Vec<String> = Vec::new();
+        for line in mime.lines() {
+            if let Some((_, id)) = line
+                .strip_prefix("Default application for")
+                .and_then(|rest| rest.split_once(": "))
+            {
+                default = Some(id.trim().to_string());
+            } else if line.starts_with(char::is_whitespace) {
+                // Registered and recommended applications overlap.
+                let id = line.trim();
+                if id.ends_with(".desktop") && !ids.iter().any(|seen| seen == id) {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+        let dirs = application_dirs();
+        Ok(ids
+            .into_iter()
+            .map(|id| OpenWithApp {
+                name: entry_name(&dirs, &id)
+                    .unwrap_or_else(|| id.trim_end_matches(".desktop").to_string()),
+                is_default: default.as_deref() == Some(id.as_str()),
+                id,
+            })
+            .collect())
+    }
+
+    pub(super) fn open_with(path: &Path, app: Option<&str>) -> Result<(), String> {
+        let Some(app) = app else {
+            return Err(
+                "There is no system Open with dialog here; pick one of the listed apps."
+                    .to_string(),
+            );
+        };
+        let entry = application_dirs()
+            .into_iter()
+            .map(|dir| dir.join(app))
+            .find(|entry| entry.is_file())
+            .ok_or_else(|| format!("No application {} is installed.", app))?;
+        gio(&["launch", &entry.to_string_lossy(), &path.to_string_lossy()]).map(|_| ())
+    }
+}
+
+fn existing(path: String) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    if !path.exists() {
+        return Err(format!("Path does not exist: {}", path.to_string_lossy()));
+    }
+    Ok(path)
+}
+
+/// The applications registered for the file at `path`, the default one flagged. Empty
+/// on Windows, where `open_with` without an app shows the system's chooser instead.
+pub async fn open_with_apps(path: String) -> Result<Vec<OpenWithApp>, String> {
+    let path = existing(path)?;
+    tauri::async_runtime::spawn_blocking(move || platform::apps(&path))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+/// Opens the file at `path` with `app`, an id from `open_with_apps`. Without one, shows
+/// the system's Open with dialog where there is one (Windows).
+pub async fn open_with(path: String, app: Option<String>) -> Result<(), String> {
+    let path = existing(path)?;
+    tracing::info!(path = %path.display(), app = ?app, "open with");
+    tauri::async_runtime::spawn_blocking(move || platform::open_with(&path, app.as_deref()))
+        .await
+        .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,302 @@
+use resvg::usvg;
+use serde::Serialize;
+use std::{
+    fs,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::{
+    categories::{category_for_extension, FileCategory},
+    cloud_sync::{is_cloud_only, open_local, read_local},
+};
+
+// Text files are counted up to this many bytes; beyond it the line count is partial.
+const TEXT_MAX_BYTES: u64 = 64 * 1024 * 1024;
+// PDFs are searched for page objects up to this many bytes.
+const PDF_MAX_BYTES: u64 = 64 * 1024 * 1024;
+// A file of unknown type counts as text when its start has no NUL and decodes as UTF-8.
+const TEXT_SNIFF_BYTES: usize = 8 * 1024;
+const READ_BUFFER_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PreviewKind {
+    Image,
+    Video,
+    Audio,
+    Pdf,
+    Text,
+    Other,
+}
+
+/// What the detail panel shows about a file, without its contents.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewInfo {
+    pub path: String,
+    pub kind: PreviewKind,
+    pub size_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u64>,
+    // Absent when neither ffprobe nor the platform's metadata index could tell.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_count: Option<u64>,
+    // The line count stops at the first 64 MiB of the file.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub line_count_truncated: bool,
+    // The contents are only in the cloud, so nothing was read to keep from downloading
+    // them.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cloud_only: bool,
+}
+
+/// Runs a metadata tool and returns its trimmed stdout, or `None` when it is missing
+/// or fails.
+fn tool_output(program: &str, args: &[&str], path: &Path) -> Option<String> {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !stdout.is_empty()).then_some(stdout)
+}
+
+/// Pixel size from the image header; SVGs are parsed for their intrinsic size.
+fn image_size(path: &Path, ext: &str) -> Option<(u64, u64)> {
+    if ext == "svg" {
+        let data = read_local(path).ok()?;
+        let tree = usvg::Tree::from_data(&data, &usvg::Options::default()).ok()?;
+        let size = tree.size();
+        return Some((size.width().round() as u64, size.height().round() as u64));
+    }
+    let size = imagesize::reader_size(BufReader::new(open_local(path).ok()?)).ok()?;
+    Some((size.width as u64, size.height as u64))
+}
+
+fn media_duration(path: &Path) -> Option<f64> {
+    let probed = tool_output(
+        "ffprobe",
+        &[
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+        ],
+        path,
+    );
+    // Spotlight has durations for most media without ffprobe installed.
+    #[cfg(target_os = "macos")]
+    let probed =
+        probed.or_else(|| tool_output("mdls", &["-raw", "-name", "kMDItemDurationSeconds"], path));
+    probed?
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Counts `/Type /Page` objects. Misses pages kept in compressed object streams, so
+/// `pdfinfo` is asked first.
+fn scan_pdf_pages(data: &[u8]) -> u64 {
+    let mut pages = 0;
+    let mut rest = data;
+    while let Some(at) = find(rest, b"/Type") {
+        rest = &rest[at + b"/Type".len()..];
+        let value = rest.trim_ascii_start();
+        if let Some(after) = value.strip_prefix(b"/Page") {
+            if !after.first().is_some_and(u8::is_ascii_alphanumeric) {
+                pages += 1;
+            }
+        }
+    }
+    pages
+}
+
+fn pdf_page_count(path: &Path) -> Option<u64> {
+    let from_tool = tool_output("pdfinfo", &[], path).and_then(|info| {
+        info.lines()
+            .find_map(|line| line.strip_prefix("Pages:"))
+            .and_then(|pages| pages.trim().parse().ok())
+    });
+    if from_tool.is_some() {
+        return from_tool;
+    }
+    let mut data = vec![];
+    open_local(path)
+        .ok()?
+        .take(PDF_MAX_BYTES)
+        .read_to_end(&mut data)
+        .ok()?;
+    Some(scan_pdf_pages(&data)).filter(|pages| *pages > 0)
+}
+
+fn looks_like_text(path: &Path) -> bool {
+    let mut head = Vec::with_capacity(TEXT_SNIFF_BYTES);
+    let Ok(file) = open_local(path) else {
+        return false;
+    };
+    if file
+        .take(TEXT_SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .is_err()
+    {
+        return false;
+    }
+    if head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(&head) {
+        Ok(_) => true,
+        // A character cut off by the sniff window is still text.
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// Lines in the first `TEXT_MAX_BYTES` of the file, counting a last line without a
+/// line break.
+fn line_count(path: &Path) -> Option<u64> {
+    let mut file = open_local(path).ok()?.take(TEXT_MAX_BYTES);
+    let mut buffer = vec![0u8; READ_BUFFER_BYTES];
+    let mut lines = 0u64;
+    let mut last = b'\n';
+    loop {
+        let read = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => return None,
+        };
+        lines += buffer[..read].iter().filter(|b| **b == b'\n').count() as u64;
+        last = buffer[read - 1];
+    }
+    if last != b'\n' {
+        lines += 1;
+    }
+    Some(lines)
+}
+
+fn preview_blocking(path: &Path, size_bytes: u64, cloud_only: bool) -> PreviewInfo {
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    let category = category_for_extension(ext.as_deref());
+    let kind = match (category, ext.as_deref()) {
+        (_, Some("pdf")) => PreviewKind::Pdf,
+        (FileCategory::Image, _) => PreviewKind::Image,
+        (FileCategory::Video, _) => PreviewKind::Video,
+        (FileCategory::Audio, _) => PreviewKind::Audio,
+        (FileCategory::Code, _) | (_, Some("txt" | "md" | "csv" | "log")) => PreviewKind::Text,
+        _ if !cloud_only && looks_like_text(path) => PreviewKind::Text,
+        _ => PreviewKind::Other,
+    };
+
+    let mut info = PreviewInfo {
+        path: path.to_string_lossy().into_owned(),
+        kind,
+        size_bytes,
+        width: None,
+        height: None,
+        duration_secs: None,
+        page_count: None,
+        line_count: None,
+        line_count_truncated: false,
+        cloud_only,
+    };
+    if cloud_only {
+        return info;
+    }
+    match kind {
+        PreviewKind::Image => {
+            if let Some((width, height)) = image_size(path, ext.as_deref().unwrap_or_default()) {
+                info.width = Some(width);
+                info.height = Some(height);
+            }
+        }
+        PreviewKind::Video | PreviewKind::Audio => info.duration_secs = media_duration(path),
+        PreviewKind::Pdf => info.page_count = pdf_page_count(path),
+        PreviewKind::Text => {
+            info.line_count = line_count(path);
+            info.line_count_truncated = info.line_count.is_some() && size_bytes > TEXT_MAX_BYTES;
+        }
+        PreviewKind::Other => {}
+    }
+    info
+}
+
+/// Image dimensions, media duration, PDF page count or text line count of a file, for
+/// the detail panel. Only headers are read where the format allows; fields that cannot
+/// be determined are left out rather than failing the call.
+pub async fn preview_info(path: String) -> Result<PreviewInfo, String> {
+    let target = PathBuf::from(path);
+    let meta = fs::metadata(&target)
+        .map_err(|e| format!("Failed to read {}: {}", target.to_string_lossy(), e))?;
+    if !meta.is_file() {
+        return Err(format!("Not a file: {}", target.to_string_lossy()));
+    }
+
+    let cloud_only = is_cloud_only(&meta);
+    tauri::async_runtime::spawn_blocking(move || preview_blocking(&target, meta.len(), cloud_only))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloud_only_files_are_not_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "one\ntwo\nthree\n").unwrap();
+
+        let info = preview_blocking(&path, 14, true);
+        assert!(info.cloud_only);
+        assert!(matches!(info.kind, PreviewKind::Text));
+        assert_eq!(info.line_count, None);
+
+        let info = preview_blocking(&path, 14, false);
+        assert!(!info.cloud_only);
+        assert_eq!(info.line_count, Some(3));
+    }
+
+    #[test]
+    fn cloud_only_files_are_not_sniffed_for_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("README");
+        fs::write(&path, "plain text\n").unwrap();
+
+        assert!(matches!(
+            preview_blocking(&path, 11, true).kind,
+            PreviewKind::Other
+        ));
+        assert!(matches!(
+            preview_blocking(&path, 11, false).kind,
+            PreviewKind::Text
+        ));
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+/// How much a scan may compete with the user's own work for CPU and disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScanMode {
+    #[default]
+    Normal,
+    // Lowest CPU and I/O priority, for scans nobody is waiting on (API, cron jobs).
+    Background,
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentThread, SetThreadPriority, THREAD_MODE_BACKGROUND_BEGIN,
+    };
+
+    pub(super) fn lower_current_thread() -> Result<(), String> {
+        // Background mode lowers CPU, I/O and memory priority of this thread only, unlike
+        // PROCESS_MODE_BACKGROUND_BEGIN, which would slow down the UI as well.
+        // SAFETY: GetCurrentThread returns a pseudo handle that needs no closing.
+        if unsafe { SetThreadPriority(GetCurrentThread(), THREAD_MODE_BACKGROUND_BEGIN) } == 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    pub(super) fn lower_current_thread() -> Result<(), String> {
+        // The background QoS class also puts the thread's disk I/O in the throttled tier.
+        // SAFETY: only changes the calling thread's scheduling class.
+        let rc = unsafe {
+            libc::pthread_set_qos_class_self_np(libc::qos_class_t::QOS_CLASS_BACKGROUND, 0)
+        };
+        if rc != 0 {
+            return Err(std::io::Error::from_raw_os_error(rc).to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    // From linux/ioprio.h: the idle class only gets disk time nobody else wants.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const IOPRIO_CLASS_IDLE: libc::c_int = 3;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+    const LOWEST_NICE: libc::c_int = 19;
+
+    pub(super) fn lower_current_thread() -> Result<(), String> {
+        // On Linux, `who = 0` addresses the calling thread rather than the whole process
+        // for both calls.
+        // SAFETY: plain syscalls on the calling thread.
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, LOWEST_NICE) } != 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let ioprio = IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT;
+            // SAFETY: as above.
+            if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, ioprio) } != 0 {
+                return Err(std::io::Error::last_os_error().to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Drops the calling thread to background CPU and I/O priority for the rest of its
+/// life; there is no way back without privileges on Unix, so call this only on a thread
+/// of its own. Failure is logged and the scan simply runs at normal priority.
+pub(crate) fn lower_current_thread() {
+    if let Err(error) = platform::lower_current_thread() {
+        tracing::warn!(error = %error, "failed to lower scan priority");
+    }
+}
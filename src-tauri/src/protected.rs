@@ -0,0 +1,138 @@
+use std::path::{Component, Path, PathBuf};
+
+/// System-critical locations that delete/move commands refuse to touch unless the
+/// caller explicitly overrides the guard.
+pub struct ProtectedPaths {
+    // Neither these nor anything below them may be modified.
+    trees: Vec<PathBuf>,
+    // Only these folders themselves are protected; their contents are fair game.
+    roots: Vec<PathBuf>,
+}
+
+#[cfg(target_os = "windows")]
+fn builtin() -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let env_or = |name: &str, fallback: &str| {
+        std::env::var_os(name)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(fallback))
+    };
+    let trees = vec![
+        env_or("SystemRoot", r"C:\Windows"),
+        env_or("ProgramFiles", r"C:\Program Files"),
+        env_or("ProgramFiles(x86)", r"C:\Program Files (x86)"),
+    ];
+    let mut roots = vec![PathBuf::from(r"C:\Users")];
+    roots.extend(std::env::var_os("USERPROFILE").map(PathBuf::from));
+    (trees, roots)
+}
+
+#[cfg(target_os = "macos")]
+fn builtin() -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let trees = [
+        "/System",
+        "/usr",
+        "/bin",
+        "/sbin",
+        "/Library",
+        "/private/etc",
+        "/private/var/db",
+    ];
+    let mut roots: Vec<PathBuf> = ["/Applications", "/Users", "/Volumes", "/private/var"]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+    roots.extend(std::env::var_os("HOME").map(PathBuf::from));
+    (trees.iter().map(PathBuf::from).collect(), roots)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn builtin() -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let trees = [
+        "/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc", "/boot", "/dev", "/proc",
+        "/sys", "/run", "/var/lib",
+    ];
+    let mut roots: Vec<PathBuf> = ["/home", "/root", "/var", "/opt", "/mnt", "/media"]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+    roots.extend(std::env::var_os("HOME").map(PathBuf::from));
+    (trees.iter().map(PathBuf::from).collect(), roots)
+}
+
+/// A comparable form of `path`: `.`/`..` resolved lexically, verbatim prefixes dropped and,
+/// on case-insensitive platforms, lowercased.
+fn normalize(path: &Path) -> PathBuf {
+    let text = path.to_string_lossy();
+    let text = text.strip_prefix(r"\\?\").unwrap_or(&text);
+    let text = if cfg!(any(windows, target_os = "macos")) {
+        text.to_lowercase()
+    } else {
+        text.to_string()
+    };
+    let mut out = PathBuf::new();
+    for component in Path::new(&text).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+impl ProtectedPaths {
+    /// The built-in list plus the user's `extra` folders, which are protected as trees.
+    pub fn new(extra: &[String]) -> Self {
+        let (mut trees, roots) = builtin();
+        trees.extend(
+            extra
+                .iter()
+                .filter(|p| !p.trim().is_empty())
+                .map(PathBuf::from),
+        );
+        ProtectedPaths {
+            trees: trees.iter().map(|p| normalize(p)).collect(),
+            roots: roots.iter().map(|p| normalize(p)).collect(),
+        }
+    }
+
+    /// Every protected folder, for display in the UI.
+    pub fn list(&self) -> Vec<String> {
+        self.trees
+            .iter()
+            .chain(&self.roots)
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    /// Rejects `path` if it is protected, lies inside a protected tree, or contains a
+    /// protected folder (so deleting `/` or a parent of the profile is refused too).
+    pub fn check(&self, path: &Path) -> Result<(), String> {
+        // Resolve the parent so `/tmp/../usr` or a symlinked parent cannot sneak past;
+        // the last component stays as is, since moving a symlink leaves its target alone.
+        let resolved = match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => parent
+                .canonicalize()
+                .map(|p| p.join(name))
+                .unwrap_or_else(|_| path.to_path_buf()),
+            _ => path.to_path_buf(),
+        };
+        for candidate in [normalize(path), normalize(&resolved)] {
+            let hit = self
+                .trees
+                .iter()
+                .find(|tree| candidate.starts_with(tree) || tree.starts_with(&candidate))
+                .or_else(|| self.roots.iter().find(|root| root.starts_with(&candidate)));
+            if let Some(hit) = hit {
+                return Err(format!(
+                    "{} is protected ({}). Confirm the override to modify it anyway.",
+                    path.to_string_lossy(),
+                    hit.to_string_lossy()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::volumes::space_for_path;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaInfo {
+    pub path: String,
+    // False when the platform/filesystem offers no way to query quotas.
+    pub supported: bool,
+    // True when a quota actually limits the current user on this volume.
+    pub enforced: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soft_limit_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_bytes: Option<u64>,
+    // What the user can really still write: the tighter of quota headroom and volume free space.
+    pub available_bytes: u64,
+    pub volume_free_bytes: u64,
+}
+
+/// Per-user quota figures as reported by the platform.
+#[derive(Debug, Default)]
+struct UserQuota {
+    supported: bool,
+    limit_bytes: Option<u64>,
+    soft_limit_bytes: Option<u64>,
+    used_bytes: Option<u64>,
+}
+
+pub(crate) fn quota_info_blocking(path: &Path) -> Result<QuotaInfo, String> {
+    let space = space_for_path(path)?;
+    let quota = platform::user_quota(path, &space);
+
+    let headroom = quota
+        .limit_bytes
+        .map(|limit| limit.saturating_sub(quota.used_bytes.unwrap_or(0)));
+    let available_bytes = match headroom {
+        Some(headroom) => headroom.min(space.available_bytes),
+        None => space.available_bytes,
+    };
+
+    Ok(QuotaInfo {
+        path: path.to_string_lossy().into_owned(),
+        supported: quota.supported,
+        enforced: quota.limit_bytes.is_some() || quota.soft_limit_bytes.is_some(),
+        limit_bytes: quota.limit_bytes,
+        soft_limit_bytes: quota.soft_limit_bytes,
+        used_bytes: quota.used_bytes,
+        available_bytes,
+        volume_free_bytes: space.available_bytes,
+    })
+}
+
+pub async fn quota_info(path: String) -> Result<QuotaInfo, String> {
+    let target = PathBuf::from(path);
+    if !target.exists() {
+        return Err(format!("Path does not exist: {}", target.to_string_lossy()));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || quota_info_blocking(&target))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::UserQuota;
+    use crate::volumes::SpaceInfo;
+    use std::path::Path;
+
+    /// NTFS quotas can only be enumerated by administrators, but GetDiskFreeSpaceEx
+    /// already reports the caller's quota-limited figures. A caller total below the
+    /// volume's free+used means a quota applies; its limit is that total.
+    pub(super) fn user_quota(_path: &Path, space: &SpaceInfo) -> UserQuota {
+        let quota_limited = space.available_bytes < space.free_bytes;
+        if !quota_limited {
+            return UserQuota {
+                supported: true,
+                ..UserQuota::default()
+            };
+        }
+        UserQuota {
+            supported: true,
+            limit_bytes: Some(space.total_bytes),
+            soft_limit_bytes: None,
+            used_bytes: Some(space.total_bytes.saturating_sub(space.available_bytes)),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::UserQuota;
+    use crate::volumes::{mount_for_path, SpaceInfo};
+    use std::{ffi::CString, path::Path};
+
+    // From <sys/quota.h>.
+    const Q_GETQUOTA: libc::c_int = 0x80_0007;
+    const USRQUOTA: libc::c_int = 0;
+    // Block limits are expressed in 1 KiB quota blocks; current space is in bytes.
+    const QUOTA_BLOCK_SIZE: u64 = 1024;
+
+    // Mirrors the kernel's `struct if_dqblk`; not every field is read.
+    #[repr(C)]
+    #[derive(Default)]
+    #[allow(dead_code)]
+    struct Dqblk {
+        bhardlimit: u64,
+        bsoftlimit: u64,
+        curspace: u64,
+        ihardlimit: u64,
+        isoftlimit: u64,
+        curinodes: u64,
+        btime: u64,
+        itime: u64,
+        valid: u32,
+    }
+
+    pub(super) fn user_quota(path: &Path, _space: &SpaceInfo) -> UserQuota {
+        let mount = match mount_for_path(path) {
+            Ok(mount) => mount,
+            Err(_) => return UserQuota::default(),
+        };
+        // Network filesystems apply server-side quotas to the statfs figures already.
+        if !mount.source.starts_with("/dev/") {
+            return UserQuota::default();
+        }
+        let device = match CString::new(mount.source.as_bytes()) {
+            Ok(device) => device,
+            Err(_) => return UserQuota::default(),
+        };
+
+        let mut dq = Dqblk::default();
+        let cmd = (Q_GETQUOTA << 8) | (USRQUOTA & 0xff);
+        // SAFETY: `device` is NUL-terminated and `dq` matches the kernel's `struct if_dqblk`.
+        let rc = unsafe {
+            libc::syscall(
+                libc::SYS_quotactl,
+                cmd,
+                device.as_ptr(),
+                libc::getuid(),
+                &mut dq as *mut Dqblk,
+            )
+        };
+        if rc != 0 {
+            // ESRCH: quotas are not enabled on this filesystem.
+            return UserQuota {
+                supported: std::io::Error::last_os_error().raw_os_error() == Some(libc::ESRCH),
+                ..UserQuota::default()
+            };
+        }
+
+        // A zero limit means "no limit".
+        let limit = |blocks: u64| {
+            if blocks == 0 {
+                None
+            } else {
+                Some(blocks.saturating_mul(QUOTA_BLOCK_SIZE))
+            }
+        };
+        UserQuota {
+            supported: true,
+            limit_bytes: limit(dq.bhardlimit),
+            soft_limit_bytes: limit(dq.bsoftlimit),
+            used_bytes: Some(dq.curspace),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod platform {
+    use super::UserQuota;
+    use crate::volumes::SpaceInfo;
+    use std::path::Path;
+
+    // Quotas are practically unused on macOS/BSD desktops; statfs figures stand in.
+    pub(super) fn user_quota(_path: &Path, _space: &SpaceInfo) -> UserQuota {
+        UserQuota::default()
+    }
+}
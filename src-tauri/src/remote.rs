@@ -0,0 +1,365 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::{Duration, Instant, SystemTime},
+};
+use tauri::Manager;
+
+use diskcheck_core::{
+    DirEntries, DirEntry, EntryMetadata, FileSystem, FsNodeKind, NoProgress, ProgressSnapshot,
+    ScanHooks, ScanOptions, ScanProgress, DEFAULT_MIN_NODE_BYTES,
+};
+
+use crate::{
+    scanner::{skipped_preview, ScanResult, WindowProgress},
+    settings::SettingsStore,
+    spill::ScanSpill,
+    store::{unix_secs, ScanStore, ScanSummary},
+};
+
+const SSH: &str = "ssh";
+const CONNECT_TIMEOUT_SECS: u32 = 15;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+// Stderr lines kept for the error message; ssh explains failures at the end.
+const MAX_STDERR_LINES: usize = 20;
+
+/// Entries listed by some other means than the local file system (`find` over ssh, an
+/// object store), as directory listings the scanner can walk.
+pub(crate) struct RemoteListing {
+    root: PathBuf,
+    dirs: HashMap<PathBuf, Vec<DirEntry>>,
+}
+
+impl FileSystem for RemoteListing {
+    fn symlink_metadata(&self, path: &Path) -> io::Result<EntryMetadata> {
+        if self.dirs.contains_key(path) {
+            return Ok(EntryMetadata {
+                kind: FsNodeKind::Directory,
+                len: 0,
+                modified: None,
+            });
+        }
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} was not listed", path.to_string_lossy()),
+        ))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<DirEntries<'_>> {
+        let entries = self.dirs.get(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} was not listed", path.to_string_lossy()),
+            )
+        })?;
+        Ok(Box::new(entries.iter().cloned().map(Ok)))
+    }
+}
+
+impl RemoteListing {
+    pub(crate) fn new(root: PathBuf) -> Self {
+        Self {
+            dirs: HashMap::from([(root.clone(), vec![])]),
+            root,
+        }
+    }
+
+    /// Adds the entry at `path`, which must lie below the root. Its parent folders are
+    /// created as needed.
+    pub(crate) fn insert(&mut self, path: PathBuf, kind: FsNodeKind, len: u64) {
+        if let FsNodeKind::Directory = kind {
+            if self.dirs.contains_key(&path) {
+                return;
+            }
+            self.dirs.insert(path.clone(), vec![]);
+        }
+        let Some(parent) = path.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        if !self.dirs.contains_key(&parent) && parent != self.root {
+            self.insert(parent.clone(), FsNodeKind::Directory, 0);
+        }
+        self.dirs.entry(parent).or_default().push(DirEntry {
+            path,
+            metadata: EntryMetadata {
+                kind,
+                len,
+                modified: None,
+            },
+        });
+    }
+
+    /// Adds one `find -printf '%y %s %P\0'` record: type letter, size, path relative to
+    /// the start folder (empty for the folder itself). Returns the entry's kind and size.
+    fn add(&mut self, record: &[u8]) -> Option<(FsNodeKind, u64)> {
+        let record = String::from_utf8_lossy(record);
+        let mut fields = record.splitn(3, ' ');
+        let kind = match fields.next()? {
+            "f" => FsNodeKind::File,
+            "d" => FsNodeKind::Directory,
+            "l" => FsNodeKind::Symlink,
+            _ => FsNodeKind::Other,
+        };
+        let size = fields.next()?;
+        let len = match kind {
+            FsNodeKind::File => size.parse().ok()?,
+            _ => 0,
+        };
+        let relative = fields.next()?;
+        if relative.is_empty() {
+            return Some((kind, len));
+        }
+
+        let path = self.root.join(relative);
+        self.insert(path, kind, len);
+        Some((kind, len))
+    }
+}
+
+/// Reads NUL-terminated records in the `find -printf '%y %s %P\0'` format into `listing`,
+/// reporting progress as they arrive. Returns the number of files and folders read.
+pub(crate) fn read_records(
+    output: impl io::Read,
+    listing: &mut RemoteListing,
+    progress: &dyn ScanProgress,
+) -> io::Result<(u64, u64)> {
+    let (mut files, mut dirs, mut bytes) = (0u64, 0u64, 0u64);
+    let mut last_emit = Instant::now();
+    let mut reader = BufReader::new(output);
+    let mut record = vec![];
+    loop {
+        record.clear();
+        if reader.read_until(0, &mut record)? == 0 {
+            return Ok((files, dirs));
+        }
+        if record.last() == Some(&0) {
+            record.pop();
+        }
+        match listing.add(&record) {
+            Some((FsNodeKind::File, len)) => {
+                files += 1;
+                bytes += len;
+            }
+            Some((FsNodeKind::Directory, _)) => dirs += 1,
+            _ => {}
+        }
+        if last_emit.elapsed() >= PROGRESS_INTERVAL {
+            last_emit = Instant::now();
+            progress.on_progress(ProgressSnapshot {
+                scanned_files: files,
+                scanned_dirs: dirs,
+                total_bytes: bytes,
+                current_path: None,
+            });
+        }
+    }
+}
+
+/// Quotes `value` for the remote POSIX shell.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn ssh_command(host: &str, port: Option<u16>, path: &str) -> Command {
+    let mut command = Command::new(SSH);
+    // No terminal to type a password into: keys, agents and the user's ssh config only.
+    command
+        .arg("-o")
+        .arg("BatchMode=yes")
+        .arg("-o")
+        .arg(format!("ConnectTimeout={}", CONNECT_TIMEOUT_SECS));
+    if let Some(port) = port {
+        command.arg("-p").arg(port.to_string());
+    }
+    // GNU find; `-xdev` keeps to the file system the folder is on, like a local scan of
+    // a mount point would.
+    command.arg(host).arg("--").arg(format!(
+        "LC_ALL=C find {} -xdev -printf '%y %s %P\\0'",
+        shell_quote(path)
+    ));
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+/// Lists `path` on `host` through the system ssh client, reporting progress as records
+/// arrive. Returns the listing and the number of entries `find` could not read.
+fn list_remote(
+    host: &str,
+    port: Option<u16>,
+    path: &str,
+    root: PathBuf,
+    progress: &dyn ScanProgress,
+) -> Result<(RemoteListing, u64), String> {
+    let mut child = ssh_command(host, port, path).spawn().map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            "ssh was not found. Install an OpenSSH client to scan remote servers.".to_string()
+        } else {
+            format!("Failed to run ssh: {}", e)
+        }
+    })?;
+    let stdout = child.stdout.take().ok_or("Failed to read ssh output.")?;
+    let stderr = child.stderr.take().ok_or("Failed to read ssh output.")?;
+    // Drained on its own thread so a chatty stderr cannot block the listing. Every
+    // `find:` line is an entry that could not be read.
+    let stderr_reader = std::thread::spawn(move || {
+        let mut unreadable = 0u64;
+        let mut tail = VecDeque::new();
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            if line.starts_with("find:") {
+                unreadable += 1;
+            }
+            if tail.len() == MAX_STDERR_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
+        (unreadable, Vec::from(tail).join("\n"))
+    });
+
+    let mut listing = RemoteListing::new(root);
+    let (files, dirs) = read_records(stdout, &mut listing, progress)
+        .map_err(|e| format!("Failed to read ssh output: {}", e))?;
+
+    let status = child.wait().map_err(|e| e.to_string())?;
+    let (unreadable, stderr) = stderr_reader.join().unwrap_or_default();
+    // find exits with 1 after permission errors and still lists everything else; ssh
+    // itself fails with 255.
+    if !status.success() && (status.code() == Some(255) || files + dirs == 0) {
+        let message = stderr.trim();
+        return Err(if message.is_empty() {
+            format!("Remote scan of {}:{} failed ({}).", host, path, status)
+        } else {
+            format!("Remote scan of {}:{} failed: {}", host, path, message)
+        });
+    }
+    Ok((listing, unreadable))
+}
+
+/// Scans `path` on `host` over SSH and stores the result like a local scan. The server
+/// needs nothing but sshd and GNU find; the tree is rooted at `host:path`.
+pub(crate) fn scan_remote_blocking(
+    host: &str,
+    port: Option<u16>,
+    path: &str,
+    min_node_bytes: Option<u64>,
+    excludes: Vec<String>,
+    window: Option<tauri::Window>,
+) -> Result<crate::scanner::FinishedScan, String> {
+    if host.is_empty() || host.starts_with('-') {
+        return Err(format!("Invalid host: {}", host));
+    }
+    if !path.starts_with('/') {
+        return Err(format!("Remote path must be absolute: {}", path));
+    }
+    let started_at = SystemTime::now();
+    let started = Instant::now();
+    let root = PathBuf::from(format!("{}:{}", host, path));
+    let opts = ScanOptions::local(min_node_bytes.unwrap_or(DEFAULT_MIN_NODE_BYTES))
+        .with_excludes(excludes);
+    let progress: Box<dyn ScanProgress> = match window {
+        Some(window) => Box::new(WindowProgress::new(window, &root)),
+        None => Box::new(NoProgress),
+    };
+
+    tracing::info!(host = %host, path = %path, "remote scan started");
+    let (listing, unreadable) = list_remote(host, port, path, root.clone(), progress.as_ref())
+        .inspect_err(|err| tracing::error!(host = %host, error = %err, "remote scan failed"))?;
+    scan_listing(listing, &opts, started_at, started, unreadable)
+}
+
+/// Builds the scan of a finished listing, spilling large folders like a local scan.
+/// `unreadable` entries could not be listed.
+pub(crate) fn scan_listing(
+    listing: RemoteListing,
+    opts: &ScanOptions,
+    started_at: SystemTime,
+    started: Instant,
+    unreadable: u64,
+) -> Result<crate::scanner::FinishedScan, String> {
+    let root = listing.root.clone();
+    let mut spill = ScanSpill::default();
+    let hooks = ScanHooks {
+        sink: Some(&mut spill),
+        ..ScanHooks::default()
+    };
+    // Building the tree from memory takes a moment; progress already covered the listing.
+    let mut scan = diskcheck_core::scan_with_hooks(&listing, &root, opts, &NoProgress, hooks)?;
+    drop(listing);
+    scan.stats.skipped_entries += unreadable;
+
+    let pruned = scan.view(opts);
+    let summary = ScanSummary {
+        root_path: root.to_string_lossy().into_owned(),
+        started_at_secs: unix_secs(started_at),
+        duration_ms: started.elapsed().as_millis() as u64,
+        total_bytes: scan.tree.size(),
+        file_count: scan.stats.file_count,
+        dir_count: scan.stats.dir_count,
+        skipped_entries: scan.stats.skipped_entries,
+        denied_dirs: vec![],
+        skipped: vec![],
+        partial: scan.partial,
+    };
+    tracing::info!(
+        root = %root.display(),
+        total_bytes = summary.total_bytes,
+        files = summary.file_count,
+        dirs = summary.dir_count,
+        skipped_entries = summary.skipped_entries,
+        duration_ms = summary.duration_ms,
+        "remote scan finished"
+    );
+    Ok(crate::scanner::FinishedScan {
+        tree: scan.tree,
+        spill: spill.into_file(),
+        pruned,
+        summary,
+        partial: scan.partial,
+    })
+}
+
+pub async fn scan_remote(
+    window: tauri::Window,
+    store: &ScanStore,
+    host: String,
+    port: Option<u16>,
+    path: String,
+    min_node_bytes: Option<u64>,
+) -> Result<ScanResult, String> {
+    let defaults = window.state::<SettingsStore>().scan();
+    let min_node_bytes = min_node_bytes.or(defaults.min_node_bytes);
+    let finished = tauri::async_runtime::spawn_blocking(move || {
+        scan_remote_blocking(
+            &host,
+            port,
+            &path,
+            min_node_bytes,
+            defaults.excludes,
+            Some(window),
+        )
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    let skipped = skipped_preview(&finished.summary);
+    let scan_id = store.insert_spilled(finished.tree, finished.spill, finished.summary)?;
+    Ok(ScanResult {
+        scan_id,
+        root: finished.pruned,
+        skipped,
+        usage_check: None,
+        denied_dirs: vec![],
+        partial: finished.partial,
+    })
+}
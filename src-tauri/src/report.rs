@@ -0,0 +1,274 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Write, hash::Hash, path::Path, time::SystemTime};
+
+use crate::{
+    categories::category_for_extension,
+    fileinfo::{file_details, format_bytes, iso8601_utc, owner_name},
+    installers::age_buckets,
+    scanner::{FsNodeKind, NodeRef},
+    store::{unix_secs, StoredScan},
+    treemap::{escape_xml, render_svg},
+};
+
+const TOP_FILES: usize = 50;
+const TOP_EXTENSIONS: usize = 30;
+const TREEMAP_WIDTH: u32 = 1200;
+const TREEMAP_HEIGHT: u32 = 700;
+const NO_EXTENSION: &str = "(none)";
+const UNKNOWN_OWNER: &str = "(unknown)";
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReportKind {
+    Extension,
+    Category,
+    Owner,
+    Age,
+}
+
+/// One line of an aggregation report; `key` is machine-friendly, `label` for humans.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportRow {
+    pub key: String,
+    pub label: String,
+    pub files: u64,
+    pub bytes: u64,
+    // Share of the scanned total, in percent.
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionStat {
+    pub extension: String,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// Every file in the tree, without recursion. Entries listed inside archive files are
+/// left out; the archive itself counts.
+pub(crate) fn files_of(root: NodeRef<'_>) -> Vec<NodeRef<'_>> {
+    let mut files = vec![];
+    let mut pending: Vec<NodeRef> = vec![root];
+    while let Some(node) = pending.pop() {
+        if matches!(node.kind(), FsNodeKind::File) {
+            files.push(node);
+            continue;
+        }
+        pending.extend(node.children());
+    }
+    files
+}
+
+/// The `limit` largest files, largest first.
+pub(crate) fn largest_files(root: NodeRef<'_>, limit: usize) -> Vec<NodeRef<'_>> {
+    let mut files = files_of(root);
+    if files.len() > limit {
+        files.select_nth_unstable_by(limit, |a, b| b.size().cmp(&a.size()));
+        files.truncate(limit);
+    }
+    files.sort_by_key(|f| std::cmp::Reverse(f.size()));
+    files
+}
+
+/// Bytes and file counts per extension, largest first.
+pub(crate) fn extension_stats(root: NodeRef<'_>) -> Vec<ExtensionStat> {
+    let mut by_extension: HashMap<String, (u64, u64)> = HashMap::new();
+    for file in files_of(root) {
+        let key = file.extension().unwrap_or_else(|| NO_EXTENSION.to_string());
+        let entry = by_extension.entry(key).or_default();
+        entry.0 = entry.0.saturating_add(file.size());
+        entry.1 += 1;
+    }
+    let mut stats: Vec<ExtensionStat> = by_extension
+        .into_iter()
+        .map(|(extension, (bytes, files))| ExtensionStat {
+            extension,
+            bytes,
+            files,
+        })
+        .collect();
+    stats.sort_by_key(|s| std::cmp::Reverse(s.bytes));
+    stats
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// Sums files per group key, largest group first.
+fn group_files<'a, K: Eq + Hash>(
+    files: &[NodeRef<'a>],
+    mut key: impl FnMut(NodeRef<'a>) -> K,
+) -> Vec<(K, u64, u64)> {
+    let mut groups: HashMap<K, (u64, u64)> = HashMap::new();
+    for &file in files {
+        let entry = groups.entry(key(file)).or_default();
+        entry.0 += 1;
+        entry.1 = entry.1.saturating_add(file.size());
+    }
+    let mut groups: Vec<(K, u64, u64)> = groups
+        .into_iter()
+        .map(|(key, (files, bytes))| (key, files, bytes))
+        .collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.2));
+    groups
+}
+
+/// Aggregates the files of `root` for `kind`. Owner and age reports re-stat each file,
+/// since the scanned tree carries neither.
+pub(crate) fn aggregate(root: NodeRef<'_>, kind: ReportKind) -> Vec<ReportRow> {
+    let total = root.size();
+    let files = files_of(root);
+    let row = |key: String, label: String, files: u64, bytes: u64| ReportRow {
+        key,
+        label,
+        files,
+        bytes,
+        percent: percent(bytes, total),
+    };
+
+    match kind {
+        ReportKind::Extension => extension_stats(root)
+            .into_iter()
+            .map(|stat| {
+                row(
+                    stat.extension.clone(),
+                    stat.extension,
+                    stat.files,
+                    stat.bytes,
+                )
+            })
+            .collect(),
+        ReportKind::Category => group_files(&files, |file| {
+            category_for_extension(file.extension().as_deref())
+        })
+        .into_iter()
+        .map(|(category, files, bytes)| {
+            row(
+                category.key().to_string(),
+                category.label().to_string(),
+                files,
+                bytes,
+            )
+        })
+        .collect(),
+        ReportKind::Owner => group_files(&files, |file| {
+            owner_name(Path::new(&file.path())).unwrap_or_else(|| UNKNOWN_OWNER.to_string())
+        })
+        .into_iter()
+        .map(|(owner, files, bytes)| row(owner.clone(), owner, files, bytes))
+        .collect(),
+        ReportKind::Age => {
+            let now = unix_secs(SystemTime::now());
+            age_buckets(files.iter().map(|file| {
+                let age_days = file_details(Path::new(&file.path()))
+                    .and_then(|d| d.modified_secs)
+                    .map(|modified| now.saturating_sub(modified) / SECS_PER_DAY);
+                (age_days, file.size())
+            }))
+            .into_iter()
+            .map(|bucket| {
+                row(
+                    bucket.label.clone(),
+                    bucket.label,
+                    bucket.count,
+                    bucket.bytes,
+                )
+            })
+            .collect()
+        }
+    }
+}
+
+const STYLE: &str = "\
+body{font-family:system-ui,sans-serif;margin:2rem;color:#111827;background:#F9FAFB}\
+h1{font-size:1.4rem;margin-bottom:.25rem}h2{font-size:1.1rem;margin-top:2rem}\
+.muted{color:#6B7280}table{border-collapse:collapse;width:100%;background:#fff}\
+th,td{text-align:left;padding:.35rem .6rem;border-bottom:1px solid #E5E7EB;font-size:.85rem}\
+td.num,th.num{text-align:right;font-variant-numeric:tabular-nums;white-space:nowrap}\
+td.path{word-break:break-all}.treemap svg{max-width:100%;height:auto;border-radius:6px}";
+
+/// The opening of a standalone report page titled `title`, up to its heading.
+pub(crate) fn html_head(title: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"utf-8\"><title>{title}</title><style>{style}</style></head><body><h1>{title}</h1>",
+        title = escape_xml(title),
+        style = STYLE,
+    )
+}
+
+/// The summary, largest files, extensions and treemap of `scan`, under `<h2>` headings
+/// prefixed with `heading_prefix`.
+pub(crate) fn render_sections(scan: &StoredScan, heading_prefix: &str, html: &mut String) {
+    let summary = &scan.summary;
+    let tree = scan.tree.root();
+    let prefix = escape_xml(heading_prefix);
+
+    let _ = write!(
+        html,
+        "<h2>{}Summary</h2><table>\
+         <tr><th>Total size</th><td class=\"num\">{}</td></tr>\
+         <tr><th>Files</th><td class=\"num\">{}</td></tr>\
+         <tr><th>Folders</th><td class=\"num\">{}</td></tr>\
+         <tr><th>Skipped entries</th><td class=\"num\">{}</td></tr></table>",
+        prefix,
+        format_bytes(summary.total_bytes),
+        summary.file_count,
+        summary.dir_count,
+        summary.skipped_entries
+    );
+
+    let _ = write!(html, "<h2>{}Largest files</h2><table><tr><th>Path</th><th class=\"num\">Size</th><th class=\"num\">Share</th></tr>", prefix);
+    for file in largest_files(tree, TOP_FILES) {
+        let _ = write!(
+            html,
+            "<tr><td class=\"path\">{}</td><td class=\"num\">{}</td><td class=\"num\">{:.1}%</td></tr>",
+            escape_xml(&file.path()),
+            format_bytes(file.size()),
+            percent(file.size(), tree.size())
+        );
+    }
+    html.push_str("</table>");
+
+    let _ = write!(html, "<h2>{}By extension</h2><table><tr><th>Extension</th><th class=\"num\">Files</th><th class=\"num\">Size</th><th class=\"num\">Share</th></tr>", prefix);
+    for stat in extension_stats(tree).iter().take(TOP_EXTENSIONS) {
+        let _ = write!(
+            html,
+            "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{:.1}%</td></tr>",
+            escape_xml(&stat.extension),
+            stat.files,
+            format_bytes(stat.bytes),
+            percent(stat.bytes, tree.size())
+        );
+    }
+    html.push_str("</table>");
+
+    let _ = write!(
+        html,
+        "<h2>{}Treemap</h2><div class=\"treemap\">{}</div>",
+        prefix,
+        render_svg(tree, TREEMAP_WIDTH, TREEMAP_HEIGHT)
+    );
+}
+
+/// A standalone HTML report (no external assets) for attaching to tickets or emails.
+pub(crate) fn render_html(scan: &StoredScan) -> String {
+    let summary = &scan.summary;
+    let mut html = html_head(&format!("DiskCheck report: {}", summary.root_path));
+    let _ = write!(
+        html,
+        "<p class=\"muted\">Scanned {when} UTC in {secs:.1} s</p>",
+        when = iso8601_utc(summary.started_at_secs),
+        secs = summary.duration_ms as f64 / 1000.0,
+    );
+    render_sections(scan, "", &mut html);
+    html.push_str("</body></html>");
+    html
+}
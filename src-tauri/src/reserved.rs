@@ -0,0 +1,230 @@
+use serde::Serialize;
+use std::{fs, path::Path};
+
+use crate::volumes::{list_volumes_blocking, space_for_path, VolumeInfo};
+
+// Each platform only produces some of these.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReservedKind {
+    PageFile,
+    HibernationFile,
+    SwapFile,
+    ShadowCopies,
+    FilesystemReserved,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservedItem {
+    pub kind: ReservedKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservedSpaceReport {
+    pub mount_point: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub reserved_bytes: u64,
+    pub items: Vec<ReservedItem>,
+}
+
+fn file_item(kind: ReservedKind, path: &Path) -> Option<ReservedItem> {
+    // Locked system files (pagefile.sys, ...) still expose their size via metadata.
+    let meta = fs::metadata(path).ok()?;
+    Some(ReservedItem {
+        kind,
+        path: Some(path.to_string_lossy().into_owned()),
+        bytes: meta.len(),
+        note: None,
+    })
+}
+
+/// Blocks only the superuser may allocate (ext4 reserves 5% by default).
+fn filesystem_reserved(mount_point: &str) -> Option<ReservedItem> {
+    let space = space_for_path(Path::new(mount_point)).ok()?;
+    let bytes = space.free_bytes.saturating_sub(space.available_bytes);
+    if bytes == 0 {
+        return None;
+    }
+    Some(ReservedItem {
+        kind: ReservedKind::FilesystemReserved,
+        path: None,
+        bytes,
+        note: Some("Free blocks reserved for the system administrator".to_string()),
+    })
+}
+
+fn report_for_volume(volume: &VolumeInfo) -> ReservedSpaceReport {
+    let mut items = platform::system_files(&volume.mount_point);
+    items.extend(filesystem_reserved(&volume.mount_point));
+
+    ReservedSpaceReport {
+        mount_point: volume.mount_point.clone(),
+        total_bytes: volume.total_bytes,
+        used_bytes: volume.used_bytes,
+        reserved_bytes: items
+            .iter()
+            .fold(0u64, |acc, item| acc.saturating_add(item.bytes)),
+        items,
+    }
+}
+
+pub(crate) fn reserved_space_blocking(
+    mount_point: Option<String>,
+) -> Result<Vec<ReservedSpaceReport>, String> {
+    let volumes = list_volumes_blocking()?;
+    let selected: Vec<&VolumeInfo> = match &mount_point {
+        Some(mount_point) => {
+            let volume = volumes
+                .iter()
+                .find(|v| &v.mount_point == mount_point)
+                .ok_or_else(|| format!("Volume is not mounted: {}", mount_point))?;
+            vec![volume]
+        }
+        // Reserved system files only live on local disks.
+        None => volumes.iter().filter(|v| !v.is_network).collect(),
+    };
+
+    Ok(selected.into_iter().map(report_for_volume).collect())
+}
+
+pub async fn reserved_space(
+    mount_point: Option<String>,
+) -> Result<Vec<ReservedSpaceReport>, String> {
+    tauri::async_runtime::spawn_blocking(move || reserved_space_blocking(mount_point))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{file_item, ReservedItem, ReservedKind};
+    use std::{os::windows::process::CommandExt, path::Path, process::Command};
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    /// Parses a size such as `12.5 GB` from vssadmin output.
+    fn parse_vss_size(text: &str) -> Option<u64> {
+        let mut parts = text.split_whitespace();
+        let value: f64 = parts.next()?.replace(',', "").parse().ok()?;
+        let unit = match parts.next()? {
+            "B" | "bytes" => 1.0,
+            "KB" => 1024.0,
+            "MB" => 1024.0 * 1024.0,
+            "GB" => 1024.0 * 1024.0 * 1024.0,
+            "TB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+            _ => return None,
+        };
+        Some((value * unit) as u64)
+    }
+
+    /// System Restore / Volume Shadow Copy usage. Needs administrator rights; without
+    /// them vssadmin fails and we report nothing rather than guessing.
+    fn shadow_copies(mount_point: &str) -> Option<ReservedItem> {
+        let drive = mount_point.trim_end_matches('\\');
+        let output = Command::new("vssadmin")
+            .args(["list", "shadowstorage", &format!("/for={}", drive)])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let text = String::from_utf8_lossy(&output.stdout);
+        let used = text
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Used Shadow Copy Storage space:"))
+            .and_then(parse_vss_size)?;
+        Some(ReservedItem {
+            kind: ReservedKind::ShadowCopies,
+            path: Some(format!("{}\\System Volume Information", drive)),
+            bytes: used,
+            note: Some("System Restore points and shadow copies".to_string()),
+        })
+    }
+
+    pub(super) fn system_files(mount_point: &str) -> Vec<ReservedItem> {
+        let root = Path::new(mount_point);
+        let mut items: Vec<ReservedItem> = [
+            (ReservedKind::PageFile, "pagefile.sys"),
+            (ReservedKind::HibernationFile, "hiberfil.sys"),
+            (ReservedKind::SwapFile, "swapfile.sys"),
+        ]
+        .iter()
+        .filter_map(|(kind, name)| file_item(*kind, &root.join(name)))
+        .collect();
+        items.extend(shadow_copies(mount_point));
+        items
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{file_item, ReservedItem, ReservedKind};
+    use std::{fs, path::Path};
+
+    const VM_DIR: &str = "/private/var/vm";
+
+    pub(super) fn system_files(mount_point: &str) -> Vec<ReservedItem> {
+        // Swap and the sleep image live on the boot volume's data partition.
+        if mount_point != "/" && mount_point != "/System/Volumes/Data" {
+            return vec![];
+        }
+        let mut items = vec![];
+        if let Ok(rd) = fs::read_dir(VM_DIR) {
+            for entry in rd.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let kind = if name == "sleepimage" {
+                    ReservedKind::HibernationFile
+                } else if name.starts_with("swapfile") {
+                    ReservedKind::SwapFile
+                } else {
+                    continue;
+                };
+                items.extend(file_item(kind, &Path::new(VM_DIR).join(&name)));
+            }
+        }
+        items
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use super::{file_item, ReservedItem, ReservedKind};
+    use crate::volumes::volume_for_path;
+    use std::{fs, path::Path};
+
+    /// Swap files listed in /proc/swaps that live on this mount (partitions are skipped).
+    pub(super) fn system_files(mount_point: &str) -> Vec<ReservedItem> {
+        let swaps = match fs::read_to_string("/proc/swaps") {
+            Ok(swaps) => swaps,
+            Err(_) => return vec![],
+        };
+        swaps
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let filename = fields.next()?;
+                if fields.next()? != "file" {
+                    return None;
+                }
+                let path = Path::new(filename);
+                // Only count the file under its innermost mount, not every ancestor mount.
+                let innermost = volume_for_path(path).map(|v| v.mount_point);
+                if innermost.as_deref() != Some(mount_point) {
+                    return None;
+                }
+                file_item(ReservedKind::SwapFile, path)
+            })
+            .collect()
+    }
+}
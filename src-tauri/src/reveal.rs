@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::{path::Path, process::Command};
+
+    pub(super) fn reveal(target: &Path) -> Result<(), String> {
+        Command::new("explorer")
+            .arg("/select,")
+            .arg(target)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{path::Path, process::Command};
+
+    pub(super) fn reveal(target: &Path) -> Result<(), String> {
+        Command::new("open")
+            .arg("-R")
+            .arg(target)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::{path::Path, process::Command};
+
+    // xdg-open is an optional package on the BSDs and minimal Linux installs; the
+    // desktops' own openers do the same job.
+    const OPENERS: &[&[&str]] = &[
+        &["xdg-open"],
+        &["gio", "open"],
+        &["kde-open"],
+        &["exo-open"],
+    ];
+
+    /// Asks the file manager to open the folder holding `target` with `target` selected,
+    /// over the FileManager1 D-Bus interface (Nautilus, Dolphin, Nemo, Caja, Thunar).
+    fn show_item(target: &Path) -> Result<(), String> {
+        let target = std::path::absolute(target).map_err(|e| e.to_string())?;
+        // Commas would split the array; `file_uri` escapes them.
+        let item = format!("array:string:{}", crate::clipboard::file_uri(&target));
+        crate::volumes::run_tool(
+            "dbus-send",
+            &[
+                "--session",
+                "--print-reply",
+                "--reply-timeout=5000",
+                "--dest=org.freedesktop.FileManager1",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+                &item,
+                // Startup ID: none.
+                "string:",
+            ],
+        )
+    }
+
+    fn open_folder(dir: &Path) -> Result<(), String> {
+        for opener in OPENERS {
+            match Command::new(opener[0]).args(&opener[1..]).arg(dir).spawn() {
+                Ok(_) => return Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        Err("No program to open folders was found; install xdg-utils.".to_string())
+    }
+
+    pub(super) fn reveal(target: &Path) -> Result<(), String> {
+        if let Err(error) = show_item(target) {
+            tracing::debug!(error = %error, "FileManager1 unavailable, opening the folder instead");
+            return open_folder(target.parent().unwrap_or(target));
+        }
+        Ok(())
+    }
+}
+
+/// Opens the file manager on the folder holding `path`, with `path` selected where the
+/// file manager supports it.
+pub async fn reveal_in_explorer(path: String) -> Result<(), String> {
+    let target = PathBuf::from(path);
+    if !target.exists() {
+        return Err(format!("Path does not exist: {}", target.to_string_lossy()));
+    }
+    tauri::async_runtime::spawn_blocking(move || platform::reveal(&target))
+        .await
+        .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,597 @@
+use quick_xml::{escape::resolve_predefined_entity, events::Event, Reader};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    time::{Instant, SystemTime},
+};
+use tauri::Manager;
+
+use diskcheck_core::{
+    FsNodeKind, NoProgress, ProgressSnapshot, ScanOptions, ScanProgress, DEFAULT_MIN_NODE_BYTES,
+};
+
+use crate::{
+    fileinfo::iso8601_utc,
+    remote::{self, RemoteListing},
+    scanner::{FsNode, WindowProgress},
+    settings::SettingsStore,
+    store::{unix_secs, ScanStore},
+};
+
+const CURL: &str = "curl";
+const CONNECT_TIMEOUT_SECS: u32 = 15;
+// One page of at most 1000 keys; a stalled request should not hang the scan forever.
+const REQUEST_TIMEOUT_SECS: u32 = 120;
+const DEFAULT_REGION: &str = "us-east-1";
+// SHA-256 of an empty body; every listing request is a bodiless GET.
+const EMPTY_PAYLOAD_SHA256: &str =
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+// Objects listed without a storage class are in the default one.
+const DEFAULT_STORAGE_CLASS: &str = "STANDARD";
+
+/// The bucket to scan and how to reach it. Unset credentials fall back to
+/// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`; without any the
+/// bucket is listed anonymously, which only public buckets allow.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketTarget {
+    pub bucket: String,
+    // Only keys below this prefix, taken as a folder.
+    pub prefix: Option<String>,
+    // Falls back to AWS_REGION, AWS_DEFAULT_REGION, then us-east-1.
+    pub region: Option<String>,
+    // `https://host[:port]` of an S3-compatible service, addressed path-style. AWS if unset.
+    pub endpoint: Option<String>,
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    pub session_token: Option<String>,
+}
+
+/// Objects and bytes stored in one storage class.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageClassUsage {
+    pub storage_class: String,
+    pub objects: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketScanResult {
+    pub scan_id: String,
+    pub root: FsNode,
+    // Largest first.
+    pub storage_classes: Vec<StorageClassUsage>,
+}
+
+struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+/// Where listing requests go: `https://{host}{path}`.
+struct Endpoint {
+    scheme: String,
+    host: String,
+    path: String,
+}
+
+#[derive(Default)]
+struct Object {
+    key: String,
+    size: u64,
+    storage_class: Option<String>,
+}
+
+#[derive(Default)]
+struct Page {
+    objects: Vec<Object>,
+    truncated: bool,
+    next_token: Option<String>,
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn credentials(target: &BucketTarget) -> Result<Option<Credentials>, String> {
+    let access_key_id = target
+        .access_key_id
+        .clone()
+        .or_else(|| env_var("AWS_ACCESS_KEY_ID"));
+    let secret_access_key = target
+        .secret_access_key
+        .clone()
+        .or_else(|| env_var("AWS_SECRET_ACCESS_KEY"));
+    match (access_key_id, secret_access_key) {
+        (Some(access_key_id), Some(secret_access_key)) => Ok(Some(Credentials {
+            access_key_id,
+            secret_access_key,
+            session_token: target
+                .session_token
+                .clone()
+                .or_else(|| env_var("AWS_SESSION_TOKEN")),
+        })),
+        (None, None) => Ok(None),
+        _ => Err("Both an access key ID and a secret access key are needed.".to_string()),
+    }
+}
+
+/// Bucket names as S3 allows them: 3 to 63 lowercase letters, digits, dots and hyphens.
+fn valid_bucket(bucket: &str) -> bool {
+    (3..=63).contains(&bucket.len())
+        && bucket
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'.' || b == b'-')
+}
+
+fn endpoint(target: &BucketTarget, region: &str) -> Result<Endpoint, String> {
+    let bucket = &target.bucket;
+    let Some(url) = &target.endpoint else {
+        // Dotted names do not match the wildcard certificate of virtual-hosted style.
+        return Ok(if bucket.contains('.') {
+            Endpoint {
+                scheme: "https".to_string(),
+                host: format!("s3.{}.amazonaws.com", region),
+                path: format!("/{}", bucket),
+            }
+        } else {
+            Endpoint {
+                scheme: "https".to_string(),
+                host: format!("{}.s3.{}.amazonaws.com", bucket, region),
+                path: "/".to_string(),
+            }
+        });
+    };
+    let (scheme, rest) = url
+        .split_once("://")
+        .filter(|(scheme, _)| matches!(*scheme, "http" | "https"))
+        .ok_or_else(|| format!("Endpoint must be an http(s) URL: {}", url))?;
+    let host = rest.trim_end_matches('/');
+    if host.is_empty()
+        || !host
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b".-:[]".contains(&b))
+    {
+        return Err(format!("Invalid endpoint: {}", url));
+    }
+    Ok(Endpoint {
+        scheme: scheme.to_string(),
+        host: host.to_string(),
+        path: format!("/{}", bucket),
+    })
+}
+
+/// Percent-encodes everything but the unreserved characters, as SigV4 expects.
+pub(crate) fn uri_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(data);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// Headers for a GET of `path?query`, signed with AWS Signature Version 4 when there are
+/// credentials. `query` must already be canonical: encoded and sorted by name.
+fn request_headers(
+    endpoint: &Endpoint,
+    query: &str,
+    region: &str,
+    credentials: Option<&Credentials>,
+) -> Vec<(String, String)> {
+    // 2024-05-01T09:30:12Z -> 20240501T093012Z
+    let amz_date: String = iso8601_utc(unix_secs(SystemTime::now()))
+        .chars()
+        .filter(|c| !matches!(c, '-' | ':'))
+        .collect();
+    let mut headers = vec![
+        ("host".to_string(), endpoint.host.clone()),
+        (
+            "x-amz-content-sha256".to_string(),
+            EMPTY_PAYLOAD_SHA256.to_string(),
+        ),
+        ("x-amz-date".to_string(), amz_date.clone()),
+    ];
+    let Some(credentials) = credentials else {
+        return headers;
+    };
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token".to_string(), token.clone()));
+    }
+
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let canonical_request = format!(
+        "GET\n{}\n{}\n{}\n{}\n{}",
+        endpoint.path, query, canonical_headers, signed_headers, EMPTY_PAYLOAD_SHA256
+    );
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let key = [region, "s3", "aws4_request"].iter().fold(
+        hmac_sha256(
+            format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        ),
+        |key, part| hmac_sha256(&key, part.as_bytes()),
+    );
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+    headers.push((
+        "authorization".to_string(),
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            credentials.access_key_id, scope, signed_headers, signature
+        ),
+    ));
+    headers
+}
+
+/// Quotes `value` for a curl config file.
+fn config_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// What a request sends along.
+pub(crate) enum RequestBody<'a> {
+    Text(&'a str),
+    // Uploaded as is; curl PUTs it unless told otherwise.
+    File(&'a std::path::Path),
+}
+
+/// Sends a `method` request to `url` through the system curl and returns the status code
+/// and body. The URL, headers, user and body go through stdin, so signatures, tokens and
+/// passwords never show up in process lists.
+pub(crate) fn curl_request(
+    method: &str,
+    url: &str,
+    headers: &[(String, String)],
+    user: Option<(&str, &str)>,
+    body: Option<RequestBody<'_>>,
+) -> Result<(u16, Vec<u8>), String> {
+    let mut command = Command::new(CURL);
+    command
+        .arg("--silent")
+        .arg("--show-error")
+        .arg("--connect-timeout")
+        .arg(CONNECT_TIMEOUT_SECS.to_string())
+        .arg("--max-time")
+        .arg(REQUEST_TIMEOUT_SECS.to_string())
+        .arg("--write-out")
+        .arg("%{http_code}")
+        .arg("--config")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = command.spawn().map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            "curl was not found. Install curl to scan cloud storage.".to_string()
+        } else {
+            format!("Failed to run curl: {}", e)
+        }
+    })?;
+
+    let mut config = format!("url = {}\n", config_quote(url));
+    if method != "GET" {
+        config.push_str(&format!("request = {}\n", config_quote(method)));
+    }
+    if let Some((name, password)) = user {
+        config.push_str(&format!(
+            "user = {}\n",
+            config_quote(&format!("{}:{}", name, password))
+        ));
+    }
+    match body {
+        Some(RequestBody::Text(text)) => {
+            config.push_str(&format!("data-binary = {}\n", config_quote(text)));
+        }
+        Some(RequestBody::File(path)) => {
+            config.push_str(&format!(
+                "upload-file = {}\n",
+                config_quote(&path.to_string_lossy())
+            ));
+        }
+        None => {}
+    }
+    for (name, value) in headers.iter().filter(|(name, _)| name != "host") {
+        config.push_str(&format!(
+            "header = {}\n",
+            config_quote(&format!("{}: {}", name, value))
+        ));
+    }
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(config.as_bytes())
+            .map_err(|e| format!("Failed to run curl: {}", e))?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!(
+            "Request to {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    // --write-out appends the three-digit status after the body.
+    let mut body = output.stdout;
+    let status = body
+        .len()
+        .checked_sub(3)
+        .map(|at| body.split_off(at))
+        .and_then(|code| String::from_utf8(code).ok())
+        .and_then(|code| code.parse().ok())
+        .ok_or("curl returned no status code.")?;
+    Ok((status, body))
+}
+
+/// Calls `on_end(name, text)` for every closing (or empty) element, with the text it
+/// contained since the last opening element.
+pub(crate) fn walk_xml(xml: &str, mut on_end: impl FnMut(&str, String)) -> Result<(), String> {
+    let mut reader = Reader::from_str(xml);
+    let mut text = String::new();
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(_) => text.clear(),
+            Event::Text(t) => text.push_str(&t.decode().map_err(|e| e.to_string())?),
+            Event::GeneralRef(r) => {
+                if let Some(c) = r.resolve_char_ref().map_err(|e| e.to_string())? {
+                    text.push(c);
+                } else {
+                    let name = r.decode().map_err(|e| e.to_string())?;
+                    text.push_str(resolve_predefined_entity(&name).unwrap_or_default());
+                }
+            }
+            Event::End(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                on_end(&name, std::mem::take(&mut text));
+            }
+            Event::Empty(e) => {
+                text.clear();
+                on_end(
+                    &String::from_utf8_lossy(e.local_name().as_ref()),
+                    String::new(),
+                );
+            }
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+/// Reads a `ListObjectsV2` response.
+fn parse_page(xml: &str) -> Result<Page, String> {
+    let mut page = Page::default();
+    let mut object = Object::default();
+    walk_xml(xml, |name, text| match name {
+        "Key" => object.key = text,
+        "Size" => object.size = text.parse().unwrap_or(0),
+        "StorageClass" => object.storage_class = Some(text),
+        "Contents" => page.objects.push(std::mem::take(&mut object)),
+        "IsTruncated" => page.truncated = text == "true",
+        "NextContinuationToken" => page.next_token = Some(text),
+        _ => {}
+    })?;
+    Ok(page)
+}
+
+/// The code and message of an S3 error response.
+fn error_message(xml: &str) -> Option<String> {
+    let (mut code, mut message) = (None, None);
+    walk_xml(xml, |name, text| match name {
+        "Code" => code = Some(text),
+        "Message" => message = Some(text),
+        _ => {}
+    })
+    .ok()?;
+    match (code, message) {
+        (Some(code), Some(message)) => Some(format!("{}: {}", code, message)),
+        (code, message) => code.or(message),
+    }
+}
+
+/// Lists every object of the bucket below `prefix`, one page of up to 1000 keys at a
+/// time, adding them to `listing` as files below folders split at `/`. Returns the
+/// usage per storage class.
+fn list_bucket(
+    target: &BucketTarget,
+    prefix: &str,
+    listing: &mut RemoteListing,
+    root: &std::path::Path,
+    progress: &dyn ScanProgress,
+) -> Result<Vec<StorageClassUsage>, String> {
+    let region = target
+        .region
+        .clone()
+        .or_else(|| env_var("AWS_REGION"))
+        .or_else(|| env_var("AWS_DEFAULT_REGION"))
+        .unwrap_or_else(|| DEFAULT_REGION.to_string());
+    let endpoint = endpoint(target, &region)?;
+    let credentials = credentials(target)?;
+
+    let mut classes: HashMap<String, (u64, u64)> = HashMap::new();
+    let (mut files, mut bytes) = (0u64, 0u64);
+    let mut token: Option<String> = None;
+    loop {
+        // Already sorted by name, as the signature requires.
+        let mut query = vec![];
+        if let Some(token) = &token {
+            query.push(format!("continuation-token={}", uri_encode(token)));
+        }
+        query.push("list-type=2".to_string());
+        if !prefix.is_empty() {
+            query.push(format!("prefix={}", uri_encode(prefix)));
+        }
+        let query = query.join("&");
+        let headers = request_headers(&endpoint, &query, &region, credentials.as_ref());
+        let url = format!(
+            "{}://{}{}?{}",
+            endpoint.scheme, endpoint.host, endpoint.path, query
+        );
+
+        let (status, body) = curl_request("GET", &url, &headers, None, None)?;
+        let body = String::from_utf8_lossy(&body);
+        if status != 200 {
+            return Err(match error_message(&body) {
+                Some(message) => format!("Listing {} failed: {}", target.bucket, message),
+                None => format!("Listing {} failed with HTTP {}.", target.bucket, status),
+            });
+        }
+        let page = parse_page(&body)?;
+        for object in page.objects {
+            let relative = object.key.strip_prefix(prefix).unwrap_or(&object.key);
+            // Keys are free-form: drop the empty and `.` segments a path would fold away.
+            let segments: Vec<&str> = relative
+                .split('/')
+                .filter(|segment| !matches!(*segment, "" | "."))
+                .collect();
+            if segments.is_empty() {
+                continue;
+            }
+            let path = segments.iter().fold(root.to_path_buf(), |p, s| p.join(s));
+            // Zero-byte keys ending in `/` are the folder markers consoles create.
+            if object.key.ends_with('/') {
+                listing.insert(path, FsNodeKind::Directory, 0);
+                continue;
+            }
+            let class = object
+                .storage_class
+                .unwrap_or_else(|| DEFAULT_STORAGE_CLASS.to_string());
+            let usage = classes.entry(class).or_default();
+            usage.0 += 1;
+            usage.1 += object.size;
+            files += 1;
+            bytes += object.size;
+            listing.insert(path, FsNodeKind::File, object.size);
+        }
+        progress.on_progress(ProgressSnapshot {
+            scanned_files: files,
+            scanned_dirs: 0,
+            total_bytes: bytes,
+            current_path: None,
+        });
+        match page.next_token {
+            Some(next) if page.truncated => token = Some(next),
+            _ => break,
+        }
+    }
+
+    let mut classes: Vec<StorageClassUsage> = classes
+        .into_iter()
+        .map(|(storage_class, (objects, bytes))| StorageClassUsage {
+            storage_class,
+            objects,
+            bytes,
+        })
+        .collect();
+    classes.sort_by_key(|usage| std::cmp::Reverse(usage.bytes));
+    Ok(classes)
+}
+
+/// Lists a bucket (or the part of it below a prefix) and stores it like a local scan,
+/// with `/`-separated key prefixes as folders. The tree is rooted at `s3://bucket/prefix`.
+pub(crate) fn scan_bucket_blocking(
+    target: &BucketTarget,
+    min_node_bytes: Option<u64>,
+    excludes: Vec<String>,
+    window: Option<tauri::Window>,
+) -> Result<(crate::scanner::FinishedScan, Vec<StorageClassUsage>), String> {
+    if !valid_bucket(&target.bucket) {
+        return Err(format!("Invalid bucket name: {}", target.bucket));
+    }
+    let prefix = target
+        .prefix
+        .as_deref()
+        .map(|prefix| prefix.trim_matches('/'))
+        .filter(|prefix| !prefix.is_empty())
+        .map(|prefix| format!("{}/", prefix))
+        .unwrap_or_default();
+    let started_at = SystemTime::now();
+    let started = Instant::now();
+    let root = PathBuf::from(format!(
+        "s3://{}/{}",
+        target.bucket,
+        prefix.trim_end_matches('/')
+    ));
+    let opts = ScanOptions::network(min_node_bytes.unwrap_or(DEFAULT_MIN_NODE_BYTES))
+        .with_excludes(excludes);
+    let progress: Box<dyn ScanProgress> = match window {
+        Some(window) => Box::new(WindowProgress::new(window, &root)),
+        None => Box::new(NoProgress),
+    };
+
+    tracing::info!(bucket = %target.bucket, prefix = %prefix, "bucket scan started");
+    let mut listing = RemoteListing::new(root.clone());
+    let classes = list_bucket(target, &prefix, &mut listing, &root, progress.as_ref())
+        .inspect_err(
+            |err| tracing::error!(bucket = %target.bucket, error = %err, "bucket scan failed"),
+        )?;
+    let finished = remote::scan_listing(listing, &opts, started_at, started, 0)?;
+    Ok((finished, classes))
+}
+
+pub async fn scan_bucket(
+    window: tauri::Window,
+    store: &ScanStore,
+    target: BucketTarget,
+    min_node_bytes: Option<u64>,
+) -> Result<BucketScanResult, String> {
+    let defaults = window.state::<SettingsStore>().scan();
+    let min_node_bytes = min_node_bytes.or(defaults.min_node_bytes);
+    let (finished, storage_classes) = tauri::async_runtime::spawn_blocking(move || {
+        scan_bucket_blocking(&target, min_node_bytes, defaults.excludes, Some(window))
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    let scan_id = store.insert_spilled(finished.tree, finished.spill, finished.summary)?;
+    Ok(BucketScanResult {
+        scan_id,
+        root: finished.pruned,
+        storage_classes,
+    })
+}
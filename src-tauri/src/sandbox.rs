@@ -0,0 +1,286 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tauri::Manager;
+use tauri_plugin_diskcheck_storage::{BookmarkedFolder, StorageExt};
+
+const GRANTED_FOLDERS_FILE: &str = "granted-folders.json";
+
+#[cfg(target_os = "ios")]
+mod platform {
+    use std::{
+        ffi::{c_void, CStr},
+        os::unix::ffi::OsStrExt,
+        path::PathBuf,
+        ptr,
+    };
+
+    type CFTypeRef = *const c_void;
+    type CFDataRef = *const c_void;
+    type CFURLRef = *const c_void;
+    type CFErrorRef = *const c_void;
+    type CFIndex = isize;
+    type Boolean = u8;
+
+    // kCFURLBookmarkResolutionWithoutUIMask: never ask the user while resolving.
+    const RESOLVE_WITHOUT_UI: usize = 1 << 8;
+    const MAX_PATH_BYTES: usize = 4096;
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFDataCreate(allocator: CFTypeRef, bytes: *const u8, length: CFIndex) -> CFDataRef;
+        fn CFDataGetLength(data: CFDataRef) -> CFIndex;
+        fn CFDataGetBytePtr(data: CFDataRef) -> *const u8;
+        fn CFURLCreateByResolvingBookmarkData(
+            allocator: CFTypeRef,
+            bookmark: CFDataRef,
+            options: usize,
+            relative_to: CFURLRef,
+            resource_properties: CFTypeRef,
+            is_stale: *mut Boolean,
+            error: *mut CFErrorRef,
+        ) -> CFURLRef;
+        fn CFURLCreateBookmarkData(
+            allocator: CFTypeRef,
+            url: CFURLRef,
+            options: usize,
+            resource_properties: CFTypeRef,
+            relative_to: CFURLRef,
+            error: *mut CFErrorRef,
+        ) -> CFDataRef;
+        fn CFURLGetFileSystemRepresentation(
+            url: CFURLRef,
+            resolve_against_base: Boolean,
+            buffer: *mut u8,
+            max_len: CFIndex,
+        ) -> Boolean;
+        fn CFURLStartAccessingSecurityScopedResource(url: CFURLRef) -> Boolean;
+        fn CFURLStopAccessingSecurityScopedResource(url: CFURLRef);
+        fn CFRelease(cf: CFTypeRef);
+    }
+
+    /// Access to a folder outside the sandbox, given up when dropped.
+    pub(crate) struct Access {
+        url: CFURLRef,
+        accessing: bool,
+    }
+
+    // The URL is only retained, released and passed to thread-safe CoreFoundation calls.
+    unsafe impl Send for Access {}
+
+    impl Drop for Access {
+        fn drop(&mut self) {
+            unsafe {
+                if self.accessing {
+                    CFURLStopAccessingSecurityScopedResource(self.url);
+                }
+                CFRelease(self.url);
+            }
+        }
+    }
+
+    pub(super) struct Resolved {
+        pub(super) path: PathBuf,
+        pub(super) stale: bool,
+        pub(super) access: Access,
+    }
+
+    /// Resolves `bookmark` and starts accessing the folder it names.
+    pub(super) fn resolve(bookmark: &[u8]) -> Result<Resolved, String> {
+        let mut stale: Boolean = 0;
+        let url = unsafe {
+            let data = CFDataCreate(ptr::null(), bookmark.as_ptr(), bookmark.len() as CFIndex);
+            if data.is_null() {
+                return Err("Failed to read the folder's bookmark.".to_string());
+            }
+            let url = CFURLCreateByResolvingBookmarkData(
+                ptr::null(),
+                data,
+                RESOLVE_WITHOUT_UI,
+                ptr::null(),
+                ptr::null(),
+                &mut stale,
+                ptr::null_mut(),
+            );
+            CFRelease(data);
+            url
+        };
+        if url.is_null() {
+            return Err(
+                "The folder is gone or access to it was withdrawn; pick it again.".to_string(),
+            );
+        }
+        let accessing = unsafe { CFURLStartAccessingSecurityScopedResource(url) } != 0;
+        let access = Access { url, accessing };
+        let mut buffer = vec![0u8; MAX_PATH_BYTES];
+        let ok = unsafe {
+            CFURLGetFileSystemRepresentation(url, 1, buffer.as_mut_ptr(), buffer.len() as CFIndex)
+        };
+        if ok == 0 {
+            return Err("Failed to find the picked folder.".to_string());
+        }
+        let path = CStr::from_bytes_until_nul(&buffer).map_err(|e| e.to_string())?;
+        Ok(Resolved {
+            path: PathBuf::from(std::ffi::OsStr::from_bytes(path.to_bytes())),
+            stale: stale != 0,
+            access,
+        })
+    }
+
+    /// A fresh bookmark for the folder being accessed, to replace a stale one.
+    pub(super) fn bookmark(access: &Access) -> Result<Vec<u8>, String> {
+        unsafe {
+            let data = CFURLCreateBookmarkData(
+                ptr::null(),
+                access.url,
+                0,
+                ptr::null(),
+                ptr::null(),
+                ptr::null_mut(),
+            );
+            if data.is_null() {
+                return Err("Failed to renew the folder's bookmark.".to_string());
+            }
+            let bytes =
+                std::slice::from_raw_parts(CFDataGetBytePtr(data), CFDataGetLength(data) as usize)
+                    .to_vec();
+            CFRelease(data);
+            Ok(bytes)
+        }
+    }
+}
+
+#[cfg(not(target_os = "ios"))]
+mod platform {
+    use std::path::PathBuf;
+
+    pub(crate) struct Access;
+
+    pub(super) struct Resolved {
+        pub(super) path: PathBuf,
+        pub(super) stale: bool,
+        pub(super) access: Access,
+    }
+
+    pub(super) fn resolve(_bookmark: &[u8]) -> Result<Resolved, String> {
+        Err("Folder bookmarks are only used on iOS.".to_string())
+    }
+
+    pub(super) fn bookmark(_access: &Access) -> Result<Vec<u8>, String> {
+        Err("Folder bookmarks are only used on iOS.".to_string())
+    }
+}
+
+/// Keeps a folder picked in the Files app readable while it is scanned.
+pub(crate) struct ScopedAccess {
+    _access: platform::Access,
+}
+
+fn granted_folders_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(GRANTED_FOLDERS_FILE))
+        .map_err(|e| e.to_string())
+}
+
+fn load(app: &tauri::AppHandle) -> Vec<BookmarkedFolder> {
+    granted_folders_path(app)
+        .ok()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &tauri::AppHandle, folders: &[BookmarkedFolder]) -> Result<(), String> {
+    let path = granted_folders_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.to_string_lossy(), e))?;
+    }
+    let json = serde_json::to_vec_pretty(folders).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)
+        .and_then(|_| fs::rename(&tmp, &path))
+        .map_err(|e| format!("Failed to save {}: {}", path.to_string_lossy(), e))
+}
+
+/// Makes `root` readable for a scan. Outside iOS, and inside the app's own container,
+/// there is nothing to do. Elsewhere on iOS, `root` must lie in a folder granted with
+/// `grant_folder`, whose bookmark is resolved (and renewed when stale) to start access.
+pub(crate) fn open_for_scan(
+    app: &tauri::AppHandle,
+    root: &Path,
+) -> Result<Option<ScopedAccess>, String> {
+    if !cfg!(target_os = "ios") {
+        return Ok(None);
+    }
+    if let Ok(home) = app.path().home_dir() {
+        if root.starts_with(home) {
+            return Ok(None);
+        }
+    }
+    let mut folders = load(app);
+    let Some(folder) = folders
+        .iter_mut()
+        .find(|folder| root.starts_with(&folder.path))
+    else {
+        return Err(format!(
+            "iOS only lets DiskCheck read {} once the folder is picked in the Files app.",
+            root.to_string_lossy()
+        ));
+    };
+    let bookmark = hex::decode(&folder.bookmark).map_err(|e| e.to_string())?;
+    let resolved = platform::resolve(&bookmark)?;
+    let moved = resolved.path != Path::new(&folder.path);
+    if resolved.stale || moved {
+        tracing::info!(from = %folder.path, to = %resolved.path.display(), "renewed folder bookmark");
+        folder.bookmark = hex::encode(platform::bookmark(&resolved.access)?);
+        folder.path = resolved.path.to_string_lossy().into_owned();
+        save(app, &folders)?;
+    }
+    if moved {
+        return Err(format!(
+            "The folder has moved to {}; scan it there.",
+            resolved.path.to_string_lossy()
+        ));
+    }
+    Ok(Some(ScopedAccess {
+        _access: resolved.access,
+    }))
+}
+
+/// Lets the user pick a folder in the Files app and keeps its bookmark, so it can be
+/// scanned now and after restarts. `None` when they cancel. Only on iOS.
+pub async fn grant_folder(app: tauri::AppHandle) -> Result<Option<BookmarkedFolder>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(picked) = app.storage().pick_bookmarked_folder()? else {
+            return Ok(None);
+        };
+        let mut folders = load(&app);
+        folders.retain(|folder| folder.path != picked.path);
+        folders.push(picked.clone());
+        save(&app, &folders)?;
+        tracing::info!(path = %picked.path, "folder granted");
+        Ok(Some(picked))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// The folders granted with `grant_folder`.
+pub fn granted_folders(app: &tauri::AppHandle) -> Vec<BookmarkedFolder> {
+    load(app)
+}
+
+/// Forgets the folder granted at `path`. Returns whether there was one.
+pub fn revoke_folder(app: &tauri::AppHandle, path: &str) -> Result<bool, String> {
+    let mut folders = load(app);
+    let before = folders.len();
+    folders.retain(|folder| folder.path != path);
+    if folders.len() == before {
+        return Ok(false);
+    }
+    save(app, &folders)?;
+    Ok(true)
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+use tauri::Manager;
+
+use crate::store::unix_secs;
+
+const SAVINGS_FILE: &str = "savings.json";
+// Earlier sessions listed in the report; the cumulative totals count every session.
+const MAX_SESSIONS: usize = 100;
+
+/// Space freed during one run of the app.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSavings {
+    pub started_at_secs: u64,
+    pub freed_bytes: u64,
+    pub items: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavingsReport {
+    pub total_freed_bytes: u64,
+    pub total_items: u64,
+    pub current_session: SessionSavings,
+    // Newest first, the current session included.
+    pub sessions: Vec<SessionSavings>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SavingsHistory {
+    total_freed_bytes: u64,
+    total_items: u64,
+    // Oldest first.
+    sessions: Vec<SessionSavings>,
+}
+
+/// Space freed through DiskCheck, kept across runs in the app data folder.
+pub struct Savings {
+    session_started_at_secs: u64,
+    // Serializes read-modify-write cycles of the file.
+    lock: Mutex<()>,
+}
+
+impl Default for Savings {
+    fn default() -> Self {
+        Self {
+            session_started_at_secs: unix_secs(SystemTime::now()),
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+fn savings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(SAVINGS_FILE))
+        .map_err(|e| e.to_string())
+}
+
+fn load(path: &Path) -> SavingsHistory {
+    fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save(path: &Path, history: &SavingsHistory) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.to_string_lossy(), e))?;
+    }
+    let json = serde_json::to_vec_pretty(history).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(|e| format!("Failed to save {}: {}", path.to_string_lossy(), e))
+}
+
+fn adjust(total: u64, delta: i64) -> u64 {
+    total.saturating_add_signed(delta)
+}
+
+impl Savings {
+    fn current(&self) -> SessionSavings {
+        SessionSavings {
+            started_at_secs: self.session_started_at_secs,
+            freed_bytes: 0,
+            items: 0,
+        }
+    }
+
+    fn update(&self, app: &tauri::AppHandle, bytes: i64, items: i64) -> Result<(), String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let path = savings_path(app)?;
+        let mut history = load(&path);
+        history.total_freed_bytes = adjust(history.total_freed_bytes, bytes);
+        history.total_items = adjust(history.total_items, items);
+        if history
+            .sessions
+            .last()
+            .is_none_or(|s| s.started_at_secs != self.session_started_at_secs)
+        {
+            history.sessions.push(self.current());
+        }
+        if let Some(session) = history.sessions.last_mut() {
+            session.freed_bytes = adjust(session.freed_bytes, bytes);
+            session.items = adjust(session.items, items);
+        }
+        let excess = history.sessions.len().saturating_sub(MAX_SESSIONS);
+        history.sessions.drain(..excess);
+        save(&path, &history)
+    }
+
+    /// Counts `bytes` in `items` as freed, or given back when negative (items restored
+    /// from the trash). Failures are logged: they must not fail the action itself.
+    pub fn record(&self, app: &tauri::AppHandle, bytes: i64, items: i64) {
+        if bytes == 0 && items == 0 {
+            return;
+        }
+        if let Err(error) = self.update(app, bytes, items) {
+            tracing::warn!(error = %error, "failed to record freed space");
+        }
+    }
+
+    /// The space freed with DiskCheck in total, in this session and in earlier ones.
+    pub fn report(&self, app: &tauri::AppHandle) -> Result<SavingsReport, String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let history = load(&savings_path(app)?);
+        let current_session = history
+            .sessions
+            .last()
+            .filter(|s| s.started_at_secs == self.session_started_at_secs)
+            .cloned()
+            .unwrap_or_else(|| self.current());
+        let mut sessions = history.sessions;
+        if sessions
+            .last()
+            .is_none_or(|s| s.started_at_secs != self.session_started_at_secs)
+        {
+            sessions.push(current_session.clone());
+        }
+        sessions.reverse();
+        Ok(SavingsReport {
+            total_freed_bytes: history.total_freed_bytes,
+            total_items: history.total_items,
+            current_session,
+            sessions,
+        })
+    }
+}
@@ -0,0 +1,288 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use tauri::Manager;
+
+use crate::{
+    scanner::same_path,
+    store::{ScanStore, ScanSummary},
+};
+
+const HISTORY_FILE: &str = "scan_history.json";
+// Kept apart from the history so notes outlive the scans dropping off it.
+const ANNOTATIONS_FILE: &str = "scan_annotations.json";
+// Older scans drop off the list.
+const MAX_ENTRIES: usize = 50;
+
+/// What users wrote about a scan, e.g. "before the Windows update". Also stored in
+/// snapshots with bincode, so no field may be skipped when serializing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanAnnotation {
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl ScanAnnotation {
+    /// Trimmed, without blank or repeated tags; `None` when nothing is left.
+    fn normalized(self) -> Option<Self> {
+        let note = self
+            .note
+            .map(|note| note.trim().to_string())
+            .filter(|note| !note.is_empty());
+        let mut tags: Vec<String> = vec![];
+        for tag in self.tags {
+            let tag = tag.trim();
+            if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+        (note.is_some() || !tags.is_empty()).then_some(Self { note, tags })
+    }
+}
+
+/// A past scan as listed on the landing page, newest first.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentScan {
+    pub root_path: String,
+    pub started_at_secs: u64,
+    pub duration_ms: u64,
+    pub total_bytes: u64,
+    pub file_count: u64,
+    pub dir_count: u64,
+    pub skipped_entries: u64,
+    // Still in memory: opens instantly.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_id: Option<String>,
+    // Last snapshot saved of, or opened for, this scan.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_path: Option<String>,
+    // The snapshot file is still there to reopen.
+    pub snapshot_available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<ScanAnnotation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryEntry {
+    root_path: String,
+    started_at_secs: u64,
+    duration_ms: u64,
+    total_bytes: u64,
+    file_count: u64,
+    dir_count: u64,
+    skipped_entries: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    snapshot_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnnotationEntry {
+    root_path: String,
+    started_at_secs: u64,
+    #[serde(flatten)]
+    annotation: ScanAnnotation,
+}
+
+impl AnnotationEntry {
+    fn is_of(&self, root_path: &str, started_at_secs: u64) -> bool {
+        same_path(&self.root_path, root_path) && self.started_at_secs == started_at_secs
+    }
+}
+
+impl HistoryEntry {
+    fn new(summary: &ScanSummary) -> Self {
+        Self {
+            root_path: summary.root_path.clone(),
+            started_at_secs: summary.started_at_secs,
+            duration_ms: summary.duration_ms,
+            total_bytes: summary.total_bytes,
+            file_count: summary.file_count,
+            dir_count: summary.dir_count,
+            skipped_entries: summary.skipped_entries,
+            snapshot_path: None,
+        }
+    }
+
+    fn is_of(&self, summary: &ScanSummary) -> bool {
+        same_path(&self.root_path, &summary.root_path)
+            && self.started_at_secs == summary.started_at_secs
+    }
+}
+
+/// Summaries of completed scans, kept across runs in the app data folder.
+#[derive(Default)]
+pub struct ScanHistory {
+    // Serializes read-modify-write cycles of the file.
+    lock: Mutex<()>,
+}
+
+fn data_path(app: &tauri::AppHandle, file: &str) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(file))
+        .map_err(|e| e.to_string())
+}
+
+/// Oldest first. A missing or unreadable file counts as no entries.
+fn load<T: DeserializeOwned>(path: &Path) -> Vec<T> {
+    fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save<T: Serialize>(path: &Path, entries: &[T]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.to_string_lossy(), e))?;
+    }
+    let json = serde_json::to_vec_pretty(entries).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(|e| format!("Failed to save {}: {}", path.to_string_lossy(), e))
+}
+
+impl ScanHistory {
+    fn update(
+        &self,
+        app: &tauri::AppHandle,
+        summary: &ScanSummary,
+        snapshot: Option<&Path>,
+    ) -> Result<(), String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let path = data_path(app, HISTORY_FILE)?;
+        let mut entries: Vec<HistoryEntry> = load(&path);
+        let at = match entries.iter().position(|entry| entry.is_of(summary)) {
+            Some(at) => at,
+            None => {
+                entries.push(HistoryEntry::new(summary));
+                entries.len() - 1
+            }
+        };
+        if let Some(snapshot) = snapshot {
+            entries[at].snapshot_path = Some(snapshot.to_string_lossy().into_owned());
+        }
+        entries.sort_by_key(|entry| entry.started_at_secs);
+        let excess = entries.len().saturating_sub(MAX_ENTRIES);
+        entries.drain(..excess);
+        save(&path, &entries)
+    }
+
+    /// Adds a completed scan. Failures are logged: they must not fail the scan.
+    pub fn record(&self, app: &tauri::AppHandle, summary: &ScanSummary) {
+        if let Err(error) = self.update(app, summary, None) {
+            tracing::warn!(error = %error, "failed to record scan history");
+        }
+    }
+
+    /// Remembers `snapshot` as holding the scan `summary` describes, so it can be
+    /// reopened from the list.
+    pub fn record_snapshot(&self, app: &tauri::AppHandle, summary: &ScanSummary, snapshot: &Path) {
+        if let Err(error) = self.update(app, summary, Some(snapshot)) {
+            tracing::warn!(error = %error, "failed to record scan history");
+        }
+    }
+
+    /// Sets the note and tags of the scan of `root_path` started at `started_at_secs`,
+    /// replacing earlier ones; blank ones clear them.
+    pub fn annotate(
+        &self,
+        app: &tauri::AppHandle,
+        root_path: &str,
+        started_at_secs: u64,
+        annotation: ScanAnnotation,
+    ) -> Result<(), String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let path = data_path(app, ANNOTATIONS_FILE)?;
+        let mut entries: Vec<AnnotationEntry> = load(&path);
+        entries.retain(|entry| !entry.is_of(root_path, started_at_secs));
+        if let Some(annotation) = annotation.normalized() {
+            entries.push(AnnotationEntry {
+                root_path: root_path.to_string(),
+                started_at_secs,
+                annotation,
+            });
+        }
+        save(&path, &entries)
+    }
+
+    /// Recent scans, newest first, with the ID of those still in memory and whether
+    /// their snapshot can be reopened.
+    pub fn recent(
+        &self,
+        app: &tauri::AppHandle,
+        store: &ScanStore,
+    ) -> Result<Vec<RecentScan>, String> {
+        let (entries, annotations) = {
+            let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+            let entries: Vec<HistoryEntry> = load(&data_path(app, HISTORY_FILE)?);
+            let annotations: Vec<AnnotationEntry> = load(&data_path(app, ANNOTATIONS_FILE)?);
+            (entries, annotations)
+        };
+        let stored = store.list()?;
+        Ok(entries
+            .into_iter()
+            .rev()
+            .map(|entry| {
+                let scan_id = stored
+                    .iter()
+                    .rev()
+                    .find(|scan| entry.is_of(&scan.summary))
+                    .map(|scan| scan.id.clone());
+                let snapshot_available = entry
+                    .snapshot_path
+                    .as_deref()
+                    .is_some_and(|path| Path::new(path).is_file());
+                let annotation = annotations
+                    .iter()
+                    .find(|a| a.is_of(&entry.root_path, entry.started_at_secs))
+                    .map(|a| a.annotation.clone());
+                RecentScan {
+                    root_path: entry.root_path,
+                    started_at_secs: entry.started_at_secs,
+                    duration_ms: entry.duration_ms,
+                    total_bytes: entry.total_bytes,
+                    file_count: entry.file_count,
+                    dir_count: entry.dir_count,
+                    skipped_entries: entry.skipped_entries,
+                    scan_id,
+                    snapshot_path: entry.snapshot_path,
+                    snapshot_available,
+                    annotation,
+                }
+            })
+            .collect())
+    }
+}
+
+/// The notes and tags of the scans `scans` describe, read off the async runtime.
+pub(crate) async fn annotations_of<const N: usize>(
+    app: &tauri::AppHandle,
+    scans: [&ScanSummary; N],
+) -> Result<[Option<ScanAnnotation>; N], String> {
+    let app = app.clone();
+    let keys = scans.map(|scan| (scan.root_path.clone(), scan.started_at_secs));
+    tauri::async_runtime::spawn_blocking(move || {
+        let history = app.state::<ScanHistory>();
+        let _guard = history.lock.lock().map_err(|e| e.to_string())?;
+        let entries: Vec<AnnotationEntry> = load(&data_path(&app, ANNOTATIONS_FILE)?);
+        Ok(keys.map(|(root_path, started_at_secs)| {
+            entries
+                .iter()
+                .find(|entry| entry.is_of(&root_path, started_at_secs))
+                .map(|entry| entry.annotation.clone())
+        }))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
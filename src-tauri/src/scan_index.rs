@@ -0,0 +1,126 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Condvar, Mutex},
+};
+use tauri::Manager;
+
+use diskcheck_core::ScanIndex;
+
+const INDEX_DIR: &str = "scan-index";
+// Indexes are read once per refresh scan and written in the background, so favour ratio.
+const ZSTD_LEVEL: i32 = 3;
+
+// Serializes read-merge-write cycles, so two scans of one volume cannot drop each
+// other's folders.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+// Index files with a save under way, which readers of the previous listings wait for.
+static SAVING: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+static SAVED: Condvar = Condvar::new();
+
+/// Where a scan keeps the per-volume index of what it listed, and whether it may reuse
+/// the previous one.
+#[derive(Debug, Clone)]
+pub(crate) struct IndexOptions {
+    dir: PathBuf,
+    // Reuse the listings of unchanged folders. Off by default: files rewritten in place
+    // keep their indexed size until their folder changes.
+    pub refresh: bool,
+}
+
+impl IndexOptions {
+    pub(crate) fn new(app: &tauri::AppHandle, refresh: bool) -> Option<Self> {
+        let dir = app.path().app_cache_dir().ok()?.join(INDEX_DIR);
+        Some(Self { dir, refresh })
+    }
+
+    /// The index file of the volume holding `root`; `None` for network mounts, whose
+    /// mtimes come from another clock and whose scans are not worth the bookkeeping.
+    pub(crate) fn file_for(&self, root: &Path) -> Option<PathBuf> {
+        let volume = crate::volumes::volume_for_path(root)?;
+        if volume.is_network {
+            return None;
+        }
+        let key = blake3::hash(volume.mount_point.as_bytes()).to_hex();
+        Some(self.dir.join(format!("{}.bin", &key[..32])))
+    }
+}
+
+/// Reads a volume index. A missing or unreadable file counts as no index.
+pub(crate) fn load(file: &Path) -> Option<ScanIndex> {
+    let bytes = fs::read(file).ok()?;
+    let bytes = zstd::decode_all(bytes.as_slice()).ok()?;
+    match bincode::deserialize(&bytes) {
+        Ok(index) => Some(index),
+        Err(e) => {
+            tracing::warn!(path = %file.display(), error = %e, "ignored unreadable scan index");
+            None
+        }
+    }
+}
+
+/// Where the listings replaced by the latest scans of a volume are kept, next to its
+/// index `file`.
+fn previous_file(file: &Path) -> PathBuf {
+    file.with_extension("prev.bin")
+}
+
+fn save(file: &Path, index: &ScanIndex) -> Result<(), String> {
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.to_string_lossy(), e))?;
+    }
+    let bytes = bincode::serialize(index).map_err(|e| e.to_string())?;
+    let bytes = zstd::encode_all(bytes.as_slice(), ZSTD_LEVEL).map_err(|e| e.to_string())?;
+    // Write next to the target and rename, so a crash never leaves half a file.
+    let tmp = file.with_extension("bin.tmp");
+    fs::write(&tmp, bytes)
+        .and_then(|_| fs::rename(&tmp, file))
+        .map_err(|e| format!("Failed to save {}: {}", file.to_string_lossy(), e))
+}
+
+/// Folds the index of a scan of `root` into the volume index at `file`, replacing what
+/// was recorded below `root` before. The replaced listings move to the previous index,
+/// so the next reader can tell what this scan found new.
+fn persist(file: &Path, root: &Path, scanned: ScanIndex) -> Result<(), String> {
+    let _guard = WRITE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut index = load(file).unwrap_or_default();
+    let replaced = index.take_below(root);
+    index.merge(root, scanned);
+    save(file, &index)?;
+    tracing::debug!(path = %file.display(), dirs = index.len(), "scan index saved");
+
+    let previous_file = previous_file(file);
+    let mut previous = load(&previous_file).unwrap_or_default();
+    previous.merge(root, replaced);
+    save(&previous_file, &previous)
+}
+
+/// Runs `persist` on a thread of its own: saving a large index takes a while.
+pub(crate) fn persist_in_background(file: PathBuf, root: PathBuf, scanned: ScanIndex) {
+    if let Ok(mut saving) = SAVING.lock() {
+        saving.push(file.clone());
+    }
+    std::thread::spawn(move || {
+        if let Err(error) = persist(&file, &root, scanned) {
+            tracing::warn!(path = %file.display(), error = %error, "failed to save scan index");
+        }
+        if let Ok(mut saving) = SAVING.lock() {
+            if let Some(at) = saving.iter().position(|f| *f == file) {
+                saving.swap_remove(at);
+            }
+        }
+        SAVED.notify_all();
+    });
+}
+
+/// The volume index `file` along with its listings as they were before the latest scan
+/// of each folder, once saves under way are done.
+pub(crate) fn load_with_previous(file: &Path) -> Option<(ScanIndex, ScanIndex)> {
+    let saving = SAVING.lock().ok()?;
+    let saving = SAVED
+        .wait_while(saving, |saving| saving.iter().any(|f| f == file))
+        .ok()?;
+    drop(saving);
+    Some((load(file)?, load(&previous_file(file))?))
+}
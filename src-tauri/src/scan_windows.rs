@@ -0,0 +1,73 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+use tauri::{Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+// Matched by the `scan-*` window pattern in capabilities/default.json.
+const SCAN_WINDOW_PREFIX: &str = "scan-";
+const SCAN_WINDOW_WIDTH: f64 = 800.0;
+const SCAN_WINDOW_HEIGHT: f64 = 600.0;
+
+/// Windows opened with `open_scan_window`, each with the folder it should scan first.
+/// Every window keeps its own scan ID, and scan events go only to the window that
+/// started the scan.
+#[derive(Default)]
+pub struct ScanWindows {
+    next: AtomicU64,
+    pending: Mutex<HashMap<String, String>>,
+}
+
+impl ScanWindows {
+    /// The folder the window labelled `label` was opened on, handed out once.
+    pub fn take_path(&self, label: &str) -> Option<String> {
+        self.pending.lock().ok()?.remove(label)
+    }
+}
+
+/// Opens another window, scanning `path` right away if given, so two folders or
+/// drives can be looked at side by side. Returns the new window's label.
+pub async fn open_scan_window(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<String, String> {
+    if let Some(path) = &path {
+        if !Path::new(path).is_dir() {
+            return Err(format!("Not a folder: {}", path));
+        }
+    }
+    let windows = app.state::<ScanWindows>();
+    let label = format!(
+        "{}{}",
+        SCAN_WINDOW_PREFIX,
+        windows.next.fetch_add(1, Ordering::Relaxed) + 1
+    );
+    let title = match &path {
+        Some(path) => format!("diskcheck — {}", path),
+        None => "diskcheck".to_string(),
+    };
+    if let Some(path) = path {
+        if let Ok(mut pending) = windows.pending.lock() {
+            pending.insert(label.clone(), path);
+        }
+    }
+    WebviewWindowBuilder::new(&app, &label, WebviewUrl::default())
+        .title(title)
+        .inner_size(SCAN_WINDOW_WIDTH, SCAN_WINDOW_HEIGHT)
+        .build()
+        .map_err(|e| format!("Failed to open a window: {}", e))?;
+    tracing::info!(label = %label, "opened scan window");
+    Ok(label)
+}
+
+/// Forgets a closed window's folder if it never got to scan it.
+pub fn handle_window_event(window: &tauri::Window, event: &WindowEvent) {
+    if !matches!(event, WindowEvent::Destroyed) || !window.label().starts_with(SCAN_WINDOW_PREFIX) {
+        return;
+    }
+    let _ = window.state::<ScanWindows>().take_path(window.label());
+}
@@ -1,47 +1,40 @@
 use serde::Serialize;
 use std::{
-    fs,
-    fs::ReadDir,
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
     path::{Path, PathBuf},
-    sync::{
-        atomic::{AtomicU64, Ordering},
-        Mutex,
-    },
-    time::{Duration, Instant},
+    sync::{atomic::AtomicBool, Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
 };
-use tauri::Emitter;
+use tauri::{Emitter, Manager};
 
-const SCAN_PROGRESS_EVENT: &str = "scan_progress";
-// NOTE: Returning the full file tree for large folders can crash the WebView IPC
-// serialization. We defensively prune the returned tree while still calculating
-// accurate directory sizes.
-const DEFAULT_MIN_NODE_BYTES: u64 = 1 * 1024 * 1024; // 1 MiB
-const DEFAULT_MAX_CHILDREN_PER_DIR: usize = 1_000;
-const DEFAULT_MAX_TOTAL_NODES: usize = 10_000;
+pub(crate) use diskcheck_core::{
+    display_name, file_extension_lower, pruned_view, same_path, walk, walk_files, Children, FsNode,
+    FsNodeKind, NodeRef, ScanTree, SkipReason,
+};
+use diskcheck_core::{
+    take_scan_dir, ArchiveLister, NoProgress, ProgressSnapshot, RealFs, Scan, ScanHooks, ScanIndex,
+    ScanOptions, ScanProgress, DEFAULT_MIN_NODE_BYTES,
+};
 
-#[derive(Debug, Clone, Copy, Serialize)]
-#[serde(rename_all = "lowercase")]
-pub enum FsNodeKind {
-    File,
-    Directory,
-    Symlink,
-    Other,
-}
+use crate::{
+    archives::Archives,
+    error::{CommandError, InternalError},
+    priority::{self, ScanMode},
+    scan_index::{self, IndexOptions},
+    settings::SettingsStore,
+    spill::{ScanSpill, SpillFile},
+    store::{unix_secs, ScanStore, ScanSummary, SkippedPath},
+    usage_check::{self, UsageCheck},
+};
 
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct FsNode {
-    pub name: String,
-    pub path: String,
-    pub kind: FsNodeKind,
-    pub size: u64,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub children: Vec<FsNode>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub extension: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub error: Option<String>,
-}
+const SCAN_PROGRESS_EVENT: &str = "scan_progress";
+// Progress estimates stop short of 100% while the scan is still running.
+const MAX_RUNNING_FRACTION: f64 = 0.99;
+// Below this the elapsed time says too little about the rest of the scan.
+const MIN_ETA_FRACTION: f64 = 0.02;
+// Skipped entries sent with a scan result; the rest are listed on request.
+const RESULT_SKIPPED_ENTRIES: usize = 100;
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -50,375 +43,418 @@ struct ScanProgressPayload {
     scanned_dirs: u64,
     total_bytes: u64,
     current_path: Option<String>,
+    // Estimated from the previous scan of the same root; absent without one.
+    percent: Option<f64>,
+    eta_secs: Option<u64>,
 }
 
-struct ProgressReporter {
+/// Forwards scan progress to the window that started the scan.
+pub(crate) struct WindowProgress {
     window: tauri::Window,
-    scanned_files: AtomicU64,
-    scanned_dirs: AtomicU64,
-    total_bytes: AtomicU64,
-    last_emit: Mutex<Instant>,
+    started: Instant,
+    // The last stored scan of the same root, as the measure of how much is left.
+    previous: Option<ScanSummary>,
 }
 
-impl ProgressReporter {
-    fn new(window: tauri::Window) -> Self {
+impl WindowProgress {
+    pub(crate) fn new(window: tauri::Window, root: &Path) -> Self {
+        let previous = window
+            .state::<ScanStore>()
+            .latest_summary(&root.to_string_lossy())
+            .filter(|summary| summary.file_count > 0 || summary.total_bytes > 0);
         Self {
             window,
-            scanned_files: AtomicU64::new(0),
-            scanned_dirs: AtomicU64::new(0),
-            total_bytes: AtomicU64::new(0),
-            last_emit: Mutex::new(Instant::now()),
-        }
-    }
-
-    fn file_scanned(&self, bytes: u64, current_path: &Path) {
-        let next = self.scanned_files.fetch_add(1, Ordering::Relaxed) + 1;
-        self.total_bytes.fetch_add(bytes, Ordering::Relaxed);
-
-        // Emit infrequently to keep overhead low when scanning millions of files.
-        if next % 512 == 0 {
-            self.maybe_emit(Some(current_path));
-        }
-    }
-
-    fn dir_scanned(&self, current_path: &Path) {
-        let next = self.scanned_dirs.fetch_add(1, Ordering::Relaxed) + 1;
-        if next % 64 == 0 {
-            self.maybe_emit(Some(current_path));
-        }
-    }
-
-    fn emit_force(&self, current_path: Option<&Path>) {
-        self.emit(current_path);
-        if let Ok(mut last_emit) = self.last_emit.lock() {
-            *last_emit = Instant::now();
+            started: Instant::now(),
+            previous,
         }
     }
 
-    fn maybe_emit(&self, current_path: Option<&Path>) {
-        let now = Instant::now();
-        let should_emit = self
-            .last_emit
-            .lock()
-            .map(|last| now.duration_since(*last) >= Duration::from_millis(120))
-            .unwrap_or(true);
-
-        if should_emit {
-            self.emit_force(current_path);
-        }
+    /// Fraction done, going by files seen and bytes counted against the previous scan.
+    /// Held below 1 until the scan actually ends, since the tree may have grown since.
+    fn fraction(&self, snapshot: &ProgressSnapshot<'_>) -> Option<f64> {
+        let previous = self.previous.as_ref()?;
+        let ratio = |done: u64, total: u64| (total > 0).then(|| done as f64 / total as f64);
+        let fraction = match (
+            ratio(snapshot.scanned_files, previous.file_count),
+            ratio(snapshot.total_bytes, previous.total_bytes),
+        ) {
+            (Some(files), Some(bytes)) => (files + bytes) / 2.0,
+            (files, bytes) => files.or(bytes)?,
+        };
+        Some(fraction.min(MAX_RUNNING_FRACTION))
     }
+}
 
-    fn emit(&self, current_path: Option<&Path>) {
+impl ScanProgress for WindowProgress {
+    fn on_progress(&self, snapshot: ProgressSnapshot<'_>) {
+        let fraction = self.fraction(&snapshot);
+        let eta_secs = fraction
+            .filter(|f| *f >= MIN_ETA_FRACTION)
+            .map(|f| (self.started.elapsed().as_secs_f64() * (1.0 - f) / f).ceil() as u64);
         let payload = ScanProgressPayload {
-            scanned_files: self.scanned_files.load(Ordering::Relaxed),
-            scanned_dirs: self.scanned_dirs.load(Ordering::Relaxed),
-            total_bytes: self.total_bytes.load(Ordering::Relaxed),
-            current_path: current_path.map(|p| p.to_string_lossy().into_owned()),
+            scanned_files: snapshot.scanned_files,
+            scanned_dirs: snapshot.scanned_dirs,
+            total_bytes: snapshot.total_bytes,
+            current_path: snapshot
+                .current_path
+                .map(|p| p.to_string_lossy().into_owned()),
+            percent: fraction.map(|f| (f * 1000.0).round() / 10.0),
+            eta_secs,
         };
-
-        let _ = self.window.emit(SCAN_PROGRESS_EVENT, payload);
+        let _ = self
+            .window
+            .emit_to(self.window.label(), SCAN_PROGRESS_EVENT, payload);
     }
 }
 
-fn display_name(path: &Path) -> String {
-    path.file_name()
-        .map(|s| s.to_string_lossy().into_owned())
-        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanResult {
+    pub scan_id: String,
+    pub root: FsNode,
+    // The first entries the scan skipped; `list_skipped_entries` has them all.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<SkippedPath>,
+    // Fresh scans of a volume root: the total compared with the volume's used space.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage_check: Option<UsageCheck>,
+    // Local folders the scan was denied, which an elevated rescan can fill in. Sent here
+    // because the summary does not serialize them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub denied_dirs: Vec<String>,
+    // The scan was cancelled or ran out of time; folders it had not finished are marked
+    // with an error and hold what was counted so far.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
 }
 
-fn file_extension_lower(path: &Path) -> Option<String> {
-    path.extension()
-        .map(|s| s.to_string_lossy().to_lowercase())
-        .filter(|s| !s.is_empty())
+/// The skipped entries sent along with the result of the scan `summary` describes.
+pub(crate) fn skipped_preview(summary: &ScanSummary) -> Vec<SkippedPath> {
+    summary
+        .skipped
+        .iter()
+        .take(RESULT_SKIPPED_ENTRIES)
+        .cloned()
+        .collect()
 }
 
-fn error_node(path: &Path, kind: FsNodeKind, err: impl ToString) -> FsNode {
-    FsNode {
-        name: display_name(path),
-        path: path.to_string_lossy().into_owned(),
-        kind,
-        size: 0,
-        children: vec![],
-        extension: file_extension_lower(path),
-        error: Some(err.to_string()),
-    }
+/// Everything the scan `scan_id` skipped and why, up to 10,000 entries.
+pub fn list_skipped_entries(store: &ScanStore, scan_id: &str) -> Result<Vec<SkippedPath>, String> {
+    Ok(store.get(scan_id)?.summary.skipped.clone())
 }
 
-#[derive(Debug, Clone, Copy)]
-struct ScanOptions {
-    min_node_bytes: u64,
-    max_children_per_dir: usize,
-    max_total_nodes: usize,
+/// Re-opens a stored scan, e.g. one picked from the tray's recent scans.
+pub fn get_scan(
+    store: &ScanStore,
+    scan_id: &str,
+    min_node_bytes: Option<u64>,
+) -> Result<ScanResult, String> {
+    let scan = store.get(scan_id)?;
+    Ok(ScanResult {
+        scan_id: scan.id.clone(),
+        root: pruned_view(scan.tree.root(), min_node_bytes),
+        skipped: skipped_preview(&scan.summary),
+        usage_check: None,
+        denied_dirs: scan.summary.denied_dirs.clone(),
+        partial: scan.summary.partial,
+    })
 }
 
-#[derive(Debug, Default)]
-struct ScanStats {
-    skipped_entries: u64,
-    hit_node_limit: bool,
+/// The pruned children of the folder at `path`, for expanding it on demand. Folders
+/// spilled to disk are loaded back first.
+pub fn get_children(
+    store: &ScanStore,
+    scan_id: &str,
+    path: &str,
+    min_node_bytes: Option<u64>,
+) -> Result<Vec<FsNode>, String> {
+    let scan = store.get_for_path(scan_id, Some(path))?;
+    Ok(pruned_view(scan.node(Some(path))?, min_node_bytes).children)
 }
 
-#[derive(Debug)]
-struct DirFrame {
-    path: PathBuf,
-    name: String,
-    depth: usize,
-    iter: ReadDir,
-    // Total size of this directory (includes filtered-out children).
-    size: u64,
-    // Children we actually return to the UI (pruned for IPC safety).
-    children: Vec<FsNode>,
+/// A scan ready for the result store.
+pub(crate) struct FinishedScan {
+    // The complete tree, minus the folders moved to `spill` while it was scanned.
+    pub tree: ScanTree,
+    pub spill: Option<Arc<SpillFile>>,
+    // The pruned copy for the UI.
+    pub pruned: FsNode,
+    pub summary: ScanSummary,
+    // Stopped before the walk was done; see `ScanResult::partial`.
+    pub partial: bool,
 }
 
-fn maybe_keep_child(children: &mut Vec<FsNode>, child: FsNode, max_children_per_dir: usize) {
-    children.push(child);
-
-    // Keep only the largest items to reduce IPC payload. We avoid sorting on every insert.
-    if children.len() >= max_children_per_dir.saturating_mul(2) {
-        children.sort_by(|a, b| b.size.cmp(&a.size));
-        children.truncate(max_children_per_dir);
-    }
+/// Cancel flags of the scans running in each window, so a window can stop its scan and
+/// keep what it counted so far.
+#[derive(Default)]
+pub struct RunningScans {
+    // By window label.
+    cancels: Mutex<HashMap<String, Arc<AtomicBool>>>,
 }
 
-fn scan_pruned_tree(root: &Path, progress: &ProgressReporter, opts: ScanOptions) -> Result<FsNode, String> {
-    let meta = fs::symlink_metadata(root).map_err(|e| {
-        format!(
-            "Failed to read metadata for {}: {}",
-            root.to_string_lossy(),
-            e
-        )
-    })?;
-
-    let file_type = meta.file_type();
-    if file_type.is_symlink() {
-        // Do not follow symlinks (prevents cycles and surprising traversal).
-        return Ok(FsNode {
-            name: display_name(root),
-            path: root.to_string_lossy().into_owned(),
-            kind: FsNodeKind::Symlink,
-            size: 0,
-            children: vec![],
-            extension: file_extension_lower(root),
-            error: None,
-        });
-    }
-
-    if meta.is_file() {
-        let size = meta.len();
-        progress.file_scanned(size, root);
-        return Ok(FsNode {
-            name: display_name(root),
-            path: root.to_string_lossy().into_owned(),
-            kind: FsNodeKind::File,
-            size,
-            children: vec![],
-            extension: file_extension_lower(root),
-            error: None,
-        });
+impl RunningScans {
+    fn start(&self, label: &str) -> Arc<AtomicBool> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        if let Ok(mut cancels) = self.cancels.lock() {
+            cancels.insert(label.to_string(), cancel.clone());
+        }
+        cancel
     }
 
-    if !meta.is_dir() {
-        return Ok(FsNode {
-            name: display_name(root),
-            path: root.to_string_lossy().into_owned(),
-            kind: FsNodeKind::Other,
-            size: 0,
-            children: vec![],
-            extension: file_extension_lower(root),
-            error: None,
-        });
+    fn finish(&self, label: &str, cancel: &Arc<AtomicBool>) {
+        if let Ok(mut cancels) = self.cancels.lock() {
+            // A newer scan in the same window keeps its own flag.
+            if cancels.get(label).is_some_and(|c| Arc::ptr_eq(c, cancel)) {
+                cancels.remove(label);
+            }
+        }
     }
 
-    let read_dir = fs::read_dir(root).map_err(|e| {
-        format!(
-            "Failed to read directory {}: {}",
-            root.to_string_lossy(),
-            e
-        )
-    })?;
-
-    // Explicit stack to avoid recursion/stack overflows on very deep trees.
-    let mut stack: Vec<DirFrame> = vec![DirFrame {
-        path: root.to_path_buf(),
-        name: display_name(root),
-        depth: 0,
-        iter: read_dir,
-        size: 0,
-        children: vec![],
-    }];
-
-    let mut stats = ScanStats::default();
-    let mut returned_nodes: usize = 1; // root
-
-    progress.dir_scanned(root);
-
-    loop {
-        let next_entry = match stack.last_mut() {
-            Some(frame) => frame.iter.next(),
-            None => break,
+    /// Stops the scan running in the window `label`, which then returns the partial
+    /// tree. False when the window has no scan running.
+    pub fn cancel(&self, label: &str) -> Result<bool, String> {
+        let cancels = self.cancels.lock().map_err(|e| e.to_string())?;
+        let Some(cancel) = cancels.get(label) else {
+            return Ok(false);
         };
+        cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+        tracing::info!(window = %label, "scan cancelled");
+        Ok(true)
+    }
+}
 
-        match next_entry {
-            Some(Ok(entry)) => {
-                let child_path = entry.path();
-
-                let meta = match fs::symlink_metadata(&child_path) {
-                    Ok(m) => m,
-                    Err(_) => {
-                        stats.skipped_entries = stats.skipped_entries.saturating_add(1);
-                        continue;
-                    }
-                };
-
-                let file_type = meta.file_type();
-                if file_type.is_symlink() {
-                    // Skip symlinks for safety and to reduce noise.
-                    continue;
-                }
-
-                if meta.is_file() {
-                    let size = meta.len();
-                    progress.file_scanned(size, &child_path);
-
-                    if let Some(frame) = stack.last_mut() {
-                        frame.size = frame.size.saturating_add(size);
-                    }
-
-                    if size >= opts.min_node_bytes && returned_nodes < opts.max_total_nodes {
-                        returned_nodes += 1;
-                        if let Some(frame) = stack.last_mut() {
-                            maybe_keep_child(
-                                &mut frame.children,
-                                FsNode {
-                                    name: display_name(&child_path),
-                                    path: child_path.to_string_lossy().into_owned(),
-                                    kind: FsNodeKind::File,
-                                    size,
-                                    children: vec![],
-                                    extension: file_extension_lower(&child_path),
-                                    error: None,
-                                },
-                                opts.max_children_per_dir,
-                            );
-                        }
-                    } else if size >= opts.min_node_bytes {
-                        stats.hit_node_limit = true;
-                    }
-
-                    continue;
-                }
-
-                if meta.is_dir() {
-                    progress.dir_scanned(&child_path);
-
-                    match fs::read_dir(&child_path) {
-                        Ok(rd) => {
-                            let name = display_name(&child_path);
-                            let depth = stack.last().map(|f| f.depth).unwrap_or(0);
-                            stack.push(DirFrame {
-                                path: child_path,
-                                name,
-                                depth: depth + 1,
-                                iter: rd,
-                                size: 0,
-                                children: vec![],
-                            });
-                        }
-                        Err(_) => {
-                            // Permission denied / system folder etc. Skip (do not panic, do not include).
-                            stats.skipped_entries = stats.skipped_entries.saturating_add(1);
-                        }
-                    }
-                    continue;
-                }
-
-                // Non-file, non-dir: ignore.
-            }
-            Some(Err(_)) => {
-                // Error reading a single entry; skip and continue.
-                stats.skipped_entries = stats.skipped_entries.saturating_add(1);
-            }
-            None => {
-                // Completed this directory; finalize node and attach to parent.
-                let completed = match stack.pop() {
-                    Some(f) => f,
-                    None => break,
-                };
-
-                let mut children = completed.children;
-                children.sort_by(|a, b| b.size.cmp(&a.size));
-                if children.len() > opts.max_children_per_dir {
-                    children.truncate(opts.max_children_per_dir);
-                }
-
-                let mut node = FsNode {
-                    name: completed.name,
-                    path: completed.path.to_string_lossy().into_owned(),
-                    kind: FsNodeKind::Directory,
-                    size: completed.size,
-                    children,
-                    extension: None,
-                    error: None,
-                };
-
-                // Only keep large subtrees to protect IPC. Always keep the root node.
-                let keep_this = completed.depth == 0
-                    || (node.size >= opts.min_node_bytes && returned_nodes < opts.max_total_nodes);
+/// Runs `scan` on this thread, turning a panic into an [`InternalError`] with the panic
+/// message and the folder the walk was in. The scan owns everything it touches, so
+/// nothing is left half-updated for the next one.
+fn catch_panic<T>(root: &Path, scan: impl FnOnce() -> T) -> Result<T, InternalError> {
+    panic::catch_unwind(AssertUnwindSafe(scan)).map_err(|payload| {
+        let error = InternalError::from_panic(payload, take_scan_dir().as_deref());
+        tracing::error!(path = %root.display(), error = %error, "scan panicked");
+        error
+    })
+}
 
-                if completed.depth != 0 && node.size >= opts.min_node_bytes && returned_nodes >= opts.max_total_nodes {
-                    stats.hit_node_limit = true;
-                }
+/// Runs the walk itself, recording an index of what it listed when `index_file` is set
+/// and moving large finished folders to a spill file as the tree grows. With `archives`,
+/// the contents of archive files are listed as their children.
+fn run_scan(
+    root: &Path,
+    opts: &ScanOptions,
+    progress: &dyn ScanProgress,
+    index_file: Option<&Path>,
+    refresh: bool,
+    archives: bool,
+    cancel: Option<&AtomicBool>,
+) -> Result<(Scan, ScanSpill, Option<ScanIndex>), String> {
+    let previous = index_file.filter(|_| refresh).and_then(scan_index::load);
+    let mut index = index_file.map(|_| ScanIndex::new());
+    let mut spill = ScanSpill::default();
+    let hooks = ScanHooks {
+        previous: previous.as_ref(),
+        record: index.as_mut(),
+        sink: Some(&mut spill),
+        archives: archives.then_some(&Archives as &dyn ArchiveLister),
+        cancel,
+    };
+    let scan = diskcheck_core::scan_with_hooks(&RealFs, root, opts, progress, hooks)?;
+    Ok((scan, spill, index))
+}
 
-                if let Some(parent) = stack.last_mut() {
-                    parent.size = parent.size.saturating_add(node.size);
-                    if keep_this && completed.depth != 0 {
-                        returned_nodes += 1;
-                        maybe_keep_child(&mut parent.children, node, opts.max_children_per_dir);
-                    }
-                } else {
-                    // Root completed.
-                    if stats.hit_node_limit {
-                        node.error = Some(format!(
-                            "Result truncated to <= {} nodes for stability. Increase the minimum size filter to reduce output.",
-                            opts.max_total_nodes
-                        ));
-                    } else if stats.skipped_entries > 0 {
-                        node.error = Some(format!(
-                            "Skipped {} entries due to permission/errors.",
-                            stats.skipped_entries
-                        ));
-                    }
-                    return Ok(node);
-                }
-            }
-        }
+/// Scans `root`, blocking the current thread; background scans run on a short-lived
+/// thread of their own. With `index`, what the scan listed is saved to the
+/// volume's index afterwards, and refresh scans reuse the listing of every folder left
+/// unchanged since. Scans with a window can be stopped with [`RunningScans::cancel`],
+/// and stop by themselves after the time limit in the scan settings.
+pub(crate) fn scan_blocking(
+    root: &Path,
+    min_node_bytes: Option<u64>,
+    excludes: Vec<String>,
+    archives: bool,
+    mode: ScanMode,
+    index: Option<IndexOptions>,
+    window: Option<tauri::Window>,
+) -> Result<FinishedScan, CommandError> {
+    let started_at = SystemTime::now();
+    let started = Instant::now();
+    let min_node_bytes = min_node_bytes.unwrap_or(DEFAULT_MIN_NODE_BYTES);
+    let running = window
+        .as_ref()
+        .map(|window| (window.app_handle().clone(), window.label().to_string()));
+    let time_limit = running
+        .as_ref()
+        .and_then(|(app, _)| app.state::<SettingsStore>().scan().time_limit_secs)
+        .map(Duration::from_secs);
+    // Listings that stop answering are skipped rather than hang the scan.
+    let opts = if crate::volumes::is_network_path(root) {
+        ScanOptions::network(min_node_bytes).with_guarded_paths(vec![root.to_path_buf()])
+    } else {
+        ScanOptions::local(min_node_bytes)
+            .with_guarded_paths(crate::volumes::network_mounts_below(root))
     }
-
-    Err("Scan aborted unexpectedly.".to_string())
+    .with_excludes(excludes)
+    .with_time_limit(time_limit);
+
+    let index_file = index.as_ref().and_then(|index| index.file_for(root));
+    let refresh = index.is_some_and(|index| index.refresh);
+
+    let cancel = running
+        .as_ref()
+        .map(|(app, label)| app.state::<RunningScans>().start(label));
+
+    tracing::info!(path = %root.display(), ?mode, refresh, archives, "scan started");
+    let run = || match window {
+        Some(window) => run_scan(
+            root,
+            &opts,
+            &WindowProgress::new(window, root),
+            index_file.as_deref(),
+            refresh,
+            archives,
+            cancel.as_deref(),
+        ),
+        None => run_scan(
+            root,
+            &opts,
+            &NoProgress,
+            index_file.as_deref(),
+            refresh,
+            archives,
+            None,
+        ),
+    };
+    let scanned = match mode {
+        ScanMode::Normal => catch_panic(root, run),
+        // Lowered priority cannot be raised again without privileges, so keep it off
+        // pooled worker threads.
+        ScanMode::Background => std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    priority::lower_current_thread();
+                    catch_panic(root, run)
+                })
+                .join()
+                .unwrap_or_else(|payload| Err(InternalError::from_panic(payload, None)))
+        }),
+    };
+    if let (Some((app, label)), Some(cancel)) = (&running, &cancel) {
+        app.state::<RunningScans>().finish(label, cancel);
+    }
+    let (scan, spill, scanned_index) = scanned
+        .map_err(CommandError::Internal)?
+        .inspect_err(|err| tracing::error!(path = %root.display(), error = %err, "scan failed"))?;
+    let pruned = scan.view(&opts);
+    let summary = ScanSummary {
+        root_path: root.to_string_lossy().into_owned(),
+        started_at_secs: unix_secs(started_at),
+        duration_ms: started.elapsed().as_millis() as u64,
+        total_bytes: scan.tree.size(),
+        file_count: scan.stats.file_count,
+        dir_count: scan.stats.dir_count,
+        skipped_entries: scan.stats.skipped_entries,
+        denied_dirs: scan
+            .denied
+            .iter()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .collect(),
+        skipped: scan
+            .skipped
+            .iter()
+            .map(|entry| SkippedPath {
+                path: entry.path.to_string_lossy().into_owned(),
+                reason: entry.reason,
+                message: entry.message.clone(),
+            })
+            .collect(),
+        partial: scan.partial,
+    };
+    tracing::info!(
+        path = %root.display(),
+        total_bytes = summary.total_bytes,
+        files = summary.file_count,
+        dirs = summary.dir_count,
+        skipped_entries = scan.stats.skipped_entries,
+        denied_dirs = scan.denied.len(),
+        timed_out_dirs = scan.stats.timed_out_dirs,
+        hung_dirs = scan.stats.hung_dirs,
+        vanished_entries = scan.stats.vanished_entries,
+        reused_dirs = scan.stats.reused_dirs,
+        partial = scan.partial,
+        spilled_dirs = scan.tree.spilled_paths().len(),
+        duration_ms = summary.duration_ms,
+        "scan finished"
+    );
+
+    // A partial index would replace a complete one with fewer listings to reuse.
+    if let (Some(file), Some(scanned), false) = (index_file, scanned_index, scan.partial) {
+        // The result need not wait for the index to be saved.
+        scan_index::persist_in_background(file, root.to_path_buf(), scanned);
+    }
+    Ok(FinishedScan {
+        tree: scan.tree,
+        spill: spill.into_file(),
+        pruned,
+        summary,
+        partial: scan.partial,
+    })
 }
 
 pub async fn scan_directory(
     window: tauri::Window,
+    store: &ScanStore,
     path: String,
     min_node_bytes: Option<u64>,
-) -> Result<FsNode, String> {
+    mode: ScanMode,
+    refresh: bool,
+) -> Result<ScanResult, CommandError> {
     let root = PathBuf::from(path);
+    // Held until the scan ends; on iOS a picked folder is unreadable without it.
+    let _access = crate::sandbox::open_for_scan(window.app_handle(), &root)?;
     if !root.exists() {
-        return Err(format!("Path does not exist: {}", root.to_string_lossy()));
+        return Err(format!("Path does not exist: {}", root.to_string_lossy()).into());
     }
 
-    let window_clone = window.clone();
-    tauri::async_runtime::spawn_blocking(move || {
-        let progress = ProgressReporter::new(window_clone);
-        progress.emit_force(Some(&root));
-        let opts = ScanOptions {
-            min_node_bytes: min_node_bytes.unwrap_or(DEFAULT_MIN_NODE_BYTES),
-            max_children_per_dir: DEFAULT_MAX_CHILDREN_PER_DIR,
-            max_total_nodes: DEFAULT_MAX_TOTAL_NODES,
-        };
-        let node = scan_pruned_tree(&root, &progress, opts)?;
-        progress.emit_force(Some(&root));
-        Ok::<_, String>(node)
+    let defaults = window.state::<SettingsStore>().scan();
+    let min_node_bytes = min_node_bytes.or(defaults.min_node_bytes);
+    let index = IndexOptions::new(window.app_handle(), refresh);
+    let progress_window = window.clone();
+    let app = window.app_handle().clone();
+    let (finished, usage_check) = tauri::async_runtime::spawn_blocking(move || {
+        let finished = scan_blocking(
+            &root,
+            min_node_bytes,
+            defaults.excludes,
+            defaults.scan_archives,
+            mode,
+            index,
+            Some(progress_window),
+        )?;
+        // A partial total says nothing about the volume.
+        let usage_check = (!finished.partial)
+            .then(|| usage_check::check(&app, &root, &finished.tree, &finished.summary))
+            .flatten();
+        Ok::<_, CommandError>((finished, usage_check))
     })
     .await
-    .map_err(|err| err.to_string())?
+    .map_err(|err| err.to_string())??;
+
+    // A partial scan would read as the folder having shrunk.
+    if !finished.partial {
+        window
+            .state::<crate::scan_history::ScanHistory>()
+            .record(window.app_handle(), &finished.summary);
+    }
+    let skipped = skipped_preview(&finished.summary);
+    let denied_dirs = finished.summary.denied_dirs.clone();
+    let scan_id = store.insert_spilled(finished.tree, finished.spill, finished.summary)?;
+    if !finished.partial {
+        crate::baseline::on_scan_finished(&window, &scan_id);
+    }
+    Ok(ScanResult {
+        scan_id,
+        root: finished.pruned,
+        skipped,
+        usage_check,
+        denied_dirs,
+        partial: finished.partial,
+    })
 }
@@ -0,0 +1,389 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs,
+    io::Write as _,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{
+    budgets::measure_scan,
+    export::{create_dest, csv_field, UTF8_BOM},
+    fileinfo::{format_bytes, iso8601_utc},
+    report::{html_head, render_sections},
+    store::{unix_secs, StoredScan},
+    treemap::escape_xml,
+};
+
+const SCHEDULED_REPORT_EVENT: &str = "scheduled_report";
+// Time of the last report and the sizes it found, so restarts keep the schedule and
+// the next report can show what changed.
+const STATE_FILE: &str = "scheduled_report.json";
+const DEFAULT_INTERVAL_SECS: u64 = 24 * 60 * 60;
+// Every report re-scans the roots in full.
+const MIN_INTERVAL_SECS: u64 = 60 * 60;
+const CSV_HEADER: &str = "root,scanned_at,total_bytes,change_bytes,files,folders,skipped_entries";
+// How often the worker wakes up to check whether it was stopped.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScheduledReportFormat {
+    // One page with a summary of every root, then each root's full report.
+    #[default]
+    Html,
+    // One row per root.
+    Csv,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportSchedule {
+    pub roots: Vec<String>,
+    #[serde(default)]
+    pub format: ScheduledReportFormat,
+    // Folder the reports are written to; without one only the notification is shown.
+    #[serde(default)]
+    pub output_dir: Option<String>,
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+    #[serde(default = "default_notify")]
+    pub notify: bool,
+}
+
+fn default_notify() -> bool {
+    true
+}
+
+impl ReportSchedule {
+    fn interval(&self) -> Duration {
+        Duration::from_secs(
+            self.interval_secs
+                .unwrap_or(DEFAULT_INTERVAL_SECS)
+                .max(MIN_INTERVAL_SECS),
+        )
+    }
+}
+
+/// A root's line in a scheduled report.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RootSummary {
+    pub root: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+    // Against the previous report; absent for roots it did not measure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_bytes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledReport {
+    pub generated_at_secs: u64,
+    // The written file, if an output folder is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub roots: Vec<RootSummary>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ReportState {
+    last_run_secs: u64,
+    totals: HashMap<String, u64>,
+}
+
+struct SchedulerHandle {
+    config: ReportSchedule,
+    stop: Arc<AtomicBool>,
+}
+
+/// Writes the scheduled reports in the background.
+#[derive(Default)]
+pub struct ReportScheduler {
+    current: Mutex<Option<SchedulerHandle>>,
+}
+
+fn state_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(STATE_FILE))
+        .map_err(|e| e.to_string())
+}
+
+fn load_state(app: &tauri::AppHandle) -> ReportState {
+    state_path(app)
+        .ok()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(app: &tauri::AppHandle, state: &ReportState) -> Result<(), String> {
+    let path = state_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.to_string_lossy(), e))?;
+    }
+    let json = serde_json::to_vec_pretty(state).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)
+        .and_then(|_| fs::rename(&tmp, &path))
+        .map_err(|e| format!("Failed to save {}: {}", path.to_string_lossy(), e))
+}
+
+fn render_html(generated_at_secs: u64, scans: &[(RootSummary, Option<Arc<StoredScan>>)]) -> String {
+    let mut html = html_head("DiskCheck scheduled report");
+    let _ = write!(
+        html,
+        "<p class=\"muted\">Generated {} UTC</p><h2>Roots</h2><table><tr><th>Root</th><th class=\"num\">Size</th><th class=\"num\">Change</th></tr>",
+        iso8601_utc(generated_at_secs)
+    );
+    for (summary, _) in scans {
+        let size = match (&summary.total_bytes, &summary.error) {
+            (Some(bytes), _) => format_bytes(*bytes),
+            (None, Some(error)) => escape_xml(error),
+            (None, None) => String::new(),
+        };
+        let _ = write!(
+            html,
+            "<tr><td class=\"path\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+            escape_xml(&summary.root),
+            size,
+            summary.change_bytes.map(signed_bytes).unwrap_or_default()
+        );
+    }
+    html.push_str("</table>");
+    for (summary, scan) in scans {
+        if let Some(scan) = scan {
+            render_sections(scan, &format!("{}: ", summary.root), &mut html);
+        }
+    }
+    html.push_str("</body></html>");
+    html
+}
+
+fn write_csv(
+    out: &mut impl std::io::Write,
+    scans: &[(RootSummary, Option<Arc<StoredScan>>)],
+) -> std::io::Result<()> {
+    writeln!(out, "{}{}", UTF8_BOM, CSV_HEADER)?;
+    for (summary, scan) in scans {
+        let Some(scan) = scan else {
+            continue;
+        };
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{}",
+            csv_field(&summary.root),
+            iso8601_utc(scan.summary.started_at_secs),
+            scan.summary.total_bytes,
+            summary
+                .change_bytes
+                .map(|c| c.to_string())
+                .unwrap_or_default(),
+            scan.summary.file_count,
+            scan.summary.dir_count,
+            scan.summary.skipped_entries
+        )?;
+    }
+    Ok(())
+}
+
+fn signed_bytes(change: i64) -> String {
+    let sign = if change < 0 { "-" } else { "+" };
+    format!("{}{}", sign, format_bytes(change.unsigned_abs()))
+}
+
+/// Writes the report file into `dir`, named after the time it was generated.
+fn write_report(
+    dir: &Path,
+    config: &ReportSchedule,
+    generated_at_secs: u64,
+    scans: &[(RootSummary, Option<Arc<StoredScan>>)],
+) -> Result<PathBuf, String> {
+    let extension = match config.format {
+        ScheduledReportFormat::Html => "html",
+        ScheduledReportFormat::Csv => "csv",
+    };
+    let stamp = iso8601_utc(generated_at_secs).replace(':', "");
+    let dest = dir.join(format!("diskcheck-report-{}.{}", stamp, extension));
+    let mut out = create_dest(&dest)?;
+    match config.format {
+        ScheduledReportFormat::Html => {
+            out.write_all(render_html(generated_at_secs, scans).as_bytes())
+        }
+        ScheduledReportFormat::Csv => write_csv(&mut out, scans),
+    }
+    .and_then(|()| out.flush())
+    .map_err(|e| format!("Failed to write {}: {}", dest.to_string_lossy(), e))?;
+    Ok(dest)
+}
+
+/// Measures every root and writes the report, remembering the sizes for the next one.
+fn generate(
+    app: &tauri::AppHandle,
+    config: &ReportSchedule,
+    stop: Option<&AtomicBool>,
+) -> Result<ScheduledReport, String> {
+    let mut state = load_state(app);
+    let mut scans = Vec::new();
+    for root in &config.roots {
+        if stop.is_some_and(|stop| stop.load(Ordering::Relaxed)) {
+            return Err("The scheduled report was stopped.".to_string());
+        }
+        let measured = measure_scan(app, Path::new(root));
+        let total_bytes = measured.as_ref().ok().map(|scan| scan.summary.total_bytes);
+        let summary = RootSummary {
+            root: root.clone(),
+            total_bytes,
+            change_bytes: total_bytes
+                .zip(state.totals.get(root))
+                .map(|(now, before)| now as i64 - *before as i64),
+            error: measured.as_ref().err().cloned(),
+        };
+        scans.push((summary, measured.ok()));
+    }
+
+    let generated_at_secs = unix_secs(SystemTime::now());
+    let path = match &config.output_dir {
+        Some(dir) => Some(write_report(
+            Path::new(dir),
+            config,
+            generated_at_secs,
+            &scans,
+        )?),
+        None => None,
+    };
+    state.last_run_secs = generated_at_secs;
+    for (summary, _) in &scans {
+        if let Some(total) = summary.total_bytes {
+            state.totals.insert(summary.root.clone(), total);
+        }
+    }
+    save_state(app, &state)?;
+
+    Ok(ScheduledReport {
+        generated_at_secs,
+        path: path.map(|p| p.to_string_lossy().into_owned()),
+        roots: scans.into_iter().map(|(summary, _)| summary).collect(),
+    })
+}
+
+fn notify_report(app: &tauri::AppHandle, report: &ScheduledReport) {
+    let mut body = report
+        .roots
+        .iter()
+        .map(|root| match (root.total_bytes, root.change_bytes) {
+            (Some(total), Some(change)) => format!(
+                "{}: {} ({})",
+                root.root,
+                format_bytes(total),
+                signed_bytes(change)
+            ),
+            (Some(total), None) => format!("{}: {}", root.root, format_bytes(total)),
+            (None, _) => format!("{}: not measured", root.root),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if let Some(path) = &report.path {
+        let _ = write!(body, "\nSaved to {}", path);
+    }
+    let _ = app
+        .notification()
+        .builder()
+        .title("Disk usage report")
+        .body(body)
+        .show();
+}
+
+fn run_scheduler(app: tauri::AppHandle, config: ReportSchedule, stop: Arc<AtomicBool>) {
+    // Picks up the schedule where the last run (possibly before a restart) left it.
+    let since_last = Duration::from_secs(
+        unix_secs(SystemTime::now()).saturating_sub(load_state(&app).last_run_secs),
+    );
+    let mut next_run = Instant::now() + config.interval().saturating_sub(since_last);
+
+    while !stop.load(Ordering::Relaxed) {
+        if Instant::now() < next_run {
+            thread::sleep(STOP_POLL_INTERVAL);
+            continue;
+        }
+        next_run = Instant::now() + config.interval();
+
+        match generate(&app, &config, Some(&stop)) {
+            Ok(report) => {
+                tracing::info!(path = ?report.path, roots = report.roots.len(), "scheduled report generated");
+                let _ = app.emit(SCHEDULED_REPORT_EVENT, &report);
+                if config.notify || report.path.is_none() {
+                    notify_report(&app, &report);
+                }
+            }
+            Err(error) => tracing::warn!(error = %error, "scheduled report failed"),
+        }
+    }
+}
+
+impl ReportScheduler {
+    pub fn start(&self, app: tauri::AppHandle, config: ReportSchedule) -> Result<(), String> {
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = current.take() {
+            previous.stop.store(true, Ordering::Relaxed);
+        }
+        if config.roots.is_empty() {
+            return Ok(());
+        }
+        if let Some(dir) = &config.output_dir {
+            if !Path::new(dir).is_dir() {
+                return Err(format!("Report folder does not exist: {}", dir));
+            }
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let worker_stop = stop.clone();
+        let worker_config = config.clone();
+        thread::Builder::new()
+            .name("report-scheduler".to_string())
+            .spawn(move || run_scheduler(app, worker_config, worker_stop))
+            .map_err(|e| format!("Failed to start the scheduled reports: {}", e))?;
+
+        *current = Some(SchedulerHandle { config, stop });
+        Ok(())
+    }
+
+    pub fn stop(&self) -> Result<(), String> {
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = current.take() {
+            previous.stop.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    pub fn config(&self) -> Result<Option<ReportSchedule>, String> {
+        let current = self.current.lock().map_err(|e| e.to_string())?;
+        Ok(current.as_ref().map(|h| h.config.clone()))
+    }
+}
+
+/// Generates the scheduled report now, as configured.
+pub async fn generate_scheduled_report(app: tauri::AppHandle) -> Result<ScheduledReport, String> {
+    let config = app
+        .state::<ReportScheduler>()
+        .config()?
+        .ok_or_else(|| "No scheduled report is configured.".to_string())?;
+    tauri::async_runtime::spawn_blocking(move || generate(&app, &config, None))
+        .await
+        .map_err(|err| err.to_string())?
+}
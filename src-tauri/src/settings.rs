@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf, sync::Mutex};
+use tauri::Manager;
+
+use crate::{
+    api::ApiServer,
+    budgets::{BudgetConfig, BudgetWatcher},
+    error::CommandError,
+    growth::{GrowthConfig, GrowthMonitor},
+    monitor::{MonitorConfig, SpaceMonitor},
+    protected::ProtectedPaths,
+    scheduled_report::{ReportSchedule, ReportScheduler},
+    shortcut::{ScanShortcut, ScanShortcutConfig},
+    tray::CloseToTray,
+};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScanSettings {
+    // Folder preselected in the UI.
+    pub default_path: Option<String>,
+    // Smallest node sent to the UI when a scan does not ask for one (1 MiB otherwise).
+    pub min_node_bytes: Option<u64>,
+    // Entry names or full paths left out of every scan.
+    pub excludes: Vec<String>,
+    // List the contents of .zip, .tar, .tar.gz and .7z files as their children. Off by
+    // default: every archive is opened, which slows scans of folders full of them.
+    pub scan_archives: bool,
+    // Scans started from a window stop after this many seconds and keep what they
+    // counted so far; no limit if unset.
+    pub time_limit_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApiServerSettings {
+    pub enabled: bool,
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub scan: ScanSettings,
+    // Volumes, low-space thresholds and notification preference; no monitoring if unset.
+    pub space_monitor: Option<MonitorConfig>,
+    // Folders with a size limit, checked in the background; no checks if unset.
+    pub folder_budgets: Option<BudgetConfig>,
+    // Folders whose growth rate is watched; no sampling if unset.
+    pub growth_monitor: Option<GrowthConfig>,
+    // Roots reported on periodically; no reports if unset.
+    pub scheduled_report: Option<ReportSchedule>,
+    pub scan_shortcut: Option<ScanShortcutConfig>,
+    pub close_to_tray: bool,
+    pub api_server: ApiServerSettings,
+    // Folders added to the built-in list that delete/move commands refuse to touch.
+    pub protected_paths: Vec<String>,
+    // Analysis-only install: every command that deletes, moves or cleans is refused
+    // with `SafeModeEnabled`. That is trashing and restoring, deleting local snapshots,
+    // clearing baselines, ejecting volumes and (un)registering the context menu.
+    pub safe_mode: bool,
+}
+
+/// The persisted settings, mirrored in memory.
+#[derive(Default)]
+pub struct SettingsStore {
+    current: Mutex<Settings>,
+}
+
+fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .map_err(|e| e.to_string())
+}
+
+impl SettingsStore {
+    /// Reads the settings file. A missing or unreadable file leaves the defaults in
+    /// place; it is only rewritten by the next `save`.
+    pub fn load(&self, app: &tauri::AppHandle) -> Result<Settings, String> {
+        let settings = settings_path(app)
+            .ok()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|bytes| serde_json::from_slice::<Settings>(&bytes).ok())
+            .unwrap_or_default();
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        *current = settings.clone();
+        Ok(settings)
+    }
+
+    pub fn save(&self, app: &tauri::AppHandle, settings: Settings) -> Result<(), String> {
+        let path = settings_path(app)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.to_string_lossy(), e))?;
+        }
+        let json = serde_json::to_vec_pretty(&settings).map_err(|e| e.to_string())?;
+        // Write next to the target and rename, so a crash never leaves half a file.
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| format!("Failed to save {}: {}", path.to_string_lossy(), e))?;
+
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        *current = settings;
+        Ok(())
+    }
+
+    pub fn get(&self) -> Result<Settings, String> {
+        let current = self.current.lock().map_err(|e| e.to_string())?;
+        Ok(current.clone())
+    }
+
+    pub fn scan(&self) -> ScanSettings {
+        self.get().map(|s| s.scan).unwrap_or_default()
+    }
+
+    /// Fails with `SafeModeEnabled` when safe mode is on. Mutating commands call this
+    /// before touching anything.
+    pub fn ensure_mutable(&self) -> Result<(), CommandError> {
+        if self.get().map(|s| s.safe_mode).unwrap_or(false) {
+            return Err(CommandError::SafeModeEnabled(
+                "Safe mode is enabled; DiskCheck will not modify files.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn protected_paths(&self) -> ProtectedPaths {
+        ProtectedPaths::new(&self.get().map(|s| s.protected_paths).unwrap_or_default())
+    }
+}
+
+/// Brings the monitors, scheduled reports, shortcut, tray and API server in line with `settings`. Every
+/// part is applied even if an earlier one fails; the first error is returned.
+pub fn apply(app: &tauri::AppHandle, settings: &Settings) -> Result<(), String> {
+    let monitor = app.state::<SpaceMonitor>();
+    let monitor_result = match &settings.space_monitor {
+        Some(config) => monitor.start(app.clone(), config.clone()),
+        None => monitor.stop(),
+    };
+
+    let budgets = app.state::<BudgetWatcher>();
+    let budgets_result = match &settings.folder_budgets {
+        Some(config) => budgets.start(app.clone(), config.clone()),
+        None => budgets.stop(),
+    };
+
+    let growth = app.state::<GrowthMonitor>();
+    let growth_result = match &settings.growth_monitor {
+        Some(config) => growth.start(app.clone(), config.clone()),
+        None => growth.stop(),
+    };
+
+    let reports = app.state::<ReportScheduler>();
+    let reports_result = match &settings.scheduled_report {
+        Some(config) => reports.start(app.clone(), config.clone()),
+        None => reports.stop(),
+    };
+
+    let shortcut_result = app
+        .state::<ScanShortcut>()
+        .set(app, settings.scan_shortcut.clone());
+
+    app.state::<CloseToTray>().set(settings.close_to_tray);
+
+    let server = app.state::<ApiServer>();
+    let api_result = if settings.api_server.enabled {
+        // Restarting would hand out a new token, so keep a server that already matches.
+        match server.info() {
+            Ok(Some(info)) if settings.api_server.port.is_none_or(|p| p == info.port) => Ok(()),
+            _ => server
+                .start(app.clone(), settings.api_server.port)
+                .map(|_| ()),
+        }
+    } else {
+        server.stop()
+    };
+
+    monitor_result
+        .and(budgets_result)
+        .and(growth_result)
+        .and(reports_result)
+        .and(shortcut_result)
+        .and(api_result)
+}
+
+/// Saves and applies `settings`. Safe mode can be turned on here but not off: an
+/// analysis-only install must not be unlocked from the app it restricts.
+pub fn set_settings(
+    app: &tauri::AppHandle,
+    store: &SettingsStore,
+    settings: Settings,
+) -> Result<(), String> {
+    if store.get()?.safe_mode && !settings.safe_mode {
+        return Err(format!(
+            "Safe mode cannot be turned off from DiskCheck; remove `safeMode` from {} instead.",
+            settings_path(app)?.to_string_lossy()
+        ));
+    }
+    store.save(app, settings.clone())?;
+    apply(app, &settings)
+}
+
+/// Changes one part of the saved settings and applies them, so the per-feature
+/// commands (space monitor, shortcut, tray, API server) persist what they turn on.
+pub fn update_settings(
+    app: &tauri::AppHandle,
+    store: &SettingsStore,
+    change: impl FnOnce(&mut Settings),
+) -> Result<(), String> {
+    let mut settings = store.get()?;
+    change(&mut settings);
+    set_settings(app, store, settings)
+}
@@ -0,0 +1,327 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+const MENU_LABEL: &str = "Analyze with DiskCheck";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextMenuStatus {
+    pub supported: bool,
+    pub registered: bool,
+    // Where the entry lives (registry key, script or workflow path).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+fn current_exe() -> Result<PathBuf, String> {
+    std::env::current_exe().map_err(|e| format!("Failed to locate the DiskCheck executable: {}", e))
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{current_exe, ContextMenuStatus, MENU_LABEL};
+    use std::{os::windows::process::CommandExt, process::Command};
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    // Per-user registration: no elevation needed. Keep in sync with windows/hooks.nsh.
+    // Drive roots end in a backslash, which would escape a closing quote (`"D:\"` reaches
+    // the app as `D:"`); they hold no spaces, so they are passed unquoted.
+    const KEYS: [(&str, &str); 3] = [
+        (r"HKCU\Software\Classes\Directory\shell\DiskCheck", "\"%1\""),
+        (
+            r"HKCU\Software\Classes\Directory\Background\shell\DiskCheck",
+            "\"%V\"",
+        ),
+        (r"HKCU\Software\Classes\Drive\shell\DiskCheck", "%1"),
+    ];
+
+    fn reg(args: &[&str]) -> Result<bool, String> {
+        let status = Command::new("reg")
+            .args(args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map_err(|e| format!("Failed to run reg.exe: {}", e))?;
+        Ok(status.status.success())
+    }
+
+    pub(super) fn register() -> Result<(), String> {
+        let exe = current_exe()?.to_string_lossy().into_owned();
+        for (key, argument) in KEYS {
+            let command_key = format!(r"{}\command", key);
+            let command = format!("\"{}\" {}", exe, argument);
+            let ok = reg(&["add", key, "/ve", "/d", MENU_LABEL, "/f"])?
+                && reg(&["add", key, "/v", "Icon", "/d", &exe, "/f"])?
+                && reg(&["add", &command_key, "/ve", "/d", &command, "/f"])?;
+            if !ok {
+                return Err(format!("Failed to write registry key {}", key));
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn unregister() -> Result<(), String> {
+        for (key, _) in KEYS {
+            // Fails harmlessly when the key is already gone.
+            reg(&["delete", key, "/f"])?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn status() -> ContextMenuStatus {
+        let registered = reg(&["query", KEYS[0].0]).unwrap_or(false);
+        ContextMenuStatus {
+            supported: true,
+            registered,
+            location: Some(KEYS[0].0.to_string()),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{current_exe, ContextMenuStatus, MENU_LABEL};
+    use std::{fs, path::PathBuf};
+
+    /// Finder Quick Action (Services menu) running a shell script per selected folder.
+    fn workflow_dir() -> Result<PathBuf, String> {
+        let home = std::env::var_os("HOME").ok_or("HOME is not set.")?;
+        Ok(PathBuf::from(home)
+            .join("Library/Services")
+            .join(format!("{}.workflow", MENU_LABEL)))
+    }
+
+    fn escape_xml(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+
+    fn info_plist() -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>NSServices</key>
+	<array>
+		<dict>
+			<key>NSMenuItem</key>
+			<dict>
+				<key>default</key>
+				<string>{label}</string>
+			</dict>
+			<key>NSMessage</key>
+			<string>runWorkflowAsService</string>
+			<key>NSRequiredContext</key>
+			<dict>
+				<key>NSApplicationIdentifier</key>
+				<string>com.apple.finder</string>
+			</dict>
+			<key>NSSendFileTypes</key>
+			<array>
+				<string>public.folder</string>
+			</array>
+		</dict>
+	</array>
+</dict>
+</plist>
+"#,
+            label = escape_xml(MENU_LABEL)
+        )
+    }
+
+    fn document_wflow(script: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>AMApplicationBuild</key>
+	<string>521</string>
+	<key>AMApplicationVersion</key>
+	<string>2.10</string>
+	<key>AMDocumentVersion</key>
+	<string>2</string>
+	<key>actions</key>
+	<array>
+		<dict>
+			<key>action</key>
+			<dict>
+				<key>AMAccepts</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Optional</key>
+					<true/>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.path</string>
+					</array>
+				</dict>
+				<key>AMActionVersion</key>
+				<string>2.0.3</string>
+				<key>AMApplication</key>
+				<array>
+					<string>Automator</string>
+				</array>
+				<key>AMProvides</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.string</string>
+					</array>
+				</dict>
+				<key>ActionBundlePath</key>
+				<string>/System/Library/Automator/Run Shell Script.action</string>
+				<key>ActionName</key>
+				<string>Run Shell Script</string>
+				<key>ActionParameters</key>
+				<dict>
+					<key>COMMAND_STRING</key>
+					<string>{script}</string>
+					<key>CheckedForUserDefaultShell</key>
+					<true/>
+					<key>inputMethod</key>
+					<integer>1</integer>
+					<key>shell</key>
+					<string>/bin/sh</string>
+					<key>source</key>
+					<string></string>
+				</dict>
+				<key>BundleIdentifier</key>
+				<string>com.apple.RunShellScript</string>
+				<key>CFBundleVersion</key>
+				<string>2.0.3</string>
+				<key>CanShowSelectedItemsWhenRun</key>
+				<false/>
+				<key>CanShowWhenRun</key>
+				<true/>
+				<key>Class Name</key>
+				<string>RunShellScriptAction</string>
+				<key>InputUUID</key>
+				<string>5C9F1F55-8A4B-4E0B-9D0E-6E1B0A9E3D01</string>
+				<key>OutputUUID</key>
+				<string>5C9F1F55-8A4B-4E0B-9D0E-6E1B0A9E3D02</string>
+				<key>UUID</key>
+				<string>5C9F1F55-8A4B-4E0B-9D0E-6E1B0A9E3D03</string>
+			</dict>
+		</dict>
+	</array>
+	<key>connectors</key>
+	<dict/>
+	<key>workflowMetaData</key>
+	<dict>
+		<key>serviceInputTypeIdentifier</key>
+		<string>com.apple.Automator.fileSystemObject.folder</string>
+		<key>serviceOutputTypeIdentifier</key>
+		<string>com.apple.Automator.nothing</string>
+		<key>serviceProcessesInput</key>
+		<integer>0</integer>
+		<key>workflowTypeIdentifier</key>
+		<string>com.apple.Automator.servicesMenu</string>
+	</dict>
+</dict>
+</plist>
+"#,
+            script = escape_xml(script)
+        )
+    }
+
+    pub(super) fn register() -> Result<(), String> {
+        let exe = current_exe()?.to_string_lossy().replace('\'', r"'\''");
+        // The running instance picks the folder up through single-instance forwarding.
+        let script = format!(
+            "for f in \"$@\"; do '{}' \"$f\" >/dev/null 2>&1 & done",
+            exe
+        );
+        let dir = workflow_dir()?;
+        let contents = dir.join("Contents");
+        fs::create_dir_all(&contents).map_err(|e| e.to_string())?;
+        fs::write(contents.join("Info.plist"), info_plist()).map_err(|e| e.to_string())?;
+        fs::write(contents.join("document.wflow"), document_wflow(&script))
+            .map_err(|e| e.to_string())
+    }
+
+    pub(super) fn unregister() -> Result<(), String> {
+        let dir = workflow_dir()?;
+        if dir.exists() {
+            fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn status() -> ContextMenuStatus {
+        let dir = workflow_dir().ok();
+        ContextMenuStatus {
+            supported: true,
+            registered: dir.as_ref().is_some_and(|d| d.exists()),
+            location: dir.map(|d| d.to_string_lossy().into_owned()),
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use super::{current_exe, ContextMenuStatus, MENU_LABEL};
+    use std::{fs, os::unix::fs::PermissionsExt, path::PathBuf};
+
+    /// Nautilus lists executables in this folder under "Scripts" in the context menu.
+    fn script_path() -> Result<PathBuf, String> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")))
+            .ok_or("HOME is not set.")?;
+        Ok(data_home.join("nautilus/scripts").join(MENU_LABEL))
+    }
+
+    pub(super) fn register() -> Result<(), String> {
+        let exe = current_exe()?.to_string_lossy().replace('\'', r"'\''");
+        let path = script_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let script = format!(
+            "#!/bin/sh\n# Added by DiskCheck.\nfor f in \"$@\"; do\n  [ -d \"$f\" ] && exec '{}' \"$(realpath \"$f\")\"\ndone\n",
+            exe
+        );
+        fs::write(&path, script).map_err(|e| e.to_string())?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())
+    }
+
+    pub(super) fn unregister() -> Result<(), String> {
+        let path = script_path()?;
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn status() -> ContextMenuStatus {
+        let path = script_path().ok();
+        ContextMenuStatus {
+            supported: true,
+            registered: path.as_ref().is_some_and(|p| p.exists()),
+            location: path.map(|p| p.to_string_lossy().into_owned()),
+        }
+    }
+}
+
+pub async fn register_context_menu() -> Result<ContextMenuStatus, String> {
+    tauri::async_runtime::spawn_blocking(|| platform::register().map(|()| platform::status()))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+pub async fn unregister_context_menu() -> Result<ContextMenuStatus, String> {
+    tauri::async_runtime::spawn_blocking(|| platform::unregister().map(|()| platform::status()))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+pub async fn context_menu_status() -> Result<ContextMenuStatus, String> {
+    tauri::async_runtime::spawn_blocking(platform::status)
+        .await
+        .map_err(|err| err.to_string())
+}
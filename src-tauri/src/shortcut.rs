@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::Manager;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::{launch, volumes::system_root};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanShortcutConfig {
+    // Accelerator such as `CommandOrControl+Shift+D`.
+    pub accelerator: String,
+    // Folder or drive to scan; the system drive when omitted.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+/// The optional global "scan now" shortcut.
+#[derive(Default)]
+pub struct ScanShortcut {
+    current: Mutex<Option<(Shortcut, ScanShortcutConfig)>>,
+}
+
+/// Handler for the global-shortcut plugin: brings the window forward and starts the scan.
+pub fn handle(app: &tauri::AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let state = app.state::<ScanShortcut>();
+    let path = match state.current.lock() {
+        Ok(current) => match current.as_ref() {
+            Some((registered, config)) if registered == shortcut => {
+                config.path.clone().unwrap_or_else(system_root)
+            }
+            _ => return,
+        },
+        Err(_) => return,
+    };
+    launch::request_scan(app, path);
+}
+
+impl ScanShortcut {
+    /// Replaces the registered shortcut; `None` just removes it.
+    pub fn set(
+        &self,
+        app: &tauri::AppHandle,
+        config: Option<ScanShortcutConfig>,
+    ) -> Result<(), String> {
+        let mut current = self.current.lock().map_err(|e| e.to_string())?;
+        if let Some((previous, _)) = current.take() {
+            let _ = app.global_shortcut().unregister(previous);
+        }
+        let config = match config {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+
+        let shortcut: Shortcut = config
+            .accelerator
+            .parse()
+            .map_err(|e| format!("Invalid shortcut {}: {}", config.accelerator, e))?;
+        app.global_shortcut().register(shortcut).map_err(|e| {
+            format!(
+                "Could not register {} (is it used by another app?): {}",
+                config.accelerator, e
+            )
+        })?;
+        *current = Some((shortcut, config));
+        Ok(())
+    }
+
+    pub fn config(&self) -> Result<Option<ScanShortcutConfig>, String> {
+        let current = self.current.lock().map_err(|e| e.to_string())?;
+        Ok(current.as_ref().map(|(_, config)| config.clone()))
+    }
+}
@@ -0,0 +1,263 @@
+use image_webp::WebPDecoder;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+use zune_jpeg::{
+    zune_core::{colorspace::ColorSpace, options::DecoderOptions},
+    JpegDecoder,
+};
+
+use crate::cloud_sync::{open_local, read_local};
+
+pub(crate) const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp"];
+// Resized copies are usually far smaller than the original, so images are compared from
+// this size up whatever the exact search's minimum; only thumbnails are left out.
+pub(crate) const IMAGE_MIN_BYTES: u64 = 32 << 10;
+// Decoding costs more than a match is worth beyond this.
+const IMAGE_MAX_BYTES: u64 = 256 << 20;
+pub(crate) const DEFAULT_SIMILARITY: f64 = 0.9;
+// Below this, unrelated photos of the same scene start to match.
+const MIN_SIMILARITY: f64 = 0.75;
+// The difference hash compares neighbours on a 9x8 grid, giving 64 bits.
+const GRID_WIDTH: usize = 9;
+const GRID_HEIGHT: usize = 8;
+const HASH_BITS: u32 = 64;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarImage {
+    pub path: String,
+    pub size: u64,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarImageGroup {
+    // Largest first: the copy most worth keeping.
+    pub images: Vec<SimilarImage>,
+    // Lowest similarity of any image to the first, from 0 to 1.
+    pub similarity: f64,
+    // Bytes freed by keeping only the first image.
+    pub wasted_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarImages {
+    // Largest savings first.
+    pub groups: Vec<SimilarImageGroup>,
+    pub similarity: f64,
+    pub compared_images: u64,
+    // Unreadable, corrupt or in an unsupported variant of the format.
+    pub undecodable_images: u64,
+}
+
+pub(crate) struct ImageCandidate {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+struct Fingerprint {
+    hash: u64,
+    width: u32,
+    height: u32,
+}
+
+/// Weights of ITU-R BT.601, as JPEG uses for its own luma.
+fn luma(rgb: &[u8]) -> u8 {
+    let weighted = u32::from(rgb[0]) * 299 + u32::from(rgb[1]) * 587 + u32::from(rgb[2]) * 114;
+    (weighted / 1000) as u8
+}
+
+/// Grayscale pixels from interleaved samples with `channels` per pixel; alpha is dropped.
+fn to_luma(samples: &[u8], channels: usize) -> Vec<u8> {
+    match channels {
+        1 => samples.to_vec(),
+        2 => samples.chunks_exact(2).map(|p| p[0]).collect(),
+        _ => samples.chunks_exact(channels).map(luma).collect(),
+    }
+}
+
+fn decode_jpeg(path: &Path) -> Result<(u32, u32, Vec<u8>), String> {
+    let data = read_local(path).map_err(|e| e.to_string())?;
+    let options = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::Luma);
+    let mut decoder = JpegDecoder::new_with_options(data, options);
+    let pixels = decoder.decode().map_err(|e| format!("{:?}", e))?;
+    let info = decoder.info().ok_or("missing JPEG header")?;
+    Ok((u32::from(info.width), u32::from(info.height), pixels))
+}
+
+fn decode_png(path: &Path) -> Result<(u32, u32, Vec<u8>), String> {
+    let file = open_local(path).map_err(|e| e.to_string())?;
+    let mut decoder = png::Decoder::new(BufReader::new(file));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buf = vec![0u8; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+    let pixels = to_luma(&buf[..frame.buffer_size()], frame.color_type.samples());
+    Ok((frame.width, frame.height, pixels))
+}
+
+fn decode_webp(path: &Path) -> Result<(u32, u32, Vec<u8>), String> {
+    let file = open_local(path).map_err(|e| e.to_string())?;
+    let mut decoder = WebPDecoder::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+    let (width, height) = decoder.dimensions();
+    let channels = if decoder.has_alpha() { 4 } else { 3 };
+    let mut buf = vec![0u8; decoder.output_buffer_size().ok_or("image too large")?];
+    decoder.read_image(&mut buf).map_err(|e| e.to_string())?;
+    Ok((width, height, to_luma(&buf, channels)))
+}
+
+/// Difference hash: the image shrunk to a 9x8 grid of average brightness, one bit per
+/// pair of horizontal neighbours telling which is brighter. Survives resizing and
+/// re-encoding, unlike a content hash.
+fn difference_hash(width: usize, height: usize, pixels: &[u8]) -> Option<u64> {
+    if width < GRID_WIDTH || height < GRID_HEIGHT || pixels.len() < width * height {
+        return None;
+    }
+    let mut grid = [[0u64; GRID_WIDTH]; GRID_HEIGHT];
+    for (gy, row) in grid.iter_mut().enumerate() {
+        let (y0, y1) = (gy * height / GRID_HEIGHT, (gy + 1) * height / GRID_HEIGHT);
+        for (gx, cell) in row.iter_mut().enumerate() {
+            let (x0, x1) = (gx * width / GRID_WIDTH, (gx + 1) * width / GRID_WIDTH);
+            let sum: u64 = (y0..y1)
+                .flat_map(|y| &pixels[y * width + x0..y * width + x1])
+                .map(|p| u64::from(*p))
+                .sum();
+            *cell = sum / ((y1 - y0) * (x1 - x0)) as u64;
+        }
+    }
+    let mut hash = 0u64;
+    for row in grid {
+        for pair in row.windows(2) {
+            hash = (hash << 1) | u64::from(pair[0] < pair[1]);
+        }
+    }
+    Some(hash)
+}
+
+fn fingerprint(candidate: &ImageCandidate) -> Option<Fingerprint> {
+    let ext = candidate
+        .path
+        .extension()?
+        .to_string_lossy()
+        .to_ascii_lowercase();
+    let decoded = match ext.as_str() {
+        "jpg" | "jpeg" => decode_jpeg(&candidate.path),
+        "png" => decode_png(&candidate.path),
+        "webp" => decode_webp(&candidate.path),
+        _ => return None,
+    };
+    let (width, height, pixels) = decoded
+        .inspect_err(|err| {
+            tracing::debug!(path = %candidate.path.display(), error = %err, "failed to decode image")
+        })
+        .ok()?;
+    let hash = difference_hash(width as usize, height as usize, &pixels)?;
+    Some(Fingerprint {
+        hash,
+        width,
+        height,
+    })
+}
+
+fn find_root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
+
+fn similarity_of(a: u64, b: u64) -> f64 {
+    1.0 - f64::from((a ^ b).count_ones()) / f64::from(HASH_BITS)
+}
+
+/// Groups images that look alike at `similarity` (0 to 1) or more: resized, recompressed
+/// or converted copies of the same photo. `same_content` tells two paths that are byte
+/// for byte equal, so groups the exact search already reports are left out.
+pub(crate) fn find_similar_images(
+    candidates: Vec<ImageCandidate>,
+    similarity: f64,
+    same_content: impl Fn(&str, &str) -> bool,
+) -> SimilarImages {
+    let similarity = similarity.clamp(MIN_SIMILARITY, 1.0);
+    let max_distance = ((1.0 - similarity) * f64::from(HASH_BITS)).floor() as u32;
+
+    let candidates: Vec<ImageCandidate> = candidates
+        .into_iter()
+        .filter(|candidate| candidate.size <= IMAGE_MAX_BYTES)
+        .collect();
+    let fingerprints: Vec<Option<Fingerprint>> = candidates.par_iter().map(fingerprint).collect();
+    let undecodable = fingerprints.iter().filter(|f| f.is_none()).count() as u64;
+    let images: Vec<(ImageCandidate, Fingerprint)> = candidates
+        .into_iter()
+        .zip(fingerprints)
+        .filter_map(|(candidate, fingerprint)| Some((candidate, fingerprint?)))
+        .collect();
+
+    let mut parents: Vec<usize> = (0..images.len()).collect();
+    for i in 0..images.len() {
+        for j in i + 1..images.len() {
+            if (images[i].1.hash ^ images[j].1.hash).count_ones() <= max_distance {
+                let (a, b) = (find_root(&mut parents, i), find_root(&mut parents, j));
+                parents[b] = a;
+            }
+        }
+    }
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for index in 0..images.len() {
+        let root = find_root(&mut parents, index);
+        members.entry(root).or_default().push(index);
+    }
+
+    let mut groups: Vec<SimilarImageGroup> = members
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .filter_map(|mut group| {
+            group.sort_by_key(|&i| std::cmp::Reverse(images[i].0.size));
+            let paths: Vec<String> = group
+                .iter()
+                .map(|&i| images[i].0.path.to_string_lossy().into_owned())
+                .collect();
+            if paths[1..].iter().all(|path| same_content(&paths[0], path)) {
+                return None;
+            }
+            let first = images[group[0]].1.hash;
+            let lowest = group[1..]
+                .iter()
+                .map(|&i| similarity_of(first, images[i].1.hash))
+                .fold(1.0, f64::min);
+            let wasted_bytes = group[1..].iter().map(|&i| images[i].0.size).sum();
+            let images = group
+                .iter()
+                .zip(paths)
+                .map(|(&i, path)| SimilarImage {
+                    path,
+                    size: images[i].0.size,
+                    width: images[i].1.width,
+                    height: images[i].1.height,
+                })
+                .collect();
+            Some(SimilarImageGroup {
+                images,
+                similarity: lowest,
+                wasted_bytes,
+            })
+        })
+        .collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.wasted_bytes));
+
+    SimilarImages {
+        groups,
+        similarity,
+        compared_images: images.len() as u64,
+        undecodable_images: undecodable,
+    }
+}
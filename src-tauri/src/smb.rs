@@ -0,0 +1,422 @@
+use tauri::Manager;
+
+use crate::{
+    credentials::{self, Credential},
+    error::CommandError,
+    priority::ScanMode,
+    scanner::{self, skipped_preview, ScanResult},
+    settings::SettingsStore,
+    store::ScanStore,
+};
+
+/// A folder on an SMB share, given as `smb://server/share/folder` or
+/// `\\server\share\folder`.
+#[derive(Debug, Clone)]
+pub(crate) struct Share {
+    server: String,
+    share: String,
+    folder: Vec<String>,
+}
+
+enum ConnectError {
+    // The server wants a user name and password, or refused the ones saved.
+    Credentials(String),
+    Failed(String),
+}
+
+/// Decodes the `%XX` escapes of a URL.
+pub(crate) fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+impl Share {
+    pub(crate) fn parse(target: &str) -> Result<Self, String> {
+        let invalid = || format!("Not an SMB share: {}", target);
+        let (rest, url) = match target.strip_prefix("smb://") {
+            Some(rest) => (rest, true),
+            None => (target.strip_prefix("\\\\").ok_or_else(invalid)?, false),
+        };
+        let mut parts = rest
+            .split(['/', '\\'])
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                if url {
+                    percent_decode(part)
+                } else {
+                    part.to_string()
+                }
+            });
+        // A user in the URL is ignored; credentials come from the keychain.
+        let server = parts.next().ok_or_else(invalid)?;
+        let server = server
+            .rsplit_once('@')
+            .map_or(server.as_str(), |(_, host)| host);
+        let share = parts.next().ok_or_else(invalid)?;
+        let folder: Vec<String> = parts.filter(|part| part != ".").collect();
+        // Server and share end up on mount tool command lines.
+        let server_ok = !server.starts_with('-')
+            && server
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"-._".contains(&b));
+        let names_ok = std::iter::once(&share)
+            .chain(&folder)
+            .all(|name| name != ".." && !name.chars().any(char::is_control));
+        if server.is_empty() || !server_ok || !names_ok || share.starts_with('-') {
+            return Err(invalid());
+        }
+        Ok(Self {
+            server: server.to_string(),
+            share,
+            folder,
+        })
+    }
+
+    /// The key the share's credential is saved under: `smb://server/share`, in lower case
+    /// as servers and shares are case-insensitive.
+    pub(crate) fn account(&self) -> String {
+        format!("smb://{}/{}", self.server, self.share).to_lowercase()
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::{ffi::OsStr, path::PathBuf};
+    use windows_sys::Win32::{
+        Foundation::{ERROR_ACCESS_DENIED, ERROR_INVALID_PASSWORD, ERROR_LOGON_FAILURE},
+        NetworkManagement::WNet::{
+            WNetAddConnection2W, CONNECT_TEMPORARY, NETRESOURCEW, RESOURCETYPE_DISK,
+        },
+    };
+
+    use super::{ConnectError, Share};
+    use crate::{credentials::Credential, volumes::to_wide};
+
+    /// Connects to the share without a drive letter and returns its UNC path. An existing
+    /// session (a mapped drive, Explorer) is reused as is.
+    pub(super) fn connect(
+        share: &Share,
+        credential: Option<&Credential>,
+    ) -> Result<PathBuf, ConnectError> {
+        let root = PathBuf::from(format!(r"\\{}\{}", share.server, share.share));
+        if std::fs::read_dir(&root).is_ok() {
+            return Ok(root);
+        }
+        let mut remote = to_wide(root.as_os_str());
+        let username = credential.map(|c| to_wide(OsStr::new(&c.username)));
+        let password = credential.map(|c| to_wide(OsStr::new(&c.password)));
+        // SAFETY: all-zero is a valid NETRESOURCEW; the strings outlive the call.
+        let mut resource: NETRESOURCEW = unsafe { std::mem::zeroed() };
+        resource.dwType = RESOURCETYPE_DISK;
+        resource.lpRemoteName = remote.as_mut_ptr();
+        // Null user and password mean the signed-in user's.
+        let code = unsafe {
+            WNetAddConnection2W(
+                &resource,
+                password.as_ref().map_or(std::ptr::null(), |p| p.as_ptr()),
+                username.as_ref().map_or(std::ptr::null(), |u| u.as_ptr()),
+                CONNECT_TEMPORARY,
+            )
+        };
+        match code {
+            0 => Ok(root),
+            ERROR_ACCESS_DENIED | ERROR_LOGON_FAILURE | ERROR_INVALID_PASSWORD => {
+                Err(ConnectError::Credentials(format!(
+                    "{} refused the connection (error {}).",
+                    share.server, code
+                )))
+            }
+            code => Err(ConnectError::Failed(format!(
+                "Failed to connect to {} (error {}).",
+                root.to_string_lossy(),
+                code
+            ))),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{path::PathBuf, process::Command};
+
+    use super::{ConnectError, Share};
+    use crate::credentials::Credential;
+
+    const MOUNT_SMBFS: &str = "/sbin/mount_smbfs";
+
+    /// Percent-encodes a URL component for mount_smbfs.
+    fn encode(value: &str) -> String {
+        value
+            .bytes()
+            .map(|b| {
+                if b.is_ascii_alphanumeric() || b"-_.~".contains(&b) {
+                    (b as char).to_string()
+                } else {
+                    format!("%{:02X}", b)
+                }
+            })
+            .collect()
+    }
+
+    /// Mounts the share under the temporary folder, or reuses that mount if it is still
+    /// there, and returns the mount point.
+    pub(super) fn connect(
+        share: &Share,
+        credential: Option<&Credential>,
+    ) -> Result<PathBuf, ConnectError> {
+        let mount_point = std::env::temp_dir().join("DiskCheck-smb").join(format!(
+            "{}-{}",
+            share.server,
+            encode(&share.share)
+        ));
+        let mounted = crate::volumes::volume_for_path(&mount_point)
+            .is_some_and(|v| mount_point.to_string_lossy() == v.mount_point);
+        if mounted {
+            return Ok(mount_point);
+        }
+        std::fs::create_dir_all(&mount_point)
+            .map_err(|e| ConnectError::Failed(format!("Failed to create mount point: {}", e)))?;
+
+        // mount_smbfs only takes the password inside the URL, and a domain as `DOMAIN;user`.
+        let user = credential
+            .map(|c| {
+                let user = match c.username.split_once('\\') {
+                    Some((domain, user)) => format!("{};{}", encode(domain), encode(user)),
+                    None => encode(&c.username),
+                };
+                format!("{}:{}@", user, encode(&c.password))
+            })
+            .unwrap_or_default();
+        let output = Command::new(MOUNT_SMBFS)
+            .arg("-N")
+            .arg(format!(
+                "//{}{}/{}",
+                user,
+                share.server,
+                encode(&share.share)
+            ))
+            .arg(&mount_point)
+            .output()
+            .map_err(|e| ConnectError::Failed(format!("Failed to run mount_smbfs: {}", e)))?;
+        if output.status.success() {
+            return Ok(mount_point);
+        }
+        let _ = std::fs::remove_dir(&mount_point);
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if message.contains("Authentication error") || message.contains("Permission denied") {
+            Err(ConnectError::Credentials(message))
+        } else {
+            Err(ConnectError::Failed(message))
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::{
+        io::Write,
+        path::PathBuf,
+        process::{Command, Stdio},
+    };
+
+    use super::{ConnectError, Share};
+    use crate::credentials::Credential;
+
+    const GIO: &str = "gio";
+
+    /// Where gvfs exposes the share through its FUSE daemon.
+    fn gvfs_path(share: &Share) -> PathBuf {
+        let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                // SAFETY: getuid has no preconditions.
+                PathBuf::from(format!("/run/user/{}", unsafe { libc::getuid() }))
+            });
+        runtime_dir.join("gvfs").join(format!(
+            "smb-share:server={},share={}",
+            share.server.to_lowercase(),
+            share.share.to_lowercase()
+        ))
+    }
+
+    /// Mounts the share through gvfs (as the file manager would), or reuses an existing
+    /// gvfs mount, and returns its FUSE path.
+    pub(super) fn connect(
+        share: &Share,
+        credential: Option<&Credential>,
+    ) -> Result<PathBuf, ConnectError> {
+        let root = gvfs_path(share);
+        if root.is_dir() {
+            return Ok(root);
+        }
+        let url = format!("smb://{}/{}/", share.server, share.share);
+        let mut command = Command::new(GIO);
+        command.arg("mount");
+        if credential.is_none() {
+            command.arg("--anonymous");
+        }
+        let mut child = command
+            .arg(&url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| ConnectError::Failed(format!("Failed to run gio: {}", e)))?;
+        if let (Some(credential), Some(mut stdin)) = (credential, child.stdin.take()) {
+            // gio asks for the user, the domain and the password, in that order.
+            let (domain, user) = credential
+                .username
+                .split_once('\\')
+                .unwrap_or(("", &credential.username));
+            let _ = write!(stdin, "{}\n{}\n{}\n", user, domain, credential.password);
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| ConnectError::Failed(e.to_string()))?;
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if !output.status.success() {
+            let lower = message.to_lowercase();
+            let refused = ["denied", "password", "anonymous", "logon", "authentication"]
+                .iter()
+                .any(|hint| lower.contains(hint));
+            return Err(if refused || credential.is_none() {
+                ConnectError::Credentials(message)
+            } else {
+                ConnectError::Failed(message)
+            });
+        }
+        if !root.is_dir() {
+            return Err(ConnectError::Failed(format!(
+                "{} was mounted, but {} is missing. Is gvfs-fuse running?",
+                url,
+                root.to_string_lossy()
+            )));
+        }
+        Ok(root)
+    }
+}
+
+/// Connects to the share with the credential saved for it and scans the folder like a
+/// network volume. The connection is left open, so the scanned files stay reachable.
+/// Fails with `CredentialsRequired` when the server wants other credentials; the UI
+/// asks for them, saves them with `save_share_credentials` and scans again.
+pub(crate) fn scan_share_blocking(
+    share: &Share,
+    min_node_bytes: Option<u64>,
+    excludes: Vec<String>,
+    archives: bool,
+    mode: ScanMode,
+    window: Option<tauri::Window>,
+) -> Result<scanner::FinishedScan, CommandError> {
+    let account = share.account();
+    let credential = credentials::load(&account);
+    tracing::info!(share = %account, saved_credential = credential.is_some(), "connecting to share");
+    let root = platform::connect(share, credential.as_ref()).map_err(|error| match error {
+        ConnectError::Credentials(message) => {
+            tracing::info!(share = %account, error = %message, "share needs credentials");
+            CommandError::CredentialsRequired(if message.is_empty() {
+                format!("{} needs a user name and password.", account)
+            } else {
+                message
+            })
+        }
+        ConnectError::Failed(message) => {
+            tracing::error!(share = %account, error = %message, "failed to connect to share");
+            CommandError::Failed(message)
+        }
+    })?;
+    let root = share.folder.iter().fold(root, |path, part| path.join(part));
+    if !root.is_dir() {
+        return Err(CommandError::Failed(format!(
+            "Folder not found on {}: {}",
+            account,
+            share.folder.join("/")
+        )));
+    }
+    // Shares scan with the network settings; the per-volume index only covers local disks.
+    scanner::scan_blocking(
+        &root,
+        min_node_bytes,
+        excludes,
+        archives,
+        mode,
+        None,
+        window,
+    )
+}
+
+pub async fn scan_share(
+    window: tauri::Window,
+    store: &ScanStore,
+    target: String,
+    min_node_bytes: Option<u64>,
+    mode: ScanMode,
+) -> Result<ScanResult, CommandError> {
+    let share = Share::parse(&target)?;
+    let defaults = window.state::<SettingsStore>().scan();
+    let min_node_bytes = min_node_bytes.or(defaults.min_node_bytes);
+    let finished = tauri::async_runtime::spawn_blocking(move || {
+        scan_share_blocking(
+            &share,
+            min_node_bytes,
+            defaults.excludes,
+            defaults.scan_archives,
+            mode,
+            Some(window),
+        )
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    let skipped = skipped_preview(&finished.summary);
+    let scan_id = store.insert_spilled(finished.tree, finished.spill, finished.summary)?;
+    Ok(ScanResult {
+        scan_id,
+        root: finished.pruned,
+        skipped,
+        usage_check: None,
+        denied_dirs: vec![],
+        partial: finished.partial,
+    })
+}
+
+pub async fn save_share_credentials(
+    target: String,
+    username: String,
+    password: String,
+) -> Result<(), String> {
+    let share = Share::parse(&target)?;
+    if username.is_empty() {
+        return Err("A user name is needed.".to_string());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        credentials::save(&share.account(), &Credential { username, password })
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub async fn forget_share_credentials(target: String) -> Result<(), String> {
+    let share = Share::parse(&target)?;
+    tauri::async_runtime::spawn_blocking(move || credentials::delete(&share.account()))
+        .await
+        .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,288 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    export::create_dest,
+    scan_history::ScanAnnotation,
+    scanner::{
+        file_extension_lower, pruned_view, FsNode, FsNodeKind, NodeRef, ScanResult, ScanTree,
+    },
+    store::{ScanStore, ScanSummary},
+};
+
+// File layout: magic, little-endian format version, then a zstd-compressed bincode
+// `Snapshot`. Bump the version whenever `Snapshot` or `SnapshotNode` changes.
+const SNAPSHOT_MAGIC: &[u8; 8] = b"DCSNAP\0\0";
+const SNAPSHOT_VERSION: u32 = 3;
+// Before annotations were saved; still read.
+const SNAPSHOT_VERSION_UNANNOTATED: u32 = 1;
+// Before the summary saved whether the scan was partial; still read.
+const SNAPSHOT_VERSION_UNMARKED: u32 = 2;
+const ZSTD_LEVEL: i32 = 3;
+
+/// Tree nodes flattened in pre-order. Paths and extensions are derived again on load,
+/// which keeps multi-million node snapshots small.
+#[derive(Serialize, Deserialize)]
+struct SnapshotNode {
+    name: String,
+    kind: FsNodeKind,
+    size: u64,
+    error: Option<String>,
+    child_count: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    summary: ScanSummary,
+    nodes: Vec<SnapshotNode>,
+    annotation: Option<ScanAnnotation>,
+}
+
+/// `ScanSummary` as versions 1 and 2 saved it, without `partial`.
+#[derive(Deserialize)]
+struct UnmarkedSummary {
+    root_path: String,
+    started_at_secs: u64,
+    duration_ms: u64,
+    total_bytes: u64,
+    file_count: u64,
+    dir_count: u64,
+    skipped_entries: u64,
+}
+
+impl From<UnmarkedSummary> for ScanSummary {
+    fn from(old: UnmarkedSummary) -> Self {
+        ScanSummary {
+            root_path: old.root_path,
+            started_at_secs: old.started_at_secs,
+            duration_ms: old.duration_ms,
+            total_bytes: old.total_bytes,
+            file_count: old.file_count,
+            dir_count: old.dir_count,
+            skipped_entries: old.skipped_entries,
+            denied_dirs: vec![],
+            skipped: vec![],
+            partial: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct UnannotatedSnapshot {
+    summary: UnmarkedSummary,
+    nodes: Vec<SnapshotNode>,
+}
+
+#[derive(Deserialize)]
+struct UnmarkedSnapshot {
+    summary: UnmarkedSummary,
+    nodes: Vec<SnapshotNode>,
+    annotation: Option<ScanAnnotation>,
+}
+
+fn flatten(root: NodeRef<'_>) -> Vec<SnapshotNode> {
+    let mut nodes = vec![];
+    let mut pending: Vec<NodeRef> = vec![root];
+    while let Some(node) = pending.pop() {
+        let children: Vec<NodeRef> = node.children().collect();
+        nodes.push(SnapshotNode {
+            name: node.name().to_string(),
+            kind: node.kind(),
+            size: node.size(),
+            error: node.error().map(str::to_string),
+            child_count: children.len() as u32,
+        });
+        pending.extend(children.into_iter().rev());
+    }
+    nodes
+}
+
+fn to_fs_node(snap: SnapshotNode, path: String) -> FsNode {
+    let extension = match snap.kind {
+        FsNodeKind::Directory => None,
+        _ => file_extension_lower(Path::new(&snap.name)),
+    };
+    FsNode {
+        name: snap.name,
+        path,
+        kind: snap.kind,
+        size: snap.size,
+        children: Vec::with_capacity(snap.child_count as usize),
+        extension,
+        error: snap.error,
+        uncompressed_size: None,
+        error_count: 0,
+    }
+}
+
+fn rebuild(nodes: Vec<SnapshotNode>, root_path: &str) -> Result<FsNode, String> {
+    let truncated = || "The snapshot is truncated or corrupt.".to_string();
+    let mut nodes = nodes.into_iter();
+    let root = nodes.next().ok_or_else(truncated)?;
+    let root_children = root.child_count;
+    // Each frame is a node still waiting for `remaining` children.
+    let mut stack: Vec<(FsNode, u32)> =
+        vec![(to_fs_node(root, root_path.to_string()), root_children)];
+
+    loop {
+        while matches!(stack.last(), Some((_, 0))) {
+            let (node, _) = stack.pop().ok_or_else(truncated)?;
+            match stack.last_mut() {
+                Some((parent, _)) => parent.children.push(node),
+                None => return Ok(node),
+            }
+        }
+
+        let snap = nodes.next().ok_or_else(truncated)?;
+        let (parent, remaining) = stack.last_mut().ok_or_else(truncated)?;
+        *remaining -= 1;
+        let path = Path::new(&parent.path)
+            .join(&snap.name)
+            .to_string_lossy()
+            .into_owned();
+        let child_count = snap.child_count;
+        stack.push((to_fs_node(snap, path), child_count));
+    }
+}
+
+/// Writes the snapshot format to any writer (a file, or an entry of a bundle).
+pub(crate) fn write_snapshot(
+    summary: &ScanSummary,
+    tree: &ScanTree,
+    annotation: Option<&ScanAnnotation>,
+    mut out: impl Write,
+) -> Result<(), String> {
+    let snapshot = Snapshot {
+        summary: summary.clone(),
+        nodes: flatten(tree.root()),
+        annotation: annotation.cloned(),
+    };
+
+    out.write_all(SNAPSHOT_MAGIC)
+        .and_then(|()| out.write_all(&SNAPSHOT_VERSION.to_le_bytes()))
+        .map_err(|e| e.to_string())?;
+    let mut encoder = zstd::Encoder::new(out, ZSTD_LEVEL).map_err(|e| e.to_string())?;
+    bincode::serialize_into(&mut encoder, &snapshot).map_err(|e| e.to_string())?;
+    encoder
+        .finish()
+        .and_then(|mut out| out.flush())
+        .map_err(|e| e.to_string())
+}
+
+/// The tree, summary and annotation a snapshot holds.
+pub(crate) fn read_snapshot(
+    mut reader: impl Read,
+) -> Result<(ScanTree, ScanSummary, Option<ScanAnnotation>), String> {
+    let mut magic = [0u8; 8];
+    let mut version = [0u8; 4];
+    reader
+        .read_exact(&mut magic)
+        .and_then(|()| reader.read_exact(&mut version))
+        .map_err(|_| "Not a DiskCheck snapshot file.".to_string())?;
+    if &magic != SNAPSHOT_MAGIC {
+        return Err("Not a DiskCheck snapshot file.".to_string());
+    }
+    let version = u32::from_le_bytes(version);
+    if ![
+        SNAPSHOT_VERSION,
+        SNAPSHOT_VERSION_UNANNOTATED,
+        SNAPSHOT_VERSION_UNMARKED,
+    ]
+    .contains(&version)
+    {
+        return Err(format!(
+            "Unsupported snapshot version {} (this build reads version {}).",
+            version, SNAPSHOT_VERSION
+        ));
+    }
+
+    let decoder = zstd::Decoder::new(reader).map_err(|e| e.to_string())?;
+    let snapshot: Snapshot = match version {
+        SNAPSHOT_VERSION_UNANNOTATED => {
+            let old: UnannotatedSnapshot =
+                bincode::deserialize_from(decoder).map_err(|e| e.to_string())?;
+            Snapshot {
+                summary: old.summary.into(),
+                nodes: old.nodes,
+                annotation: None,
+            }
+        }
+        SNAPSHOT_VERSION_UNMARKED => {
+            let old: UnmarkedSnapshot =
+                bincode::deserialize_from(decoder).map_err(|e| e.to_string())?;
+            Snapshot {
+                summary: old.summary.into(),
+                nodes: old.nodes,
+                annotation: old.annotation,
+            }
+        }
+        _ => bincode::deserialize_from(decoder).map_err(|e| e.to_string())?,
+    };
+    let tree = rebuild(snapshot.nodes, &snapshot.summary.root_path)?;
+    Ok((ScanTree::from(&tree), snapshot.summary, snapshot.annotation))
+}
+
+fn save_blocking(
+    summary: &ScanSummary,
+    tree: &ScanTree,
+    annotation: Option<&ScanAnnotation>,
+    dest: &Path,
+) -> Result<(), String> {
+    let out = create_dest(dest)?;
+    write_snapshot(summary, tree, annotation, out)
+        .map_err(|e| format!("Failed to write {}: {}", dest.to_string_lossy(), e))
+}
+
+fn open_blocking(path: &Path) -> Result<(ScanTree, ScanSummary, Option<ScanAnnotation>), String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.to_string_lossy(), e))?;
+    read_snapshot(BufReader::new(file))
+}
+
+/// Saves a stored scan (full tree, summary and annotation) as a compressed binary
+/// snapshot.
+pub async fn save_snapshot(
+    store: &ScanStore,
+    scan_id: String,
+    annotation: Option<ScanAnnotation>,
+    dest: String,
+) -> Result<(), String> {
+    let scan = store.get(&scan_id)?;
+    let dest = PathBuf::from(dest);
+    tauri::async_runtime::spawn_blocking(move || {
+        let scan = scan.complete()?;
+        save_blocking(&scan.summary, &scan.tree, annotation.as_ref(), &dest)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Loads a snapshot into the result store as a new scan, returning the annotation
+/// saved with it.
+pub async fn open_snapshot(
+    store: &ScanStore,
+    path: String,
+) -> Result<(ScanResult, Option<ScanAnnotation>), String> {
+    let path = PathBuf::from(path);
+    let (tree, summary, annotation) =
+        tauri::async_runtime::spawn_blocking(move || open_blocking(&path))
+            .await
+            .map_err(|err| err.to_string())??;
+
+    let root = pruned_view(tree.root(), None);
+    let partial = summary.partial;
+    let scan_id = store.insert(tree, summary)?;
+    let result = ScanResult {
+        scan_id,
+        root,
+        skipped: vec![],
+        usage_check: None,
+        denied_dirs: vec![],
+        partial,
+    };
+    Ok((result, annotation))
+}
@@ -0,0 +1,132 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    sync::{Arc, Mutex},
+};
+use tempfile::TempPath;
+
+use diskcheck_core::SubtreeSink;
+
+use crate::scanner::ScanTree;
+
+// Spilled subtrees are read back while the user waits, so favour speed over ratio.
+const ZSTD_LEVEL: i32 = 1;
+// A running scan starts moving finished folders to disk once its tree holds this many
+// nodes, half of what all stored scans may keep in memory together.
+const SCAN_RESIDENT_NODES: usize = 2_500_000;
+// Smaller finished folders stay and leave later as part of their parent.
+const MIN_SCAN_SPILL_NODES: usize = 10_000;
+
+#[derive(Debug)]
+struct SpillRecords {
+    file: File,
+    len: u64,
+    // Folder path -> (offset, length) of its latest record.
+    index: HashMap<String, (u64, u64)>,
+}
+
+/// Temporary on-disk home of the subtrees a stored scan moved out of memory. Records
+/// are zstd-compressed bincode, appended and never rewritten, so older copies of the
+/// scan can still read what they split off. The file is deleted with the scan.
+#[derive(Debug)]
+pub struct SpillFile {
+    // Deletes the file when dropped.
+    path: TempPath,
+    records: Mutex<SpillRecords>,
+}
+
+impl SpillFile {
+    /// Creates the file in the temp folder under a random name, readable by this user
+    /// only: it lists every scanned file, and the folder may be shared.
+    pub fn create() -> Result<Self, String> {
+        let (file, path) = tempfile::Builder::new()
+            .prefix("diskcheck-spill-")
+            .suffix(".bin")
+            .tempfile()
+            .map_err(|e| format!("Failed to create a spill file: {}", e))?
+            .into_parts();
+        Ok(SpillFile {
+            path,
+            records: Mutex::new(SpillRecords {
+                file,
+                len: 0,
+                index: HashMap::new(),
+            }),
+        })
+    }
+
+    /// Appends `subtree`, replacing any earlier record for the same folder.
+    pub fn write(&self, subtree: &ScanTree) -> Result<(), String> {
+        let write_err =
+            |e: std::io::Error| format!("Failed to write {}: {}", self.path.to_string_lossy(), e);
+        let bytes = bincode::serialize(subtree).map_err(|e| e.to_string())?;
+        let bytes = zstd::encode_all(bytes.as_slice(), ZSTD_LEVEL).map_err(write_err)?;
+
+        let mut records = self.records.lock().map_err(|e| e.to_string())?;
+        let offset = records.len;
+        records
+            .file
+            .seek(SeekFrom::Start(offset))
+            .map_err(write_err)?;
+        records.file.write_all(&bytes).map_err(write_err)?;
+        records.len += bytes.len() as u64;
+        records.index.insert(
+            subtree.root_path().to_string(),
+            (offset, bytes.len() as u64),
+        );
+        Ok(())
+    }
+
+    /// The latest record for the folder at `path`.
+    pub fn read(&self, path: &str) -> Result<ScanTree, String> {
+        let read_err =
+            |e: std::io::Error| format!("Failed to read {}: {}", self.path.to_string_lossy(), e);
+        let mut records = self.records.lock().map_err(|e| e.to_string())?;
+        let (offset, len) = *records
+            .index
+            .get(path)
+            .ok_or_else(|| format!("No spilled data for {}", path))?;
+        let mut bytes = vec![0; len as usize];
+        records
+            .file
+            .seek(SeekFrom::Start(offset))
+            .map_err(read_err)?;
+        records.file.read_exact(&mut bytes).map_err(read_err)?;
+        drop(records);
+
+        let bytes = zstd::decode_all(bytes.as_slice()).map_err(read_err)?;
+        bincode::deserialize(&bytes).map_err(|e| e.to_string())
+    }
+}
+
+/// Moves large finished folders of a running scan to a spill file, so the end of a huge
+/// scan does not have to split the whole tree at once before storing it.
+#[derive(Default)]
+pub struct ScanSpill {
+    file: Option<Arc<SpillFile>>,
+    // After a failed write the rest of the scan stays in memory.
+    failed: bool,
+}
+
+impl ScanSpill {
+    /// The spill file, if anything was moved out; it goes with the stored scan.
+    pub fn into_file(self) -> Option<Arc<SpillFile>> {
+        self.file
+    }
+}
+
+impl SubtreeSink for ScanSpill {
+    fn wants(&mut self, nodes: usize, resident: usize) -> bool {
+        !self.failed && resident > SCAN_RESIDENT_NODES && nodes >= MIN_SCAN_SPILL_NODES
+    }
+
+    fn write(&mut self, subtree: &ScanTree) -> Result<(), String> {
+        let file = match &self.file {
+            Some(file) => file.clone(),
+            None => Arc::new(SpillFile::create()?),
+        };
+        self.file = Some(file.clone());
+        file.write(subtree).inspect_err(|_| self.failed = true)
+    }
+}
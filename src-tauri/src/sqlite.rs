@@ -0,0 +1,255 @@
+use std::{borrow::Cow, path::Path};
+
+use crate::cloud_sync::read_local;
+
+// Every database file starts with this.
+const MAGIC: &[u8] = b"SQLite format 3\0";
+const HEADER_BYTES: usize = 100;
+const TEXT_UTF8: u32 = 1;
+
+// B-tree page kinds that hold table rows; index pages are never visited.
+const INTERIOR_TABLE: u8 = 0x05;
+const LEAF_TABLE: u8 = 0x0d;
+
+/// One column of a row.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl Value {
+    pub(crate) fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// Read-only access to the rows of a SQLite database file, enough to pull data out of
+/// the small metadata databases other programs leave around without linking SQLite. It
+/// reads the main file only: changes still sitting in a `-wal` file are not seen.
+pub(crate) struct Database {
+    data: Vec<u8>,
+    page_size: usize,
+    // Page size minus the bytes reserved at the end of every page.
+    usable_size: usize,
+}
+
+fn corrupt() -> String {
+    "The database file is malformed.".to_string()
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Result<usize, String> {
+    let b = bytes.get(at..at + 2).ok_or_else(corrupt)?;
+    Ok(usize::from(u16::from_be_bytes([b[0], b[1]])))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Result<u32, String> {
+    let b = bytes.get(at..at + 4).ok_or_else(corrupt)?;
+    Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// A big-endian variable-length integer and the bytes it took.
+fn varint(bytes: &[u8]) -> Result<(u64, usize), String> {
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().enumerate().take(9) {
+        if i == 8 {
+            return Ok(((value << 8) | u64::from(byte), 9));
+        }
+        value = (value << 7) | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(corrupt())
+}
+
+/// Splits a record into its columns.
+fn record(payload: &[u8]) -> Result<Vec<Value>, String> {
+    let (header_len, mut at) = varint(payload)?;
+    let header_len = header_len as usize;
+    let mut serial_types = vec![];
+    while at < header_len {
+        let (serial_type, len) = varint(payload.get(at..).ok_or_else(corrupt)?)?;
+        serial_types.push(serial_type);
+        at += len;
+    }
+
+    let mut body = header_len;
+    let mut values = Vec::with_capacity(serial_types.len());
+    for serial_type in serial_types {
+        let len = match serial_type {
+            0 | 8 | 9 => 0,
+            1..=4 => serial_type as usize,
+            5 => 6,
+            6 | 7 => 8,
+            10 | 11 => return Err(corrupt()),
+            n => ((n - 12) / 2) as usize,
+        };
+        let bytes = payload.get(body..body + len).ok_or_else(corrupt)?;
+        body += len;
+        values.push(match serial_type {
+            0 => Value::Null,
+            8 => Value::Integer(0),
+            9 => Value::Integer(1),
+            1..=6 => {
+                // Two's complement, sign-extended from the first byte.
+                let sign = if bytes[0] & 0x80 != 0 { -1i64 } else { 0 };
+                Value::Integer(
+                    bytes
+                        .iter()
+                        .fold(sign, |value, &b| (value << 8) | i64::from(b)),
+                )
+            }
+            7 => Value::Real(f64::from_be_bytes(bytes.try_into().map_err(|_| corrupt())?)),
+            n if n % 2 == 0 => Value::Blob(bytes.to_vec()),
+            _ => Value::Text(String::from_utf8_lossy(bytes).into_owned()),
+        });
+    }
+    Ok(values)
+}
+
+impl Database {
+    pub(crate) fn open(path: &Path) -> Result<Self, String> {
+        let data = read_local(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.to_string_lossy(), e))?;
+        if !data.starts_with(MAGIC) || data.len() < HEADER_BYTES {
+            return Err(format!(
+                "{} is not a SQLite database (or is encrypted).",
+                path.to_string_lossy()
+            ));
+        }
+        // 1 stands for 65536, which does not fit the two bytes.
+        let page_size = match read_u16(&data, 16)? {
+            1 => 65_536,
+            size => size,
+        };
+        let reserved = usize::from(data[20]);
+        if page_size < 512 || !page_size.is_power_of_two() || reserved >= page_size - 480 {
+            return Err(corrupt());
+        }
+        if read_u32(&data, 56)? > TEXT_UTF8 {
+            return Err("Only UTF-8 databases are supported.".to_string());
+        }
+        Ok(Self {
+            page_size,
+            usable_size: page_size - reserved,
+            data,
+        })
+    }
+
+    fn page(&self, number: u32) -> Result<&[u8], String> {
+        let start = (number as usize)
+            .checked_sub(1)
+            .ok_or_else(corrupt)?
+            .checked_mul(self.page_size)
+            .ok_or_else(corrupt)?;
+        self.data
+            .get(start..start + self.page_size)
+            .ok_or_else(corrupt)
+    }
+
+    /// The `size`-byte payload of a cell starting at `at`, following overflow pages.
+    fn payload<'a>(
+        &'a self,
+        page: &'a [u8],
+        at: usize,
+        size: usize,
+    ) -> Result<Cow<'a, [u8]>, String> {
+        let usable = self.usable_size;
+        let max_local = usable - 35;
+        if size <= max_local {
+            return page
+                .get(at..at + size)
+                .map(Cow::Borrowed)
+                .ok_or_else(corrupt);
+        }
+        let min_local = (usable - 12) * 32 / 255 - 23;
+        let local = match min_local + (size - min_local) % (usable - 4) {
+            local if local <= max_local => local,
+            _ => min_local,
+        };
+        let mut payload = Vec::with_capacity(size);
+        payload.extend_from_slice(page.get(at..at + local).ok_or_else(corrupt)?);
+        let mut next = read_u32(page, at + local)?;
+        while payload.len() < size {
+            if next == 0 {
+                return Err(corrupt());
+            }
+            let overflow = self.page(next)?;
+            next = read_u32(overflow, 0)?;
+            let take = (size - payload.len()).min(usable - 4);
+            payload.extend_from_slice(overflow.get(4..4 + take).ok_or_else(corrupt)?);
+        }
+        Ok(Cow::Owned(payload))
+    }
+
+    /// Calls `visit` with the columns of every row of the table b-tree at `root`.
+    fn walk(&self, root: u32, visit: &mut dyn FnMut(Vec<Value>)) -> Result<(), String> {
+        let page_count = self.data.len() / self.page_size;
+        let mut pending = vec![root];
+        let mut visited = 0;
+        while let Some(number) = pending.pop() {
+            // A well-formed tree visits each page once; more means a cycle.
+            visited += 1;
+            if visited > page_count {
+                return Err(corrupt());
+            }
+            let page = self.page(number)?;
+            let header = if number == 1 { HEADER_BYTES } else { 0 };
+            let cells = read_u16(page, header + 3)?;
+            match page[header] {
+                INTERIOR_TABLE => {
+                    pending.push(read_u32(page, header + 8)?);
+                    for cell in 0..cells {
+                        let at = read_u16(page, header + 12 + cell * 2)?;
+                        pending.push(read_u32(page, at)?);
+                    }
+                }
+                LEAF_TABLE => {
+                    for cell in 0..cells {
+                        let mut at = read_u16(page, header + 8 + cell * 2)?;
+                        let (size, len) = varint(page.get(at..).ok_or_else(corrupt)?)?;
+                        at += len;
+                        let (_rowid, len) = varint(page.get(at..).ok_or_else(corrupt)?)?;
+                        at += len;
+                        visit(record(&self.payload(page, at, size as usize)?)?);
+                    }
+                }
+                _ => return Err(corrupt()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `visit` with the columns of every row of `table`, in storage order.
+    pub(crate) fn rows(
+        &self,
+        table: &str,
+        mut visit: impl FnMut(Vec<Value>),
+    ) -> Result<(), String> {
+        // The schema table (type, name, tbl_name, rootpage, sql) is rooted at page 1.
+        let mut root = None;
+        self.walk(1, &mut |row| {
+            if row.first().and_then(Value::as_text) == Some("table")
+                && row.get(1).and_then(Value::as_text) == Some(table)
+            {
+                root = row.get(3).and_then(Value::as_integer);
+            }
+        })?;
+        let root = root.ok_or_else(|| format!("The database has no {} table.", table))?;
+        self.walk(u32::try_from(root).map_err(|_| corrupt())?, &mut visit)
+    }
+}
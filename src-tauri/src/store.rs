@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::SystemTime,
+};
+
+use crate::{
+    scanner::{same_path, NodeRef, ScanTree, SkipReason},
+    spill::SpillFile,
+};
+
+// Full trees can be large; only the most recent scans are kept in memory.
+const MAX_STORED_SCANS: usize = 4;
+// Tree nodes kept in memory across all stored scans (a few hundred MB); beyond that,
+// folders are spilled to a temporary file and loaded back when asked for.
+const MAX_RESIDENT_NODES: usize = 5_000_000;
+// Largest folder (in nodes) spilled as one piece, so loading one back stays quick.
+const SPILL_CHUNK_NODES: usize = 100_000;
+
+pub(crate) fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanSummary {
+    pub root_path: String,
+    pub started_at_secs: u64,
+    pub duration_ms: u64,
+    pub total_bytes: u64,
+    pub file_count: u64,
+    pub dir_count: u64,
+    pub skipped_entries: u64,
+    // Folders the scan was denied, which an elevated rescan can fill in. Not saved:
+    // snapshots and bundles store the summary with bincode, which needs a fixed layout.
+    #[serde(skip)]
+    pub denied_dirs: Vec<String>,
+    // What the scan skipped and why, up to 10,000 entries. Not saved either.
+    #[serde(skip)]
+    pub skipped: Vec<SkippedPath>,
+    // Stopped before the walk was done, so the totals are too low to compare with.
+    // Saved, so reopened snapshots and bundles still say so; missing in older files.
+    #[serde(default)]
+    pub partial: bool,
+}
+
+/// An entry a scan left out, so users can see which folders were not counted.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedPath {
+    pub path: String,
+    pub reason: SkipReason,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct StoredScan {
+    pub id: String,
+    pub summary: ScanSummary,
+    // The complete, unpruned tree, minus any folders spilled to `spill`.
+    pub tree: ScanTree,
+    pub spill: Option<Arc<SpillFile>>,
+}
+
+impl StoredScan {
+    /// The node at `path`, or the root when no path is given.
+    pub fn node(&self, path: Option<&str>) -> Result<NodeRef<'_>, String> {
+        match path {
+            Some(path) => self
+                .tree
+                .find(path)
+                .ok_or_else(|| format!("Path is not part of this scan: {}", path)),
+            None => Ok(self.tree.root()),
+        }
+    }
+
+    /// This scan with every spilled folder loaded back, for consumers that walk the
+    /// whole tree (exports, reports). The stored copy is left as it is.
+    pub fn complete(self: Arc<Self>) -> Result<Arc<StoredScan>, String> {
+        let spill = match &self.spill {
+            Some(spill) if !self.tree.spilled_paths().is_empty() => spill.clone(),
+            _ => return Ok(self),
+        };
+        let mut complete = (*self).clone();
+        loop {
+            // Loaded folders may hold spilled folders of their own.
+            let paths = complete.tree.spilled_paths();
+            if paths.is_empty() {
+                break;
+            }
+            for path in paths {
+                complete.tree.graft(spill.read(&path)?);
+            }
+        }
+        Ok(Arc::new(complete))
+    }
+
+    /// Moves folders out to the spill file until the tree holds at most `max_nodes`,
+    /// sparing `keep`. On failure the folders are put back.
+    fn spill_down_to(&mut self, max_nodes: usize, keep: Option<&str>) -> Result<(), String> {
+        let subtrees = self.tree.split_off(max_nodes, SPILL_CHUNK_NODES, keep);
+        if subtrees.is_empty() {
+            return Ok(());
+        }
+        let written = match &self.spill {
+            Some(spill) => Ok(spill.clone()),
+            None => SpillFile::create().map(Arc::new),
+        }
+        .and_then(|spill| {
+            self.spill = Some(spill.clone());
+            subtrees.iter().try_for_each(|subtree| spill.write(subtree))
+        });
+        if let Err(err) = written {
+            for subtree in subtrees {
+                self.tree.graft(subtree);
+            }
+            return Err(err);
+        }
+        tracing::info!(
+            scan_id = %self.id,
+            folders = subtrees.len(),
+            resident_nodes = self.tree.node_count(),
+            "spilled folders to disk"
+        );
+        Ok(())
+    }
+}
+
+/// Spills folders, oldest scans first, until all scans together fit in
+/// `MAX_RESIDENT_NODES`. `keep` names a scan and path that must stay in memory.
+fn enforce_node_cap(
+    scans: &mut VecDeque<Arc<StoredScan>>,
+    keep: Option<(&str, &str)>,
+) -> Result<(), String> {
+    let mut total: usize = scans.iter().map(|s| s.tree.node_count()).sum();
+    for scan in scans.iter_mut() {
+        if total <= MAX_RESIDENT_NODES {
+            break;
+        }
+        let before = scan.tree.node_count();
+        let target = before.saturating_sub(total - MAX_RESIDENT_NODES);
+        let keep = keep.filter(|(id, _)| *id == scan.id).map(|(_, path)| path);
+        let scan = Arc::make_mut(scan);
+        scan.spill_down_to(target, keep)?;
+        total = total - before + scan.tree.node_count();
+    }
+    Ok(())
+}
+
+/// Loads back every spilled folder on the way to `path`. Returns whether any was.
+fn load_path(scan: &mut Arc<StoredScan>, path: &str) -> Result<bool, String> {
+    let mut loaded = false;
+    while let Some(spilled) = scan.tree.spilled_on_path(path).map(|n| n.path()) {
+        let subtree = match &scan.spill {
+            Some(spill) => spill.read(&spilled)?,
+            None => return Err(format!("No spilled data for {}", spilled)),
+        };
+        if !Arc::make_mut(scan).tree.graft(subtree) {
+            return Err(format!("Failed to load {} back", spilled));
+        }
+        loaded = true;
+    }
+    Ok(loaded)
+}
+
+/// Backend-side home of completed scans, addressed by scan ID.
+#[derive(Default)]
+pub struct ScanStore {
+    scans: Mutex<VecDeque<Arc<StoredScan>>>,
+    next_id: AtomicU64,
+}
+
+impl ScanStore {
+    pub fn insert(&self, tree: ScanTree, summary: ScanSummary) -> Result<String, String> {
+        self.insert_spilled(tree, None, summary)
+    }
+
+    /// Like [`ScanStore::insert`], for a tree whose spilled folders are in `spill`.
+    pub fn insert_spilled(
+        &self,
+        tree: ScanTree,
+        spill: Option<Arc<SpillFile>>,
+        summary: ScanSummary,
+    ) -> Result<String, String> {
+        let seq = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let id = format!("scan-{}-{}", summary.started_at_secs, seq);
+
+        let mut scans = self.scans.lock().map_err(|e| e.to_string())?;
+        scans.push_back(Arc::new(StoredScan {
+            id: id.clone(),
+            summary,
+            tree,
+            spill,
+        }));
+        while scans.len() > MAX_STORED_SCANS {
+            scans.pop_front();
+        }
+        if let Err(err) = enforce_node_cap(&mut scans, None) {
+            tracing::warn!(error = %err, "failed to spill scan results to disk");
+        }
+        Ok(id)
+    }
+
+    /// All stored scans, oldest first.
+    pub fn list(&self) -> Result<Vec<Arc<StoredScan>>, String> {
+        let scans = self.scans.lock().map_err(|e| e.to_string())?;
+        Ok(scans.iter().cloned().collect())
+    }
+
+    /// Applies a change made on disk (e.g. a deletion) to a stored tree and keeps the
+    /// summary total in sync. Readers holding the previous version keep their copy.
+    pub fn update_tree<R>(
+        &self,
+        scan_id: &str,
+        change: impl FnOnce(&mut ScanTree) -> R,
+    ) -> Result<R, String> {
+        let mut scans = self.scans.lock().map_err(|e| e.to_string())?;
+        let scan = scans
+            .iter_mut()
+            .find(|s| s.id == scan_id)
+            .ok_or_else(|| format!("Unknown or expired scan: {}", scan_id))?;
+        let scan = Arc::make_mut(scan);
+        let result = change(&mut scan.tree);
+        scan.summary.total_bytes = scan.tree.size();
+        Ok(result)
+    }
+
+    /// Adds a folder the scan was denied, scanned since with `file_count` files and
+    /// `dir_count` folders, to the stored tree and its summary. Returns false when the
+    /// folder's parent is not part of the scan or already holds it.
+    pub fn attach(
+        &self,
+        scan_id: &str,
+        subtree: ScanTree,
+        file_count: u64,
+        dir_count: u64,
+    ) -> Result<bool, String> {
+        let mut scans = self.scans.lock().map_err(|e| e.to_string())?;
+        let index = scans
+            .iter()
+            .position(|s| s.id == scan_id)
+            .ok_or_else(|| format!("Unknown or expired scan: {}", scan_id))?;
+        let path = subtree.root_path().to_string();
+        if let Some(parent) = Path::new(&path).parent() {
+            load_path(&mut scans[index], &parent.to_string_lossy())?;
+        }
+        let scan = Arc::make_mut(&mut scans[index]);
+        if !scan.tree.attach(subtree) {
+            return Ok(false);
+        }
+        let summary = &mut scan.summary;
+        summary.total_bytes = scan.tree.size();
+        summary.file_count = summary.file_count.saturating_add(file_count);
+        summary.dir_count = summary.dir_count.saturating_add(dir_count);
+        if let Some(at) = summary.denied_dirs.iter().position(|d| *d == path) {
+            summary.denied_dirs.remove(at);
+            summary.skipped_entries = summary.skipped_entries.saturating_sub(1);
+        }
+        summary.skipped.retain(|skipped| skipped.path != path);
+        if let Err(err) = enforce_node_cap(&mut scans, None) {
+            tracing::warn!(error = %err, "failed to spill scan results to disk");
+        }
+        Ok(true)
+    }
+
+    /// Summary of the most recent stored scan of `root_path`, if any.
+    pub fn latest_summary(&self, root_path: &str) -> Option<ScanSummary> {
+        let scans = self.scans.lock().ok()?;
+        scans
+            .iter()
+            .rev()
+            .find(|s| !s.summary.partial && same_path(&s.summary.root_path, root_path))
+            .map(|s| s.summary.clone())
+    }
+
+    pub fn get(&self, scan_id: &str) -> Result<Arc<StoredScan>, String> {
+        let scans = self.scans.lock().map_err(|e| e.to_string())?;
+        scans
+            .iter()
+            .find(|s| s.id == scan_id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown or expired scan: {}", scan_id))
+    }
+
+    /// Like [`ScanStore::get`], but first loads back any spilled folder on the way to
+    /// `path`, so the node there and its contents are in memory.
+    pub fn get_for_path(
+        &self,
+        scan_id: &str,
+        path: Option<&str>,
+    ) -> Result<Arc<StoredScan>, String> {
+        let path = match path {
+            Some(path) => path,
+            None => return self.get(scan_id),
+        };
+        let mut scans = self.scans.lock().map_err(|e| e.to_string())?;
+        let index = scans
+            .iter()
+            .position(|s| s.id == scan_id)
+            .ok_or_else(|| format!("Unknown or expired scan: {}", scan_id))?;
+
+        let loaded = load_path(&mut scans[index], path)?;
+        if loaded {
+            enforce_node_cap(&mut scans, Some((scan_id, path)))?;
+        }
+        Ok(scans[index].clone())
+    }
+}
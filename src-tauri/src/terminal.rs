@@ -0,0 +1,90 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+#[cfg(target_os = "windows")]
+fn terminals(dir: &Path) -> Vec<Command> {
+    // Windows Terminal is the default console host on Windows 11; `start` gives cmd a
+    // console window of its own where it is not installed.
+    let mut wt = Command::new("wt.exe");
+    // wt splits its command line into subcommands at every unescaped `;`, paths included.
+    wt.arg("-d")
+        .arg(dir.as_os_str().to_string_lossy().replace(';', "\\;"));
+    let mut cmd = Command::new("cmd.exe");
+    cmd.args(["/C", "start", "", "cmd.exe", "/K"])
+        .current_dir(dir);
+    vec![wt, cmd]
+}
+
+#[cfg(target_os = "macos")]
+fn terminals(dir: &Path) -> Vec<Command> {
+    let mut apps = Vec::new();
+    // macOS has no default terminal setting; having installed iTerm says enough.
+    if Path::new("/Applications/iTerm.app").exists() {
+        apps.push("iTerm");
+    }
+    apps.push("Terminal");
+    apps.into_iter()
+        .map(|app| {
+            let mut open = Command::new("open");
+            open.arg("-a").arg(app).arg(dir);
+            open
+        })
+        .collect()
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn terminals(dir: &Path) -> Vec<Command> {
+    // x-terminal-emulator is Debian's pick of the default; $TERMINAL is the common
+    // convention elsewhere. All of these start their shell in their working directory.
+    let mut programs: Vec<String> = vec!["x-terminal-emulator".to_string()];
+    if let Ok(terminal) = std::env::var("TERMINAL") {
+        if !terminal.trim().is_empty() {
+            programs.push(terminal);
+        }
+    }
+    programs.extend(
+        [
+            "gnome-terminal",
+            "konsole",
+            "xfce4-terminal",
+            "kitty",
+            "alacritty",
+            "xterm",
+        ]
+        .map(String::from),
+    );
+    programs
+        .into_iter()
+        .map(|program| {
+            let mut terminal = Command::new(program);
+            terminal.current_dir(dir);
+            terminal
+        })
+        .collect()
+}
+
+/// Opens the platform's terminal in `path`, or in the folder holding it if it is a file.
+pub fn open_terminal(path: &str) -> Result<(), String> {
+    let target = PathBuf::from(path);
+    let dir = if target.is_dir() {
+        target.as_path()
+    } else if target.exists() {
+        target.parent().unwrap_or(&target)
+    } else {
+        return Err(format!("Path does not exist: {}", target.to_string_lossy()));
+    };
+    for mut terminal in terminals(dir) {
+        match terminal.spawn() {
+            Ok(_) => {
+                tracing::info!(path = %dir.display(), program = ?terminal.get_program(), "opened terminal");
+                return Ok(());
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to open a terminal: {}", e)),
+        }
+    }
+    Err("No terminal program was found.".to_string())
+}
@@ -0,0 +1,643 @@
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::SystemTime,
+};
+
+use crate::{
+    protected::ProtectedPaths,
+    scanner::{walk_files, FsNode},
+    store::{unix_secs, ScanStore},
+};
+
+// Undo only covers recent deletions; older ones are still in the system trash.
+const MAX_OPERATIONS: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashOperation {
+    pub operation_id: String,
+    pub trashed: Vec<String>,
+    pub failed: Vec<TrashFailure>,
+    pub freed_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReport {
+    pub operation_id: String,
+    pub restored: Vec<String>,
+    pub failed: Vec<TrashFailure>,
+    // Size of the restored items as measured when they were trashed.
+    pub restored_bytes: u64,
+}
+
+struct TrashedItem {
+    original: PathBuf,
+    // Where the item ended up, when the platform tells us at deletion time.
+    location: Option<PathBuf>,
+    // The subtree detached from the scan, re-attached on restore.
+    node: Option<FsNode>,
+    // Bytes measured on disk just before trashing.
+    size: u64,
+}
+
+struct RecordedOperation {
+    id: String,
+    scan_id: Option<String>,
+    trashed_at_secs: u64,
+    items: Vec<TrashedItem>,
+}
+
+/// Recent trash operations that `restore_from_trash` can undo, addressed by operation ID.
+#[derive(Default)]
+pub struct TrashLog {
+    operations: Mutex<VecDeque<RecordedOperation>>,
+    next_id: AtomicU64,
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::{
+        fs,
+        io::Write,
+        os::unix::fs::{DirBuilderExt, MetadataExt},
+        path::{Path, PathBuf},
+    };
+
+    fn home_trash() -> Result<PathBuf, String> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")))
+            .ok_or("HOME is not set.")?;
+        Ok(data_home.join("Trash"))
+    }
+
+    /// The home trash when `path` lives on the same device, otherwise
+    /// `$topdir/.Trash-$uid` on the item's own volume (freedesktop.org trash spec).
+    fn trash_dir_for(path: &Path, dev: u64) -> Result<PathBuf, String> {
+        let home = home_trash()?;
+        let home_dev = home
+            .ancestors()
+            .find_map(|dir| fs::metadata(dir).ok())
+            .map(|meta| meta.dev());
+        if home_dev == Some(dev) {
+            return Ok(home);
+        }
+        let mut top = path;
+        for dir in path.ancestors().skip(1) {
+            match fs::metadata(dir) {
+                Ok(meta) if meta.dev() == dev => top = dir,
+                _ => break,
+            }
+        }
+        // SAFETY: getuid has no preconditions and cannot fail.
+        let uid = unsafe { libc::getuid() };
+        Ok(top.join(format!(".Trash-{}", uid)))
+    }
+
+    fn percent_encode(path: &Path) -> String {
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut out = String::new();
+        for &b in path.as_os_str().as_bytes() {
+            if b.is_ascii_alphanumeric() || b"/-_.~".contains(&b) {
+                out.push(b as char);
+            } else {
+                out.push_str(&format!("%{:02X}", b));
+            }
+        }
+        out
+    }
+
+    fn percent_decode(value: &str) -> PathBuf {
+        use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+        let bytes = value.as_bytes();
+        let mut out = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok());
+            match (bytes[i], hex) {
+                (b'%', Some(byte)) => {
+                    out.push(byte);
+                    i += 3;
+                }
+                (byte, _) => {
+                    out.push(byte);
+                    i += 1;
+                }
+            }
+        }
+        PathBuf::from(OsString::from_vec(out))
+    }
+
+    /// `DeletionDate` is local time without a zone, e.g. `2024-05-01T09:30:12`.
+    fn deletion_date() -> String {
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        // SAFETY: time(NULL) has no preconditions; localtime_r only writes into `tm`.
+        unsafe {
+            let now = libc::time(std::ptr::null_mut());
+            libc::localtime_r(&now, &mut tm);
+        }
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            tm.tm_year + 1900,
+            tm.tm_mon + 1,
+            tm.tm_mday,
+            tm.tm_hour,
+            tm.tm_min,
+            tm.tm_sec
+        )
+    }
+
+    fn info_path(location: &Path) -> Option<PathBuf> {
+        let name = location.file_name()?;
+        let trash = location.parent()?.parent()?;
+        let mut info = name.to_os_string();
+        info.push(".trashinfo");
+        Some(trash.join("info").join(info))
+    }
+
+    pub(super) fn trash(path: &Path) -> Result<Option<PathBuf>, String> {
+        let meta = fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+        let trash = trash_dir_for(path, meta.dev())?;
+        for sub in ["files", "info"] {
+            fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(trash.join(sub))
+                .map_err(|e| format!("Failed to create {}: {}", trash.display(), e))?;
+        }
+
+        let name = path
+            .file_name()
+            .ok_or("Cannot move a filesystem root to the trash.")?
+            .to_string_lossy()
+            .into_owned();
+        // Claim a unique name by creating its .trashinfo exclusively, as the spec requires.
+        for n in 0..1000 {
+            let candidate = if n == 0 {
+                name.clone()
+            } else {
+                format!("{}.{}", name, n)
+            };
+            let location = trash.join("files").join(&candidate);
+            let info = trash.join("info").join(format!("{}.trashinfo", candidate));
+            let mut file = match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&info)
+            {
+                Ok(file) => file,
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(format!("Failed to write {}: {}", info.display(), e)),
+            };
+            let written = write!(
+                file,
+                "[Trash Info]\nPath={}\nDeletionDate={}\n",
+                percent_encode(path),
+                deletion_date()
+            );
+            if let Err(e) = written.and_then(|()| fs::rename(path, &location)) {
+                let _ = fs::remove_file(&info);
+                return Err(e.to_string());
+            }
+            return Ok(Some(location));
+        }
+        Err(format!("No free name in {}", trash.display()))
+    }
+
+    pub(super) fn restore(
+        original: &Path,
+        location: Option<&Path>,
+        _trashed_at_secs: u64,
+    ) -> Result<(), String> {
+        let location = location.ok_or("The trash location of this item is unknown.")?;
+        let info = info_path(location).ok_or("The trash location of this item is unknown.")?;
+        let contents = fs::read_to_string(&info)
+            .map_err(|_| "The item is no longer in the trash.".to_string())?;
+        let recorded = contents
+            .lines()
+            .find_map(|line| line.strip_prefix("Path="))
+            .map(percent_decode)
+            .ok_or_else(|| format!("Malformed trash info: {}", info.display()))?;
+        // Relative paths are relative to the volume holding the trash directory.
+        let recorded = match location.parent().and_then(Path::parent) {
+            Some(trash) if recorded.is_relative() => trash.parent().unwrap_or(trash).join(recorded),
+            _ => recorded,
+        };
+        if recorded != original {
+            return Err(format!(
+                "The trash entry now belongs to {}",
+                recorded.display()
+            ));
+        }
+        super::move_back(location, original)?;
+        let _ = fs::remove_file(&info);
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{
+        path::{Path, PathBuf},
+        process::Command,
+    };
+
+    /// Deletes through Finder so "Put Back" keeps working, and returns where the
+    /// item landed in the trash. The path is passed as an argument, never spliced
+    /// into the script.
+    pub(super) fn trash(path: &Path) -> Result<Option<PathBuf>, String> {
+        let output = Command::new("osascript")
+            .args([
+                "-e",
+                "on run argv",
+                "-e",
+                "tell application \"Finder\" to set trashed to delete (POSIX file (item 1 of argv) as alias)",
+                "-e",
+                "return POSIX path of (trashed as alias)",
+                "-e",
+                "end run",
+            ])
+            .arg(path)
+            .output()
+            .map_err(|e| format!("Failed to run osascript: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Finder could not move the item to the trash: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let location = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(Some(PathBuf::from(location.trim_end_matches('/'))))
+    }
+
+    pub(super) fn restore(
+        original: &Path,
+        location: Option<&Path>,
+        _trashed_at_secs: u64,
+    ) -> Result<(), String> {
+        let location = location.ok_or("The trash location of this item is unknown.")?;
+        if std::fs::symlink_metadata(location).is_err() {
+            return Err("The item is no longer in the trash.".to_string());
+        }
+        super::move_back(location, original)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::{
+        fs,
+        os::windows::ffi::OsStrExt,
+        path::{Component, Path, PathBuf},
+    };
+    use windows_sys::Win32::UI::Shell::{
+        SHFileOperationW, FOF_ALLOWUNDO, FOF_NOCONFIRMATION, FOF_NOERRORUI, FOF_SILENT,
+        FOF_WANTNUKEWARNING, FO_DELETE, SHFILEOPSTRUCTW,
+    };
+
+    // FILETIME ticks (100 ns since 1601) at the Unix epoch.
+    const FILETIME_UNIX_EPOCH: u64 = 116_444_736_000_000_000;
+
+    /// The Recycle Bin does not report where an item went; `restore` finds it through
+    /// the `$I` metadata files instead.
+    pub(super) fn trash(path: &Path) -> Result<Option<PathBuf>, String> {
+        let started_at_secs = crate::store::unix_secs(std::time::SystemTime::now());
+        // pFrom is a list of NUL-terminated paths ending with an extra NUL.
+        let from: Vec<u16> = path.as_os_str().encode_wide().chain([0, 0]).collect();
+        let mut op: SHFILEOPSTRUCTW = unsafe { std::mem::zeroed() };
+        op.wFunc = FO_DELETE as _;
+        op.pFrom = from.as_ptr();
+        // Without FOF_WANTNUKEWARNING, items too large for the Recycle Bin (or on a drive
+        // without one) are deleted for good and the call still succeeds. With it, Windows
+        // asks first, and declining aborts the operation.
+        op.fFlags =
+            (FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_SILENT | FOF_NOERRORUI | FOF_WANTNUKEWARNING)
+                as _;
+        // SAFETY: `op` is fully initialised and `from` outlives the call.
+        let rc = unsafe { SHFileOperationW(&mut op) };
+        if rc != 0 || op.fAnyOperationsAborted != 0 {
+            return Err(format!(
+                "The Recycle Bin refused the item (error {:#x}).",
+                rc
+            ));
+        }
+        // Confirming the warning deletes the item for good; it must not be logged as
+        // something `restore_from_trash` could bring back.
+        if find_record(path, started_at_secs).is_none() {
+            return Err(format!(
+                "{} was deleted permanently instead of going to the Recycle Bin.",
+                path.display()
+            ));
+        }
+        Ok(None)
+    }
+
+    /// Parses a `$I` record: version, size, deletion FILETIME, then the original path
+    /// (fixed 260 chars in version 1, length-prefixed in version 2).
+    fn read_record(path: &Path) -> Option<(u64, PathBuf)> {
+        let data = fs::read(path).ok()?;
+        let u64_at = |at: usize| Some(u64::from_le_bytes(data.get(at..at + 8)?.try_into().ok()?));
+        let (version, deleted) = (u64_at(0)?, u64_at(16)?);
+        let name = match version {
+            1 => data.get(24..24 + 520)?,
+            2 => {
+                let len = u32::from_le_bytes(data.get(24..28)?.try_into().ok()?) as usize;
+                data.get(28..28 + len * 2)?
+            }
+            _ => return None,
+        };
+        let wide: Vec<u16> = name
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|&c| c != 0)
+            .collect();
+        Some((deleted, PathBuf::from(String::from_utf16_lossy(&wide))))
+    }
+
+    /// The newest `$I` record for `original` written since `trashed_at_secs`.
+    fn find_record(original: &Path, trashed_at_secs: u64) -> Option<PathBuf> {
+        let root: PathBuf = original
+            .components()
+            .take_while(|c| matches!(c, Component::Prefix(_) | Component::RootDir))
+            .collect();
+        let bin = root.join("$Recycle.Bin");
+        let wanted = original.to_string_lossy().to_lowercase();
+        let since = trashed_at_secs.saturating_sub(60) * 10_000_000 + FILETIME_UNIX_EPOCH;
+
+        // Other users' SID folders are unreadable; only ours yields entries.
+        let mut newest: Option<(u64, PathBuf)> = None;
+        for dir in fs::read_dir(&bin).into_iter().flatten().flatten() {
+            for entry in fs::read_dir(dir.path()).into_iter().flatten().flatten() {
+                if !entry.file_name().to_string_lossy().starts_with("$I") {
+                    continue;
+                }
+                let Some((deleted, path)) = read_record(&entry.path()) else {
+                    continue;
+                };
+                if deleted >= since
+                    && path.to_string_lossy().to_lowercase() == wanted
+                    && newest.as_ref().map_or(true, |(t, _)| deleted > *t)
+                {
+                    newest = Some((deleted, entry.path()));
+                }
+            }
+        }
+        newest.map(|(_, record)| record)
+    }
+
+    pub(super) fn restore(
+        original: &Path,
+        _location: Option<&Path>,
+        trashed_at_secs: u64,
+    ) -> Result<(), String> {
+        let record = find_record(original, trashed_at_secs)
+            .ok_or("The item is no longer in the Recycle Bin.")?;
+        let name = record.file_name().unwrap_or_default().to_string_lossy();
+        let data = record.with_file_name(format!("$R{}", &name[2..]));
+        super::move_back(&data, original)?;
+        let _ = fs::remove_file(&record);
+        Ok(())
+    }
+}
+
+/// Puts a trashed item back, refusing to overwrite whatever now occupies its place.
+fn move_back(from: &Path, to: &Path) -> Result<(), String> {
+    if std::fs::symlink_metadata(to).is_ok() {
+        return Err(format!("{} already exists.", to.display()));
+    }
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::rename(from, to).map_err(|e| e.to_string())
+}
+
+/// Bytes of the files at or below `path`. Measured rather than taken from the scan,
+/// which may be missing, pruned or out of date.
+fn measure(path: &Path) -> u64 {
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => {
+            let mut bytes = 0u64;
+            walk_files(path, |_, meta| bytes = bytes.saturating_add(meta.len()));
+            bytes
+        }
+        Ok(meta) if meta.is_file() => meta.len(),
+        _ => 0,
+    }
+}
+
+fn validate(
+    path: &str,
+    protected: &ProtectedPaths,
+    allow_protected: bool,
+) -> Result<PathBuf, String> {
+    let target = PathBuf::from(path);
+    if !target.is_absolute() {
+        return Err(format!("Path must be absolute: {}", path));
+    }
+    if target.parent().is_none() {
+        return Err(format!("Refusing to trash a filesystem root: {}", path));
+    }
+    if std::fs::symlink_metadata(&target).is_err() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+    if !allow_protected {
+        protected.check(&target)?;
+    }
+    Ok(target)
+}
+
+impl TrashLog {
+    fn record(&self, operation: RecordedOperation) -> Result<(), String> {
+        let mut operations = self.operations.lock().map_err(|e| e.to_string())?;
+        operations.push_back(operation);
+        while operations.len() > MAX_OPERATIONS {
+            operations.pop_front();
+        }
+        Ok(())
+    }
+
+    fn take(&self, operation_id: &str) -> Result<RecordedOperation, String> {
+        let mut operations = self.operations.lock().map_err(|e| e.to_string())?;
+        let index = operations
+            .iter()
+            .position(|op| op.id == operation_id)
+            .ok_or_else(|| format!("Unknown or expired trash operation: {}", operation_id))?;
+        Ok(operations.remove(index).expect("index is in bounds"))
+    }
+}
+
+/// Moves `paths` to the system trash and, when `scan_id` is given, drops them from that
+/// scan's tree. The returned operation ID can be handed to `restore_from_trash`.
+/// Protected paths are refused unless `allow_protected` is set.
+pub async fn move_to_trash(
+    store: &ScanStore,
+    log: &TrashLog,
+    protected: &ProtectedPaths,
+    scan_id: Option<String>,
+    paths: Vec<String>,
+    allow_protected: bool,
+) -> Result<TrashOperation, String> {
+    let targets = paths
+        .iter()
+        .map(|path| validate(path, protected, allow_protected))
+        .collect::<Result<Vec<_>, _>>()?;
+    if allow_protected {
+        tracing::warn!(paths = ?paths, "protected-path guard overridden");
+    }
+    let trashed_at_secs = unix_secs(SystemTime::now());
+    let results = tauri::async_runtime::spawn_blocking(move || {
+        targets
+            .into_iter()
+            .map(|target| {
+                let size = measure(&target);
+                let result = platform::trash(&target);
+                (target, size, result)
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let seq = log.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+    let mut report = TrashOperation {
+        operation_id: format!("trash-{}-{}", trashed_at_secs, seq),
+        trashed: vec![],
+        failed: vec![],
+        freed_bytes: 0,
+    };
+    let mut items = vec![];
+    for (target, size, result) in results {
+        let path = target.to_string_lossy().into_owned();
+        let location = match result {
+            Ok(location) => location,
+            Err(error) => {
+                tracing::warn!(path = %path, error = %error, "failed to move to trash");
+                report.failed.push(TrashFailure { path, error });
+                continue;
+            }
+        };
+        let node = match &scan_id {
+            Some(scan_id) => store
+                .get_for_path(scan_id, Some(&path))
+                .and_then(|_| store.update_tree(scan_id, |tree| tree.remove_descendant(&target)))
+                .unwrap_or_else(|err| {
+                    tracing::warn!(error = %err, "trashed item not removed from scan");
+                    None
+                }),
+            None => None,
+        };
+        report.freed_bytes += size;
+        report.trashed.push(path);
+        items.push(TrashedItem {
+            original: target,
+            location,
+            node,
+            size,
+        });
+    }
+
+    tracing::info!(
+        operation_id = %report.operation_id,
+        trashed = report.trashed.len(),
+        failed = report.failed.len(),
+        "moved items to trash"
+    );
+    if !items.is_empty() {
+        log.record(RecordedOperation {
+            id: report.operation_id.clone(),
+            scan_id,
+            trashed_at_secs,
+            items,
+        })?;
+    }
+    Ok(report)
+}
+
+/// Puts back every item of a trash operation and re-attaches it to the scan it was
+/// removed from. Items that could not be restored stay available for another attempt.
+pub async fn restore_from_trash(
+    store: &ScanStore,
+    log: &TrashLog,
+    operation_id: String,
+) -> Result<RestoreReport, String> {
+    let operation = log.take(&operation_id)?;
+    let trashed_at_secs = operation.trashed_at_secs;
+    let (operation, results) = tauri::async_runtime::spawn_blocking(move || {
+        let results = operation
+            .items
+            .iter()
+            .map(|item| {
+                platform::restore(&item.original, item.location.as_deref(), trashed_at_secs)
+            })
+            .collect::<Vec<_>>();
+        (operation, results)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut report = RestoreReport {
+        operation_id,
+        restored: vec![],
+        failed: vec![],
+        restored_bytes: 0,
+    };
+    let mut remaining = vec![];
+    for (item, result) in operation.items.into_iter().zip(results) {
+        let path = item.original.to_string_lossy().into_owned();
+        if let Err(error) = result {
+            tracing::warn!(path = %path, error = %error, "failed to restore from trash");
+            report.failed.push(TrashFailure { path, error });
+            remaining.push(item);
+            continue;
+        }
+        report.restored_bytes += item.size;
+        if let (Some(scan_id), Some(node)) = (&operation.scan_id, item.node) {
+            let parent = item
+                .original
+                .parent()
+                .map(|p| p.to_string_lossy().into_owned());
+            let inserted = store
+                .get_for_path(scan_id, parent.as_deref())
+                .and_then(|_| store.update_tree(scan_id, |tree| tree.insert_descendant(node)));
+            if !matches!(inserted, Ok(true)) {
+                tracing::warn!(path = %path, "restored item not re-added to scan");
+            }
+        }
+        report.restored.push(path);
+    }
+
+    tracing::info!(
+        operation_id = %report.operation_id,
+        restored = report.restored.len(),
+        failed = report.failed.len(),
+        "restored items from trash"
+    );
+    if !remaining.is_empty() {
+        log.record(RecordedOperation {
+            id: report.operation_id.clone(),
+            scan_id: operation.scan_id,
+            trashed_at_secs,
+            items: remaining,
+        })?;
+    }
+    Ok(report)
+}
@@ -0,0 +1,188 @@
+use serde::Serialize;
+use std::{
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+};
+use tauri::{
+    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent},
+    Emitter, Manager, WindowEvent,
+};
+
+use crate::{
+    fileinfo::format_bytes,
+    launch,
+    store::ScanStore,
+    volumes::{list_volumes_blocking, space_for_path, system_root},
+};
+
+const TRAY_ID: &str = "main";
+const OPEN_SCAN_EVENT: &str = "open_scan";
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+const MENU_SHOW: &str = "show";
+const MENU_QUIT: &str = "quit";
+// Menu ids carry their argument after the prefix.
+const MENU_SCAN_PREFIX: &str = "scan:";
+const MENU_OPEN_PREFIX: &str = "open:";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenScanPayload {
+    scan_id: String,
+}
+
+/// Whether closing the main window hides it to the tray (keeping the monitor, watcher
+/// and API running) instead of quitting.
+#[derive(Default)]
+pub struct CloseToTray(AtomicBool);
+
+impl CloseToTray {
+    pub fn set(&self, enabled: bool) {
+        self.0.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+fn tooltip() -> String {
+    let root = system_root();
+    match space_for_path(Path::new(&root)) {
+        Ok(space) => format!(
+            "DiskCheck\n{} {} free of {}",
+            root,
+            format_bytes(space.available_bytes),
+            format_bytes(space.total_bytes)
+        ),
+        Err(_) => "DiskCheck".to_string(),
+    }
+}
+
+fn build_menu(app: &tauri::AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let show = MenuItem::with_id(app, MENU_SHOW, "Show DiskCheck", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, MENU_QUIT, "Quit", true, None::<&str>)?;
+
+    let recent = Submenu::new(app, "Recent scans", true)?;
+    let scans = app.state::<ScanStore>().list().unwrap_or_default();
+    if scans.is_empty() {
+        recent.append(&MenuItem::new(app, "No scans yet", false, None::<&str>)?)?;
+    }
+    for scan in scans.iter().rev() {
+        let label = format!(
+            "{} ({})",
+            scan.summary.root_path,
+            format_bytes(scan.summary.total_bytes)
+        );
+        recent.append(&MenuItem::with_id(
+            app,
+            format!("{}{}", MENU_OPEN_PREFIX, scan.id),
+            label,
+            true,
+            None::<&str>,
+        )?)?;
+    }
+
+    let quick = Submenu::new(app, "Quick scan", true)?;
+    for volume in list_volumes_blocking()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|v| !v.is_network)
+    {
+        let label = format!(
+            "{} ({} free)",
+            volume.mount_point,
+            format_bytes(volume.free_bytes)
+        );
+        quick.append(&MenuItem::with_id(
+            app,
+            format!("{}{}", MENU_SCAN_PREFIX, volume.mount_point),
+            label,
+            true,
+            None::<&str>,
+        )?)?;
+    }
+
+    Menu::with_items(
+        app,
+        &[
+            &show,
+            &PredefinedMenuItem::separator(app)?,
+            &recent,
+            &quick,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )
+}
+
+fn refresh(app: &tauri::AppHandle, tray: &TrayIcon) {
+    let _ = tray.set_tooltip(Some(tooltip()));
+    if let Ok(menu) = build_menu(app) {
+        let _ = tray.set_menu(Some(menu));
+    }
+}
+
+fn on_menu_event(app: &tauri::AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+    if id == MENU_SHOW {
+        launch::focus_main_window(app);
+    } else if id == MENU_QUIT {
+        app.exit(0);
+    } else if let Some(path) = id.strip_prefix(MENU_SCAN_PREFIX) {
+        launch::request_scan(app, path.to_string());
+    } else if let Some(scan_id) = id.strip_prefix(MENU_OPEN_PREFIX) {
+        launch::focus_main_window(app);
+        let _ = app.emit_to(
+            launch::MAIN_WINDOW,
+            OPEN_SCAN_EVENT,
+            OpenScanPayload {
+                scan_id: scan_id.to_string(),
+            },
+        );
+    }
+}
+
+/// Creates the tray icon and keeps its free-space tooltip and menus current.
+pub fn init(app: &tauri::AppHandle) -> tauri::Result<()> {
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(tooltip())
+        .menu(&build_menu(app)?)
+        .show_menu_on_left_click(false)
+        .on_menu_event(on_menu_event)
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                launch::focus_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    let tray = builder.build(app)?;
+
+    let app = app.clone();
+    let _ = thread::Builder::new()
+        .name("tray-refresh".to_string())
+        .spawn(move || loop {
+            thread::sleep(REFRESH_INTERVAL);
+            refresh(&app, &tray);
+        });
+    Ok(())
+}
+
+pub fn handle_window_event(window: &tauri::Window, event: &WindowEvent) {
+    if let WindowEvent::CloseRequested { api, .. } = event {
+        // Extra scan windows close as usual.
+        if window.label() == launch::MAIN_WINDOW && window.state::<CloseToTray>().get() {
+            api.prevent_close();
+            let _ = window.hide();
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::fmt::Write;
+
+use crate::{
+    fileinfo::format_bytes,
+    scanner::{FsNodeKind, NodeRef},
+};
+
+// Same palette and hashing as the frontend Treemap so exported images match the app.
+const PALETTE: [&str; 10] = [
+    "#60A5FA", "#34D399", "#FBBF24", "#F472B6", "#A78BFA", "#FB7185", "#22D3EE", "#F97316",
+    "#4ADE80", "#E879F9",
+];
+const DIRECTORY_FILL: &str = "#1F2937";
+const BACKGROUND_FILL: &str = "#111827";
+// Rectangles smaller than this are not subdivided or drawn.
+const MIN_RECT_PX: f64 = 2.0;
+const LABEL_MIN_WIDTH: f64 = 80.0;
+const LABEL_MIN_HEIGHT: f64 = 20.0;
+// Keeps exported SVGs of huge trees to a size browsers can still render.
+const MAX_RECTS: usize = 20_000;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+impl Rect {
+    fn inset(self, by: f64) -> Rect {
+        Rect {
+            x: self.x + by,
+            y: self.y + by,
+            w: (self.w - 2.0 * by).max(0.0),
+            h: (self.h - 2.0 * by).max(0.0),
+        }
+    }
+
+    fn is_visible(&self) -> bool {
+        self.w >= MIN_RECT_PX && self.h >= MIN_RECT_PX
+    }
+}
+
+pub(crate) struct TreemapTile<'a> {
+    pub node: NodeRef<'a>,
+    pub rect: Rect,
+}
+
+fn hash_string(input: &str) -> u32 {
+    input.encode_utf16().fold(0u32, |hash, unit| {
+        hash.wrapping_mul(31).wrapping_add(u32::from(unit))
+    })
+}
+
+pub(crate) fn color_for_node(node: NodeRef<'_>) -> &'static str {
+    match node.kind() {
+        FsNodeKind::File => {
+            let extension = node.extension();
+            let key = extension.as_deref().unwrap_or("<none>");
+            PALETTE[hash_string(key) as usize % PALETTE.len()]
+        }
+        _ => DIRECTORY_FILL,
+    }
+}
+
+/// Worst aspect ratio of a row of `areas` laid along a side of length `side`.
+fn worst_ratio(sum: f64, min: f64, max: f64, side: f64) -> f64 {
+    let side2 = side * side;
+    let sum2 = sum * sum;
+    (side2 * max / sum2).max(sum2 / (side2 * min))
+}
+
+/// Squarified layout (Bruls et al.) of `values`, which must be sorted largest first.
+fn squarify(values: &[f64], bounds: Rect) -> Vec<Rect> {
+    let total: f64 = values.iter().sum();
+    let mut rects = Vec::with_capacity(values.len());
+    if total <= 0.0 || bounds.w <= 0.0 || bounds.h <= 0.0 {
+        return rects;
+    }
+
+    let scale = bounds.w * bounds.h / total;
+    let areas: Vec<f64> = values.iter().map(|v| v * scale).collect();
+    let mut free = bounds;
+    let mut start = 0;
+
+    while start < areas.len() {
+        let side = free.w.min(free.h);
+        let mut end = start + 1;
+        let mut sum = areas[start];
+        let mut min = areas[start];
+        let mut max = areas[start];
+        while end < areas.len() {
+            let a = areas[end];
+            let next = worst_ratio(sum + a, min.min(a), max.max(a), side);
+            if next > worst_ratio(sum, min, max, side) {
+                break;
+            }
+            sum += a;
+            min = min.min(a);
+            max = max.max(a);
+            end += 1;
+        }
+
+        // Lay the row along the shorter side, then shrink the free space.
+        if free.w >= free.h {
+            let width = if free.h > 0.0 { sum / free.h } else { 0.0 };
+            let mut y = free.y;
+            for &a in &areas[start..end] {
+                let h = if width > 0.0 { a / width } else { 0.0 };
+                rects.push(Rect {
+                    x: free.x,
+                    y,
+                    w: width,
+                    h,
+                });
+                y += h;
+            }
+            free.x += width;
+            free.w = (free.w - width).max(0.0);
+        } else {
+            let height = if free.w > 0.0 { sum / free.w } else { 0.0 };
+            let mut x = free.x;
+            for &a in &areas[start..end] {
+                let w = if height > 0.0 { a / height } else { 0.0 };
+                rects.push(Rect {
+                    x,
+                    y: free.y,
+                    w,
+                    h: height,
+                });
+                x += w;
+            }
+            free.y += height;
+            free.h = (free.h - height).max(0.0);
+        }
+        start = end;
+    }
+    rects
+}
+
+/// Lays out `root` into `bounds`. Directories are returned before their contents so
+/// they can be painted as backgrounds; children are expected to be sorted by size.
+pub(crate) fn layout(root: NodeRef<'_>, bounds: Rect) -> Vec<TreemapTile<'_>> {
+    let mut tiles = vec![];
+    let mut pending: Vec<(NodeRef, Rect)> = vec![(root, bounds)];
+
+    while let Some((node, rect)) = pending.pop() {
+        if tiles.len() >= MAX_RECTS {
+            break;
+        }
+        tiles.push(TreemapTile { node, rect });
+        if !node.has_children() {
+            continue;
+        }
+
+        let children: Vec<NodeRef> = node.children().collect();
+        let values: Vec<f64> = children.iter().map(|c| c.size() as f64).collect();
+        // 1px gap between siblings, like the frontend's paddingInner(1).
+        let child_rects = squarify(&values, rect.inset(0.5));
+        for (child, child_rect) in children.into_iter().zip(child_rects).rev() {
+            let child_rect = child_rect.inset(0.5);
+            if child.size() > 0 && child_rect.is_visible() {
+                pending.push((child, child_rect));
+            }
+        }
+    }
+    tiles
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Renders the tree as a standalone SVG document of `width` x `height` pixels.
+pub(crate) fn render_svg(root: NodeRef<'_>, width: u32, height: u32) -> String {
+    let bounds = Rect {
+        x: 0.0,
+        y: 0.0,
+        w: f64::from(width),
+        h: f64::from(height),
+    };
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="system-ui, sans-serif" font-size="11">"#,
+        w = width,
+        h = height
+    );
+    let _ = write!(
+        svg,
+        r#"<rect width="{}" height="{}" fill="{}"/>"#,
+        width, height, BACKGROUND_FILL
+    );
+
+    for tile in layout(root, bounds) {
+        let Rect { x, y, w, h } = tile.rect;
+        let _ = write!(
+            svg,
+            r#"<g><title>{} ({})</title><rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="2" fill="{}"/>"#,
+            escape_xml(&tile.node.path()),
+            format_bytes(tile.node.size()),
+            x,
+            y,
+            w,
+            h,
+            color_for_node(tile.node)
+        );
+        if matches!(tile.node.kind(), FsNodeKind::File)
+            && w >= LABEL_MIN_WIDTH
+            && h >= LABEL_MIN_HEIGHT
+        {
+            // Clip labels to their tile rather than measuring text.
+            let _ = write!(
+                svg,
+                r##"<svg x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}"><text x="4" y="13" fill="#FFFFFF" fill-opacity="0.9">{}</text><text x="4" y="26" fill="#FFFFFF" fill-opacity="0.7" font-family="monospace">{}</text></svg>"##,
+                x,
+                y,
+                w,
+                h,
+                escape_xml(tile.node.name()),
+                format_bytes(tile.node.size())
+            );
+        }
+        svg.push_str("</g>");
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
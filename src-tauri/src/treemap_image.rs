@@ -0,0 +1,80 @@
+use resvg::{tiny_skia, usvg};
+use serde::Deserialize;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    export::create_dest,
+    store::{ScanStore, StoredScan},
+    treemap::render_svg,
+};
+
+const DEFAULT_WIDTH: u32 = 1600;
+const DEFAULT_HEIGHT: u32 = 1000;
+// Beyond this the PNG buffer alone runs into hundreds of megabytes.
+const MAX_DIMENSION: u32 = 8192;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+fn rasterize(svg: &str, width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let mut options = usvg::Options::default();
+    // Labels need real fonts; without them text is silently dropped.
+    options.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|e| e.to_string())?;
+    let mut pixmap =
+        tiny_skia::Pixmap::new(width, height).ok_or_else(|| "Invalid image size.".to_string())?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|e| e.to_string())
+}
+
+fn export_blocking(
+    scan: Arc<StoredScan>,
+    path: Option<String>,
+    format: ImageFormat,
+    dest: &Path,
+    width: u32,
+    height: u32,
+) -> Result<(), String> {
+    let scan = scan.complete()?;
+    let node = scan.node(path.as_deref())?;
+    let svg = render_svg(node, width, height);
+    let bytes = match format {
+        ImageFormat::Svg => svg.into_bytes(),
+        ImageFormat::Png => rasterize(&svg, width, height)?,
+    };
+
+    let mut out = create_dest(dest)?;
+    out.write_all(&bytes)
+        .and_then(|()| out.flush())
+        .map_err(|e| format!("Failed to write {}: {}", dest.to_string_lossy(), e))
+}
+
+/// Renders the treemap of `path` (the folder currently shown; the scan root when
+/// omitted) with the backend layout and writes it as SVG or PNG.
+pub async fn export_treemap_image(
+    store: &ScanStore,
+    scan_id: String,
+    path: Option<String>,
+    format: ImageFormat,
+    dest: String,
+    width: Option<u32>,
+    height: Option<u32>,
+) -> Result<(), String> {
+    let scan = store.get(&scan_id)?;
+    let width = width.unwrap_or(DEFAULT_WIDTH).clamp(1, MAX_DIMENSION);
+    let height = height.unwrap_or(DEFAULT_HEIGHT).clamp(1, MAX_DIMENSION);
+    let dest = PathBuf::from(dest);
+    tauri::async_runtime::spawn_blocking(move || {
+        export_blocking(scan, path, format, &dest, width, height)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
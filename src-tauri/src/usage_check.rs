@@ -0,0 +1,182 @@
+use serde::Serialize;
+use std::path::Path;
+
+use crate::{
+    cloud_sync::sync_folders,
+    reserved::{reserved_space_blocking, ReservedKind},
+    scanner::ScanTree,
+    store::ScanSummary,
+    volumes::{volume_for_path, VolumeInfo},
+};
+
+// Differences below this share of the volume are filesystem metadata and cluster
+// rounding, not worth explaining.
+const TOLERANCE_RATIO: f64 = 0.01;
+const MIN_TOLERANCE_BYTES: u64 = 512 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiscrepancyKind {
+    // Folders the scan could not read; their contents are on the volume but not counted.
+    Permissions,
+    // Paging, hibernation and swap files the scan could not see, and shadow copies.
+    ReservedSpace,
+    // A file with several links is counted once per link but stored once.
+    Hardlinks,
+    // Sync clients' placeholders are counted at full size but take next to nothing.
+    CloudPlaceholders,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscrepancyCause {
+    pub kind: DiscrepancyKind,
+    // What the cause accounts for at most, when it can be told.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    pub detail: String,
+}
+
+/// How the total of a scan of a volume root compares with the used space the OS
+/// reports for the volume, and what likely explains the gap.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageCheck {
+    pub mount_point: String,
+    pub volume_used_bytes: u64,
+    pub scanned_bytes: u64,
+    // Used space minus the scan total: positive when the scan found less than the OS.
+    pub difference_bytes: i64,
+    // Within 1% of the volume (at least 512 MiB); no causes are listed then.
+    pub within_tolerance: bool,
+    // Likeliest first.
+    pub causes: Vec<DiscrepancyCause>,
+}
+
+fn is_volume_root(root: &Path, volume: &VolumeInfo) -> bool {
+    #[cfg(unix)]
+    let root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let trim = |p: &str| p.trim_end_matches(['/', '\\']).to_ascii_lowercase();
+    trim(&root.to_string_lossy()) == trim(&volume.mount_point)
+}
+
+/// Reserved system files the scan did not count, and space held outside any file.
+fn reserved_cause(tree: &ScanTree, mount_point: &str) -> Option<DiscrepancyCause> {
+    let report = reserved_space_blocking(Some(mount_point.to_string()))
+        .ok()?
+        .into_iter()
+        .next()?;
+    let mut bytes = 0u64;
+    let mut paths = vec![];
+    for item in report.items {
+        match (&item.kind, &item.path) {
+            // Free blocks kept back for the superuser: not part of the used figure.
+            (ReservedKind::FilesystemReserved, _) => continue,
+            // Counted by the scan like any file.
+            (_, Some(path)) if tree.find(path).is_some() => continue,
+            (_, path) => {
+                bytes = bytes.saturating_add(item.bytes);
+                paths.extend(path.clone());
+            }
+        }
+    }
+    (bytes > 0).then(|| DiscrepancyCause {
+        kind: DiscrepancyKind::ReservedSpace,
+        bytes: Some(bytes),
+        paths,
+        detail: "System files and shadow copies the scan could not read take up space.".to_string(),
+    })
+}
+
+fn permissions_cause(summary: &ScanSummary) -> Option<DiscrepancyCause> {
+    (summary.skipped_entries > 0).then(|| DiscrepancyCause {
+        kind: DiscrepancyKind::Permissions,
+        bytes: None,
+        paths: summary.denied_dirs.iter().take(20).cloned().collect(),
+        detail: format!(
+            "{} entries could not be read; rescanning with administrator rights counts them.",
+            summary.skipped_entries
+        ),
+    })
+}
+
+/// Sync folders on the volume, with their scanned size as the most placeholders can
+/// account for.
+fn cloud_cause(app: &tauri::AppHandle, tree: &ScanTree) -> Option<DiscrepancyCause> {
+    let mut bytes = 0u64;
+    let mut paths = vec![];
+    for (_, folder) in sync_folders(app) {
+        let path = folder.to_string_lossy();
+        if let Some(node) = tree.find(&path) {
+            bytes = bytes.saturating_add(node.size());
+            paths.push(path.into_owned());
+        }
+    }
+    (!paths.is_empty()).then(|| DiscrepancyCause {
+        kind: DiscrepancyKind::CloudPlaceholders,
+        bytes: Some(bytes),
+        paths,
+        detail: "Files kept only in the cloud count at their full size here but use no space."
+            .to_string(),
+    })
+}
+
+fn hardlinks_cause() -> DiscrepancyCause {
+    let detail = if cfg!(windows) {
+        "Hard-linked files, like those in C:\\Windows\\WinSxS, are counted once per link."
+    } else {
+        "Files with several hard links are counted once per link but stored once."
+    };
+    DiscrepancyCause {
+        kind: DiscrepancyKind::Hardlinks,
+        bytes: None,
+        paths: vec![],
+        detail: detail.to_string(),
+    }
+}
+
+/// Compares a finished scan with its volume's used space; `None` unless `root` is the
+/// root of a mounted volume, where the two are expected to match.
+pub(crate) fn check(
+    app: &tauri::AppHandle,
+    root: &Path,
+    tree: &ScanTree,
+    summary: &ScanSummary,
+) -> Option<UsageCheck> {
+    let volume = volume_for_path(root).filter(|volume| is_volume_root(root, volume))?;
+    let scanned_bytes = summary.total_bytes;
+    let difference = i128::from(volume.used_bytes) - i128::from(scanned_bytes);
+    let difference_bytes = difference.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64;
+    let tolerance = ((volume.total_bytes as f64 * TOLERANCE_RATIO) as u64).max(MIN_TOLERANCE_BYTES);
+    let within_tolerance = difference_bytes.unsigned_abs() <= tolerance;
+
+    let mut causes = vec![];
+    if !within_tolerance && difference_bytes > 0 {
+        causes.extend(permissions_cause(summary));
+        if !volume.is_network {
+            causes.extend(reserved_cause(tree, &volume.mount_point));
+        }
+    } else if !within_tolerance {
+        causes.extend(cloud_cause(app, tree));
+        causes.push(hardlinks_cause());
+    }
+    tracing::info!(
+        path = %root.display(),
+        volume_used_bytes = volume.used_bytes,
+        scanned_bytes,
+        difference_bytes,
+        causes = causes.len(),
+        "scan total checked against volume usage"
+    );
+
+    Some(UsageCheck {
+        mount_point: volume.mount_point,
+        volume_used_bytes: volume.used_bytes,
+        scanned_bytes,
+        difference_bytes,
+        within_tolerance,
+        causes,
+    })
+}
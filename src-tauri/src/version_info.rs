@@ -0,0 +1,268 @@
+use serde::Serialize;
+use std::{fs, path::PathBuf};
+
+use crate::cloud_sync::is_cloud_only;
+
+/// Who made a binary, from its version resource (`.exe`, `.dll`, ...) or, for
+/// installer packages, the `.msi` Property table.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutableVersion {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_version: Option<String>,
+    // Manufacturer for `.msi` packages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub company_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<String>,
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::ExecutableVersion;
+    use crate::volumes::{from_wide, to_wide};
+    use std::{
+        ffi::{c_void, OsStr},
+        path::Path,
+        ptr::null_mut,
+    };
+    use windows_sys::Win32::{
+        Foundation::{ERROR_MORE_DATA, ERROR_SUCCESS},
+        Storage::FileSystem::{
+            GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW, VS_FIXEDFILEINFO,
+        },
+        System::ApplicationInstallationAndServicing::{
+            MsiCloseHandle, MsiDatabaseOpenViewW, MsiOpenDatabaseW, MsiRecordGetStringW,
+            MsiViewExecute, MsiViewFetch, MSIDBOPEN_READONLY, MSIHANDLE,
+        },
+    };
+
+    // Tried when the resource lists no translation: US English, Unicode then Western.
+    const FALLBACK_TRANSLATIONS: [(u16, u16); 2] = [(0x0409, 0x04b0), (0x0409, 0x04e4)];
+
+    /// Pointer into `block` and length of a `VerQueryValueW` value: bytes for binary
+    /// values, characters for strings.
+    fn query_raw(block: &[u8], sub_block: &str) -> Option<(*const c_void, usize)> {
+        let key = to_wide(OsStr::new(sub_block));
+        let mut value = null_mut();
+        let mut len: u32 = 0;
+        // SAFETY: `block` was filled by GetFileVersionInfoW and `key` is NUL-terminated;
+        // the returned pointer points into `block`.
+        let found =
+            unsafe { VerQueryValueW(block.as_ptr().cast(), key.as_ptr(), &mut value, &mut len) };
+        (found != 0 && !value.is_null()).then_some((value as *const c_void, len as usize))
+    }
+
+    fn query(block: &[u8], sub_block: &str) -> Option<&[u8]> {
+        let (value, len) = query_raw(block, sub_block)?;
+        // SAFETY: binary values are `len` bytes inside `block`, which outlives the slice.
+        Some(unsafe { std::slice::from_raw_parts(value.cast::<u8>(), len) })
+    }
+
+    fn query_string(block: &[u8], (lang, code_page): (u16, u16), name: &str) -> Option<String> {
+        let key = format!("\\StringFileInfo\\{:04x}{:04x}\\{}", lang, code_page, name);
+        let (value, len) = query_raw(block, &key)?;
+        // SAFETY: string values are `len` UTF-16 units inside `block`.
+        let chars = unsafe { std::slice::from_raw_parts(value.cast::<u16>(), len) };
+        Some(from_wide(chars).trim().to_string()).filter(|s| !s.is_empty())
+    }
+
+    fn resource_version(path: &Path) -> Result<ExecutableVersion, String> {
+        let wide = to_wide(path.as_os_str());
+        // SAFETY: `wide` is NUL-terminated; the handle argument is ignored.
+        let size = unsafe { GetFileVersionInfoSizeW(wide.as_ptr(), null_mut()) };
+        if size == 0 {
+            return Err(format!(
+                "{} has no version information.",
+                path.to_string_lossy()
+            ));
+        }
+        let mut block = vec![0u8; size as usize];
+        // SAFETY: `block` holds the `size` bytes asked for.
+        let ok = unsafe { GetFileVersionInfoW(wide.as_ptr(), 0, size, block.as_mut_ptr().cast()) };
+        if ok == 0 {
+            return Err(format!(
+                "Failed to read version information of {}: {}",
+                path.to_string_lossy(),
+                std::io::Error::last_os_error()
+            ));
+        }
+
+        let mut translations: Vec<(u16, u16)> = query(&block, "\\VarFileInfo\\Translation")
+            .map(|raw| {
+                raw.chunks_exact(4)
+                    .map(|c| {
+                        (
+                            u16::from_le_bytes([c[0], c[1]]),
+                            u16::from_le_bytes([c[2], c[3]]),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        translations.extend(FALLBACK_TRANSLATIONS);
+        let string = |name: &str| {
+            translations
+                .iter()
+                .find_map(|t| query_string(&block, *t, name))
+        };
+
+        // The numeric version, for binaries whose strings leave it out.
+        let fixed_version = query(&block, "\\")
+            .filter(|raw| raw.len() >= std::mem::size_of::<VS_FIXEDFILEINFO>())
+            .map(|raw| {
+                // SAFETY: the root block is a VS_FIXEDFILEINFO, checked for size above.
+                let info =
+                    unsafe { std::ptr::read_unaligned(raw.as_ptr().cast::<VS_FIXEDFILEINFO>()) };
+                format!(
+                    "{}.{}.{}.{}",
+                    info.dwFileVersionMS >> 16,
+                    info.dwFileVersionMS & 0xffff,
+                    info.dwFileVersionLS >> 16,
+                    info.dwFileVersionLS & 0xffff
+                )
+            });
+
+        Ok(ExecutableVersion {
+            path: path.to_string_lossy().into_owned(),
+            product_name: string("ProductName"),
+            product_version: string("ProductVersion"),
+            file_version: string("FileVersion").or(fixed_version),
+            company_name: string("CompanyName"),
+            file_description: string("FileDescription"),
+            copyright: string("LegalCopyright"),
+        })
+    }
+
+    /// Closes an MSI handle when dropped.
+    struct MsiHandle(MSIHANDLE);
+
+    impl Drop for MsiHandle {
+        fn drop(&mut self) {
+            // SAFETY: the handle was opened by the Msi* call that created it.
+            unsafe { MsiCloseHandle(self.0) };
+        }
+    }
+
+    fn record_string(record: &MsiHandle, field: u32) -> Option<String> {
+        let mut buf = vec![0u16; 256];
+        loop {
+            let mut len = buf.len() as u32;
+            // SAFETY: `buf` holds `len` characters; on ERROR_MORE_DATA `len` is the
+            // length needed, without the terminating NUL.
+            let rc = unsafe { MsiRecordGetStringW(record.0, field, buf.as_mut_ptr(), &mut len) };
+            match rc {
+                ERROR_SUCCESS => return Some(from_wide(&buf)),
+                ERROR_MORE_DATA => buf.resize(len as usize + 1, 0),
+                _ => return None,
+            }
+        }
+    }
+
+    fn package_version(path: &Path) -> Result<ExecutableVersion, String> {
+        let err = |what: &str, rc: u32| {
+            format!(
+                "Failed to {} {}: {}",
+                what,
+                path.to_string_lossy(),
+                std::io::Error::from_raw_os_error(rc as i32)
+            )
+        };
+        let wide = to_wide(path.as_os_str());
+        let mut database: MSIHANDLE = 0;
+        // SAFETY: `wide` is NUL-terminated and `database` is a valid out pointer.
+        let rc = unsafe { MsiOpenDatabaseW(wide.as_ptr(), MSIDBOPEN_READONLY, &mut database) };
+        if rc != ERROR_SUCCESS {
+            return Err(err("open", rc));
+        }
+        let database = MsiHandle(database);
+
+        let query = to_wide(OsStr::new("SELECT `Property`, `Value` FROM `Property`"));
+        let mut view: MSIHANDLE = 0;
+        // SAFETY: as above.
+        let rc = unsafe { MsiDatabaseOpenViewW(database.0, query.as_ptr(), &mut view) };
+        if rc != ERROR_SUCCESS {
+            return Err(err("query", rc));
+        }
+        let view = MsiHandle(view);
+        // SAFETY: `view` is an open view; the query takes no parameters.
+        let rc = unsafe { MsiViewExecute(view.0, 0) };
+        if rc != ERROR_SUCCESS {
+            return Err(err("query", rc));
+        }
+
+        let mut version = ExecutableVersion {
+            path: path.to_string_lossy().into_owned(),
+            ..ExecutableVersion::default()
+        };
+        loop {
+            let mut record: MSIHANDLE = 0;
+            // SAFETY: `view` was executed; fetching past the last row returns an error.
+            if unsafe { MsiViewFetch(view.0, &mut record) } != ERROR_SUCCESS {
+                break;
+            }
+            let record = MsiHandle(record);
+            let (Some(name), Some(value)) = (record_string(&record, 1), record_string(&record, 2))
+            else {
+                continue;
+            };
+            let slot = match name.as_str() {
+                "ProductName" => &mut version.product_name,
+                "ProductVersion" => &mut version.product_version,
+                "Manufacturer" => &mut version.company_name,
+                "ARPCOMMENTS" => &mut version.file_description,
+                _ => continue,
+            };
+            *slot = Some(value).filter(|v| !v.trim().is_empty());
+        }
+        Ok(version)
+    }
+
+    pub(super) fn executable_version(path: &Path) -> Result<ExecutableVersion, String> {
+        let is_package = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("msi"));
+        if is_package {
+            package_version(path)
+        } else {
+            resource_version(path)
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use super::ExecutableVersion;
+    use std::path::Path;
+
+    pub(super) fn executable_version(_path: &Path) -> Result<ExecutableVersion, String> {
+        Err("Version information of executables can only be read on Windows.".to_string())
+    }
+}
+
+/// Product name, version and company of a Windows executable, library or installer
+/// package, to tell which product an unknown binary belongs to.
+pub async fn executable_version(path: String) -> Result<ExecutableVersion, String> {
+    let target = PathBuf::from(path);
+    if !target.is_file() {
+        return Err(format!("Not a file: {}", target.to_string_lossy()));
+    }
+    // Loading the version resource reads the file, which would download a placeholder.
+    if fs::metadata(&target).is_ok_and(|meta| is_cloud_only(&meta)) {
+        return Err(format!(
+            "{} is only in the cloud; reading its version would download it.",
+            target.to_string_lossy()
+        ));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || platform::executable_version(&target))
+        .await
+        .map_err(|err| err.to_string())?
+}
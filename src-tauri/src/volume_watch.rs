@@ -0,0 +1,77 @@
+use serde::Serialize;
+use std::{collections::BTreeMap, thread, time::Duration};
+use tauri::Emitter;
+
+use crate::volumes::{list_volumes_blocking, VolumeInfo};
+
+const VOLUMES_CHANGED_EVENT: &str = "volumes_changed";
+const VOLUME_ATTACHED_EVENT: &str = "volume_attached";
+const VOLUME_DETACHED_EVENT: &str = "volume_detached";
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VolumesChangedPayload {
+    added: Vec<VolumeInfo>,
+    removed: Vec<VolumeInfo>,
+    // The full current list, so the drive picker can simply replace its state.
+    volumes: Vec<VolumeInfo>,
+}
+
+fn mounted_volumes() -> Option<BTreeMap<String, VolumeInfo>> {
+    // A failed enumeration is not "everything was unmounted"; skip the tick instead.
+    let volumes = list_volumes_blocking().ok()?;
+    Some(
+        volumes
+            .into_iter()
+            .map(|v| (v.mount_point.clone(), v))
+            .collect(),
+    )
+}
+
+/// Polls the mounted volumes and emits change events when volumes appear or disappear.
+/// Removable drives additionally get dedicated attach/detach events.
+pub fn spawn(app: tauri::AppHandle) {
+    let _ = thread::Builder::new()
+        .name("volume-watch".to_string())
+        .spawn(move || {
+            let mut known = mounted_volumes().unwrap_or_default();
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                let current = match mounted_volumes() {
+                    Some(current) => current,
+                    None => continue,
+                };
+
+                let added: Vec<VolumeInfo> = current
+                    .iter()
+                    .filter(|(mount_point, _)| !known.contains_key(*mount_point))
+                    .map(|(_, v)| v.clone())
+                    .collect();
+                let removed: Vec<VolumeInfo> = known
+                    .iter()
+                    .filter(|(mount_point, _)| !current.contains_key(*mount_point))
+                    .map(|(_, v)| v.clone())
+                    .collect();
+
+                for volume in added.iter().filter(|v| v.is_removable) {
+                    let _ = app.emit(VOLUME_ATTACHED_EVENT, volume);
+                }
+                for volume in removed.iter().filter(|v| v.is_removable) {
+                    let _ = app.emit(VOLUME_DETACHED_EVENT, volume);
+                }
+                if !added.is_empty() || !removed.is_empty() {
+                    let _ = app.emit(
+                        VOLUMES_CHANGED_EVENT,
+                        VolumesChangedPayload {
+                            added,
+                            removed,
+                            volumes: current.values().cloned().collect(),
+                        },
+                    );
+                }
+
+                known = current;
+            }
+        });
+}
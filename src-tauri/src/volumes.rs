@@ -0,0 +1,1380 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri_plugin_diskcheck_storage::{StorageExt, VolumeKind};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeInfo {
+    pub mount_point: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub file_system: String,
+    pub total_bytes: u64,
+    // Bytes available to the current user (excludes root-reserved blocks).
+    pub free_bytes: u64,
+    pub used_bytes: u64,
+    pub is_removable: bool,
+    pub is_network: bool,
+    // Internal storage, SD card or USB drive; only known on Android.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<VolumeKind>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EncryptionState {
+    Unknown,
+    Unencrypted,
+    Encrypted,
+    // Encryption or decryption is currently running.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    InProgress,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeDetails {
+    pub mount_point: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+    pub file_system: String,
+    // Allocation unit: files always occupy a multiple of this on disk.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cluster_size: Option<u64>,
+    pub encryption: EncryptionState,
+    // BitLocker, FileVault, LUKS, ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption_method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compressed: Option<bool>,
+    pub read_only: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mount_options: Vec<String>,
+}
+
+/// Capacity of the volume holding a path, for "deleting this frees X of Y free".
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskUsage {
+    pub path: String,
+    pub total_bytes: u64,
+    // Including blocks reserved for the superuser.
+    pub free_bytes: u64,
+    // What the current user can still write.
+    pub available_bytes: u64,
+    pub used_bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SpaceInfo {
+    pub total_bytes: u64,
+    // Free bytes available to the current user.
+    pub available_bytes: u64,
+    // Free bytes including blocks reserved for the superuser.
+    pub free_bytes: u64,
+}
+
+impl SpaceInfo {
+    pub fn used_bytes(&self) -> u64 {
+        self.total_bytes.saturating_sub(self.free_bytes)
+    }
+}
+
+pub(crate) fn is_network_fs(file_system: &str) -> bool {
+    let fs = file_system.to_ascii_lowercase();
+    matches!(
+        fs.as_str(),
+        "nfs"
+            | "nfs4"
+            | "cifs"
+            | "smb"
+            | "smb2"
+            | "smb3"
+            | "smbfs"
+            | "afpfs"
+            | "webdav"
+            | "davfs"
+            | "9p"
+            | "afs"
+            | "ceph"
+            | "glusterfs"
+            | "fuse.sshfs"
+            | "fuse.rclone"
+    )
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) use platform::mount_for_path;
+#[cfg(target_os = "windows")]
+pub(crate) use platform::{from_wide, to_wide};
+
+/// Root of the volume the OS runs from (`C:\` or `/`).
+pub(crate) fn system_root() -> String {
+    if cfg!(windows) {
+        std::env::var("SystemDrive")
+            .map(|drive| format!("{}\\", drive))
+            .unwrap_or_else(|_| "C:\\".to_string())
+    } else {
+        "/".to_string()
+    }
+}
+
+/// Returns the capacity figures of the volume containing `path`.
+pub(crate) fn space_for_path(path: &Path) -> Result<SpaceInfo, String> {
+    platform::space_for_path(path)
+}
+
+/// Returns the mounted volume containing `path` (longest matching mount point).
+pub(crate) fn volume_for_path(path: &Path) -> Option<VolumeInfo> {
+    // Windows canonicalization yields `\\?\` verbatim paths that never match drive roots.
+    #[cfg(unix)]
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    list_volumes_blocking()
+        .ok()?
+        .into_iter()
+        .filter(|v| path.starts_with(&v.mount_point))
+        .max_by_key(|v| v.mount_point.len())
+}
+
+/// A mount point as listed in the mount table.
+struct MountPoint {
+    path: PathBuf,
+    is_network: bool,
+}
+
+/// True for UNC paths and anything living on a network mount.
+pub(crate) fn is_network_path(path: &Path) -> bool {
+    let raw = path.to_string_lossy();
+    if raw.starts_with("\\\\") && !raw.starts_with("\\\\?\\") {
+        return true;
+    }
+    if raw.starts_with("\\\\?\\UNC\\") {
+        return true;
+    }
+    // gvfs mounts (SMB, SFTP, WebDAV) hang off one FUSE mount that the volume list hides.
+    if cfg!(target_os = "linux") && raw.contains("/gvfs/") {
+        return true;
+    }
+    // Only the mount table, like `network_mounts_below`: listing volumes queries every
+    // mount for its size and would hang every scan on any dead network mount.
+    #[cfg(unix)]
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    platform::mount_points()
+        .into_iter()
+        .filter(|mount| path.starts_with(&mount.path))
+        .max_by_key(|mount| mount.path.as_os_str().len())
+        .is_some_and(|mount| mount.is_network)
+}
+
+/// Mount points of network filesystems strictly inside `root`, e.g. an NFS export
+/// mounted below a local folder.
+pub(crate) fn network_mounts_below(root: &Path) -> Vec<PathBuf> {
+    // Only the mount table: querying each mount for its size, as listing volumes does,
+    // can hang on the very network mounts being looked for.
+    platform::mount_points()
+        .into_iter()
+        .filter(|mount| mount.is_network)
+        .map(|mount| mount.path)
+        .filter(|mount| mount.starts_with(root) && mount != root)
+        .collect()
+}
+
+pub(crate) fn list_volumes_blocking() -> Result<Vec<VolumeInfo>, String> {
+    let mut volumes = platform::list_volumes()?;
+    volumes.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    Ok(volumes)
+}
+
+/// The volumes Android's storage manager reports: apps cannot read its mount table,
+/// and most mounts would be unreadable anyway.
+fn device_volumes(app: &tauri::AppHandle) -> Result<Vec<VolumeInfo>, String> {
+    let volumes = app.storage().list_volumes()?;
+    Ok(volumes
+        .into_iter()
+        .map(|volume| VolumeInfo {
+            mount_point: volume.path,
+            label: Some(volume.description),
+            // Not reported to apps.
+            file_system: String::new(),
+            total_bytes: volume.total_bytes,
+            free_bytes: volume.free_bytes,
+            used_bytes: volume.total_bytes.saturating_sub(volume.free_bytes),
+            is_removable: volume.kind != VolumeKind::Internal,
+            is_network: false,
+            kind: Some(volume.kind),
+        })
+        .collect())
+}
+
+pub async fn list_volumes(app: tauri::AppHandle) -> Result<Vec<VolumeInfo>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if cfg!(target_os = "android") {
+            return device_volumes(&app);
+        }
+        list_volumes_blocking()
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Runs an external tool, turning a non-zero exit into its stderr message.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub(crate) fn run_tool(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let message = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    Err(format!("{} failed: {}", program, message))
+}
+
+pub async fn eject_volume(mount_point: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let volume = list_volumes_blocking()?
+            .into_iter()
+            .find(|v| v.mount_point == mount_point)
+            .ok_or_else(|| format!("Volume is not mounted: {}", mount_point))?;
+        if !volume.is_removable {
+            return Err(format!(
+                "Refusing to eject {}: it is not a removable volume.",
+                volume.mount_point
+            ));
+        }
+        tracing::info!(mount_point = %volume.mount_point, "ejecting volume");
+        platform::eject(&volume.mount_point).inspect_err(
+            |err| tracing::error!(mount_point = %volume.mount_point, error = %err, "eject failed"),
+        )
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub async fn volume_info(path: String) -> Result<VolumeDetails, String> {
+    let target = PathBuf::from(path);
+    if !target.exists() {
+        return Err(format!("Path does not exist: {}", target.to_string_lossy()));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || platform::volume_details(&target))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+/// Total, free and available bytes of the volume containing `path`. A path that does
+/// not exist yet, such as a copy destination, is measured at its nearest existing parent.
+pub async fn disk_usage(path: String) -> Result<DiskUsage, String> {
+    let target = PathBuf::from(path);
+    let existing = target
+        .ancestors()
+        .find(|dir| dir.exists())
+        .map(Path::to_path_buf)
+        .ok_or_else(|| format!("Path does not exist: {}", target.to_string_lossy()))?;
+
+    let space = tauri::async_runtime::spawn_blocking(move || space_for_path(&existing))
+        .await
+        .map_err(|err| err.to_string())??;
+    Ok(DiskUsage {
+        path: target.to_string_lossy().into_owned(),
+        total_bytes: space.total_bytes,
+        free_bytes: space.free_bytes,
+        available_bytes: space.available_bytes,
+        used_bytes: space.used_bytes(),
+    })
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{is_network_fs, EncryptionState, MountPoint, SpaceInfo, VolumeDetails, VolumeInfo};
+    use std::{
+        ffi::OsStr,
+        os::windows::{ffi::OsStrExt, process::CommandExt},
+        path::{Path, PathBuf},
+        process::Command,
+        ptr,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetDiskFreeSpaceExW, GetDiskFreeSpaceW, GetDriveTypeW, GetLogicalDriveStringsW,
+        GetVolumeInformationW, GetVolumePathNameW,
+    };
+
+    const DRIVE_REMOVABLE: u32 = 2;
+    const DRIVE_REMOTE: u32 = 4;
+    const DRIVE_CDROM: u32 = 5;
+
+    const FILE_VOLUME_IS_COMPRESSED: u32 = 0x0000_8000;
+    const FILE_READ_ONLY_VOLUME: u32 = 0x0008_0000;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    pub(super) struct RawVolumeInformation {
+        pub label: Option<String>,
+        pub file_system: String,
+        pub serial: u32,
+        pub flags: u32,
+    }
+
+    pub(crate) fn to_wide(s: &OsStr) -> Vec<u16> {
+        s.encode_wide().chain(std::iter::once(0)).collect()
+    }
+
+    pub(crate) fn from_wide(buf: &[u16]) -> String {
+        let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+        String::from_utf16_lossy(&buf[..len])
+    }
+
+    pub(super) fn space_for_path(path: &Path) -> Result<SpaceInfo, String> {
+        let wide = to_wide(path.as_os_str());
+        let mut available: u64 = 0;
+        let mut total: u64 = 0;
+        let mut free: u64 = 0;
+        // SAFETY: `wide` is NUL-terminated and the out-pointers are valid for writes.
+        let ok =
+            unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, &mut total, &mut free) };
+        if ok == 0 {
+            return Err(format!(
+                "Failed to query free space for {}: {}",
+                path.to_string_lossy(),
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(SpaceInfo {
+            total_bytes: total,
+            available_bytes: available,
+            free_bytes: free,
+        })
+    }
+
+    /// Queries label, file system, serial and flags for a NUL-terminated root such as `C:\`.
+    pub(super) fn volume_information(root: &[u16]) -> Option<RawVolumeInformation> {
+        let mut label = [0u16; 261];
+        let mut fs_name = [0u16; 261];
+        let mut serial: u32 = 0;
+        let mut flags: u32 = 0;
+        // SAFETY: buffers are sized as passed; the unused out-pointer is null.
+        let ok = unsafe {
+            GetVolumeInformationW(
+                root.as_ptr(),
+                label.as_mut_ptr(),
+                label.len() as u32,
+                &mut serial,
+                ptr::null_mut(),
+                &mut flags,
+                fs_name.as_mut_ptr(),
+                fs_name.len() as u32,
+            )
+        };
+        if ok == 0 {
+            return None;
+        }
+        let label = from_wide(&label);
+        Some(RawVolumeInformation {
+            label: Some(label).filter(|l| !l.is_empty()),
+            file_system: from_wide(&fs_name),
+            serial,
+            flags,
+        })
+    }
+
+    /// Resolves the mount root (`C:\`, `\\server\share\`, or a mounted folder) containing `path`.
+    pub(super) fn volume_root(path: &Path) -> Result<Vec<u16>, String> {
+        let wide = to_wide(path.as_os_str());
+        let mut buf = [0u16; 1024];
+        // SAFETY: `wide` is NUL-terminated; `buf` is valid for `buf.len()` writes.
+        let ok = unsafe { GetVolumePathNameW(wide.as_ptr(), buf.as_mut_ptr(), buf.len() as u32) };
+        if ok == 0 {
+            return Err(format!(
+                "Failed to resolve the volume for {}: {}",
+                path.to_string_lossy(),
+                std::io::Error::last_os_error()
+            ));
+        }
+        let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+        Ok(buf[..len]
+            .iter()
+            .copied()
+            .chain(std::iter::once(0))
+            .collect())
+    }
+
+    fn cluster_size(root: &[u16]) -> Option<u64> {
+        let mut sectors_per_cluster: u32 = 0;
+        let mut bytes_per_sector: u32 = 0;
+        let mut free_clusters: u32 = 0;
+        let mut total_clusters: u32 = 0;
+        // SAFETY: `root` is NUL-terminated and the out-pointers are valid.
+        let ok = unsafe {
+            GetDiskFreeSpaceW(
+                root.as_ptr(),
+                &mut sectors_per_cluster,
+                &mut bytes_per_sector,
+                &mut free_clusters,
+                &mut total_clusters,
+            )
+        };
+        if ok == 0 {
+            return None;
+        }
+        Some(u64::from(sectors_per_cluster) * u64::from(bytes_per_sector))
+    }
+
+    /// Reads BitLocker state through the shell property store, which (unlike
+    /// `manage-bde`) works without administrator rights.
+    fn bitlocker_state(drive: &str) -> EncryptionState {
+        let script = format!(
+            "(New-Object -ComObject Shell.Application).NameSpace('{}').Self.ExtendedProperty('System.Volume.BitLockerProtection')",
+            drive.replace('\'', "''")
+        );
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output();
+        let value = match output {
+            Ok(out) if out.status.success() => {
+                String::from_utf8_lossy(&out.stdout).trim().to_string()
+            }
+            _ => return EncryptionState::Unknown,
+        };
+        match value.as_str() {
+            "1" | "5" | "6" => EncryptionState::Encrypted,
+            "2" => EncryptionState::Unencrypted,
+            "3" | "4" => EncryptionState::InProgress,
+            _ => EncryptionState::Unknown,
+        }
+    }
+
+    pub(super) fn eject(mount_point: &str) -> Result<(), String> {
+        // The shell "Eject" verb performs the same safe-removal flow as Explorer.
+        let script = format!(
+            "(New-Object -ComObject Shell.Application).Namespace(17).ParseName('{}').InvokeVerb('Eject')",
+            mount_point.trim_end_matches('\\').replace('\'', "''")
+        );
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map_err(|e| format!("Failed to run powershell: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to eject {}: {}",
+                mount_point,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    pub(super) fn volume_details(path: &Path) -> Result<VolumeDetails, String> {
+        let root = volume_root(path)?;
+        let mount_point = from_wide(&root);
+        let info = volume_information(&root).ok_or_else(|| {
+            format!(
+                "Failed to read volume information for {}: {}",
+                mount_point,
+                std::io::Error::last_os_error()
+            )
+        })?;
+
+        // BitLocker only applies to local drive letters.
+        let is_drive_letter = mount_point.len() == 3 && mount_point.ends_with(":\\");
+        let encryption = if is_drive_letter {
+            bitlocker_state(&mount_point[..2])
+        } else {
+            EncryptionState::Unknown
+        };
+
+        Ok(VolumeDetails {
+            label: info.label,
+            serial: Some(format!(
+                "{:04X}-{:04X}",
+                info.serial >> 16,
+                info.serial & 0xFFFF
+            )),
+            file_system: info.file_system,
+            cluster_size: cluster_size(&root),
+            encryption_method: matches!(
+                encryption,
+                EncryptionState::Encrypted | EncryptionState::InProgress
+            )
+            .then(|| "BitLocker".to_string()),
+            encryption,
+            compressed: Some(info.flags & FILE_VOLUME_IS_COMPRESSED != 0),
+            read_only: info.flags & FILE_READ_ONLY_VOLUME != 0,
+            mount_options: vec![],
+            mount_point,
+        })
+    }
+
+    /// Drive roots and whether each is a mapped network share. Only the drive type is
+    /// read, which does not wait on the share itself.
+    pub(super) fn mount_points() -> Vec<MountPoint> {
+        let mut buf = [0u16; 512];
+        // SAFETY: `buf` is valid for `buf.len()` u16 writes.
+        let len = unsafe { GetLogicalDriveStringsW(buf.len() as u32, buf.as_mut_ptr()) } as usize;
+        if len == 0 || len > buf.len() {
+            return vec![];
+        }
+        buf[..len]
+            .split(|&c| c == 0)
+            .filter(|s| !s.is_empty())
+            .map(|root| {
+                let wide: Vec<u16> = root.iter().copied().chain(std::iter::once(0)).collect();
+                // SAFETY: `wide` is NUL-terminated.
+                let drive_type = unsafe { GetDriveTypeW(wide.as_ptr()) };
+                MountPoint {
+                    path: PathBuf::from(String::from_utf16_lossy(root)),
+                    is_network: drive_type == DRIVE_REMOTE,
+                }
+            })
+            .collect()
+    }
+
+    pub(super) fn list_volumes() -> Result<Vec<VolumeInfo>, String> {
+        let mut buf = [0u16; 512];
+        // SAFETY: `buf` is valid for `buf.len()` u16 writes.
+        let len = unsafe { GetLogicalDriveStringsW(buf.len() as u32, buf.as_mut_ptr()) } as usize;
+        if len == 0 || len > buf.len() {
+            return Err(format!(
+                "Failed to enumerate drives: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+
+        let mut volumes = vec![];
+        // The buffer holds NUL-separated roots like `C:\`, terminated by an empty string.
+        for root in buf[..len].split(|&c| c == 0).filter(|s| !s.is_empty()) {
+            let mount_point = String::from_utf16_lossy(root);
+            let wide: Vec<u16> = root.iter().copied().chain(std::iter::once(0)).collect();
+
+            // SAFETY: `wide` is NUL-terminated.
+            let drive_type = unsafe { GetDriveTypeW(wide.as_ptr()) };
+            if drive_type == DRIVE_CDROM {
+                // Empty optical drives fail every query below; skip them entirely.
+                continue;
+            }
+
+            let space = match space_for_path(Path::new(&mount_point)) {
+                Ok(space) => space,
+                Err(_) => continue,
+            };
+            let (label, file_system) = match volume_information(&wide) {
+                Some(info) => (info.label, info.file_system),
+                None => (None, String::new()),
+            };
+
+            volumes.push(VolumeInfo {
+                is_network: drive_type == DRIVE_REMOTE || is_network_fs(&file_system),
+                is_removable: drive_type == DRIVE_REMOVABLE,
+                mount_point,
+                label,
+                file_system,
+                total_bytes: space.total_bytes,
+                free_bytes: space.available_bytes,
+                used_bytes: space.used_bytes(),
+                kind: None,
+            });
+        }
+        Ok(volumes)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{is_network_fs, EncryptionState, MountPoint, SpaceInfo, VolumeDetails, VolumeInfo};
+    use std::{
+        ffi::{CStr, CString},
+        os::unix::ffi::OsStrExt,
+        path::{Path, PathBuf},
+        process::Command,
+    };
+
+    const MNT_NOWAIT: libc::c_int = 2;
+    const MNT_RDONLY: u32 = 0x0000_0001;
+    const MNT_NOEXEC: u32 = 0x0000_0004;
+    const MNT_NOSUID: u32 = 0x0000_0008;
+    const MNT_NODEV: u32 = 0x0000_0010;
+    const MNT_LOCAL: u32 = 0x0000_1000;
+    const MNT_REMOVABLE: u32 = 0x0000_0200;
+    const MNT_JOURNALED: u32 = 0x0080_0000;
+    // System volumes (VM, Preboot, Update, ...) are mounted with this flag.
+    const MNT_DONTBROWSE: u32 = 0x0010_0000;
+
+    const MOUNT_FLAG_NAMES: &[(u32, &str)] = &[
+        (MNT_RDONLY, "rdonly"),
+        (MNT_NOEXEC, "noexec"),
+        (MNT_NOSUID, "nosuid"),
+        (MNT_NODEV, "nodev"),
+        (MNT_LOCAL, "local"),
+        (MNT_REMOVABLE, "removable"),
+        (MNT_JOURNALED, "journaled"),
+        (MNT_DONTBROWSE, "nobrowse"),
+    ];
+
+    pub(super) fn statfs(path: &Path) -> Result<libc::statfs, String> {
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| format!("Invalid path: {}", path.to_string_lossy()))?;
+        let mut st: libc::statfs = unsafe { std::mem::zeroed() };
+        // SAFETY: `c_path` is NUL-terminated and `st` is a valid out-pointer.
+        if unsafe { libc::statfs(c_path.as_ptr(), &mut st) } != 0 {
+            return Err(format!(
+                "Failed to query volume for {}: {}",
+                path.to_string_lossy(),
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(st)
+    }
+
+    pub(super) fn space_for_path(path: &Path) -> Result<SpaceInfo, String> {
+        let st = statfs(path)?;
+        let block = st.f_bsize as u64;
+        Ok(SpaceInfo {
+            total_bytes: st.f_blocks.saturating_mul(block),
+            available_bytes: st.f_bavail.saturating_mul(block),
+            free_bytes: st.f_bfree.saturating_mul(block),
+        })
+    }
+
+    pub(super) fn mount_points() -> Vec<MountPoint> {
+        let mut mounts: *mut libc::statfs = std::ptr::null_mut();
+        // SAFETY: getmntinfo returns a pointer to an internal buffer of `count` entries.
+        // MNT_NOWAIT uses the cached statistics instead of asking each file system.
+        let count = unsafe { libc::getmntinfo(&mut mounts, MNT_NOWAIT) };
+        if count <= 0 || mounts.is_null() {
+            return vec![];
+        }
+        // SAFETY: see above; the buffer stays valid until the next getmntinfo call on this thread.
+        let mounts = unsafe { std::slice::from_raw_parts(mounts, count as usize) };
+        mounts
+            .iter()
+            .map(|st| {
+                // SAFETY: both fields are NUL-terminated fixed-size C strings.
+                let file_system = unsafe { CStr::from_ptr(st.f_fstypename.as_ptr()) };
+                let mount_point = unsafe { CStr::from_ptr(st.f_mntonname.as_ptr()) };
+                MountPoint {
+                    path: PathBuf::from(mount_point.to_string_lossy().into_owned()),
+                    is_network: st.f_flags & MNT_LOCAL == 0
+                        || is_network_fs(&file_system.to_string_lossy()),
+                }
+            })
+            .collect()
+    }
+
+    pub(super) fn list_volumes() -> Result<Vec<VolumeInfo>, String> {
+        let mut mounts: *mut libc::statfs = std::ptr::null_mut();
+        // SAFETY: getmntinfo returns a pointer to an internal buffer of `count` entries.
+        let count = unsafe { libc::getmntinfo(&mut mounts, MNT_NOWAIT) };
+        if count <= 0 || mounts.is_null() {
+            return Err(format!(
+                "Failed to enumerate mounts: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        // SAFETY: see above; the buffer stays valid until the next getmntinfo call on this thread.
+        let mounts = unsafe { std::slice::from_raw_parts(mounts, count as usize) };
+
+        let mut volumes = vec![];
+        for st in mounts {
+            let flags = st.f_flags;
+            // SAFETY: both fields are NUL-terminated fixed-size C strings.
+            let mount_point = unsafe { CStr::from_ptr(st.f_mntonname.as_ptr()) }
+                .to_string_lossy()
+                .into_owned();
+            if flags & MNT_DONTBROWSE != 0 && mount_point != "/" {
+                continue;
+            }
+
+            let file_system = unsafe { CStr::from_ptr(st.f_fstypename.as_ptr()) }
+                .to_string_lossy()
+                .into_owned();
+            if matches!(file_system.as_str(), "devfs" | "autofs" | "nullfs") {
+                continue;
+            }
+
+            let block = st.f_bsize as u64;
+            let total_bytes = st.f_blocks.saturating_mul(block);
+            if total_bytes == 0 {
+                continue;
+            }
+            let free = st.f_bfree.saturating_mul(block);
+
+            let label = if mount_point == "/" {
+                Some("Macintosh HD".to_string())
+            } else {
+                Path::new(&mount_point)
+                    .file_name()
+                    .map(|s| s.to_string_lossy().into_owned())
+            };
+
+            volumes.push(VolumeInfo {
+                is_network: flags & MNT_LOCAL == 0 || is_network_fs(&file_system),
+                // External disks are rarely flagged MNT_REMOVABLE; anything local that
+                // macOS mounts under /Volumes is ejectable from Finder, so treat it alike.
+                is_removable: flags & MNT_REMOVABLE != 0
+                    || (flags & MNT_LOCAL != 0 && mount_point.starts_with("/Volumes/")),
+                mount_point,
+                label,
+                file_system,
+                total_bytes,
+                free_bytes: st.f_bavail.saturating_mul(block),
+                used_bytes: total_bytes.saturating_sub(free),
+                kind: None,
+            });
+        }
+        Ok(volumes)
+    }
+
+    pub(super) fn eject(mount_point: &str) -> Result<(), String> {
+        super::run_tool("diskutil", &["eject", mount_point])
+    }
+
+    /// Parses `Key: value` lines from `diskutil info` output.
+    fn diskutil_info(mount_point: &str) -> Vec<(String, String)> {
+        let output = match Command::new("diskutil")
+            .args(["info", mount_point])
+            .output()
+        {
+            Ok(out) if out.status.success() => out.stdout,
+            _ => return vec![],
+        };
+        String::from_utf8_lossy(&output)
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                Some((key.trim().to_string(), value.trim().to_string()))
+            })
+            .collect()
+    }
+
+    pub(super) fn volume_details(path: &Path) -> Result<VolumeDetails, String> {
+        let st = statfs(path)?;
+        // SAFETY: both fields are NUL-terminated fixed-size C strings.
+        let mount_point = unsafe { CStr::from_ptr(st.f_mntonname.as_ptr()) }
+            .to_string_lossy()
+            .into_owned();
+        let file_system = unsafe { CStr::from_ptr(st.f_fstypename.as_ptr()) }
+            .to_string_lossy()
+            .into_owned();
+
+        let info = diskutil_info(&mount_point);
+        let field = |key: &str| {
+            info.iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+                .filter(|v| !v.is_empty())
+        };
+
+        let encryption = match field("FileVault").as_deref() {
+            Some("Yes") => EncryptionState::Encrypted,
+            Some("No") => EncryptionState::Unencrypted,
+            _ => EncryptionState::Unknown,
+        };
+
+        Ok(VolumeDetails {
+            label: field("Volume Name"),
+            serial: field("Volume UUID"),
+            file_system,
+            cluster_size: Some(st.f_bsize as u64),
+            encryption_method: (encryption == EncryptionState::Encrypted)
+                .then(|| "FileVault".to_string()),
+            encryption,
+            // APFS/HFS+ per-file compression is transparent and not a volume property.
+            compressed: None,
+            read_only: st.f_flags & MNT_RDONLY != 0,
+            mount_options: MOUNT_FLAG_NAMES
+                .iter()
+                .filter(|(flag, _)| st.f_flags & flag != 0)
+                .map(|(_, name)| name.to_string())
+                .collect(),
+            mount_point,
+        })
+    }
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+mod platform {
+    use super::{is_network_fs, EncryptionState, MountPoint, SpaceInfo, VolumeDetails, VolumeInfo};
+    use std::{
+        ffi::{CStr, CString},
+        os::unix::ffi::OsStrExt,
+        path::{Path, PathBuf},
+    };
+
+    #[cfg(target_os = "freebsd")]
+    use libc::getmntinfo;
+
+    // Not bound by the libc crate on OpenBSD.
+    #[cfg(target_os = "openbsd")]
+    extern "C" {
+        fn getmntinfo(mntbufp: *mut *mut libc::statfs, flags: libc::c_int) -> libc::c_int;
+    }
+
+    const MNT_NOWAIT: libc::c_int = 2;
+    const MNT_RDONLY: u64 = 0x0000_0001;
+    const MNT_NOEXEC: u64 = 0x0000_0004;
+    const MNT_NOSUID: u64 = 0x0000_0008;
+    const MNT_LOCAL: u64 = 0x0000_1000;
+
+    const MOUNT_FLAG_NAMES: &[(u64, &str)] = &[
+        (MNT_RDONLY, "rdonly"),
+        (MNT_NOEXEC, "noexec"),
+        (MNT_NOSUID, "nosuid"),
+        (MNT_LOCAL, "local"),
+    ];
+
+    // Kernel and memory file systems, and nullfs loopbacks of folders listed elsewhere.
+    const PSEUDO_FILESYSTEMS: &[&str] = &[
+        "devfs",
+        "fdescfs",
+        "procfs",
+        "linprocfs",
+        "linsysfs",
+        "tmpfs",
+        "nullfs",
+        "autofs",
+        "mfs",
+        "kernfs",
+    ];
+
+    /// A statfs buffer, with the fields whose widths differ between the BSDs widened.
+    struct Mount {
+        mount_point: String,
+        source: String,
+        file_system: String,
+        flags: u64,
+        block: u64,
+        blocks: u64,
+        free_blocks: u64,
+        available_blocks: u64,
+    }
+
+    impl Mount {
+        // statfs field widths differ between the BSDs, so the casts are only sometimes no-ops.
+        #[allow(clippy::unnecessary_cast)]
+        fn new(st: &libc::statfs) -> Self {
+            // SAFETY: the name fields are NUL-terminated fixed-size C strings.
+            let text = |field: &[libc::c_char]| {
+                unsafe { CStr::from_ptr(field.as_ptr()) }
+                    .to_string_lossy()
+                    .into_owned()
+            };
+            Self {
+                mount_point: text(&st.f_mntonname),
+                source: text(&st.f_mntfromname),
+                file_system: text(&st.f_fstypename),
+                flags: st.f_flags as u64,
+                block: st.f_bsize as u64,
+                blocks: st.f_blocks as u64,
+                free_blocks: st.f_bfree as u64,
+                // Negative once root has dipped into the reserve.
+                available_blocks: st.f_bavail.max(0) as u64,
+            }
+        }
+
+        fn space(&self) -> SpaceInfo {
+            SpaceInfo {
+                total_bytes: self.blocks.saturating_mul(self.block),
+                available_bytes: self.available_blocks.saturating_mul(self.block),
+                free_bytes: self.free_blocks.saturating_mul(self.block),
+            }
+        }
+
+        /// Best guess only: the BSDs do not flag removable media. USB disks attach as
+        /// `da` devices on FreeBSD, and desktop automounters use /media.
+        fn is_removable(&self) -> bool {
+            self.flags & MNT_LOCAL != 0
+                && (self.mount_point.starts_with("/media/")
+                    || (cfg!(target_os = "freebsd") && self.source.starts_with("/dev/da")))
+        }
+
+        fn label(&self) -> Option<String> {
+            // ZFS datasets are better known by name than by where they are mounted.
+            if self.file_system == "zfs" {
+                return Some(self.source.clone());
+            }
+            Path::new(&self.mount_point)
+                .file_name()
+                .map(|s| s.to_string_lossy().into_owned())
+        }
+    }
+
+    fn statfs(path: &Path) -> Result<Mount, String> {
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| format!("Invalid path: {}", path.to_string_lossy()))?;
+        let mut st: libc::statfs = unsafe { std::mem::zeroed() };
+        // SAFETY: `c_path` is NUL-terminated and `st` is a valid out-pointer.
+        if unsafe { libc::statfs(c_path.as_ptr(), &mut st) } != 0 {
+            return Err(format!(
+                "Failed to query volume for {}: {}",
+                path.to_string_lossy(),
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(Mount::new(&st))
+    }
+
+    pub(super) fn space_for_path(path: &Path) -> Result<SpaceInfo, String> {
+        Ok(statfs(path)?.space())
+    }
+
+    pub(super) fn mount_points() -> Vec<MountPoint> {
+        let mut mounts: *mut libc::statfs = std::ptr::null_mut();
+        // SAFETY: getmntinfo returns a pointer to an internal buffer of `count` entries.
+        // MNT_NOWAIT uses the cached statistics instead of asking each file system.
+        let count = unsafe { getmntinfo(&mut mounts, MNT_NOWAIT) };
+        if count <= 0 || mounts.is_null() {
+            return vec![];
+        }
+        // SAFETY: see above; the buffer stays valid until the next getmntinfo call.
+        let mounts = unsafe { std::slice::from_raw_parts(mounts, count as usize) };
+        mounts
+            .iter()
+            .map(Mount::new)
+            .map(|mount| MountPoint {
+                is_network: mount.flags & MNT_LOCAL == 0 || is_network_fs(&mount.file_system),
+                path: PathBuf::from(mount.mount_point),
+            })
+            .collect()
+    }
+
+    pub(super) fn list_volumes() -> Result<Vec<VolumeInfo>, String> {
+        let mut mounts: *mut libc::statfs = std::ptr::null_mut();
+        // SAFETY: getmntinfo returns a pointer to an internal buffer of `count` entries.
+        let count = unsafe { getmntinfo(&mut mounts, MNT_NOWAIT) };
+        if count <= 0 || mounts.is_null() {
+            return Err(format!(
+                "Failed to enumerate mounts: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        // SAFETY: see above; the buffer stays valid until the next getmntinfo call.
+        let mounts = unsafe { std::slice::from_raw_parts(mounts, count as usize) };
+
+        let mut volumes = vec![];
+        for mount in mounts.iter().map(Mount::new) {
+            if PSEUDO_FILESYSTEMS.contains(&mount.file_system.as_str()) {
+                continue;
+            }
+            let space = mount.space();
+            if space.total_bytes == 0 {
+                continue;
+            }
+            volumes.push(VolumeInfo {
+                is_network: mount.flags & MNT_LOCAL == 0 || is_network_fs(&mount.file_system),
+                is_removable: mount.is_removable(),
+                label: mount.label(),
+                mount_point: mount.mount_point,
+                file_system: mount.file_system,
+                total_bytes: space.total_bytes,
+                free_bytes: space.available_bytes,
+                used_bytes: space.used_bytes(),
+                kind: None,
+            });
+        }
+        Ok(volumes)
+    }
+
+    pub(super) fn eject(mount_point: &str) -> Result<(), String> {
+        super::run_tool("umount", &[mount_point])
+    }
+
+    pub(super) fn volume_details(path: &Path) -> Result<VolumeDetails, String> {
+        let mount = statfs(path)?;
+        // GELI providers carry an `.eli` suffix; other encryption is not visible here.
+        let (encryption, encryption_method) = if mount.source.ends_with(".eli") {
+            (EncryptionState::Encrypted, Some("GELI".to_string()))
+        } else {
+            (EncryptionState::Unknown, None)
+        };
+        Ok(VolumeDetails {
+            label: mount.label(),
+            serial: None,
+            cluster_size: Some(mount.block),
+            encryption,
+            encryption_method,
+            compressed: None,
+            read_only: mount.flags & MNT_RDONLY != 0,
+            mount_options: MOUNT_FLAG_NAMES
+                .iter()
+                .filter(|(flag, _)| mount.flags & flag != 0)
+                .map(|(_, name)| name.to_string())
+                .collect(),
+            file_system: mount.file_system,
+            mount_point: mount.mount_point,
+        })
+    }
+}
+
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "freebsd", target_os = "openbsd"))
+))]
+mod platform {
+    use super::{is_network_fs, EncryptionState, MountPoint, SpaceInfo, VolumeDetails, VolumeInfo};
+    use std::{
+        collections::BTreeMap,
+        ffi::CString,
+        fs,
+        os::unix::ffi::OsStrExt,
+        path::{Path, PathBuf},
+    };
+
+    // Kernel/virtual filesystems that never hold user data.
+    const PSEUDO_FILESYSTEMS: &[&str] = &[
+        "proc",
+        "sysfs",
+        "devtmpfs",
+        "devpts",
+        "tmpfs",
+        "ramfs",
+        "cgroup",
+        "cgroup2",
+        "securityfs",
+        "debugfs",
+        "tracefs",
+        "pstore",
+        "bpf",
+        "configfs",
+        "mqueue",
+        "hugetlbfs",
+        "autofs",
+        "fusectl",
+        "binfmt_misc",
+        "nsfs",
+        "efivarfs",
+        "rpc_pipefs",
+        "squashfs",
+        "overlay",
+        "selinuxfs",
+        "fuse.portal",
+        "fuse.gvfsd-fuse",
+    ];
+
+    // statvfs field widths differ between targets, so the casts are only sometimes no-ops.
+    #[allow(clippy::unnecessary_cast)]
+    pub(super) fn space_for_path(path: &Path) -> Result<SpaceInfo, String> {
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| format!("Invalid path: {}", path.to_string_lossy()))?;
+        let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+        // SAFETY: `c_path` is NUL-terminated and `st` is a valid out-pointer.
+        if unsafe { libc::statvfs(c_path.as_ptr(), &mut st) } != 0 {
+            return Err(format!(
+                "Failed to query free space for {}: {}",
+                path.to_string_lossy(),
+                std::io::Error::last_os_error()
+            ));
+        }
+        let block = st.f_frsize as u64;
+        Ok(SpaceInfo {
+            total_bytes: (st.f_blocks as u64).saturating_mul(block),
+            available_bytes: (st.f_bavail as u64).saturating_mul(block),
+            free_bytes: (st.f_bfree as u64).saturating_mul(block),
+        })
+    }
+
+    /// Decodes the octal escapes (`\040` for space, ...) used in /proc/self/mounts.
+    pub(super) fn unescape_mount_field(field: &str) -> String {
+        let bytes = field.as_bytes();
+        let mut out = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'\\'
+                && i + 3 < bytes.len()
+                && bytes[i + 1..i + 4]
+                    .iter()
+                    .all(|b| (b'0'..=b'7').contains(b))
+            {
+                let value = bytes[i + 1..i + 4]
+                    .iter()
+                    .fold(0u32, |acc, b| acc * 8 + u32::from(b - b'0'));
+                out.push(value as u8);
+                i += 4;
+            } else {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+        String::from_utf8_lossy(&out).into_owned()
+    }
+
+    /// Decodes the `\xNN` escapes udev uses in /dev/disk/by-label names.
+    fn unescape_udev_label(label: &str) -> String {
+        let bytes = label.as_bytes();
+        let mut out = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'x') && i + 3 < bytes.len() {
+                // Byte-wise: the digits may be the start of a multibyte character.
+                let hex = |b: u8| (b as char).to_digit(16);
+                if let (Some(high), Some(low)) = (hex(bytes[i + 2]), hex(bytes[i + 3])) {
+                    out.push((high * 16 + low) as u8);
+                    i += 4;
+                    continue;
+                }
+            }
+            out.push(bytes[i]);
+            i += 1;
+        }
+        String::from_utf8_lossy(&out).into_owned()
+    }
+
+    fn labels_by_device() -> BTreeMap<PathBuf, String> {
+        udev_names_by_device("/dev/disk/by-label")
+    }
+
+    /// Maps canonical device nodes to the names of their symlinks in a /dev/disk/by-* folder.
+    fn udev_names_by_device(dir: &str) -> BTreeMap<PathBuf, String> {
+        let mut labels = BTreeMap::new();
+        if let Ok(rd) = fs::read_dir(dir) {
+            for entry in rd.flatten() {
+                if let Ok(target) = fs::canonicalize(entry.path()) {
+                    labels.insert(
+                        target,
+                        unescape_udev_label(&entry.file_name().to_string_lossy()),
+                    );
+                }
+            }
+        }
+        labels
+    }
+
+    /// Resolves `/dev/sdb1` to the sysfs directory of its whole disk (`.../block/sdb`).
+    pub(super) fn sysfs_disk_dir(source: &str) -> Option<PathBuf> {
+        let device = fs::canonicalize(source).ok()?;
+        let name = device.file_name()?;
+        let sys = fs::canonicalize(Path::new("/sys/class/block").join(name)).ok()?;
+        if sys.join("partition").exists() {
+            sys.parent().map(Path::to_path_buf)
+        } else {
+            Some(sys)
+        }
+    }
+
+    fn is_removable(source: &str) -> bool {
+        if !source.starts_with("/dev/") {
+            return false;
+        }
+        let disk = match sysfs_disk_dir(source) {
+            Some(disk) => disk,
+            None => return false,
+        };
+        // USB sticks and card readers often report removable=0; the sysfs path
+        // still tells us they hang off a USB (or MMC) bus.
+        let on_hotplug_bus = disk
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with("usb"))
+            || disk.to_string_lossy().contains("/mmc");
+        on_hotplug_bus
+            || fs::read_to_string(disk.join("removable"))
+                .map(|s| s.trim() == "1")
+                .unwrap_or(false)
+    }
+
+    pub(super) fn eject(mount_point: &str) -> Result<(), String> {
+        let mount = read_mounts()?
+            .into_iter()
+            .rev()
+            .find(|m| m.mount_point == mount_point)
+            .ok_or_else(|| format!("Volume is not mounted: {}", mount_point))?;
+
+        // udisks handles unprivileged unmounts of removable media on desktop systems.
+        match super::run_tool("udisksctl", &["unmount", "-b", &mount.source]) {
+            Ok(()) => {}
+            Err(err) if err.starts_with("Failed to run") => {
+                super::run_tool("umount", &[mount_point])?;
+            }
+            Err(err) => return Err(err),
+        }
+
+        // Spin down / power off the whole device so it can be unplugged safely.
+        if let Some(disk) =
+            sysfs_disk_dir(&mount.source).and_then(|d| d.file_name().map(|n| n.to_owned()))
+        {
+            let device = Path::new("/dev").join(disk);
+            let _ = super::run_tool("udisksctl", &["power-off", "-b", &device.to_string_lossy()]);
+        }
+        Ok(())
+    }
+
+    pub(crate) struct MountEntry {
+        pub source: String,
+        pub mount_point: String,
+        pub file_system: String,
+        pub options: String,
+    }
+
+    pub(super) fn read_mounts() -> Result<Vec<MountEntry>, String> {
+        let mounts = fs::read_to_string("/proc/self/mounts")
+            .map_err(|e| format!("Failed to read /proc/self/mounts: {}", e))?;
+        Ok(mounts
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let source = unescape_mount_field(fields.next()?);
+                let mount_point = unescape_mount_field(fields.next()?);
+                let file_system = fields.next()?.to_string();
+                let options = fields.next().unwrap_or_default().to_string();
+                Some(MountEntry {
+                    source,
+                    mount_point,
+                    file_system,
+                    options,
+                })
+            })
+            .collect())
+    }
+
+    pub(super) fn mount_points() -> Vec<MountPoint> {
+        read_mounts()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|mount| MountPoint {
+                is_network: is_network_fs(&mount.file_system),
+                path: PathBuf::from(mount.mount_point),
+            })
+            .collect()
+    }
+
+    pub(super) fn list_volumes() -> Result<Vec<VolumeInfo>, String> {
+        let labels = labels_by_device();
+        // Later mounts shadow earlier ones at the same mount point.
+        let mut by_mount: BTreeMap<String, VolumeInfo> = BTreeMap::new();
+
+        for mount in read_mounts()? {
+            if PSEUDO_FILESYSTEMS.contains(&mount.file_system.as_str()) {
+                continue;
+            }
+            let space = match space_for_path(Path::new(&mount.mount_point)) {
+                Ok(space) if space.total_bytes > 0 => space,
+                _ => continue,
+            };
+
+            let label = fs::canonicalize(&mount.source)
+                .ok()
+                .and_then(|dev| labels.get(&dev).cloned());
+
+            by_mount.insert(
+                mount.mount_point.clone(),
+                VolumeInfo {
+                    is_network: is_network_fs(&mount.file_system),
+                    is_removable: is_removable(&mount.source),
+                    mount_point: mount.mount_point,
+                    label,
+                    file_system: mount.file_system,
+                    total_bytes: space.total_bytes,
+                    free_bytes: space.available_bytes,
+                    used_bytes: space.used_bytes(),
+                    kind: None,
+                },
+            );
+        }
+
+        Ok(by_mount.into_values().collect())
+    }
+
+    /// Finds the innermost mount containing `path`.
+    pub(crate) fn mount_for_path(path: &Path) -> Result<MountEntry, String> {
+        let path = fs::canonicalize(path)
+            .map_err(|e| format!("Failed to resolve {}: {}", path.to_string_lossy(), e))?;
+        read_mounts()?
+            .into_iter()
+            .filter(|m| path.starts_with(&m.mount_point))
+            // Ties (stacked mounts) resolve to the last entry, which is the visible one.
+            .max_by_key(|m| Path::new(&m.mount_point).components().count())
+            .ok_or_else(|| format!("No mount found for {}", path.to_string_lossy()))
+    }
+
+    /// Follows device-mapper stacks (e.g. LVM on LUKS) looking for a dm-crypt layer.
+    fn dm_crypt_layer(device: &Path, depth: usize) -> Option<String> {
+        if depth > 8 {
+            return None;
+        }
+        let name = device.file_name()?;
+        let sys = Path::new("/sys/class/block").join(name);
+        if let Ok(uuid) = fs::read_to_string(sys.join("dm/uuid")) {
+            if let Some(rest) = uuid.trim().strip_prefix("CRYPT-") {
+                let kind = rest.split('-').next().unwrap_or("dm-crypt");
+                return Some(if kind.starts_with("LUKS") {
+                    "LUKS".to_string()
+                } else {
+                    format!("dm-crypt ({})", kind)
+                });
+            }
+        }
+        fs::read_dir(sys.join("slaves"))
+            .ok()?
+            .flatten()
+            .find_map(|slave| dm_crypt_layer(&slave.path(), depth + 1))
+    }
+
+    fn encryption_for_mount(mount: &MountEntry) -> (EncryptionState, Option<String>) {
+        match mount.file_system.as_str() {
+            "ecryptfs" => return (EncryptionState::Encrypted, Some("eCryptfs".to_string())),
+            "fuse.gocryptfs" | "fuse.encfs" | "fuse.cryfs" => {
+                let method = mount.file_system.trim_start_matches("fuse.").to_string();
+                return (EncryptionState::Encrypted, Some(method));
+            }
+            _ => {}
+        }
+        if !mount.source.starts_with("/dev/") {
+            return (EncryptionState::Unknown, None);
+        }
+        match fs::canonicalize(&mount.source) {
+            Ok(device) => match dm_crypt_layer(&device, 0) {
+                Some(method) => (EncryptionState::Encrypted, Some(method)),
+                None => (EncryptionState::Unencrypted, None),
+            },
+            Err(_) => (EncryptionState::Unknown, None),
+        }
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub(super) fn volume_details(path: &Path) -> Result<VolumeDetails, String> {
+        let mount = mount_for_path(path)?;
+
+        let c_path = CString::new(mount.mount_point.as_bytes())
+            .map_err(|_| format!("Invalid path: {}", mount.mount_point))?;
+        let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+        // SAFETY: `c_path` is NUL-terminated and `st` is a valid out-pointer.
+        let cluster_size = if unsafe { libc::statvfs(c_path.as_ptr(), &mut st) } == 0 {
+            Some(st.f_frsize as u64)
+        } else {
+            None
+        };
+
+        let device = fs::canonicalize(&mount.source).ok();
+        let lookup = |dir: &str| {
+            device
+                .as_ref()
+                .and_then(|dev| udev_names_by_device(dir).remove(dev))
+        };
+        let (encryption, encryption_method) = encryption_for_mount(&mount);
+        let mount_options: Vec<String> = mount.options.split(',').map(str::to_string).collect();
+
+        Ok(VolumeDetails {
+            label: lookup("/dev/disk/by-label"),
+            serial: lookup("/dev/disk/by-uuid"),
+            cluster_size,
+            encryption,
+            encryption_method,
+            compressed: Some(
+                mount_options
+                    .iter()
+                    .any(|o| o.starts_with("compress") || o == "compression"),
+            ),
+            read_only: mount_options.iter().any(|o| o == "ro"),
+            mount_options,
+            file_system: mount.file_system,
+            mount_point: mount.mount_point,
+        })
+    }
+}
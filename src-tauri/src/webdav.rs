@@ -0,0 +1,299 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+use tauri::Manager;
+
+use diskcheck_core::{
+    FsNodeKind, NoProgress, ProgressSnapshot, ScanOptions, ScanProgress, DEFAULT_MIN_NODE_BYTES,
+};
+
+use crate::{
+    credentials::{self, Credential},
+    error::CommandError,
+    remote::{self, RemoteListing},
+    s3::{curl_request, uri_encode, walk_xml, RequestBody},
+    scanner::{skipped_preview, FinishedScan, ScanResult, WindowProgress},
+    settings::SettingsStore,
+    smb::percent_decode,
+    store::ScanStore,
+};
+
+// Asks for only what the tree needs; Nextcloud and ownCloud reject `Depth: infinity`,
+// so folders are listed one level at a time.
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?><d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/><d:getcontentlength/></d:prop></d:propfind>"#;
+const MULTI_STATUS: u16 = 207;
+const UNAUTHORIZED: u16 = 401;
+
+/// A folder on a WebDAV server, e.g. `https://cloud.example.com/remote.php/dav/files/alice/`
+/// for a Nextcloud or ownCloud user's files.
+#[derive(Debug, Clone)]
+pub(crate) struct DavTarget {
+    // `https://host[:port]`, in lower case.
+    origin: String,
+    // Decoded path of the folder, with a trailing `/`.
+    path: String,
+}
+
+/// One `<response>` of a PROPFIND answer.
+#[derive(Default)]
+struct Resource {
+    href: String,
+    collection: bool,
+    size: Option<u64>,
+}
+
+impl DavTarget {
+    pub(crate) fn parse(url: &str) -> Result<Self, String> {
+        let invalid = || format!("Not a WebDAV URL: {}", url);
+        let (scheme, rest) = url.split_once("://").ok_or_else(invalid)?;
+        let scheme = scheme.to_ascii_lowercase();
+        if scheme != "https" && scheme != "http" {
+            return Err(invalid());
+        }
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        // Credentials come from the keychain, not the URL.
+        if host.is_empty() || host.contains('@') || host.chars().any(char::is_whitespace) {
+            return Err(invalid());
+        }
+        let segments: Vec<String> = path
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(percent_decode)
+            .collect();
+        if segments
+            .iter()
+            .any(|segment| segment == "." || segment == "..")
+        {
+            return Err(invalid());
+        }
+        let mut path = String::from("/");
+        for segment in &segments {
+            path.push_str(segment);
+            path.push('/');
+        }
+        Ok(Self {
+            origin: format!("{}://{}", scheme, host.to_ascii_lowercase()),
+            path,
+        })
+    }
+
+    /// The key the server's credential is saved under: its origin.
+    pub(crate) fn account(&self) -> String {
+        self.origin.clone()
+    }
+
+    /// The URL of the folder at the decoded `path`.
+    fn url(&self, path: &str) -> String {
+        let encoded: Vec<String> = path.split('/').map(uri_encode).collect();
+        format!("{}{}", self.origin, encoded.join("/"))
+    }
+}
+
+/// Reads a `207 Multi-Status` answer.
+fn parse_multistatus(xml: &str) -> Result<Vec<Resource>, String> {
+    let mut resources = vec![];
+    let mut resource = Resource::default();
+    walk_xml(xml, |name, text| match name {
+        "href" => resource.href = text,
+        "collection" => resource.collection = true,
+        // Absent properties come back empty in a 404 `propstat`.
+        "getcontentlength" => {
+            if let Ok(size) = text.trim().parse() {
+                resource.size = Some(size);
+            }
+        }
+        "response" => resources.push(std::mem::take(&mut resource)),
+        _ => {}
+    })?;
+    Ok(resources)
+}
+
+/// The decoded path of an `href`, which servers send either as an absolute path or as a
+/// full URL.
+fn href_path(href: &str) -> String {
+    let href = href.trim();
+    let path = match href.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |at| &rest[at..]),
+        None => href,
+    };
+    percent_decode(path)
+}
+
+/// PROPFINDs the folder at the decoded `path` with `Depth: 1`.
+fn list_folder(
+    target: &DavTarget,
+    path: &str,
+    credential: Option<&Credential>,
+) -> Result<Vec<Resource>, CommandError> {
+    let headers = [
+        ("Depth".to_string(), "1".to_string()),
+        (
+            "Content-Type".to_string(),
+            "application/xml; charset=utf-8".to_string(),
+        ),
+    ];
+    let user = credential.map(|c| (c.username.as_str(), c.password.as_str()));
+    let (status, body) = curl_request(
+        "PROPFIND",
+        &target.url(path),
+        &headers,
+        user,
+        Some(RequestBody::Text(PROPFIND_BODY)),
+    )?;
+    match status {
+        MULTI_STATUS => Ok(parse_multistatus(&String::from_utf8_lossy(&body))?),
+        UNAUTHORIZED => Err(CommandError::CredentialsRequired(match credential {
+            Some(_) => format!(
+                "{} refused the saved user name and password.",
+                target.origin
+            ),
+            None => format!("{} needs a user name and password.", target.origin),
+        })),
+        status => Err(CommandError::Failed(format!(
+            "Listing {} failed with HTTP {}.",
+            path, status
+        ))),
+    }
+}
+
+/// Lists the folder and everything below it, one PROPFIND per folder, and stores it like
+/// a local scan. Collections become folders. The tree is rooted at
+/// `dav://host/path`. Folders below the root that fail to list are counted as skipped.
+pub(crate) fn scan_webdav_blocking(
+    target: &DavTarget,
+    min_node_bytes: Option<u64>,
+    excludes: Vec<String>,
+    window: Option<tauri::Window>,
+) -> Result<FinishedScan, CommandError> {
+    let account = target.account();
+    let credential = credentials::load(&account);
+    let started_at = SystemTime::now();
+    let started = Instant::now();
+    let host = target.origin.split_once("://").map_or("", |(_, host)| host);
+    let root = PathBuf::from(format!(
+        "dav://{}{}",
+        host,
+        target.path.trim_end_matches('/')
+    ));
+    let opts = ScanOptions::network(min_node_bytes.unwrap_or(DEFAULT_MIN_NODE_BYTES))
+        .with_excludes(excludes);
+    let progress: Box<dyn ScanProgress> = match window {
+        Some(window) => Box::new(WindowProgress::new(window, &root)),
+        None => Box::new(NoProgress),
+    };
+
+    tracing::info!(server = %account, path = %target.path, saved_credential = credential.is_some(), "webdav scan started");
+    let mut listing = RemoteListing::new(root.clone());
+    let (mut files, mut dirs, mut bytes, mut unreadable) = (0u64, 0u64, 0u64, 0u64);
+    let mut pending = vec![target.path.clone()];
+    while let Some(folder) = pending.pop() {
+        let resources = match list_folder(target, &folder, credential.as_ref()) {
+            Ok(resources) => resources,
+            Err(error) if folder == target.path => {
+                tracing::error!(server = %account, error = ?error, "webdav scan failed");
+                return Err(error);
+            }
+            Err(error) => {
+                tracing::warn!(path = %folder, error = ?error, "skipped unreadable folder");
+                unreadable += 1;
+                continue;
+            }
+        };
+        for resource in resources {
+            let path = href_path(&resource.href);
+            // Only direct children: the folder itself is listed too, and anything else
+            // a server sends is not taken on trust.
+            let name = match path.trim_end_matches('/').rsplit_once('/') {
+                Some((parent, name)) if parent == folder.trim_end_matches('/') => name,
+                _ => continue,
+            };
+            if matches!(name, "" | "." | "..") {
+                continue;
+            }
+            let Some(relative) = path.strip_prefix(&target.path) else {
+                continue;
+            };
+            let segments: Vec<&str> = relative.split('/').filter(|s| !s.is_empty()).collect();
+            let local = segments.iter().fold(root.clone(), |p, s| p.join(s));
+            if opts.is_excluded(&local) {
+                continue;
+            }
+            if resource.collection {
+                dirs += 1;
+                listing.insert(local, FsNodeKind::Directory, 0);
+                pending.push(format!("{}/", path.trim_end_matches('/')));
+            } else {
+                let size = resource.size.unwrap_or(0);
+                files += 1;
+                bytes += size;
+                listing.insert(local, FsNodeKind::File, size);
+            }
+        }
+        progress.on_progress(ProgressSnapshot {
+            scanned_files: files,
+            scanned_dirs: dirs,
+            total_bytes: bytes,
+            current_path: Some(Path::new(&folder)),
+        });
+    }
+    Ok(remote::scan_listing(
+        listing, &opts, started_at, started, unreadable,
+    )?)
+}
+
+/// Scans a folder on a WebDAV server (Nextcloud, ownCloud, ...) with the credential
+/// saved for the server. Without one, or when the server refuses it, this fails with
+/// `CredentialsRequired`; the UI then saves them with `save_dav_credentials`.
+pub async fn scan_webdav(
+    window: tauri::Window,
+    store: &ScanStore,
+    url: String,
+    min_node_bytes: Option<u64>,
+) -> Result<ScanResult, CommandError> {
+    let target = DavTarget::parse(&url)?;
+    let defaults = window.state::<SettingsStore>().scan();
+    let min_node_bytes = min_node_bytes.or(defaults.min_node_bytes);
+    let finished = tauri::async_runtime::spawn_blocking(move || {
+        scan_webdav_blocking(&target, min_node_bytes, defaults.excludes, Some(window))
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    let skipped = skipped_preview(&finished.summary);
+    let scan_id = store.insert_spilled(finished.tree, finished.spill, finished.summary)?;
+    Ok(ScanResult {
+        scan_id,
+        root: finished.pruned,
+        skipped,
+        usage_check: None,
+        denied_dirs: vec![],
+        partial: finished.partial,
+    })
+}
+
+pub async fn save_dav_credentials(
+    url: String,
+    username: String,
+    password: String,
+) -> Result<(), String> {
+    let target = DavTarget::parse(&url)?;
+    if username.is_empty() {
+        return Err("A user name is needed.".to_string());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        credentials::save(&target.account(), &Credential { username, password })
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub async fn forget_dav_credentials(url: String) -> Result<(), String> {
+    let target = DavTarget::parse(&url)?;
+    tauri::async_runtime::spawn_blocking(move || credentials::delete(&target.account()))
+        .await
+        .map_err(|err| err.to_string())?
+}
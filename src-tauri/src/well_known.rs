@@ -0,0 +1,146 @@
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use tauri::Manager;
+
+use diskcheck_core::ScanIndex;
+
+use crate::{
+    scan_index::{self, IndexOptions},
+    store::{unix_secs, ScanStore},
+};
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WellKnownKind {
+    Home,
+    Downloads,
+    Documents,
+    Desktop,
+    AppData,
+    Caches,
+    Temp,
+}
+
+/// A folder that commonly fills up, offered for a one-click scan.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WellKnownPath {
+    pub kind: WellKnownKind,
+    pub label: String,
+    pub path: String,
+    // From the latest stored scan that covers the folder, else from its volume's scan
+    // index (which can fall short); absent when neither has seen it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    // When that size was measured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sized_at_secs: Option<u64>,
+}
+
+/// The platform's usual suspects, as kind, label and path; some may not exist.
+fn candidates(app: &tauri::AppHandle) -> Vec<(WellKnownKind, &'static str, PathBuf)> {
+    use WellKnownKind::*;
+
+    let resolver = app.path();
+    let mut paths = vec![
+        (Home, "Home", resolver.home_dir()),
+        (Downloads, "Downloads", resolver.download_dir()),
+        (Documents, "Documents", resolver.document_dir()),
+        (Desktop, "Desktop", resolver.desktop_dir()),
+    ];
+    #[cfg(target_os = "windows")]
+    paths.extend([
+        (AppData, "AppData (Roaming)", resolver.data_dir()),
+        (AppData, "AppData (Local)", resolver.local_data_dir()),
+        (Temp, "Temp", Ok(std::env::temp_dir())),
+        (
+            Temp,
+            "Windows Temp",
+            Ok(Path::new(&crate::volumes::system_root()).join("Windows\\Temp")),
+        ),
+    ]);
+    #[cfg(target_os = "macos")]
+    paths.extend([
+        (
+            AppData,
+            "Library",
+            resolver.home_dir().map(|home| home.join("Library")),
+        ),
+        (Caches, "Caches", resolver.cache_dir()),
+        (Temp, "Temporary items", Ok(std::env::temp_dir())),
+        (Temp, "/tmp", Ok(PathBuf::from("/private/tmp"))),
+    ]);
+    #[cfg(all(unix, not(target_os = "macos")))]
+    paths.extend([
+        (AppData, "Application data", resolver.data_dir()),
+        (Caches, "Caches", resolver.cache_dir()),
+        (Temp, "Temp", Ok(std::env::temp_dir())),
+        (Temp, "/var/tmp", Ok(PathBuf::from("/var/tmp"))),
+    ]);
+    paths
+        .into_iter()
+        .filter_map(|(kind, label, path)| Some((kind, label, path.ok()?)))
+        .collect()
+}
+
+/// Size and time of measurement from the most recent stored scan holding `path`.
+fn size_from_scans(store: &ScanStore, path: &Path) -> Option<(u64, u64)> {
+    let path = path.to_string_lossy();
+    store.list().ok()?.iter().rev().find_map(|scan| {
+        let node = scan.tree.find(&path)?;
+        Some((node.size(), scan.summary.started_at_secs))
+    })
+}
+
+/// Volume indexes, each loaded once along with when it was last written.
+struct Indexes {
+    options: Option<IndexOptions>,
+    loaded: HashMap<PathBuf, Option<(ScanIndex, u64)>>,
+}
+
+impl Indexes {
+    fn size_of(&mut self, path: &Path) -> Option<(u64, u64)> {
+        let file = self.options.as_ref()?.file_for(path)?;
+        let loaded = self.loaded.entry(file).or_insert_with_key(|file| {
+            let written = fs::metadata(file).and_then(|m| m.modified()).ok()?;
+            Some((scan_index::load(file)?, unix_secs(written)))
+        });
+        let (index, written) = loaded.as_ref()?;
+        Some((index.size_below(path)?, *written))
+    }
+}
+
+fn well_known_paths_blocking(app: &tauri::AppHandle) -> Vec<WellKnownPath> {
+    let store = app.state::<ScanStore>();
+    let mut indexes = Indexes {
+        options: IndexOptions::new(app, false),
+        loaded: HashMap::new(),
+    };
+    let mut seen = HashSet::new();
+    candidates(app)
+        .into_iter()
+        .filter(|(_, _, path)| path.is_dir() && seen.insert(path.clone()))
+        .map(|(kind, label, path)| {
+            let sized = size_from_scans(&store, &path).or_else(|| indexes.size_of(&path));
+            WellKnownPath {
+                kind,
+                label: label.to_string(),
+                path: path.to_string_lossy().into_owned(),
+                size_bytes: sized.map(|(size, _)| size),
+                sized_at_secs: sized.map(|(_, at)| at),
+            }
+        })
+        .collect()
+}
+
+/// The home, downloads, documents, desktop, app data and temp folders that exist here,
+/// with the sizes earlier scans saw. Nothing is scanned.
+pub async fn well_known_paths(app: tauri::AppHandle) -> Result<Vec<WellKnownPath>, String> {
+    tauri::async_runtime::spawn_blocking(move || well_known_paths_blocking(&app))
+        .await
+        .map_err(|err| err.to_string())
+}
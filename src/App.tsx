@@ -1,4 +1,4 @@
-import { listen } from "@tauri-apps/api/event";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
 import {
@@ -16,8 +16,12 @@ import { VirtualList } from "./components/VirtualList";
 import { Button } from "./components/ui/button";
 import { Card, CardContent, CardHeader, CardTitle } from "./components/ui/card";
 import {
+  type CommandError,
   type FsNode,
   type ScanProgressPayload,
+  type ScanResult,
+  type Settings,
+  errorMessage,
   getChildren,
 } from "./lib/fs";
 import { formatBytes } from "./lib/format";
@@ -47,11 +51,107 @@ export default function App() {
     null,
   );
   const [error, setError] = React.useState<string | null>(null);
+  const [deniedDirs, setDeniedDirs] = React.useState<string[]>([]);
+  // The last scan was stopped early, so unfinished folders are too small.
+  const [partial, setPartial] = React.useState(false);
 
   React.useEffect(() => {
-    const unlisten = listen<ScanProgressPayload>("scan_progress", (event) => {
-      setProgress(event.payload);
-    });
+    // Scan events go to the window that started the scan only.
+    const unlisten = getCurrentWebviewWindow().listen<ScanProgressPayload>(
+      "scan_progress",
+      (event) => {
+        setProgress(event.payload);
+      },
+    );
+
+    return () => {
+      unlisten.then((fn) => fn()).catch(() => undefined);
+    };
+  }, []);
+
+  // Folders dropped onto the window are scanned by the backend directly.
+  React.useEffect(() => {
+    const webview = getCurrentWebviewWindow();
+    const unlisteners = [
+      webview.listen<{ path: string }>("scan_started", (event) => {
+        setSelectedPath(event.payload.path);
+        setError(null);
+        setDeniedDirs([]);
+        setPartial(false);
+        setIsScanning(true);
+        setRoot(null);
+        setFocusStack([]);
+        setProgress({ scannedFiles: 0, scannedDirs: 0, totalBytes: 0 });
+      }),
+      webview.listen<ScanResult>("scan_finished", (event) => {
+        setRoot(event.payload.root);
+        setFocusStack([event.payload.root]);
+        setDeniedDirs(event.payload.deniedDirs ?? []);
+        setPartial(event.payload.partial ?? false);
+        setIsScanning(false);
+      }),
+      webview.listen<{ path: string; error: CommandError }>(
+        "scan_failed",
+        (event) => {
+          setError(errorMessage(event.payload.error));
+          setIsScanning(false);
+        },
+      ),
+    ];
+
+    return () => {
+      for (const unlisten of unlisteners) {
+        unlisten.then((fn) => fn()).catch(() => undefined);
+      }
+    };
+  }, []);
+
+  // Preselect the folder configured in the persisted settings.
+  React.useEffect(() => {
+    invoke<Settings>("get_settings")
+      .then((settings) => {
+        const defaultPath = settings.scan.defaultPath;
+        if (defaultPath) setSelectedPath((path) => path ?? defaultPath);
+      })
+      .catch(() => undefined);
+  }, []);
+
+  // Scans requested from outside the UI (diskcheck:// links). Fetching the path from
+  // the backend also picks up the request that launched the app.
+  React.useEffect(() => {
+    async function takeLaunchPath() {
+      const path = await invoke<string | null>("take_launch_path");
+      if (!path) return;
+      setSelectedPath(path);
+      void startScan(path);
+    }
+
+    void takeLaunchPath();
+    const unlisten = getCurrentWebviewWindow().listen("start_scan", () => void takeLaunchPath());
+
+    return () => {
+      unlisten.then((fn) => fn()).catch(() => undefined);
+    };
+  }, []);
+
+  // Recent scans picked from the tray menu are re-opened from the backend store.
+  React.useEffect(() => {
+    const unlisten = getCurrentWebviewWindow().listen<{ scanId: string }>(
+      "open_scan",
+      async (event) => {
+        try {
+          const result = await invoke<ScanResult>("get_scan", { scanId: event.payload.scanId });
+          setError(null);
+          setSelectedPath(result.root.path);
+          setRoot(result.root);
+          setFocusStack([result.root]);
+          setDeniedDirs(result.deniedDirs ?? []);
+          setPartial(result.partial ?? false);
+        } catch (e) {
+          setError(errorMessage(e));
+        }
+      },
+    );
 
     return () => {
       unlisten.then((fn) => fn()).catch(() => undefined);
@@ -80,31 +180,44 @@ export default function App() {
     }
   }
 
-  async function startScan() {
-    if (!selectedPath) return;
+  async function startScan(path: string | null = selectedPath) {
+    if (!path) return;
 
     setError(null);
+    setDeniedDirs([]);
+    setPartial(false);
     setIsScanning(true);
     setRoot(null);
     setFocusStack([]);
     setProgress({ scannedFiles: 0, scannedDirs: 0, totalBytes: 0 });
 
     try {
-      const tree = await invoke<FsNode>("scan_directory", { path: selectedPath });
-      setRoot(tree);
-      setFocusStack([tree]);
+      const result = await invoke<ScanResult>("scan_directory", { path });
+      setRoot(result.root);
+      setFocusStack([result.root]);
+      setDeniedDirs(result.deniedDirs ?? []);
+      setPartial(result.partial ?? false);
     } catch (e) {
-      setError(e instanceof Error ? e.message : String(e));
+      setError(errorMessage(e));
     } finally {
       setIsScanning(false);
     }
   }
 
+  // The scan returns what it counted so far, marked partial.
+  async function cancelScan() {
+    try {
+      await invoke<boolean>("cancel_scan");
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  }
+
   async function reveal(path: string) {
     try {
       await invoke("reveal_in_explorer", { path });
     } catch (e) {
-      setError(e instanceof Error ? e.message : String(e));
+      setError(errorMessage(e));
     }
   }
 
@@ -137,7 +250,7 @@ export default function App() {
                 </Button>
                 <Button
                   size="icon"
-                  onClick={startScan}
+                  onClick={() => startScan()}
                   disabled={!selectedPath || isScanning}
                   aria-label="Start scan"
                 >
@@ -152,7 +265,12 @@ export default function App() {
 
             {isScanning ? (
               <div className="mt-4 rounded-lg border bg-card/40 p-3">
-                <div className="text-xs font-medium">Scanning…</div>
+                <div className="flex items-center justify-between gap-2">
+                  <div className="text-xs font-medium">Scanning…</div>
+                  <Button variant="ghost" size="sm" onClick={cancelScan}>
+                    Stop
+                  </Button>
+                </div>
                 <div className="mt-1 flex flex-wrap gap-x-3 gap-y-1 font-mono text-[11px] text-muted-foreground">
                   <span>files: {progress?.scannedFiles ?? 0}</span>
                   <span>dirs: {progress?.scannedDirs ?? 0}</span>
@@ -171,6 +289,24 @@ export default function App() {
                 {error}
               </div>
             ) : null}
+
+            {partial ? (
+              <div className="mt-4 rounded-lg border bg-card/40 p-3 text-xs text-muted-foreground">
+                The scan was stopped early; folders it had not finished show
+                only what was counted so far.
+              </div>
+            ) : null}
+
+            {deniedDirs.length ? (
+              <div
+                className="mt-4 rounded-lg border bg-card/40 p-3 text-xs text-muted-foreground"
+                title={deniedDirs.join("\n")}
+              >
+                {deniedDirs.length === 1
+                  ? "1 folder could not be read and is not counted."
+                  : `${deniedDirs.length} folders could not be read and are not counted.`}
+              </div>
+            ) : null}
           </div>
 
           <div className="px-4 pb-3">
@@ -8,15 +8,99 @@ export type FsNode = {
   children?: FsNode[];
   extension?: string | null;
   error?: string | null;
+  // Archive files and the entries listed inside them: bytes once extracted.
+  uncompressedSize?: number;
+  // Nodes below this one, pruned or not, whose scan failed: the size is incomplete.
+  errorCount?: number;
 };
 
+export type ScanResult = {
+  scanId: string;
+  root: FsNode;
+  // Cancelled or out of time: unfinished folders hold what was counted so far.
+  partial?: boolean;
+  // Folders the scan was not allowed to read.
+  deniedDirs?: string[];
+};
+
+// "background" scans at the lowest CPU and I/O priority.
+export type ScanMode = "normal" | "background";
+
 export type ScanProgressPayload = {
   scannedFiles: number;
   scannedDirs: number;
   totalBytes: number;
   currentPath?: string | null;
+  // Estimated from the previous scan of the same root; null when there is none.
+  percent?: number | null;
+  etaSecs?: number | null;
 };
 
+export type ScanSettings = {
+  defaultPath?: string | null;
+  minNodeBytes?: number | null;
+  excludes: string[];
+  scanArchives?: boolean;
+  // Scans stop after this long and keep what they counted.
+  timeLimitSecs?: number | null;
+};
+
+export type Settings = {
+  scan: ScanSettings;
+  spaceMonitor?: {
+    mountPoints: string[];
+    minFreeBytes?: number | null;
+    minFreePercent?: number | null;
+    intervalSecs?: number | null;
+    notify: boolean;
+  } | null;
+  folderBudgets?: {
+    budgets: { path: string; maxBytes: number }[];
+    intervalSecs?: number | null;
+    notify: boolean;
+  } | null;
+  growthMonitor?: {
+    folders: { path: string; maxBytesPerHour: number }[];
+    intervalSecs?: number | null;
+    notify: boolean;
+  } | null;
+  scheduledReport?: {
+    roots: string[];
+    format?: "html" | "csv";
+    outputDir?: string | null;
+    intervalSecs?: number | null;
+    notify: boolean;
+  } | null;
+  scanShortcut?: { accelerator: string; path?: string | null } | null;
+  closeToTray: boolean;
+  apiServer: { enabled: boolean; port?: number | null };
+  protectedPaths: string[];
+  safeMode: boolean;
+};
+
+// Rejection value of commands that modify the disk (trash, restore, snapshot deletion,
+// clearing baselines, ejecting, the context menu entry), connect to a share (scan_share)
+// or scan (scan_directory).
+export type CommandError =
+  | {
+      kind: "safeModeEnabled" | "credentialsRequired" | "failed";
+      message: string;
+    }
+  // The command panicked; `path` is where the scan was.
+  | { kind: "internal"; message: { message: string; path?: string } };
+
+export function errorMessage(error: unknown): string {
+  if (error instanceof Error) return error.message;
+  if (typeof error === "object" && error !== null && "kind" in error) {
+    const { kind, message } = error as CommandError;
+    if (kind !== "internal") return message;
+    return message.path
+      ? `Internal error at ${message.path}: ${message.message}`
+      : `Internal error: ${message.message}`;
+  }
+  return String(error);
+}
+
 export function getChildren(node: FsNode | null | undefined): FsNode[] {
   return node?.children ?? [];
 }